@@ -61,6 +61,8 @@ ensogl::read_args! {
     pkg_wasm_url: String,
     platform: Option<web::platform::Platform>,
     preferred_engine_version: Option<semver::Version>,
+    /// Selects the profilers that are recorded; see the `enso_profiler::filter` module.
+    profiling_filter: Option<String>,
     project: Option<String>,
     project_manager: Option<String>,
     shaders_url: String,
@@ -353,6 +353,24 @@ pub fn analyze_crumbable(crumbable: &impl Crumbable) -> IdentifierUsage {
     analyzer.root_scope.symbols
 }
 
+/// Checks if the subtree of `ast` under `crumbs` refers to the same entities as it would if it was
+/// placed directly in the scope of `ast`.
+///
+/// Returns the first identifier used by the subtree that is bound by a pattern between the root
+/// of `ast` and the subtree (e.g. a lambda argument). Such identifier would change its meaning if
+/// the subtree was moved out of `ast`, or it was the reason why the subtree does not mean what it
+/// meant when it was put there.
+pub fn captured_identifier(ast: &Ast, crumbs: &[Crumb]) -> FallibleResult<Option<String>> {
+    let subtree = ast.get_traversing(crumbs)?;
+    let used_at_root = analyze_ast(ast).used;
+    let is_used_at_root = |name: &Located<String>| {
+        let crumbs = crumbs.iter().chain(&name.crumbs);
+        used_at_root.iter().any(|used| used.crumbs.iter().eq(crumbs.clone()))
+    };
+    let used_by_subtree = analyze_ast(subtree).used;
+    Ok(used_by_subtree.into_iter().find(|name| !is_used_at_root(name)).map(|name| name.item))
+}
+
 
 
 // =============
@@ -364,6 +382,8 @@ mod tests {
     use super::test_utils::*;
     use super::*;
 
+    use crate::test_utils::crumbs_of;
+
     /// Checks if actual observed sequence of located identifiers matches the expected one.
     /// Expected identifiers are described as code spans in the node's text representation.
     fn validate_identifiers(
@@ -438,4 +458,18 @@ mod tests {
             run_markdown_case(&parser, case)
         }
     }
+
+    #[test]
+    fn test_captured_identifier() {
+        let parser = parser::Parser::new();
+        let captured_in = |code: &str, subtree: &str| {
+            let ast = parser.parse_line_ast(code).unwrap();
+            let crumbs = crumbs_of(&ast, subtree).unwrap();
+            captured_identifier(&ast, &crumbs).unwrap()
+        };
+        assert_eq!(captured_in("foo = bar + baz", "bar + baz"), None);
+        assert_eq!(captured_in("foo = x -> bar + baz", "bar + baz"), None);
+        assert_eq!(captured_in("foo = bar -> bar + baz", "bar + baz"), Some("bar".into()));
+        assert_eq!(captured_in("foo = baz -> bar + baz", "bar + baz"), Some("baz".into()));
+    }
 }
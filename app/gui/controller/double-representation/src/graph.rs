@@ -2,6 +2,8 @@
 
 use crate::prelude::*;
 
+use crate::alias_analysis;
+use crate::alias_analysis::IdentifierUsage;
 use crate::connection;
 use crate::connection::Connection;
 use crate::definition;
@@ -9,6 +11,7 @@ use crate::definition::DefinitionInfo;
 use crate::definition::DefinitionProvider;
 use crate::node;
 use crate::node::LocatedNode;
+use crate::node::MainLine;
 use crate::node::NodeInfo;
 
 use ast::known;
@@ -98,6 +101,28 @@ impl GraphInfo {
         connection::list(&self.source.ast.rarg)
     }
 
+    /// Describes the identifiers introduced into the graph's scope by its nodes, and the
+    /// identifiers used by the nodes. The definition arguments are not included.
+    pub fn identifier_usage(&self) -> IdentifierUsage {
+        let body = &self.source.ast.rarg;
+        if let ast::Shape::Block(block) = body.shape() {
+            alias_analysis::analyze_crumbable(block)
+        } else if let Some(node) = MainLine::from_ast(body) {
+            alias_analysis::analyze_ast(node.ast())
+        } else {
+            default()
+        }
+    }
+
+    /// Names that are introduced into the graph's scope, either by the definition arguments or by
+    /// the nodes.
+    pub fn introduced_names(&self) -> HashSet<String> {
+        let args = self.source.args.iter().flat_map(|arg| arg.iter_recursive());
+        let arg_names = args.filter_map(|ast| ast::identifier::name(ast).map(ToOwned::to_owned));
+        let node_names = self.identifier_usage().introduced.into_iter().map(|name| name.item);
+        arg_names.chain(node_names).collect()
+    }
+
     /// Adds a new node to this graph.
     pub fn add_node(&mut self, node: &NodeInfo, location_hint: LocationHint) -> FallibleResult {
         let body = self.source.body();
@@ -53,6 +53,8 @@ pub mod refactorings;
 pub mod test_utils;
 pub mod text;
 
+pub use enso_parser::scope_analysis;
+
 
 
 // ===============
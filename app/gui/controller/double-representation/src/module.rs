@@ -64,7 +64,7 @@ pub struct NotDirectChild(ast::Crumbs);
 // ==========
 
 /// The segments of module name. Allow finding module in the project.
-#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[derive(Clone, Debug, serde::Deserialize, PartialEq, Eq, PartialOrd, Ord, Hash, serde::Serialize)]
 pub struct Id {
     /// The last segment being a module name. For project's main module it should be equal
     /// to [`PROJECTS_MAIN_MODULE`].
@@ -5,6 +5,7 @@ use crate::prelude::*;
 use crate::definition::ScopeKind;
 use crate::LineKind;
 
+use ast::crumbs::Crumb;
 use ast::crumbs::Crumbable;
 use ast::enumerate_non_empty_lines;
 use ast::known;
@@ -40,6 +41,11 @@ pub struct IdNotFound {
     pub id: Id,
 }
 
+#[allow(missing_docs)]
+#[derive(Clone, Debug, Fail)]
+#[fail(display = "The code `{}` cannot be used as a node's line.", _0)]
+pub struct NotANodeLine(String);
+
 /// Indices of lines belonging to a node.
 #[derive(Clone, Copy, Debug)]
 pub struct NodeLocation {
@@ -380,6 +386,16 @@ impl MainLine {
         }
     }
 
+    /// Replace the part of the node's AST located by the given crumbs. The crumbs are relative to
+    /// [`Self::ast`], e.g. like the crumbs of the connection endpoints. Maintains ID.
+    pub fn set_ast_at(&mut self, crumbs: &[Crumb], new_ast: Ast) -> FallibleResult {
+        let id = self.id();
+        let ast = self.ast().set_traversing(crumbs, new_ast)?;
+        *self = Self::from_ast(&ast).ok_or_else(|| NotANodeLine(ast.repr()))?;
+        self.set_id(id);
+        Ok(())
+    }
+
     /// The info about macro calls in the expression.
     pub fn macros_info(&self) -> &MacrosInfo {
         match self {
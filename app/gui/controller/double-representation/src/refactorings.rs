@@ -6,5 +6,15 @@
 // ==============
 
 pub mod collapse;
+pub mod extract_variable;
+pub mod inline;
+pub mod inline_variable;
+pub mod rename;
+pub mod rename_symbol;
 
 pub use collapse::collapse;
+pub use extract_variable::extract_variable;
+pub use inline::inline;
+pub use inline_variable::inline_variable;
+pub use rename::rename;
+pub use rename_symbol::rename_symbol;
@@ -0,0 +1,170 @@
+//! Module with logic for extracting a part of node's expression into a new node.
+//!
+//! See the [`extract_variable`] function for details.
+
+use crate::prelude::*;
+
+use crate::alias_analysis;
+use crate::definition::DefinitionInfo;
+use crate::graph::GraphInfo;
+use crate::graph::LocationHint;
+use crate::identifier::Identifier;
+use crate::node;
+use crate::node::NodeInfo;
+use crate::refactorings::rename::new_variable;
+
+use ast::crumbs::Crumb;
+
+
+
+// ============================
+// === Extract Variable API ===
+// ============================
+
+// === Entry point ===
+
+/// Run the "extract variable" refactoring. Generates output describing how to apply it.
+///
+/// The part of the node's expression located by `crumbs` (relative to the node's expression, e.g.
+/// the AST crumbs of a span tree port) is moved to a new node placed before the given one, and
+/// replaced with the new node's variable.
+///
+/// The refactoring is refused if the variable name is already used in the graph, or if the
+/// extracted expression uses identifiers bound within the node's expression (e.g. lambda
+/// arguments).
+pub fn extract_variable(
+    graph: &GraphInfo,
+    node: node::Id,
+    crumbs: &[Crumb],
+    name: &Identifier,
+) -> FallibleResult<ExtractedVariable> {
+    let name = new_variable(graph, name)?;
+    let mut node_info = graph.locate_node(node)?.node;
+    let expression = node_info.expression();
+    let extracted = expression.get_traversing(crumbs)?.clone_ref();
+    if matches!(extracted.shape(), ast::Shape::Opr(_) | ast::Shape::Blank(_)) {
+        return Err(CannotExtract(extracted.repr()).into());
+    }
+    if let Some(local) = alias_analysis::captured_identifier(&expression, crumbs)? {
+        return Err(ExtractedExpressionUsesLocalVariable(local).into());
+    }
+
+    node_info.set_expression(expression.set_traversing(crumbs, Ast::var(&name.name))?);
+    // The root of extracted expression might be the root of the original node's expression, and
+    // as such, it shares the ID with the original node.
+    let binding = Ast::infix(Ast::var(&name.name), "=", extracted.with_new_id());
+    let new_node = NodeInfo::from_main_line_ast(&binding).ok_or(CannotConstructNewNode)?;
+    let mut updated = graph.clone();
+    updated.set_node(&node_info)?;
+    updated.add_node(&new_node, LocationHint::Before(node))?;
+    let updated_definition = updated.source;
+    Ok(ExtractedVariable { updated_definition, new_node: new_node.id() })
+}
+
+
+// === ExtractedVariable ===
+
+/// Result of running the "extract variable" refactoring.
+#[derive(Clone, Debug)]
+pub struct ExtractedVariable {
+    /// New contents of the refactored definition.
+    pub updated_definition: DefinitionInfo,
+    /// Identifier of the node introduced for the extracted expression.
+    pub new_node:           node::Id,
+}
+
+
+// === Errors ===
+
+#[allow(missing_docs)]
+#[derive(Clone, Debug, Fail)]
+#[fail(display = "The `{}` is not an expression that can be extracted.", _0)]
+pub struct CannotExtract(String);
+
+#[allow(missing_docs)]
+#[derive(Clone, Debug, Fail)]
+#[fail(display = "The extracted expression uses `{}` which is bound within the node.", _0)]
+pub struct ExtractedExpressionUsesLocalVariable(String);
+
+#[allow(missing_docs)]
+#[derive(Clone, Copy, Debug, Fail)]
+#[fail(display = "Internal refactoring error: Cannot generate the extracted node description.")]
+pub struct CannotConstructNewNode;
+
+
+
+// ============
+// === Test ===
+// ============
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::definition::DefinitionName;
+    use crate::module;
+    use crate::refactorings::rename::NameAlreadyInUse;
+    use crate::refactorings::rename::NotAVariableName;
+    use crate::test_utils::crumbs_of;
+
+    use parser::Parser;
+
+    fn run(code: &str, node_line: usize, extracted: &str, name: &str) -> FallibleResult<String> {
+        let parser = Parser::new();
+        let ast = parser.parse_module(code, default()).unwrap();
+        let main = module::locate_child(&ast, &DefinitionName::new_plain("main")).unwrap();
+        let graph = GraphInfo::from_definition(main.item.clone());
+        let node = &graph.nodes()[node_line];
+        let crumbs = crumbs_of(&node.expression(), extracted).unwrap();
+        let name = Identifier::try_from(name).unwrap();
+        let result = extract_variable(&graph, node.id(), &crumbs, &name)?;
+        let updated = GraphInfo::from_definition(result.updated_definition);
+        ast::test_utils::assert_unique_ids(updated.source.ast.as_ref());
+        let new_node = updated.find_node(result.new_node).unwrap();
+        assert_eq!(new_node.expression().repr(), extracted);
+        assert!(updated.find_node(node.id()).is_some());
+        Ok(updated.source.ast.repr())
+    }
+
+    #[test]
+    fn test_extract_variable() {
+        let code = r"main =
+    foo = 2
+    ## Documented.
+    bar = foo + 3 * 4
+    bar";
+        let expected = r"main =
+    foo = 2
+    product = 3 * 4
+    ## Documented.
+    bar = foo + product
+    bar";
+        assert_eq!(run(code, 1, "3 * 4", "product").unwrap(), expected);
+
+        let expected = r"main =
+    foo = 2
+    sum = foo + 3 * 4
+    ## Documented.
+    bar = sum
+    bar";
+        assert_eq!(run(code, 1, "foo + 3 * 4", "sum").unwrap(), expected);
+    }
+
+    #[test]
+    fn test_extract_variable_refused() {
+        let code = r"main =
+    foo = 2
+    bar = x -> x + foo
+    bar";
+        let fails_with = |extracted, name, expected: fn(&failure::Error) -> bool| {
+            assert!(expected(&run(code, 1, extracted, name).unwrap_err()));
+        };
+        let captured = |error: &failure::Error| {
+            error.downcast_ref::<ExtractedExpressionUsesLocalVariable>().is_some()
+        };
+        fails_with("x + foo", "sum", captured);
+        fails_with("+", "sum", |error| error.downcast_ref::<CannotExtract>().is_some());
+        fails_with("foo", "bar", |error| error.downcast_ref::<NameAlreadyInUse>().is_some());
+        fails_with("foo", "Bar", |error| error.downcast_ref::<NotAVariableName>().is_some());
+    }
+}
@@ -0,0 +1,339 @@
+//! Module with logic for inlining a method call, i.e. the inverse of node collapsing.
+//!
+//! See the [`inline`] function for details.
+
+use crate::prelude::*;
+
+use crate::alias_analysis;
+use crate::definition::DefinitionInfo;
+use crate::definition::DefinitionName;
+use crate::graph::GraphInfo;
+use crate::node;
+use crate::node::MainLine;
+use crate::node::NodeInfo;
+use crate::refactorings::inline_variable::copy_expression;
+
+use ast::crumbs::Crumbable;
+use ast::known;
+use ast::BlockLine;
+use parser::Parser;
+
+
+
+// ==================
+// === Inline API ===
+// ==================
+
+// === Entry point ===
+
+/// Run the "inline method" refactoring. Returns the updated definition.
+///
+/// The given node must be a call of the `method` with all its arguments applied. The node is
+/// replaced with the lines of the method's body, where each parameter usage is replaced with a
+/// copy of the corresponding argument. The value of the last body line becomes the node's value,
+/// so the node keeps its id and its variable. This is the inverse of the
+/// [collapse](crate::refactorings::collapse) refactoring.
+///
+/// The refactoring is refused if the names introduced by the method's body collide with the names
+/// used or introduced in the graph, or if the body uses a name from outside the method that would
+/// refer to a variable in the graph after inlining.
+pub fn inline(
+    graph: &GraphInfo,
+    node: node::Id,
+    method: &DefinitionInfo,
+    parser: &Parser,
+) -> FallibleResult<DefinitionInfo> {
+    let mut node_info = graph.locate_node(node)?.node;
+    let chain = ast::prefix::Chain::from_ast_non_strict(&node_info.expression());
+    let parameters = method.args.iter().map(|arg| parameter_name(&arg.item));
+    let parameters = parameters.collect::<FallibleResult<Vec<_>>>()?;
+    if parameters.len() != chain.args.len() {
+        let expected = parameters.len();
+        return Err(ArgumentCountMismatch { expected, given: chain.args.len() }.into());
+    }
+    let arguments = chain.args.into_iter().map(|arg| arg.sast.wrapped);
+    let arguments: HashMap<_, _> = parameters.into_iter().zip(arguments).collect();
+
+    let mut lines = method.block_lines().into_iter().filter_map(|line| line.elem).collect_vec();
+    let last_line = lines.pop().ok_or(MissingLineWithAst)?;
+    let value = match MainLine::from_ast(&last_line) {
+        Some(main_line) if main_line.pattern().is_none() => main_line.expression(),
+        _ => return Err(MethodDoesNotEndWithExpression(last_line.repr()).into()),
+    };
+
+    let body = GraphInfo::from_definition(method.clone());
+    let body_usage = body.identifier_usage();
+    let introduced = body_usage.introduced.iter().map(|name| &name.item).collect::<HashSet<_>>();
+    let node_variable = node_info.pattern().and_then(ast::identifier::name).map(ToOwned::to_owned);
+    let value_name =
+        ast::identifier::name(&value).filter(|name| introduced.contains(&name.to_string()));
+    let reused_variable = value_name.filter(|name| Some(*name) == node_variable.as_deref());
+    let graph_usage = graph.identifier_usage();
+    let graph_introduced = graph.introduced_names();
+    let graph_used = graph_usage.used.iter().map(|name| &name.item);
+    let graph_names = graph_introduced.iter().chain(graph_used).collect::<HashSet<_>>();
+    for name in &introduced {
+        if graph_names.contains(name) && Some(name.as_str()) != reused_variable {
+            return Err(NameConflict((*name).clone()).into());
+        }
+    }
+    for name in &body_usage.used {
+        let is_free = !introduced.contains(&name.item) && !arguments.contains_key(&name.item);
+        if is_free && graph_introduced.contains(&name.item) {
+            return Err(NameConflict(name.item.clone()).into());
+        }
+    }
+
+    let substitute = |line: &Ast| substitute_arguments(&with_fresh_ids(line), &arguments, parser);
+    let mut lines = lines.iter().map(substitute).collect::<FallibleResult<Vec<_>>>()?;
+    let value = substitute(&value)?;
+    let mut updated = graph.clone();
+    let (lines_before, lines_after) = if let Some(name) = reused_variable {
+        // The node's variable is introduced by the body line, which takes the node's place.
+        let is_node_line = |line: &&Ast| {
+            let pattern = MainLine::from_ast(line).and_then(|line| line.pattern().cloned());
+            pattern.map_or(false, |pattern| ast::identifier::name(&pattern) == Some(name))
+        };
+        let index = lines.iter().rposition(|line| is_node_line(&line)).ok_or(MissingLineWithAst)?;
+        let mut main_line = MainLine::from_ast(&lines[index]).ok_or(MissingLineWithAst)?;
+        main_line.set_id(node);
+        let documentation = node_info.documentation;
+        updated.set_node(&NodeInfo { documentation, main_line })?;
+        let mut lines_after = lines.split_off(index);
+        lines_after.remove(0);
+        (lines, lines_after)
+    } else {
+        node_info.set_expression(value);
+        updated.set_node(&node_info)?;
+        (lines, default())
+    };
+    let index = updated.locate_node(node)?.index;
+    let mut block_lines = updated.source.block_lines();
+    let to_block_line = |elem| BlockLine { elem: Some(elem), off: 0 };
+    let after = index.last() + 1;
+    block_lines.splice(after..after, lines_after.into_iter().map(to_block_line));
+    let before = index.first();
+    block_lines.splice(before..before, lines_before.into_iter().map(to_block_line));
+    updated.source.set_block_lines(block_lines)?;
+    Ok(updated.source)
+}
+
+/// Get the name of the method called by the given node, if the method is defined in the module
+/// with the given name.
+///
+/// The method may be called either with an explicit module qualifier (like `Main.foo a b`) or
+/// with a bare name (like `foo a b`), as long as the name does not refer to a variable.
+pub fn called_method(
+    graph: &GraphInfo,
+    node: node::Id,
+    module_name: &str,
+) -> FallibleResult<DefinitionName> {
+    let expression = graph.locate_node(node)?.expression();
+    let chain = ast::prefix::Chain::from_ast_non_strict(&expression);
+    let name = if let Some(access) = ast::opr::to_access(&chain.func) {
+        let module = known::Cons::try_from(&access.larg).ok();
+        module
+            .filter(|module| module.name == module_name)
+            .and(known::Var::try_from(&access.rarg).ok())
+    } else {
+        let var = known::Var::try_from(&chain.func).ok();
+        var.filter(|var| !graph.introduced_names().contains(&var.name))
+    };
+    let name = name.ok_or(NotAMethodCall(node))?;
+    Ok(DefinitionName::new_plain(name.name.clone()))
+}
+
+fn parameter_name(parameter: &Ast) -> FallibleResult<String> {
+    let var = known::Var::try_from(parameter).map_err(|_| UnsupportedParameter(parameter.repr()));
+    Ok(var?.name.clone())
+}
+
+/// Replace usages of the method's parameters in the given line with copies of the arguments.
+fn substitute_arguments(
+    line: &Ast,
+    arguments: &HashMap<String, Ast>,
+    parser: &Parser,
+) -> FallibleResult<Ast> {
+    let expression_id = MainLine::from_ast(line).map(|main_line| main_line.id());
+    let usages = alias_analysis::analyze_ast(line).used;
+    let usages = usages
+        .into_iter()
+        .filter_map(|usage| arguments.get(&usage.item).map(|argument| (usage.crumbs, argument)));
+    let usages = usages.collect_vec();
+    let mut line = line.clone();
+    for (crumbs, argument) in &usages {
+        let is_whole_expression = line.get_traversing(crumbs)?.id == expression_id;
+        let copy = copy_expression(argument, !is_whole_expression, parser)?;
+        line = line.set_traversing(crumbs, copy)?;
+    }
+    for (crumbs, _) in &usages {
+        if let Some(name) = alias_analysis::captured_identifier(&line, crumbs)? {
+            return Err(ArgumentWouldBeCaptured(name).into());
+        }
+    }
+    Ok(line)
+}
+
+/// Copy the AST, generating new IDs for all its nodes that have one.
+fn with_fresh_ids(ast: &Ast) -> Ast {
+    let mut copy = if ast.id.is_some() { ast.with_new_id() } else { ast.clone() };
+    for crumb in ast.iter_subcrumbs().collect_vec() {
+        if let Ok(child) = ast.get(&crumb) {
+            copy = copy.set(&crumb, with_fresh_ids(child)).unwrap_or(copy);
+        }
+    }
+    copy
+}
+
+
+// === Errors ===
+
+#[allow(missing_docs)]
+#[derive(Clone, Copy, Debug, Fail)]
+#[fail(display = "The node {} is not a call of a method from the current module.", _0)]
+pub struct NotAMethodCall(node::Id);
+
+#[allow(missing_docs)]
+#[derive(Clone, Copy, Debug, Fail)]
+#[fail(display = "The method expects {} arguments, but {} are given.", expected, given)]
+pub struct ArgumentCountMismatch {
+    expected: usize,
+    given:    usize,
+}
+
+#[allow(missing_docs)]
+#[derive(Clone, Debug, Fail)]
+#[fail(display = "The method parameter `{}` is not supported by inlining.", _0)]
+pub struct UnsupportedParameter(String);
+
+#[allow(missing_docs)]
+#[derive(Clone, Debug, Fail)]
+#[fail(display = "The method's last line `{}` is not an expression.", _0)]
+pub struct MethodDoesNotEndWithExpression(String);
+
+#[allow(missing_docs)]
+#[derive(Clone, Debug, Fail)]
+#[fail(display = "The name `{}` would conflict with the name used in the graph.", _0)]
+pub struct NameConflict(String);
+
+#[allow(missing_docs)]
+#[derive(Clone, Debug, Fail)]
+#[fail(display = "The argument identifier `{}` would refer to a local binding.", _0)]
+pub struct ArgumentWouldBeCaptured(String);
+
+#[allow(missing_docs)]
+#[derive(Clone, Copy, Debug, Fail)]
+#[fail(display = "Internal refactoring error: The method's body has no line with an expression.")]
+pub struct MissingLineWithAst;
+
+
+
+// ============
+// === Test ===
+// ============
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::definition;
+    use crate::module;
+
+    fn run(code: &str, node_line: usize) -> FallibleResult<String> {
+        let parser = Parser::new();
+        let ast = parser.parse_module(code, default()).unwrap();
+        let main = module::locate_child(&ast, &DefinitionName::new_plain("main")).unwrap();
+        let graph = GraphInfo::from_definition(main.item.clone());
+        let node = graph.nodes()[node_line].id();
+        let method_name = called_method(&graph, node, "Main")?;
+        let method = module::locate_child(&ast, &method_name)?;
+        let inlined = inline(&graph, node, &method.item, &parser)?;
+        let mut module = module::Info { ast: ast.clone_ref() };
+        let id = definition::Id::new_plain_name("main");
+        module.update_definition(&id, |_| Ok(inlined.clone()))?;
+        ast::test_utils::assert_unique_ids(module.ast.as_ref());
+        assert!(GraphInfo::from_definition(inlined.clone()).find_node(node).is_some());
+        Ok(inlined.ast.repr())
+    }
+
+    #[test]
+    fn test_inline() {
+        let method = r"custom_new a =
+    b = 2
+    c = A + B
+    d = a + b
+    c";
+        let code = format!(
+            r"{method}
+
+main =
+    a = 1
+    c = Main.custom_new a
+    c + 7"
+        );
+        let expected = r"main =
+    a = 1
+    b = 2
+    c = A + B
+    d = a + b
+    c + 7";
+        assert_eq!(run(&code, 1).unwrap(), expected);
+
+        let code = r"custom_new a b = a + b
+
+main =
+    x = 1
+    custom_new x (2 * 3)
+    c + 7";
+        let expected = r"main =
+    x = 1
+    x + (2 * 3)
+    c + 7";
+        assert_eq!(run(code, 1).unwrap(), expected);
+
+        let code = r"custom_new number =
+    range = number.up_to 10
+    range.to_vector
+
+main =
+    vector = Main.custom_new 1+2
+    vector";
+        let expected = r"main =
+    range = (1+2).up_to 10
+    vector = range.to_vector
+    vector";
+        assert_eq!(run(code, 0).unwrap(), expected);
+    }
+
+    #[test]
+    fn test_inline_refused() {
+        let code = r"foo x =
+    b = x + 1
+    b + d
+
+main =
+    b = 5
+    d = 6
+    c = foo b
+    e = foo
+    f = b
+    c + d";
+        let fails_with = |node_line, expected: fn(&failure::Error) -> bool| {
+            assert!(expected(&run(code, node_line).unwrap_err()));
+        };
+        // The body introduces `b`, which is also introduced in the graph.
+        fails_with(2, |error| error.downcast_ref::<NameConflict>().is_some());
+        // Not all arguments are applied.
+        fails_with(3, |error| error.downcast_ref::<ArgumentCountMismatch>().is_some());
+        // The `b` is a variable, not a method.
+        fails_with(4, |error| error.downcast_ref::<NotAMethodCall>().is_some());
+
+        // The `d` used by the body would refer to the graph's variable.
+        let code = r"foo x = x + d
+
+main =
+    d = 6
+    foo d";
+        assert!(run(code, 1).unwrap_err().downcast::<NameConflict>().is_ok());
+    }
+}
@@ -0,0 +1,169 @@
+//! Module with logic for inlining the variable introduced by a node.
+//!
+//! See the [`inline_variable`] function for details.
+
+use crate::prelude::*;
+
+use crate::alias_analysis;
+use crate::definition::DefinitionInfo;
+use crate::graph::GraphInfo;
+use crate::node;
+use crate::refactorings::rename::node_variable;
+use crate::refactorings::rename::variable_usages;
+
+use parser::Parser;
+
+
+
+// ===========================
+// === Inline Variable API ===
+// ===========================
+
+// === Entry point ===
+
+/// Run the "inline variable" refactoring. Returns the updated definition.
+///
+/// The given node is removed from the graph, and each usage of the variable it introduces is
+/// replaced with a copy of the node's expression. The copies are parenthesized where needed.
+///
+/// The refactoring is refused if the node is the last one in the graph (as it provides the value
+/// of the whole definition), or if any identifier used by the expression would be captured by a
+/// local binding (e.g. a lambda argument) at some usage site.
+pub fn inline_variable(
+    graph: &GraphInfo,
+    node: node::Id,
+    parser: &Parser,
+) -> FallibleResult<DefinitionInfo> {
+    let last_node = graph.nodes().last().map(|node| node.id());
+    if last_node == Some(node) {
+        return Err(CannotInlineLastNode(node).into());
+    }
+    node_variable(graph, node)?;
+    let expression = graph.locate_node(node)?.expression();
+    let mut updated = graph.clone();
+    for (id, crumbs) in variable_usages(graph, node) {
+        let mut user = updated.locate_node(id)?.node;
+        for crumbs in &crumbs {
+            let is_whole_expression = user.ast().get_traversing(crumbs)?.id == Some(id);
+            let copy = copy_expression(&expression, !is_whole_expression, parser)?;
+            user.set_ast_at(crumbs, copy)?;
+        }
+        for crumbs in &crumbs {
+            if let Some(name) = alias_analysis::captured_identifier(user.ast(), crumbs)? {
+                return Err(IdentifierWouldBeCaptured(name, id).into());
+            }
+        }
+        updated.set_node(&user)?;
+    }
+    updated.remove_node(node)?;
+    Ok(updated.source)
+}
+
+/// Make a copy of the expression to be put in place of an identifier. The copy has new IDs, so it
+/// can coexist with the original in a single module.
+///
+/// If `parenthesize` is set, the copy is put in parentheses, unless it is atomic (like an
+/// identifier or a literal). This way it can be safely used e.g. as an operand.
+pub fn copy_expression(
+    expression: &Ast,
+    parenthesize: bool,
+    parser: &Parser,
+) -> FallibleResult<Ast> {
+    let is_atomic = match expression.shape() {
+        ast::Shape::Tree(tree) =>
+            tree.type_info == ast::TreeType::Group || tree.leaf_info.is_some(),
+        ast::Shape::Prefix(_)
+        | ast::Shape::Infix(_)
+        | ast::Shape::SectionLeft(_)
+        | ast::Shape::SectionRight(_)
+        | ast::Shape::SectionSides(_)
+        | ast::Shape::Block(_)
+        | ast::Shape::Module(_) => false,
+        _ => true,
+    };
+    if parenthesize && !is_atomic {
+        parser.parse_line_ast(format!("({})", expression.repr()))
+    } else {
+        parser.parse_line_ast(expression.repr())
+    }
+}
+
+
+// === Errors ===
+
+#[allow(missing_docs)]
+#[derive(Clone, Copy, Debug, Fail)]
+#[fail(display = "The node {} is the last one in the graph and cannot be inlined.", _0)]
+pub struct CannotInlineLastNode(node::Id);
+
+#[allow(missing_docs)]
+#[derive(Clone, Debug, Fail)]
+#[fail(display = "The identifier `{}` would refer to a local binding in node {}.", _0, _1)]
+pub struct IdentifierWouldBeCaptured(String, node::Id);
+
+
+
+// ============
+// === Test ===
+// ============
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::definition::DefinitionName;
+    use crate::module;
+    use crate::refactorings::rename;
+
+    fn run(code: &str, node_line: usize) -> FallibleResult<String> {
+        let parser = Parser::new();
+        let ast = parser.parse_module(code, default()).unwrap();
+        let main = module::locate_child(&ast, &DefinitionName::new_plain("main")).unwrap();
+        let graph = GraphInfo::from_definition(main.item.clone());
+        let node = graph.nodes()[node_line].id();
+        let inlined = inline_variable(&graph, node, &parser)?;
+        ast::test_utils::assert_unique_ids(inlined.ast.as_ref());
+        Ok(inlined.ast.repr())
+    }
+
+    #[test]
+    fn test_inline_variable() {
+        let code = r"main =
+    foo = 2 + 3
+    bar = foo * 4
+    baz = foo
+    qux = x -> foo.to_text + x
+    bar + baz + qux";
+        let expected = r"main =
+    bar = (2 + 3) * 4
+    baz = 2 + 3
+    qux = x -> (2 + 3).to_text + x
+    bar + baz + qux";
+        assert_eq!(run(code, 0).unwrap(), expected);
+
+        let code = r"main =
+    foo = 'text'
+    bar = foo.length
+    bar";
+        let expected = r"main =
+    bar = 'text'.length
+    bar";
+        assert_eq!(run(code, 0).unwrap(), expected);
+    }
+
+    #[test]
+    fn test_inline_variable_refused() {
+        let code = r"main x =
+    foo = x + 1
+    bar = x -> foo
+    baz = foo
+    baz";
+        // The `x` in the lambda would refer to the argument.
+        assert!(run(code, 0).unwrap_err().downcast::<IdentifierWouldBeCaptured>().is_ok());
+        // The last node provides the definition's value.
+        assert!(run(code, 3).unwrap_err().downcast::<CannotInlineLastNode>().is_ok());
+        // The node does not introduce a variable.
+        let error = run("main =\n    foo\n    bar", 0).unwrap_err();
+        assert!(error.downcast::<rename::NodeHasNoVariable>().is_ok());
+    }
+}
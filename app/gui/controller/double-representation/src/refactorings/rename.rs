@@ -0,0 +1,198 @@
+//! Module with logic for renaming the variable introduced by a node.
+//!
+//! See the [`rename`] function for details.
+
+use crate::prelude::*;
+
+use crate::alias_analysis;
+use crate::definition::DefinitionInfo;
+use crate::graph::GraphInfo;
+use crate::identifier::Identifier;
+use crate::node;
+
+use ast::crumbs::Crumbs;
+use ast::known;
+
+
+
+// ==================
+// === Rename API ===
+// ==================
+
+// === Entry point ===
+
+/// Run the "rename variable" refactoring. Returns the updated definition.
+///
+/// The variable introduced by the given node is renamed, together with all its usages in the
+/// graph. The usages are the destinations of the node's outgoing connections, so identifiers that
+/// merely have the same name but are bound elsewhere (e.g. lambda arguments) are left intact.
+///
+/// The refactoring is refused if it would change the meaning of the code, i.e. if:
+/// 1) the new name is already introduced into the graph's scope;
+/// 2) the new name is used by some node from an outer scope (the usage would be captured by the
+///    renamed variable);
+/// 3) some usage of the variable is in a scope where the new name is bound locally (the usage would
+///    be captured by the local binding).
+pub fn rename(
+    graph: &GraphInfo,
+    node: node::Id,
+    new_name: &Identifier,
+) -> FallibleResult<DefinitionInfo> {
+    let variable = node_variable(graph, node)?;
+    if variable.name == new_name.name() {
+        return Ok(graph.source.clone());
+    }
+    let new_name = new_variable(graph, new_name)?;
+
+    let usages = variable_usages(graph, node);
+    let mut updated = graph.clone();
+    let mut renamed_node = updated.locate_node(node)?.node;
+    renamed_node.set_pattern(variable.ast().with_shape(ast::Var { name: new_name.name.clone() }));
+    updated.set_node(&renamed_node)?;
+    for (id, crumbs) in usages {
+        let mut user = updated.locate_node(id)?.node;
+        for crumbs in &crumbs {
+            let usage = user.ast().get_traversing(crumbs)?;
+            let renamed = usage.with_shape(ast::Var { name: new_name.name.clone() });
+            user.set_ast_at(crumbs, renamed)?;
+        }
+        for crumbs in &crumbs {
+            if alias_analysis::captured_identifier(user.ast(), crumbs)?.is_some() {
+                return Err(UsageWouldBeCaptured(new_name.name.clone(), id).into());
+            }
+        }
+        updated.set_node(&user)?;
+    }
+    Ok(updated.source)
+}
+
+/// Get the variable that the node's pattern consists of.
+pub fn node_variable(graph: &GraphInfo, node: node::Id) -> FallibleResult<known::Var> {
+    let node_info = graph.locate_node(node)?.node;
+    let pattern = node_info.pattern().ok_or(NodeHasNoVariable(node))?;
+    Ok(known::Var::try_from(pattern).map_err(|_| NodeHasNoVariable(node))?)
+}
+
+/// Check if the identifier can be used as a name of a new variable in the graph, i.e. it is a
+/// variable name that is neither introduced into the graph's scope nor used by any node.
+pub fn new_variable(graph: &GraphInfo, name: &Identifier) -> FallibleResult<known::Var> {
+    let variable = known::Var::try_from(Ast::from(name));
+    let variable = variable.map_err(|_| NotAVariableName(name.to_string()))?;
+    let usage = graph.identifier_usage();
+    let is_used = usage.used.iter().any(|used| used.item == variable.name);
+    if is_used || graph.introduced_names().contains(&variable.name) {
+        Err(NameAlreadyInUse(variable.name.clone()).into())
+    } else {
+        Ok(variable)
+    }
+}
+
+/// Get the usages of the variables introduced by the node: the crumbs of the identifiers in the
+/// nodes that use them, relative to the nodes' ASTs.
+pub fn variable_usages(graph: &GraphInfo, node: node::Id) -> HashMap<node::Id, Vec<Crumbs>> {
+    let mut usages = HashMap::<node::Id, Vec<Crumbs>>::new();
+    for connection in graph.connections() {
+        if connection.source.node == node {
+            let destination = connection.destination;
+            usages.entry(destination.node).or_default().push(destination.crumbs);
+        }
+    }
+    usages
+}
+
+
+// === Errors ===
+
+#[allow(missing_docs)]
+#[derive(Clone, Copy, Debug, Fail)]
+#[fail(display = "The node {} does not assign its value to a single variable.", _0)]
+pub struct NodeHasNoVariable(node::Id);
+
+#[allow(missing_docs)]
+#[derive(Clone, Debug, Fail)]
+#[fail(display = "The `{}` is not a valid variable name.", _0)]
+pub struct NotAVariableName(String);
+
+#[allow(missing_docs)]
+#[derive(Clone, Debug, Fail)]
+#[fail(display = "The name `{}` is already used in the graph.", _0)]
+pub struct NameAlreadyInUse(String);
+
+#[allow(missing_docs)]
+#[derive(Clone, Debug, Fail)]
+#[fail(display = "The variable usage in node {} would refer to its local binding `{}`.", _1, _0)]
+pub struct UsageWouldBeCaptured(String, node::Id);
+
+
+
+// ============
+// === Test ===
+// ============
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::definition::DefinitionName;
+    use crate::module;
+
+    use parser::Parser;
+
+    fn run(code: &str, node_line: usize, new_name: &str) -> FallibleResult<String> {
+        let parser = Parser::new();
+        let ast = parser.parse_module(code, default()).unwrap();
+        let main = module::locate_child(&ast, &DefinitionName::new_plain("main")).unwrap();
+        let graph = GraphInfo::from_definition(main.item.clone());
+        let node = graph.nodes()[node_line].id();
+        let new_name = Identifier::try_from(new_name).unwrap();
+        rename(&graph, node, &new_name).map(|definition| definition.ast.repr())
+    }
+
+    #[test]
+    fn test_rename() {
+        let code = r"main =
+    foo = 2
+    bar = foo + 1
+    baz = x -> foo + x
+    foo.to_text";
+        let expected = r"main =
+    number = 2
+    bar = number + 1
+    baz = x -> number + x
+    number.to_text";
+        assert_eq!(run(code, 0, "number").unwrap(), expected);
+        assert_eq!(run(code, 0, "foo").unwrap(), code);
+
+        // Shadowing lambda argument is not a usage of the renamed variable.
+        let code = r"main =
+    foo = 2
+    bar = foo -> foo + 1";
+        let expected = r"main =
+    number = 2
+    bar = foo -> foo + 1";
+        assert_eq!(run(code, 0, "number").unwrap(), expected);
+    }
+
+    #[test]
+    fn test_rename_refused() {
+        let code = r"main arg =
+    foo = 2
+    bar = foo + baz
+    qux = x -> foo + x";
+        let fails_with = |new_name, expected: fn(&failure::Error) -> bool| {
+            assert!(expected(&run(code, 0, new_name).unwrap_err()));
+        };
+        // Already introduced by other node or argument.
+        fails_with("bar", |error| error.downcast_ref::<NameAlreadyInUse>().is_some());
+        fails_with("arg", |error| error.downcast_ref::<NameAlreadyInUse>().is_some());
+        // Used from the outer scope: `baz` would refer to `foo`'s value.
+        fails_with("baz", |error| error.downcast_ref::<NameAlreadyInUse>().is_some());
+        // The usage in the lambda would refer to its argument.
+        fails_with("x", |error| error.downcast_ref::<UsageWouldBeCaptured>().is_some());
+        // Not a variable name.
+        fails_with("Foo", |error| error.downcast_ref::<NotAVariableName>().is_some());
+        // The node does not introduce a variable.
+        let error = run("main =\n    foo\n    bar", 0, "baz").unwrap_err();
+        assert!(error.downcast::<NodeHasNoVariable>().is_ok());
+    }
+}
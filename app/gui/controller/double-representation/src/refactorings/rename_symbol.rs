@@ -0,0 +1,571 @@
+//! Module with logic for renaming a module method or a type across the modules of a project.
+//!
+//! See the [`rename_symbol`] function for details.
+
+use crate::prelude::*;
+
+use crate::alias_analysis;
+use crate::definition::DefinitionInfo;
+use crate::identifier::Identifier;
+use crate::import;
+use crate::import::ImportedNames;
+use crate::module;
+use crate::name::NamePath;
+use crate::name::QualifiedName;
+
+use ast::crumbs::Crumb;
+use ast::crumbs::InfixCrumb;
+use ast::known;
+use parser::Parser;
+
+
+
+// =========================
+// === Rename Symbol API ===
+// =========================
+
+/// The keyword introducing a type definition.
+const TYPE_KEYWORD: &str = "type";
+
+
+// === SymbolKind ===
+
+/// The kinds of symbols that can be renamed.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum SymbolKind {
+    /// A method defined at the top level of a module.
+    Method,
+    /// A type defined at the top level of a module.
+    Type,
+}
+
+
+// === RenamedSymbol ===
+
+/// Description of a module method or a type being renamed.
+#[derive(Clone, Debug)]
+pub struct RenamedSymbol {
+    #[allow(missing_docs)]
+    pub kind:     SymbolKind,
+    /// The module where the symbol is defined.
+    pub module:   QualifiedName,
+    /// The current name of the symbol.
+    pub name:     String,
+    /// The name the symbol is renamed to.
+    pub new_name: String,
+}
+
+impl RenamedSymbol {
+    /// Create a description of renaming a module method. Fails if the new name is not a valid
+    /// method name.
+    pub fn new_method(
+        module: QualifiedName,
+        name: impl Into<String>,
+        new_name: &Identifier,
+    ) -> FallibleResult<Self> {
+        let variable = known::Var::try_from(Ast::from(new_name));
+        let variable = variable.map_err(|_| NotAMethodName(new_name.to_string()))?;
+        let kind = SymbolKind::Method;
+        Ok(Self { kind, module, name: name.into(), new_name: variable.name.clone() })
+    }
+
+    /// Create a description of renaming a type. Fails if the new name is not a valid type name.
+    pub fn new_type(
+        module: QualifiedName,
+        name: impl Into<String>,
+        new_name: &Identifier,
+    ) -> FallibleResult<Self> {
+        let constructor = known::Cons::try_from(Ast::from(new_name));
+        let constructor = constructor.map_err(|_| NotATypeName(new_name.to_string()))?;
+        let kind = SymbolKind::Type;
+        Ok(Self { kind, module, name: name.into(), new_name: constructor.name.clone() })
+    }
+
+    /// Check if the import declaration imports the module where the symbol is defined.
+    fn is_imported_by(&self, import: &import::Info) -> bool {
+        import.qualified_module_name().map_or(false, |module| module == self.module)
+    }
+
+    /// Check if the import declaration imports the renamed type itself, like
+    /// `import local.Project.Lib.Foo`.
+    fn is_type_imported_by(&self, import: &import::Info) -> bool {
+        let Some((name, module)) = import.module.split_last() else { return false };
+        let is_module = self.full_qualifiers().iter().any(|qualifier| qualifier == module);
+        self.kind == SymbolKind::Type && name.as_str() == self.name && is_module
+    }
+
+    /// The name paths by which the symbol's module may be referred to, regardless of imports.
+    fn full_qualifiers(&self) -> Vec<NamePath> {
+        let segments = self.module.segments().cloned().collect();
+        let with_main = self.module.segments_with_main_segment().map(ImString::new).collect();
+        vec![segments, with_main]
+    }
+
+    /// Create an AST of an identifier with the new name.
+    fn new_identifier(&self, ast: &Ast) -> Ast {
+        let name = self.new_name.clone();
+        match self.kind {
+            SymbolKind::Method => ast.with_shape(ast::Var { name }),
+            SymbolKind::Type => ast.with_shape(ast::Cons { name }),
+        }
+    }
+}
+
+
+// === Entry point ===
+
+/// Run the "rename symbol" refactoring on a single module of the project. Returns the updated
+/// module, or [`None`] if the module does not refer to the renamed symbol.
+///
+/// The following occurrences of the symbol's name are renamed:
+/// 1) the symbol's definition, if `module_name` is the module where it is defined;
+/// 2) the name in import declarations listing the imported (or hidden) names;
+/// 3) the qualified usages, like `Module.method`, where `Module` refers to the symbol's module
+///    through a qualified import, an import alias or the module's full path;
+/// 4) the unqualified usages, when the symbol is defined in or imported into the module, and its
+///    name is not shadowed by a local binding.
+///
+/// For a type, the usages include references to its constructors (like `Foo.Bar` in expressions
+/// and `case` patterns), and the targets of extension methods (like `Foo.method self = ...`). The
+/// import declarations of the type itself (like `import local.Project.Lib.Foo` or
+/// `from local.Project.Lib.Foo import all`) are rewritten too. The constructors themselves are
+/// not renamed, even if one has the type's name.
+///
+/// The method calls on values (like `value.method`) are not renamed, as resolving them requires
+/// type information. Renaming the extension methods is not supported.
+///
+/// The refactoring is refused if the new name would collide with a name defined in the module, or
+/// if an unqualified usage of the method would be captured by a local binding.
+pub fn rename_symbol(
+    module: &module::Info,
+    module_name: &QualifiedName,
+    renamed: &RenamedSymbol,
+    parser: &Parser,
+) -> FallibleResult<Option<module::Info>> {
+    let is_defining_module = *module_name == renamed.module;
+    let defined_names = match renamed.kind {
+        SymbolKind::Method => top_level_methods(module).collect::<HashSet<_>>(),
+        SymbolKind::Type => top_level_types(module).collect(),
+    };
+    let mut updated = module.clone();
+    let mut is_changed = false;
+    let mut qualifiers = renamed.full_qualifiers();
+    let mut is_imported_unqualified = is_defining_module;
+    if is_defining_module {
+        qualifiers.push(vec![module_name.name().into()]);
+    }
+
+    for (crumb, import) in module.enumerate_imports() {
+        let mut rewritten = import.clone();
+        if renamed.is_imported_by(&import) {
+            match &mut rewritten.imported {
+                ImportedNames::Module { alias } => {
+                    let alias = alias.as_ref().map(ImString::new);
+                    let alias = alias.unwrap_or_else(|| renamed.module.alias_name().clone());
+                    qualifiers.push(vec![alias]);
+                }
+                ImportedNames::All => is_imported_unqualified = true,
+                ImportedNames::AllExcept { not_imported } =>
+                    if not_imported.remove(&renamed.name) {
+                        not_imported.insert(renamed.new_name.clone());
+                    } else {
+                        is_imported_unqualified = true;
+                    },
+                ImportedNames::List { names } =>
+                    if names.remove(&renamed.name) {
+                        names.insert(renamed.new_name.clone());
+                        is_imported_unqualified = true;
+                    },
+            }
+        } else if renamed.is_type_imported_by(&import) {
+            if let Some(name) = rewritten.module.last_mut() {
+                *name = renamed.new_name.clone().into();
+            }
+            if import.imported == (ImportedNames::Module { alias: None }) {
+                is_imported_unqualified = true;
+            }
+        }
+        if rewritten != import {
+            let import_ast = parser.parse_line_ast(rewritten.to_string())?;
+            set_line(&mut updated, crumb.line_index, import_ast);
+            is_changed = true;
+        }
+    }
+    // The module's own definition shadows the imported one.
+    if !is_defining_module && defined_names.contains(&renamed.name) {
+        is_imported_unqualified = false;
+    }
+    if (is_defining_module || is_imported_unqualified) && defined_names.contains(&renamed.new_name)
+    {
+        return Err(NameAlreadyDefined(renamed.new_name.clone(), module_name.to_string()).into());
+    }
+    let is_renamed = |qualifier: &Option<NamePath>| match qualifier {
+        Some(qualifier) => qualifiers.contains(qualifier),
+        None => is_imported_unqualified,
+    };
+
+    for (crumb, line) in module.ast.shape().enumerate() {
+        if import::Info::from_ast(line).is_some() {
+            continue;
+        }
+        let usage = alias_analysis::analyze_ast(line);
+        let mut renamed_line = line.clone();
+        let mut renamed_usages = Vec::new();
+        let mut unqualified_usages = Vec::new();
+        let definition = DefinitionInfo::from_root_line_ast(line);
+        match renamed.kind {
+            SymbolKind::Method if is_defining_module && defines_method(line, &renamed.name) => {
+                let definition_name = usage.introduced.iter().find(|n| n.item == renamed.name);
+                renamed_usages.extend(definition_name.map(|name| name.crumbs.clone()));
+            }
+            SymbolKind::Type => {
+                if is_defining_module && type_definition_name(line) == Some(&renamed.name) {
+                    renamed_line = rename_type_definition(&renamed_line, &renamed.new_name);
+                    is_changed = true;
+                }
+                let extended = definition.as_ref().and_then(extended_type);
+                let extended = extended.filter(|(_, name, _)| *name == renamed.name);
+                if let Some((qualifier, _, crumbs)) = extended {
+                    let qualifier = (!qualifier.is_empty()).then_some(qualifier);
+                    if is_renamed(&qualifier) {
+                        renamed_usages.push(crumbs);
+                    }
+                }
+            }
+            SymbolKind::Method => {}
+        }
+        for name in usage.used.iter().filter(|name| name.item == renamed.name) {
+            let qualifier = qualifier_of(line, &name.crumbs);
+            if is_renamed(&qualifier) && !renamed_usages.contains(&name.crumbs) {
+                renamed_usages.push(name.crumbs.clone());
+                if qualifier.is_none() {
+                    unqualified_usages.push(&name.crumbs);
+                }
+            }
+        }
+        for crumbs in &renamed_usages {
+            renamed_line = rename_at(&renamed_line, crumbs, renamed)?;
+        }
+        if renamed.kind == SymbolKind::Method {
+            for crumbs in unqualified_usages {
+                if alias_analysis::captured_identifier(&renamed_line, crumbs)?.is_some() {
+                    return Err(UsageWouldBeCaptured(renamed.new_name.clone()).into());
+                }
+            }
+        }
+        if renamed_line != *line {
+            set_line(&mut updated, crumb.line_index, renamed_line);
+            is_changed = true;
+        }
+    }
+    Ok(is_changed.then_some(updated))
+}
+
+/// Names of the methods defined directly in the module (not including the extension methods).
+fn top_level_methods(module: &module::Info) -> impl Iterator<Item = String> + '_ {
+    let lines = module.ast.shape().enumerate();
+    let definitions = lines.filter_map(|(_, line)| DefinitionInfo::from_root_line_ast(line));
+    let plain = definitions.filter(|definition| definition.name.extended_target.is_empty());
+    plain.map(|definition| definition.name.name.item.clone())
+}
+
+/// Names of the types defined in the module.
+fn top_level_types(module: &module::Info) -> impl Iterator<Item = String> + '_ {
+    let lines = module.ast.shape().enumerate();
+    lines.filter_map(|(_, line)| type_definition_name(line).cloned())
+}
+
+fn defines_method(line: &Ast, name: &str) -> bool {
+    let definition = DefinitionInfo::from_root_line_ast(line);
+    let definition_name = definition.map(|definition| definition.name.item);
+    definition_name.map_or(false, |def| def.extended_target.is_empty() && def.name.item == name)
+}
+
+/// If the line is a type definition, like `type Foo`, returns the name of the type.
+fn type_definition_name(line: &Ast) -> Option<&String> {
+    let ast::Shape::Tree(tree) = line.shape() else { return None };
+    let mut tokens = tree.span_info.iter().filter_map(|seed| match seed {
+        ast::SpanSeed::Token(ast::SpanSeedToken { token }) => Some(token),
+        _ => None,
+    });
+    let is_type_definition = matches!(tree.span_info.first(), Some(ast::SpanSeed::Token(_)));
+    let keyword = tokens.next().filter(|_| is_type_definition)?;
+    (keyword == TYPE_KEYWORD).and_option_from(|| tokens.next())
+}
+
+/// Change the name of the type defined by the line; see [`type_definition_name`].
+fn rename_type_definition(line: &Ast, new_name: &str) -> Ast {
+    let mut shape = line.shape().clone();
+    if let ast::Shape::Tree(tree) = &mut shape {
+        let mut tokens = tree.span_info.iter_mut().filter_map(|seed| match seed {
+            ast::SpanSeed::Token(ast::SpanSeedToken { token }) => Some(token),
+            _ => None,
+        });
+        if let Some(name) = tokens.nth(1) {
+            *name = new_name.to_owned();
+        }
+    }
+    line.with_shape(shape)
+}
+
+/// If the definition is an extension method, returns the qualifier and the name of the extended
+/// type (like `Lib` and `Foo` for `Lib.Foo.method`), and the crumbs of the type's name.
+fn extended_type(definition: &DefinitionInfo) -> Option<(NamePath, String, Vec<Crumb>)> {
+    let (name, qualifier) = definition.name.extended_target.split_last()?;
+    let qualifier = qualifier.iter().map(|segment| ImString::new(&segment.item)).collect();
+    let crumbs = definition.name.crumbs.iter().chain(&name.crumbs).cloned().collect();
+    Some((qualifier, name.item.clone(), crumbs))
+}
+
+/// If the identifier at given crumbs is a right operand of the access operator, returns the name
+/// path on the left side of the operator.
+fn qualifier_of(ast: &Ast, crumbs: &[Crumb]) -> Option<NamePath> {
+    let (last, parent_crumbs) = crumbs.split_last()?;
+    let is_right_operand = *last == Crumb::Infix(InfixCrumb::RightOperand);
+    let parent = ast.get_traversing(parent_crumbs).ok()?;
+    let access = is_right_operand.and_option_from(|| ast::opr::to_access(parent))?;
+    Some(name_path(&access.larg).unwrap_or_default())
+}
+
+/// The segments of the name path like `Standard.Base.Data`.
+fn name_path(ast: &Ast) -> Option<NamePath> {
+    if let Some(access) = ast::opr::to_access(ast) {
+        let mut path = name_path(&access.larg)?;
+        path.push(ast::identifier::name(&access.rarg)?.into());
+        Some(path)
+    } else {
+        Some(vec![ast::identifier::name(ast)?.into()])
+    }
+}
+
+fn rename_at(ast: &Ast, crumbs: &[Crumb], renamed: &RenamedSymbol) -> FallibleResult<Ast> {
+    let identifier = ast.get_traversing(crumbs)?;
+    ast.set_traversing(crumbs, renamed.new_identifier(identifier))
+}
+
+fn set_line(module: &mut module::Info, index: usize, ast: Ast) {
+    module.ast.update_shape(|shape| shape.lines[index].elem = Some(ast));
+}
+
+
+// === Errors ===
+
+#[allow(missing_docs)]
+#[derive(Clone, Debug, Fail)]
+#[fail(display = "The `{}` is not a valid method name.", _0)]
+pub struct NotAMethodName(String);
+
+#[allow(missing_docs)]
+#[derive(Clone, Debug, Fail)]
+#[fail(display = "The `{}` is not a valid type name.", _0)]
+pub struct NotATypeName(String);
+
+#[allow(missing_docs)]
+#[derive(Clone, Debug, Fail)]
+#[fail(display = "The name `{}` is already defined in module {}.", _0, _1)]
+pub struct NameAlreadyDefined(String, String);
+
+#[allow(missing_docs)]
+#[derive(Clone, Debug, Fail)]
+#[fail(display = "The method usage would refer to a local binding `{}`.", _0)]
+pub struct UsageWouldBeCaptured(String);
+
+
+
+// ============
+// === Test ===
+// ============
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn run(code: &str, module_name: &str) -> FallibleResult<Option<String>> {
+        let defining_module = QualifiedName::from_text("local.Project.Lib")?;
+        let new_name = Identifier::try_from("bar")?;
+        let renamed = RenamedSymbol::new_method(defining_module, "foo", &new_name)?;
+        rename(code, module_name, &renamed)
+    }
+
+    fn run_type(code: &str, module_name: &str) -> FallibleResult<Option<String>> {
+        let defining_module = QualifiedName::from_text("local.Project.Lib")?;
+        let new_name = Identifier::try_from("Baz")?;
+        let renamed = RenamedSymbol::new_type(defining_module, "Foo", &new_name)?;
+        rename(code, module_name, &renamed)
+    }
+
+    fn rename(
+        code: &str,
+        module_name: &str,
+        renamed: &RenamedSymbol,
+    ) -> FallibleResult<Option<String>> {
+        let parser = Parser::new();
+        let module = module::Info { ast: parser.parse_module(code, default())? };
+        let module_name = QualifiedName::from_text(module_name)?;
+        let updated = rename_symbol(&module, &module_name, renamed, &parser)?;
+        if let Some(updated) = &updated {
+            ast::test_utils::assert_unique_ids(updated.ast.as_ref());
+        }
+        Ok(updated.map(|module| module.ast.repr()))
+    }
+
+    #[test]
+    fn test_rename_in_defining_module() {
+        let code = r"foo a = a + 1
+
+Integer.foo self = 2
+
+main =
+    x = foo 1
+    y = Lib.foo x
+    z = x.foo
+    w = foo -> foo + 1
+    local.Project.Lib.foo y";
+        let expected = r"bar a = a + 1
+
+Integer.foo self = 2
+
+main =
+    x = bar 1
+    y = Lib.bar x
+    z = x.foo
+    w = foo -> foo + 1
+    local.Project.Lib.bar y";
+        assert_eq!(run(code, "local.Project.Lib").unwrap().unwrap(), expected);
+    }
+
+    #[test]
+    fn test_rename_in_importing_modules() {
+        let code = r"import local.Project.Lib
+import local.Project.Lib as L
+from local.Project.Lib import foo, baz
+
+main =
+    x = foo 1
+    y = Lib.foo x
+    L.foo y";
+        let expected = r"import local.Project.Lib
+import local.Project.Lib as L
+from local.Project.Lib import bar, baz
+
+main =
+    x = bar 1
+    y = Lib.bar x
+    L.bar y";
+        assert_eq!(run(code, "local.Project.Main").unwrap().unwrap(), expected);
+
+        let code = r"from local.Project.Lib import all hiding foo
+
+main = Other.foo 1";
+        let expected = r"from local.Project.Lib import all hiding bar
+
+main = Other.foo 1";
+        assert_eq!(run(code, "local.Project.Main").unwrap().unwrap(), expected);
+
+        // The module's own definition shadows the imported one.
+        let code = r"from local.Project.Lib import all
+
+foo = 2
+
+main = foo + Other.foo";
+        assert_eq!(run(code, "local.Project.Main").unwrap(), None);
+    }
+
+    #[test]
+    fn test_rename_refused() {
+        let code = r"from local.Project.Lib import all
+
+bar = 2";
+        let error = run(code, "local.Project.Main").unwrap_err();
+        assert!(error.downcast::<NameAlreadyDefined>().is_ok());
+
+        let code = r"from local.Project.Lib import all
+
+main = bar -> foo bar";
+        let error = run(code, "local.Project.Main").unwrap_err();
+        assert!(error.downcast::<UsageWouldBeCaptured>().is_ok());
+    }
+
+    #[test]
+    fn test_rename_type_in_defining_module() {
+        let code = r"type Foo
+    Bar x
+    Foo y
+
+    is_bar self = case self of
+        Foo.Bar _ -> True
+        Foo.Foo _ -> False
+
+Foo.ext self = 1
+Lib.Foo.ext2 self = 2
+
+sig : Foo -> Foo
+sig x = x
+
+main = Foo.Bar 1 . is_bar";
+        let expected = r"type Baz
+    Bar x
+    Foo y
+
+    is_bar self = case self of
+        Baz.Bar _ -> True
+        Baz.Foo _ -> False
+
+Baz.ext self = 1
+Lib.Baz.ext2 self = 2
+
+sig : Baz -> Baz
+sig x = x
+
+main = Baz.Bar 1 . is_bar";
+        assert_eq!(run_type(code, "local.Project.Lib").unwrap().unwrap(), expected);
+    }
+
+    #[test]
+    fn test_rename_type_in_importing_modules() {
+        let code = r"import local.Project.Lib
+from local.Project.Lib import Foo, Other
+
+main =
+    x = Foo.Bar 1
+    Lib.Foo.Bar x";
+        let expected = r"import local.Project.Lib
+from local.Project.Lib import Baz, Other
+
+main =
+    x = Baz.Bar 1
+    Lib.Baz.Bar x";
+        assert_eq!(run_type(code, "local.Project.Main").unwrap().unwrap(), expected);
+
+        let code = r"import local.Project.Lib.Foo
+import local.Project.Lib.Foo as F
+from local.Project.Lib.Foo import all
+
+main = Foo.Bar (F.Bar 1)";
+        let expected = r"import local.Project.Lib.Baz
+import local.Project.Lib.Baz as F
+from local.Project.Lib.Baz import all
+
+main = Baz.Bar (F.Bar 1)";
+        assert_eq!(run_type(code, "local.Project.Main").unwrap().unwrap(), expected);
+
+        let code = r"import local.Project.Other
+
+main = Other.Foo";
+        assert_eq!(run_type(code, "local.Project.Main").unwrap(), None);
+    }
+
+    #[test]
+    fn test_rename_type_refused() {
+        let code = r"type Foo
+
+type Baz";
+        let error = run_type(code, "local.Project.Lib").unwrap_err();
+        assert!(error.downcast::<NameAlreadyDefined>().is_ok());
+
+        let defining_module = QualifiedName::from_text("local.Project.Lib").unwrap();
+        let new_name = Identifier::try_from("baz").unwrap();
+        let error = RenamedSymbol::new_type(defining_module, "Foo", &new_name).unwrap_err();
+        assert!(error.downcast::<NotATypeName>().is_ok());
+    }
+}
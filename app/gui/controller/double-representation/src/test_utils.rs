@@ -48,3 +48,16 @@ impl MarkdownProcessor {
         ret
     }
 }
+
+/// Get the crumbs of the first (in the depth-first order) subtree of `ast` with the given text
+/// representation.
+pub fn crumbs_of(ast: &Ast, repr: &str) -> Option<ast::crumbs::Crumbs> {
+    if ast.repr() == repr {
+        return Some(default());
+    }
+    ast.enumerate().find_map(|(crumb, child)| {
+        let mut crumbs = crumbs_of(child, repr)?;
+        crumbs.insert(0, crumb);
+        Some(crumbs)
+    })
+}
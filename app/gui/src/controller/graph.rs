@@ -35,6 +35,7 @@ use span_tree::SpanTree;
 // === Export ===
 // ==============
 
+pub mod auto_layout;
 pub mod executed;
 pub mod widget;
 
@@ -904,6 +905,46 @@ impl Handle {
         )
     }
 
+    /// Arrange the selected nodes with the [`auto_layout`] algorithm, so that each node is placed
+    /// below the nodes it is connected to. If the selection is empty, all nodes are arranged.
+    ///
+    /// The arranged nodes keep the top-left corner of the area they occupied. The node sizes are
+    /// known only to the view, so they are passed as an argument; the nodes missing in
+    /// `node_sizes` are assumed to be of the [`auto_layout::DEFAULT_NODE_SIZE`]. All positions are
+    /// updated in a single undoable transaction.
+    #[profile(Task)]
+    pub fn auto_layout(
+        &self,
+        selection: impl IntoIterator<Item = node::Id>,
+        node_sizes: &HashMap<node::Id, Vector2>,
+    ) -> FallibleResult {
+        let _transaction_guard = self.get_or_open_transaction("Auto-layout nodes");
+        analytics::remote_log_event("graph::auto_layout");
+        let selection = selection.into_iter().collect::<HashSet<_>>();
+        let nodes = self.nodes()?;
+        let is_arranged = |node: &&Node| selection.is_empty() || selection.contains(&node.id());
+        let nodes = nodes.iter().filter(is_arranged).collect_vec();
+        let size_of = |id| node_sizes.get(&id).copied().unwrap_or(auto_layout::DEFAULT_NODE_SIZE);
+        let positioned =
+            nodes.iter().filter_map(|node| Some((node.position()?, size_of(node.id()))));
+        let left = positioned.clone().map(|(position, _)| position.vector.x).reduce(f32::min);
+        let top =
+            positioned.map(|(position, size)| position.vector.y + size.y / 2.0).reduce(f32::max);
+        let origin = Vector2(left.unwrap_or_default(), top.unwrap_or_default());
+        let node_ids = nodes.iter().map(|node| node.id()).collect_vec();
+        let connections = self.graph_info()?.connections();
+        let positions = auto_layout::layout(&node_ids, &connections, node_sizes, origin);
+        for (id, position) in positions {
+            self.module.with_node_metadata(
+                id,
+                Box::new(move |md| {
+                    md.position = Some(position);
+                }),
+            )?;
+        }
+        Ok(())
+    }
+
     /// Mark the node as skipped by prepending "SKIP" macro call to its AST.
     pub fn set_node_action_skip(&self, node_id: ast::Id, skip: bool) -> FallibleResult {
         self.update_node(node_id, |mut node| {
@@ -960,6 +1001,77 @@ impl Handle {
         Ok(collapsed_node)
     }
 
+    /// Inlines the method called by the given node, i.e. replaces the node with the lines of the
+    /// method's body. This is the inverse of [`Self::collapse`].
+    ///
+    /// Only the methods defined in the current module may be inlined.
+    #[profile(Task)]
+    pub fn inline(&self, node: node::Id) -> FallibleResult {
+        let _transaction_guard = self.get_or_open_transaction("Inline method");
+        analytics::remote_log_event("graph::inline");
+        use double_representation::refactorings::inline::called_method;
+        use double_representation::refactorings::inline::inline;
+        let graph = self.graph_info()?;
+        let method_name = called_method(&graph, node, self.module.name())?;
+        info!("Inlining method {method_name} called by node {node}.");
+        let method = module::locate_child(&self.module.ast(), &method_name)?;
+        let updated_definition = inline(&graph, node, &method.item, &self.parser)?;
+        self.update_definition_ast(|_| Ok(updated_definition))
+    }
+
+    /// Extracts the part of node's expression denoted by the span tree crumbs to a new node placed
+    /// before the given one. Returns the id of the new node.
+    #[profile(Task)]
+    pub fn extract_variable(
+        &self,
+        node: node::Id,
+        crumbs: &span_tree::Crumbs,
+        context: &impl SpanTreeContext,
+    ) -> FallibleResult<node::Id> {
+        let _transaction_guard = self.get_or_open_transaction("Extract variable");
+        analytics::remote_log_event("graph::extract_variable");
+        use double_representation::refactorings::extract_variable::extract_variable;
+        use double_representation::refactorings::extract_variable::ExtractedVariable;
+        let node_ast = self.node_info(node)?.expression();
+        let node_span_tree: SpanTree = SpanTree::new(&node_ast, context)?;
+        let ast_crumbs = node_span_tree.get_node(crumbs)?.ast_crumbs;
+        let extracted = node_ast.get_traversing(&ast_crumbs)?;
+        let graph = self.graph_info()?;
+        let used_names = self.used_names()?.into_iter().map(|located_name| located_name.item);
+        let unavailable = used_names.chain(graph.introduced_names());
+        let name = generate_name(name_for_ast(extracted), unavailable)?;
+        let extracted = extract_variable(&graph, node, &ast_crumbs, &name)?;
+        let ExtractedVariable { updated_definition, new_node } = extracted;
+        self.update_definition_ast(|_| Ok(updated_definition))?;
+        Ok(new_node)
+    }
+
+    /// Inlines the variable introduced by the given node, removing the node and replacing the
+    /// variable usages with its expression.
+    #[profile(Task)]
+    pub fn inline_variable(&self, node: node::Id) -> FallibleResult {
+        let _transaction_guard = self.get_or_open_transaction("Inline variable");
+        analytics::remote_log_event("graph::inline_variable");
+        use double_representation::refactorings::inline_variable::inline_variable;
+        let graph = self.graph_info()?;
+        let updated_definition = inline_variable(&graph, node, &self.parser)?;
+        self.update_definition_ast(|_| Ok(updated_definition))
+    }
+
+    /// Renames the variable introduced by the given node, together with all its usages in the
+    /// graph.
+    #[profile(Task)]
+    pub fn rename_variable(&self, node: node::Id, new_name: &str) -> FallibleResult {
+        let _transaction_guard = self.get_or_open_transaction("Rename variable");
+        analytics::remote_log_event("graph::rename_variable");
+        use double_representation::identifier::Identifier;
+        use double_representation::refactorings::rename::rename;
+        let new_name = Identifier::from_text(new_name)?;
+        let graph = self.graph_info()?;
+        let updated_definition = rename(&graph, node, &new_name)?;
+        self.update_definition_ast(|_| Ok(updated_definition))
+    }
+
     /// Updates the given node in the definition.
     ///
     /// The function `F` is called with the information with the state of the node so far and
@@ -1378,6 +1490,77 @@ main =
         })
     }
 
+    #[test]
+    fn inlining_method_call() {
+        let mut test = Fixture::set_up();
+        let code = r"
+func1 =
+    a = 10
+    b = 20
+    a
+
+main =
+    a = Mock_Module.func1
+    a + c";
+
+        let expected_code = "
+func1 =
+    a = 10
+    b = 20
+    a
+
+main =
+    a = 10
+    b = 20
+    a + c";
+
+        test.data.code = code.to_owned();
+        test.run(move |graph| async move {
+            let nodes = graph.nodes().unwrap();
+            graph.inline(nodes[0].info.id()).unwrap();
+            model::module::test::expect_code(&*graph.module, expected_code);
+            assert!(graph.node(nodes[0].info.id()).is_ok());
+        })
+    }
+
+    #[test]
+    fn renaming_and_inlining_variables() {
+        let mut test = Fixture::set_up();
+        test.data.code = "main =\n    a = 10\n    b = a + 20\n    b * a".to_owned();
+        test.run(move |graph| async move {
+            let nodes = graph.nodes().unwrap();
+            graph.rename_variable(nodes[0].info.id(), "number").unwrap();
+            let expected_code = "main =\n    number = 10\n    b = number + 20\n    b * number";
+            model::module::test::expect_code(&*graph.module, expected_code);
+            graph.inline_variable(nodes[1].info.id()).unwrap();
+            let expected_code = "main =\n    number = 10\n    (number + 20) * number";
+            model::module::test::expect_code(&*graph.module, expected_code);
+            assert!(graph.rename_variable(nodes[0].info.id(), "Number").is_err());
+        })
+    }
+
+    #[test]
+    fn auto_layout_of_nodes() {
+        let mut test = Fixture::set_up();
+        test.data.code = "main =\n    a = 10\n    b = 20\n    c = a + b\n    c".to_owned();
+        test.run(move |graph| async move {
+            let nodes = graph.nodes().unwrap();
+            let ids = nodes.iter().map(|node| node.id()).collect_vec();
+            graph.set_node_position(ids[0], Vector2(-50.0, 100.0)).unwrap();
+            let sizes = ids.iter().map(|id| (*id, Vector2(100.0, 20.0))).collect();
+            graph.auto_layout(vec![], &sizes).unwrap();
+            let positions = graph.nodes().unwrap().iter().map(|node| node.position()).collect_vec();
+            let position = |x, y| Some(model::module::Position::new(x, y));
+            let expected = [
+                position(-50.0, 100.0),
+                position(98.0, 100.0),
+                position(24.0, 48.0),
+                position(24.0, -4.0),
+            ];
+            assert_eq!(positions, expected);
+        })
+    }
+
     #[test]
     fn graph_controller_doubly_nested_definition() {
         // Tests editing nested definition that requires transforming inline expression into
@@ -0,0 +1,341 @@
+//! Automatic arrangement of the graph's nodes.
+//!
+//! The nodes are arranged with the layered graph drawing (the Sugiyama method), with the data
+//! flowing from the top to the bottom:
+//! 1) each node is assigned to a layer, so that all its inputs are in the layers above it;
+//! 2) the connections spanning more than one layer are split by virtual vertices, one per layer;
+//! 3) the order of the vertices in each layer is chosen to reduce the connection crossings, using
+//!    the barycenter heuristic;
+//! 4) the vertices are placed in rows, each as close as possible below its inputs.
+//!
+//! The result depends only on the nodes' order, connections and sizes, so arranging the same graph
+//! again gives the same positions.
+
+use crate::prelude::*;
+
+use crate::model::module::Position;
+
+use double_representation::connection::Connection;
+use double_representation::node;
+use std::collections::BTreeSet;
+
+
+
+// =================
+// === Constants ===
+// =================
+
+/// The horizontal gap between neighbouring vertices in a layer. It is the graph editor's default
+/// gap between nodes.
+pub const HORIZONTAL_GAP: f32 = 48.0;
+/// The vertical gap between layers. It is the graph editor's default gap between nodes.
+pub const VERTICAL_GAP: f32 = 32.0;
+/// The size assumed for the nodes whose size was not given.
+pub const DEFAULT_NODE_SIZE: Vector2 = Vector2(200.0, 28.0);
+/// The number of top-down and bottom-up passes reordering the layers to reduce crossings.
+const CROSSING_REDUCTION_PASSES: usize = 8;
+
+
+
+// ====================
+// === LayeredGraph ===
+// ====================
+
+/// A vertex of the layered graph: a node or a virtual vertex on a connection spanning many layers.
+#[derive(Clone, Debug)]
+struct Vertex {
+    node:  Option<node::Id>,
+    size:  Vector2,
+    layer: usize,
+    /// The key giving the initial order of the vertices in a layer.
+    order: f32,
+    /// The horizontal position of the vertex's left edge.
+    left:  f32,
+}
+
+impl Vertex {
+    fn center(&self) -> f32 {
+        self.left + self.size.x / 2.0
+    }
+}
+
+/// The graph with vertices assigned to layers and edges connecting only the adjacent layers.
+#[derive(Clone, Debug, Default)]
+struct LayeredGraph {
+    vertices: Vec<Vertex>,
+    /// For each vertex, the connected vertices from the layer above.
+    upper:    Vec<Vec<usize>>,
+    /// For each vertex, the connected vertices from the layer below.
+    lower:    Vec<Vec<usize>>,
+    /// The vertices of each layer, in the left-to-right order.
+    layers:   Vec<Vec<usize>>,
+}
+
+impl LayeredGraph {
+    /// Build the graph from nodes in the code order. The connections going against the code order
+    /// are reversed, so the graph is acyclic.
+    fn new(nodes: &[(node::Id, Vector2)], connections: &[Connection]) -> Self {
+        let index_of = nodes.iter().enumerate().map(|(index, (id, _))| (*id, index));
+        let index_of = index_of.collect::<HashMap<_, _>>();
+        let edges = connections.iter().filter_map(|connection| {
+            let source = *index_of.get(&connection.source.node)?;
+            let destination = *index_of.get(&connection.destination.node)?;
+            (source != destination).then(|| (source.min(destination), source.max(destination)))
+        });
+        let edges = edges.collect::<BTreeSet<_>>();
+        let node_layers = assign_layers(nodes.len(), &edges);
+
+        let mut graph = LayeredGraph::default();
+        for (index, ((id, size), layer)) in nodes.iter().zip(node_layers.iter()).enumerate() {
+            graph.add_vertex(Some(*id), *size, *layer, index as f32);
+        }
+        for (source, destination) in edges {
+            let mut upper = source;
+            for layer in node_layers[source] + 1..node_layers[destination] {
+                // Virtual vertices are initially placed right after their source node.
+                let virtual_vertex = graph.add_vertex(None, default(), layer, source as f32 + 0.5);
+                graph.add_edge(upper, virtual_vertex);
+                upper = virtual_vertex;
+            }
+            graph.add_edge(upper, destination);
+        }
+        for layer in &mut graph.layers {
+            let vertices = &graph.vertices;
+            layer.sort_by(|a, b| vertices[*a].order.total_cmp(&vertices[*b].order));
+        }
+        graph
+    }
+
+    fn add_vertex(
+        &mut self,
+        node: Option<node::Id>,
+        size: Vector2,
+        layer: usize,
+        order: f32,
+    ) -> usize {
+        let index = self.vertices.len();
+        self.vertices.push(Vertex { node, size, layer, order, left: 0.0 });
+        self.upper.push(default());
+        self.lower.push(default());
+        if self.layers.len() <= layer {
+            self.layers.resize(layer + 1, default());
+        }
+        self.layers[layer].push(index);
+        index
+    }
+
+    fn add_edge(&mut self, upper: usize, lower: usize) {
+        self.upper[lower].push(upper);
+        self.lower[upper].push(lower);
+    }
+
+    // === Crossing Reduction ===
+
+    /// Reorder the layers with the barycenter heuristic: each vertex is moved to the mean position
+    /// of its neighbours from the adjacent layer. The passes alternate between going top-down and
+    /// bottom-up, and the ordering with the least crossings is kept.
+    fn reduce_crossings(&mut self) {
+        let mut best = self.layers.clone();
+        let mut best_crossings = self.crossings();
+        let layer_count = self.layers.len();
+        for pass in 0..CROSSING_REDUCTION_PASSES {
+            let is_top_down = pass % 2 == 0;
+            let neighbours = if is_top_down { &self.upper } else { &self.lower };
+            for step in 1..layer_count {
+                let layer = if is_top_down { step } else { layer_count - 1 - step };
+                let positions = self.positions_in_layers();
+                let barycenter = |vertex: &usize| {
+                    let neighbours = &neighbours[*vertex];
+                    let sum = neighbours.iter().map(|n| positions[*n] as f32).sum::<f32>();
+                    if neighbours.is_empty() {
+                        positions[*vertex] as f32
+                    } else {
+                        sum / neighbours.len() as f32
+                    }
+                };
+                let keys = self.layers[layer].iter().map(|v| (*v, barycenter(v))).collect();
+                let keys: HashMap<usize, f32> = keys;
+                self.layers[layer].sort_by(|a, b| keys[a].total_cmp(&keys[b]));
+            }
+            let crossings = self.crossings();
+            if crossings < best_crossings {
+                best = self.layers.clone();
+                best_crossings = crossings;
+            }
+        }
+        self.layers = best;
+    }
+
+    /// The position of each vertex in its layer.
+    fn positions_in_layers(&self) -> Vec<usize> {
+        let mut positions = vec![0; self.vertices.len()];
+        for layer in &self.layers {
+            for (position, vertex) in layer.iter().enumerate() {
+                positions[*vertex] = position;
+            }
+        }
+        positions
+    }
+
+    /// The number of edge crossings between all pairs of the adjacent layers.
+    fn crossings(&self) -> usize {
+        let positions = self.positions_in_layers();
+        let mut crossings = 0;
+        for layer in self.layers.iter().skip(1) {
+            let edges = layer.iter().flat_map(|lower| {
+                self.upper[*lower].iter().map(|upper| (positions[*upper], positions[*lower]))
+            });
+            let edges = edges.collect_vec();
+            for (index, (upper_1, lower_1)) in edges.iter().enumerate() {
+                for (upper_2, lower_2) in &edges[index + 1..] {
+                    let is_crossing = (upper_1 < upper_2 && lower_1 > lower_2)
+                        || (upper_1 > upper_2 && lower_1 < lower_2);
+                    crossings += is_crossing as usize;
+                }
+            }
+        }
+        crossings
+    }
+
+    // === Coordinates ===
+
+    /// Assign the horizontal positions, going top-down. Each vertex is centered under its upper
+    /// neighbours, unless it would overlap the vertex on its left; then it is moved right.
+    fn assign_horizontal_positions(&mut self) {
+        for layer in 0..self.layers.len() {
+            let mut min_left = f32::NEG_INFINITY;
+            for vertex in self.layers[layer].clone() {
+                let upper = &self.upper[vertex];
+                let upper_centers = upper.iter().map(|u| self.vertices[*u].center());
+                let preferred_center = upper_centers.sum::<f32>() / upper.len() as f32;
+                let size = self.vertices[vertex].size;
+                let preferred_left =
+                    if upper.is_empty() { min_left } else { preferred_center - size.x / 2.0 };
+                let left = preferred_left.max(min_left);
+                let left = if left.is_finite() { left } else { 0.0 };
+                self.vertices[vertex].left = left;
+                min_left = left + size.x + HORIZONTAL_GAP;
+            }
+        }
+    }
+
+    /// The vertical position of each layer's top edge, the first layer's top being at zero.
+    fn layer_tops(&self) -> Vec<f32> {
+        let heights = self.layers.iter().map(|layer| {
+            let heights = layer.iter().map(|vertex| self.vertices[*vertex].size.y);
+            heights.fold(0.0, f32::max)
+        });
+        let tops = heights.scan(0.0, |top, height| {
+            let layer_top = *top;
+            *top -= height + VERTICAL_GAP;
+            Some(layer_top)
+        });
+        tops.collect()
+    }
+}
+
+/// Assign the nodes to layers, so that each edge goes from a lower to a higher layer. Each node is
+/// placed in the layer right below its lowest input; the nodes having no inputs are placed right
+/// above their highest output, so they are close to the nodes using them.
+fn assign_layers(node_count: usize, edges: &BTreeSet<(usize, usize)>) -> Vec<usize> {
+    let mut layers = vec![0; node_count];
+    for (source, destination) in edges {
+        layers[*destination] = layers[*destination].max(layers[*source] + 1);
+    }
+    let has_inputs = edges.iter().map(|(_, destination)| *destination).collect::<HashSet<_>>();
+    for node in (0..node_count).rev().filter(|node| !has_inputs.contains(node)) {
+        let outputs = edges.iter().filter(|(source, _)| *source == node);
+        if let Some(highest_output) = outputs.map(|(_, destination)| layers[*destination]).min() {
+            layers[node] = highest_output - 1;
+        }
+    }
+    layers
+}
+
+
+
+// ==============
+// === Layout ===
+// ==============
+
+/// Compute the positions of the nodes, given in the code order. Only the connections between the
+/// given nodes are considered. The nodes missing in `sizes` are assumed to be of the
+/// [`DEFAULT_NODE_SIZE`].
+///
+/// The nodes are arranged in a box whose top-left corner is at `origin`. The returned positions
+/// are the nodes' origins in the graph editor: the middle of the node's left edge.
+pub fn layout(
+    nodes: &[node::Id],
+    connections: &[Connection],
+    sizes: &HashMap<node::Id, Vector2>,
+    origin: Vector2,
+) -> HashMap<node::Id, Position> {
+    let size_of = |id: &node::Id| sizes.get(id).copied().unwrap_or(DEFAULT_NODE_SIZE);
+    let nodes = nodes.iter().map(|id| (*id, size_of(id))).collect_vec();
+    let mut graph = LayeredGraph::new(&nodes, connections);
+    graph.reduce_crossings();
+    graph.assign_horizontal_positions();
+    let tops = graph.layer_tops();
+    let leftmost = graph.vertices.iter().map(|vertex| vertex.left).fold(f32::INFINITY, f32::min);
+    let placed = graph.vertices.iter().filter_map(|vertex| {
+        let x = origin.x + vertex.left - leftmost;
+        let y = origin.y + tops[vertex.layer] - vertex.size.y / 2.0;
+        Some((vertex.node?, Position::new(x, y)))
+    });
+    placed.collect()
+}
+
+
+
+// ============
+// === Test ===
+// ============
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use double_representation::connection::Endpoint;
+
+    fn connection(source: node::Id, destination: node::Id) -> Connection {
+        let source = Endpoint { node: source, crumbs: default() };
+        let destination = Endpoint { node: destination, crumbs: default() };
+        Connection { source, destination }
+    }
+
+    fn arrange(node_count: usize, connections: &[(usize, usize)]) -> Vec<Vector2> {
+        let ids = (0..node_count).map(|_| node::Id::new_v4()).collect_vec();
+        let connections = connections.iter().map(|(s, d)| connection(ids[*s], ids[*d]));
+        let connections = connections.collect_vec();
+        let sizes = ids.iter().map(|id| (*id, Vector2(100.0, 20.0))).collect();
+        let positions = layout(&ids, &connections, &sizes, Vector2(10.0, 0.0));
+        ids.iter().map(|id| positions[id].vector).collect()
+    }
+
+    #[test]
+    fn arranging_chain_and_diamond() {
+        let positions = arrange(3, &[(0, 1), (1, 2)]);
+        assert_eq!(positions, [Vector2(10.0, -10.0), Vector2(10.0, -62.0), Vector2(10.0, -114.0)]);
+
+        // The node without inputs is placed right above its output.
+        let positions = arrange(4, &[(0, 1), (1, 3), (2, 3)]);
+        assert_eq!(positions.iter().map(|p| p.y).collect_vec(), [-10.0, -62.0, -62.0, -114.0]);
+        assert_eq!(positions[3].x, (positions[1].x + positions[2].x) / 2.0);
+        assert_eq!(positions[2].x - positions[1].x, 100.0 + HORIZONTAL_GAP);
+    }
+
+    #[test]
+    fn reducing_crossings() {
+        // Nodes 2 and 3 use nodes 1 and 0 respectively; node 3 should be placed on the left.
+        let positions = arrange(4, &[(1, 2), (0, 3)]);
+        assert!(positions[3].x < positions[2].x);
+        assert_eq!(positions[0].x, positions[3].x);
+        assert_eq!(positions[1].x, positions[2].x);
+
+        // The long connection goes around the node in the middle layer.
+        let positions = arrange(4, &[(0, 1), (1, 3), (0, 3), (2, 3)]);
+        assert_eq!(positions.iter().map(|p| p.y).collect_vec(), [-10.0, -62.0, -62.0, -114.0]);
+        let first = arrange(4, &[(0, 1), (1, 3), (0, 3), (2, 3)]);
+        assert_eq!(positions, first);
+    }
+}
@@ -5,14 +5,23 @@ use crate::prelude::*;
 
 use crate::controller::ide::StatusNotificationPublisher;
 
+use double_representation::identifier::Identifier;
 use double_representation::import;
 use double_representation::name::project;
 use double_representation::name::QualifiedName;
+use double_representation::refactorings::rename_symbol::rename_symbol;
+use double_representation::refactorings::rename_symbol::RenamedSymbol;
 use engine_protocol::language_server::MethodPointer;
 use engine_protocol::language_server::Path;
+use engine_protocol::language_server::TextEdit;
 use enso_frp::web::platform;
 use enso_frp::web::platform::Platform;
+use ensogl::system::web::sleep;
+use futures::future;
+use futures::future::Either;
 use parser::Parser;
+use std::collections::BTreeSet;
+use std::time::Duration;
 
 
 
@@ -28,6 +37,16 @@ pub const COMPILING_STDLIB_LABEL: &str = "Compiling standard library. It can tak
 /// This is the definition whose graph will be opened on IDE start.
 pub const MAIN_DEFINITION_NAME: &str = "main";
 
+/// Path of the file storing the undo-redo history, given as segments relative to the project root.
+///
+/// The history is kept in a separate file rather than in the project's metadata, because the
+/// metadata is stored in the main module, whose every change is recorded in the history itself.
+pub const UNDO_HISTORY_FILE_PATH: [&str; 2] = [".enso", "undo-history.json"];
+
+/// How long the undo-redo history must stay unchanged before it is saved to the
+/// [`UNDO_HISTORY_FILE_PATH`] file.
+pub const UNDO_HISTORY_SAVE_DELAY: Duration = Duration::from_secs(1);
+
 /// The code with definition of the default `main` method.
 pub fn default_main_method_code() -> String {
     format!(r#"{MAIN_DEFINITION_NAME} = "Hello, World!""#)
@@ -49,6 +68,22 @@ pub fn main_method_ptr(
 
 
 
+// ==============
+// === Errors ===
+// ==============
+
+#[allow(missing_docs)]
+#[derive(Clone, Debug, Fail)]
+#[fail(display = "The `{}` is not a module method defined in the project.", _0)]
+pub struct NotAProjectModuleMethod(String);
+
+#[allow(missing_docs)]
+#[derive(Clone, Debug, Fail)]
+#[fail(display = "The `{}` is not a type defined in the project.", _0)]
+pub struct NotAProjectType(String);
+
+
+
 // =================
 // === Utilities ===
 // =================
@@ -113,6 +148,8 @@ impl Project {
         let parser = self.model.parser();
         let module_path = self.initial_module_path();
         let file_path = module_path.file_path().clone();
+        self.restore_undo_history().await;
+        self.save_undo_history_on_change();
 
         // TODO [mwu] This solution to recreate missing main file should be considered provisional
         //   until proper decision is made. See: https://github.com/enso-org/enso/issues/1050
@@ -163,6 +200,58 @@ impl Project {
         Ok(())
     }
 
+    /// Restore the undo-redo history saved in the previous session, if there is any.
+    ///
+    /// The failure is not fatal for the project initialization: in the worst case the history
+    /// is just lost.
+    async fn restore_undo_history(&self) {
+        let rpc = self.model.json_rpc();
+        let path = Path::new(self.model.project_content_root_id(), UNDO_HISTORY_FILE_PATH);
+        let restored = async {
+            if rpc.file_exists(&path).await?.exists {
+                let contents = rpc.read_file(&path).await?.contents;
+                let history = serde_json::from_str(&contents)?;
+                self.model.urm().repository.restore_history(history);
+            }
+            FallibleResult::Ok(())
+        };
+        if let Err(err) = restored.await {
+            warn!("Failed to restore the undo-redo history: {err}");
+        }
+    }
+
+    /// Write the undo-redo history to the [`UNDO_HISTORY_FILE_PATH`] file when it changes. The
+    /// file is written once the history stays unchanged for [`UNDO_HISTORY_SAVE_DELAY`], so a
+    /// burst of changes is saved only once.
+    fn save_undo_history_on_change(&self) {
+        let rpc = self.model.json_rpc();
+        let path = Path::new(self.model.project_content_root_id(), UNDO_HISTORY_FILE_PATH);
+        let repository = self.model.urm().repository.clone_ref();
+        let mut changes = repository.subscribe();
+        let repository = Rc::downgrade(&repository);
+        executor::global::spawn(async move {
+            while changes.next().await.is_some() {
+                loop {
+                    let delay = Box::pin(sleep(UNDO_HISTORY_SAVE_DELAY));
+                    match future::select(delay, changes.next()).await {
+                        Either::Left(_) => break,
+                        Either::Right((Some(_), _)) => continue,
+                        Either::Right((None, _)) => return,
+                    }
+                }
+                let Some(history) = repository.upgrade().map(|r| r.history()) else { return };
+                let saved = async {
+                    let contents = serde_json::to_string(&history)?;
+                    rpc.write_file(&path, &contents).await?;
+                    FallibleResult::Ok(())
+                };
+                if let Err(err) = saved.await {
+                    warn!("Failed to save the undo-redo history: {err}");
+                }
+            }
+        });
+    }
+
     /// Add main method definition to the given module, if the method is not already defined.
     ///
     /// The lookup will be done using the given `main_ptr` value.
@@ -229,6 +318,114 @@ impl Project {
 }
 
 
+// === Project-wide Renaming ===
+
+impl Project {
+    /// Rename the module method defined in the project, together with its usages in all project's
+    /// modules. See [`rename_symbol`] for the description of what usages are renamed.
+    ///
+    /// The method is identified by its fully qualified name, like `local.Project.Lib.foo`. Returns
+    /// the text edits applied to each modified module. If `dry_run` is set, the edits are only
+    /// computed, and the modules are left intact.
+    #[profile(Task)]
+    pub async fn rename_method(
+        &self,
+        method: &QualifiedName,
+        new_name: &str,
+        dry_run: bool,
+    ) -> FallibleResult<HashMap<model::module::Path, Vec<TextEdit>>> {
+        use model::suggestion_database::entry::Kind;
+        let not_a_module_method = || NotAProjectModuleMethod(method.to_string());
+        let suggestion_db = self.model.suggestion_db();
+        let (_, entry) =
+            suggestion_db.lookup_by_qualified_name(method).ok_or_else(not_a_module_method)?;
+        let is_module_method = entry.kind == Kind::Method && entry.has_self_type(&entry.defined_in);
+        let is_in_project = *entry.defined_in.project() == self.model.qualified_name();
+        if !is_module_method || !is_in_project {
+            return Err(not_a_module_method().into());
+        }
+        let new_name = Identifier::from_text(new_name)?;
+        let renamed = RenamedSymbol::new_method(entry.defined_in.clone(), &entry.name, &new_name)?;
+        info!("Renaming method {method} to {}.", renamed.new_name);
+        self.rename_symbol(&renamed, "Rename method", dry_run).await
+    }
+
+    /// Rename the type defined in the project, together with its usages in all project's modules.
+    /// See [`rename_symbol`] for the description of what usages are renamed.
+    ///
+    /// The type is identified by its fully qualified name, like `local.Project.Lib.Foo`. Returns
+    /// the text edits applied to each modified module. If `dry_run` is set, the edits are only
+    /// computed, and the modules are left intact.
+    #[profile(Task)]
+    pub async fn rename_type(
+        &self,
+        type_: &QualifiedName,
+        new_name: &str,
+        dry_run: bool,
+    ) -> FallibleResult<HashMap<model::module::Path, Vec<TextEdit>>> {
+        use model::suggestion_database::entry::Kind;
+        let not_a_type = || NotAProjectType(type_.to_string());
+        let suggestion_db = self.model.suggestion_db();
+        let (_, entry) = suggestion_db.lookup_by_qualified_name(type_).ok_or_else(not_a_type)?;
+        let is_in_project = *entry.defined_in.project() == self.model.qualified_name();
+        if entry.kind != Kind::Type || !is_in_project {
+            return Err(not_a_type().into());
+        }
+        let new_name = Identifier::from_text(new_name)?;
+        let renamed = RenamedSymbol::new_type(entry.defined_in.clone(), &entry.name, &new_name)?;
+        info!("Renaming type {type_} to {}.", renamed.new_name);
+        self.rename_symbol(&renamed, "Rename type", dry_run).await
+    }
+
+    /// Run the "rename symbol" refactoring on all project's modules, applying the changes under
+    /// a single undo-redo transaction unless `dry_run` is set.
+    async fn rename_symbol(
+        &self,
+        renamed: &RenamedSymbol,
+        transaction_name: &str,
+        dry_run: bool,
+    ) -> FallibleResult<HashMap<model::module::Path, Vec<TextEdit>>> {
+        let parser = self.model.parser();
+        let content_root_id = self.model.project_content_root_id();
+        let mut updates = Vec::new();
+        for module_name in self.project_modules(&renamed.module) {
+            let path = model::module::Path::from_id(content_root_id, &module_name.module_id());
+            let module = self.model.module(path.clone()).await?;
+            if let Some(updated) = rename_symbol(&module.info(), &module_name, renamed, &parser)? {
+                updates.push((path, module, updated));
+            }
+        }
+        let edits = updates.iter().map(|(path, module, updated)| {
+            let edit =
+                TextEdit::from_prefix_postfix_differences(module.ast().repr(), updated.ast.repr());
+            (path.clone(), vec![edit])
+        });
+        let edits = edits.collect();
+        if !dry_run {
+            let _transaction_guard = self.model.urm().get_or_open_transaction(transaction_name);
+            for (_, module, updated) in updates {
+                module.update_ast(updated.ast)?;
+            }
+        }
+        Ok(edits)
+    }
+
+    /// The qualified names of the project's modules, as known to the suggestion database. The
+    /// `defining_module` is always included.
+    fn project_modules(&self, defining_module: &QualifiedName) -> BTreeSet<QualifiedName> {
+        use model::suggestion_database::entry::Kind;
+        let suggestion_db = self.model.suggestion_db();
+        let project = self.model.qualified_name();
+        let entries =
+            suggestion_db.keys().into_iter().filter_map(|id| suggestion_db.lookup(id).ok());
+        let modules = entries.filter(|entry| entry.kind == Kind::Module);
+        let modules = modules.map(|entry| entry.defined_in.clone());
+        let project_modules = modules.filter(|module| *module.project() == project);
+        project_modules.chain(iter::once(defining_module.clone())).collect()
+    }
+}
+
+
 // === Project Snapshotting ===
 
 impl Project {
@@ -257,6 +454,7 @@ mod tests {
     use super::*;
 
     use crate::executor::test_utils::TestWithLocalPoolExecutor;
+    use double_representation::refactorings::rename_symbol::SymbolKind;
     use engine_protocol::language_server;
     use std::assert_matches::assert_matches;
 
@@ -344,4 +542,127 @@ mod tests {
             assert_eq!(vcs.commit_count.get(), 3);
         });
     }
+
+
+    // === Project-wide Renaming ===
+
+    const LIB_CODE: &str = "type Foo\n    Bar\n\nfoo = Foo.Bar";
+    const MAIN_CODE: &str = "import mock_namespace.Mock_Project.Lib
+from mock_namespace.Mock_Project.Lib import Foo
+
+main = Lib.foo Foo.Bar";
+
+    /// Mock project with the `Lib` and `Main` modules, whose paths are returned along with it.
+    fn setup_renaming_project() -> (model::Project, model::module::Path, model::module::Path) {
+        use crate::test::mock::data;
+        let parser = Parser::new();
+        let urm = Rc::new(model::undo_redo::Manager::new());
+        let suggestion_db = enso_suggestion_database::mock_suggestion_database! {
+            mock_namespace.Mock_Project {
+                mod Lib {
+                    type Foo {
+                        Bar;
+                    }
+                    static fn foo -> Standard.Base.Any;
+                }
+            }
+        };
+        let mut project = model::project::MockAPI::new();
+        model::project::test::expect_qualified_name(&mut project, &data::project_qualified_name());
+        model::project::test::expect_parser(&mut project, &parser);
+        model::project::test::expect_root_id(&mut project, data::ROOT_ID);
+        model::project::test::expect_suggestion_db(&mut project, Rc::new(suggestion_db));
+        let mut add_module = |name: &str, code: &str| {
+            let path = model::module::Path::from_name_segments(data::ROOT_ID, &[name]).unwrap();
+            let ast = parser.parse_module(code, default()).unwrap();
+            let repository = urm.repository.clone_ref();
+            let module = model::module::Plain::new(path.clone(), ast, default(), repository);
+            let module = Rc::new(module);
+            urm.module_opened(module.clone());
+            model::project::test::expect_module(&mut project, module);
+            path
+        };
+        let lib_path = add_module("Lib", LIB_CODE);
+        let main_path = add_module("Main", MAIN_CODE);
+        project.expect_urm().returning_st(move || urm.clone_ref());
+        (Rc::new(project), lib_path, main_path)
+    }
+
+    /// Rename the symbol in the project created by [`setup_renaming_project`]. Checks the edits
+    /// returned by a dry run, and that the modules are changed only by the actual run.
+    async fn check_renaming(
+        kind: SymbolKind,
+        name: &str,
+        new_name: &str,
+        expected_lib_code: &str,
+        expected_main_code: &str,
+    ) {
+        let (project, lib_path, main_path) = setup_renaming_project();
+        let controller = controller::Project::new(project.clone_ref(), default());
+        let name = QualifiedName::from_text(name).unwrap();
+        let rename = |dry_run| match kind {
+            SymbolKind::Method => controller.rename_method(&name, new_name, dry_run).boxed_local(),
+            SymbolKind::Type => controller.rename_type(&name, new_name, dry_run).boxed_local(),
+        };
+        let code = |path: &model::module::Path| {
+            let module = project.module(path.clone());
+            async move { module.await.unwrap().ast().repr() }
+        };
+        let lib_edit = TextEdit::from_prefix_postfix_differences(LIB_CODE, expected_lib_code);
+        let main_edit = TextEdit::from_prefix_postfix_differences(MAIN_CODE, expected_main_code);
+        let expected_edits = HashMap::from([
+            (lib_path.clone(), vec![lib_edit]),
+            (main_path.clone(), vec![main_edit]),
+        ]);
+
+        let edits = rename(true).await.unwrap();
+        assert_eq!(edits, expected_edits);
+        assert_eq!(code(&lib_path).await, LIB_CODE);
+        assert_eq!(code(&main_path).await, MAIN_CODE);
+
+        let edits = rename(false).await.unwrap();
+        assert_eq!(edits, expected_edits);
+        assert_eq!(code(&lib_path).await, expected_lib_code);
+        assert_eq!(code(&main_path).await, expected_main_code);
+    }
+
+    #[wasm_bindgen_test]
+    fn rename_method() {
+        TestWithLocalPoolExecutor::set_up().run_task(async move {
+            let lib_code = "type Foo\n    Bar\n\nbar = Foo.Bar";
+            let main_code = "import mock_namespace.Mock_Project.Lib
+from mock_namespace.Mock_Project.Lib import Foo
+
+main = Lib.bar Foo.Bar";
+            let method = "mock_namespace.Mock_Project.Lib.foo";
+            check_renaming(SymbolKind::Method, method, "bar", lib_code, main_code).await;
+        });
+    }
+
+    #[wasm_bindgen_test]
+    fn rename_type() {
+        TestWithLocalPoolExecutor::set_up().run_task(async move {
+            let lib_code = "type Baz\n    Bar\n\nfoo = Baz.Bar";
+            let main_code = "import mock_namespace.Mock_Project.Lib
+from mock_namespace.Mock_Project.Lib import Baz
+
+main = Lib.foo Baz.Bar";
+            let type_ = "mock_namespace.Mock_Project.Lib.Foo";
+            check_renaming(SymbolKind::Type, type_, "Baz", lib_code, main_code).await;
+        });
+    }
+
+    #[wasm_bindgen_test]
+    fn renaming_refused() {
+        TestWithLocalPoolExecutor::set_up().run_task(async move {
+            let (project, _, _) = setup_renaming_project();
+            let controller = controller::Project::new(project, default());
+            let method = QualifiedName::from_text("mock_namespace.Mock_Project.Lib.foo").unwrap();
+            let type_ = QualifiedName::from_text("mock_namespace.Mock_Project.Lib.Foo").unwrap();
+            let error = controller.rename_type(&method, "Baz", true).await.unwrap_err();
+            assert!(error.downcast::<NotAProjectType>().is_ok());
+            let error = controller.rename_method(&type_, "bar", true).await.unwrap_err();
+            assert!(error.downcast::<NotAProjectModuleMethod>().is_ok());
+        });
+    }
 }
@@ -147,6 +147,7 @@ mod profile_workflow;
 #[profile(Objective)]
 #[allow(dead_code)]
 pub fn main() {
+    set_profiling_filter();
     // Logging of build information.
     #[cfg(debug_assertions)]
     let debug_mode = true;
@@ -170,3 +171,13 @@ pub fn main() {
     });
     std::mem::forget(executor);
 }
+
+/// Select the profilers that are recorded, if requested by the startup arguments.
+pub fn set_profiling_filter() {
+    if let Some(filter) = &enso_config::ARGS.profiling_filter {
+        match filter.parse() {
+            Ok(filter) => enso_profiler::filter::set(filter),
+            Err(error) => error!("Invalid profiling filter {filter:?}: {error}"),
+        }
+    }
+}
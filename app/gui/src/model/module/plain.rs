@@ -76,6 +76,7 @@ impl Module {
 
         // We want the line below to fail before changing state.
         let new_file = new_content.serialize()?;
+        transaction.fill_final_content(self.id(), new_content.clone());
         let notification = Notification::new(new_file, kind);
         self.content.replace(new_content);
         self.notifications.notify(notification);
@@ -1,9 +1,39 @@
 //! Support for IDE Undo-Redo functionality.
+//!
+//! The history of changes is a tree of [`Frame`]s: undoing a frame and making a new change does
+//! not discard the undone frame, but starts a new branch next to it. The frames store only the
+//! differences of modules' content (see [`diff`]), so the whole history can be persisted between
+//! sessions.
 
 use crate::prelude::*;
 
 use crate::controller;
 
+use flo_stream::Subscriber;
+use parser::Parser;
+use serde::Deserialize;
+use serde::Serialize;
+use std::collections::VecDeque;
+
+
+// ==============
+// === Export ===
+// ==============
+
+pub mod diff;
+
+pub use diff::ContentDiff;
+
+
+
+// =================
+// === Constants ===
+// =================
+
+/// The maximum number of frames kept in the [`History`]. See [`History::prune`] for which frames
+/// are removed when the limit is exceeded.
+pub const MAX_HISTORY_FRAMES: usize = 500;
+
 
 
 // ==============
@@ -24,13 +54,8 @@ pub struct NoActionToUndo;
 
 #[allow(missing_docs)]
 #[derive(Debug, Clone, Copy, Eq, Fail, PartialEq)]
-#[fail(display = "The faux undo transaction was leaked.")]
-pub struct FauxTransactionLeaked;
-
-#[allow(missing_docs)]
-#[derive(Debug, Clone, Copy, Eq, Fail, PartialEq)]
-#[fail(display = "No frame to pop, {} stack is empty.", _0)]
-pub struct NoFrameToPop(Stack);
+#[fail(display = "The frame {} is not a redo branch of the current frame.", _0)]
+pub struct NotARedoBranch(FrameId);
 
 #[allow(missing_docs)]
 #[derive(Debug, Clone, Eq, Fail, PartialEq)]
@@ -68,21 +93,26 @@ pub trait Aware {
 /// Transaction is a RAII-style object used to group a number of actions into a single undoable
 /// operation.
 ///
-/// When the transaction is dropped, it adds itself to the undo stack, unless it was ignored.
+/// When the transaction is dropped, it adds itself to the undo history, unless it was ignored.
 #[derive(Debug)]
 pub struct Transaction {
-    #[allow(missing_docs)]
-    frame:   RefCell<Frame>,
-    urm:     Weak<Repository>,
-    ignored: Cell<bool>,
+    name:           String,
+    /// Content of the edited modules from before the transaction.
+    snapshots:      RefCell<BTreeMap<model::module::Id, model::module::Content>>,
+    /// Content of the edited modules after the last change made in the transaction.
+    final_contents: RefCell<BTreeMap<model::module::Id, model::module::Content>>,
+    urm:            Weak<Repository>,
+    ignored:        Cell<bool>,
 }
 
 impl Transaction {
-    /// Create a new transaction, that will add to the given's repository undo stack on destruction.
+    /// Create a new transaction, that will add to the given's repository history on destruction.
     pub fn new(urm: &Rc<Repository>, name: String) -> Self {
         Self {
-            frame:   RefCell::new(Frame { name, ..default() }),
-            urm:     Rc::downgrade(urm),
+            name,
+            snapshots: default(),
+            final_contents: default(),
+            urm: Rc::downgrade(urm),
             ignored: default(),
         }
     }
@@ -91,7 +121,7 @@ impl Transaction {
     ///
     /// Currently the name serves only debugging purposes.
     pub fn name(&self) -> String {
-        self.frame.borrow().name.clone()
+        self.name.clone()
     }
 
     /// Stores the state of given module.
@@ -103,34 +133,67 @@ impl Transaction {
     /// the current transaction in context where it is not clear whether transaction was already set
     /// up or not.
     pub fn fill_content(&self, id: model::module::Id, content: model::module::Content) {
-        with(self.frame.borrow_mut(), |mut data| {
-            debug!(
-                "Filling transaction '{}' with snapshot of module '{id}':\n{content}",
-                data.name
-            );
-            if data.snapshots.try_insert(id, content).is_err() {
-                debug!("Skipping this snapshot, as module's state was already saved.")
-            }
-        })
+        let name = &self.name;
+        debug!("Filling transaction '{name}' with snapshot of module '{id}':\n{content}");
+        if self.snapshots.borrow_mut().try_insert(id, content).is_err() {
+            debug!("Skipping this snapshot, as module's state was already saved.")
+        }
+    }
+
+    /// Stores the state of given module after the change made in the transaction.
+    ///
+    /// This is the state that will be restored, when the transaction is redone. Unlike
+    /// [`Transaction::fill_content`], every call replaces the previously stored state.
+    pub fn fill_final_content(&self, id: model::module::Id, content: model::module::Content) {
+        self.final_contents.borrow_mut().insert(id, content);
     }
 
     /// Ignore the transaction.
     ///
-    /// Ignored transaction when dropped is discarded, rather than being put on top of "Redo" stack.
+    /// Ignored transaction when dropped is discarded, rather than being added to the history.
     /// It does not affect the actions belonging to transaction in any way.
     pub fn ignore(&self) {
-        info!("Marking transaction '{}' as ignored.", self.frame.borrow().name);
+        info!("Marking transaction '{}' as ignored.", self.name);
         self.ignored.set(true)
     }
+
+    /// Create a frame describing the changes made in this transaction. Returns [`None`] if no
+    /// module was changed.
+    fn frame(&self) -> FallibleResult<Option<Frame>> {
+        let snapshots = self.snapshots.borrow();
+        let final_contents = self.final_contents.borrow();
+        let mut changes = Vec::new();
+        for (id, before) in snapshots.iter() {
+            if let Some(after) = final_contents.get(id) {
+                let before = before.serialize()?;
+                let after = after.serialize()?;
+                let diff = ContentDiff::new(&before, &after);
+                if !diff.is_empty() {
+                    changes.push((id.clone(), diff));
+                }
+            }
+        }
+        let name = self.name.clone();
+        Ok((!changes.is_empty()).then(|| Frame { name, changes, ..default() }))
+    }
 }
 
 impl Drop for Transaction {
     fn drop(&mut self) {
         if let Some(urm) = self.urm.upgrade() {
             if !self.ignored.get() {
-                info!("Transaction '{}' will create a new frame. {}", self.name(), backtrace());
-                urm.push_to(Stack::Undo, self.frame.borrow().clone());
-                urm.clear(Stack::Redo);
+                match self.frame() {
+                    Ok(Some(frame)) => {
+                        info!(
+                            "Transaction '{}' will create a new frame. {}",
+                            self.name,
+                            backtrace()
+                        );
+                        urm.push(frame);
+                    }
+                    Ok(None) => info!("Transaction '{}' did not change anything.", self.name),
+                    Err(err) => error!("Cannot create a frame of '{}': {err}", self.name),
+                }
             } else {
                 info!(
                     "Dropping the ignored transaction '{}' without pushing a frame to repository.",
@@ -147,19 +210,21 @@ impl Drop for Transaction {
 // === Frame ===
 // =============
 
-/// Frame represents a state stored on undo or redo stack.
+/// Frame represents a single undoable change stored in the history.
 ///
-/// [`Manager`] is able to restore project's state to a given `Frame`.
-#[derive(Clone, Debug, Default, PartialEq)]
+/// [`Manager`] is able to undo or redo the changes described by a `Frame`.
+#[derive(Clone, Debug, Default, PartialEq, Deserialize, Serialize)]
 pub struct Frame {
     /// Name of the transaction that created this frame.
-    pub name:      String,
+    pub name:    String,
     /// Context module where the change was made.
-    pub module:    Option<model::module::Id>,
+    #[serde(skip)]
+    pub module:  Option<model::module::Id>,
     /// Context graph where the change was made.
-    pub graph:     Option<controller::graph::Id>,
-    /// Snapshots of content for all edited modules.
-    pub snapshots: BTreeMap<model::module::Id, model::module::Content>,
+    #[serde(skip)]
+    pub graph:   Option<controller::graph::Id>,
+    /// Changes of serialized content of all edited modules.
+    pub changes: Vec<(model::module::Id, ContentDiff)>,
 }
 
 impl Display for Frame {
@@ -171,8 +236,8 @@ impl Display for Frame {
         if let Some(g) = &self.graph {
             write!(f, "Graph: {g}; ")?;
         }
-        for (id, code) in &self.snapshots {
-            write!(f, "Code for {id}: {code}; ")?;
+        for (id, diff) in &self.changes {
+            write!(f, "Changes for {id}: {diff:?}; ")?;
         }
         Ok(())
     }
@@ -180,11 +245,208 @@ impl Display for Frame {
 
 
 
+// ===============
+// === History ===
+// ===============
+
+/// Index of a [`Frame`] in the [`History`].
+pub type FrameId = usize;
+
+/// A [`Frame`] placed in the [`History`] tree.
+#[derive(Clone, Debug, PartialEq, Deserialize, Serialize)]
+pub struct HistoryNode {
+    #[allow(missing_docs)]
+    pub frame:      Frame,
+    /// The frame made right before this one. [`None`] if this is one of the first frames.
+    pub parent:     Option<FrameId>,
+    /// The child frame which will be redone after undoing back to this frame.
+    pub redo_child: Option<FrameId>,
+}
+
+/// The tree of all changes made in the project.
+///
+/// The path from the root to the current frame are the changes that may be undone. Children of
+/// the current frame are the changes that were undone, and may be redone. Making a new change
+/// adds a new child of the current frame, so the previously undone changes are still kept in the
+/// history as another branch.
+#[derive(Clone, Debug, Default, PartialEq, Deserialize, Serialize)]
+pub struct History {
+    /// All frames, in the order they were made.
+    pub nodes:           Vec<HistoryNode>,
+    /// The last applied frame. [`None`] if all frames were undone.
+    pub current:         Option<FrameId>,
+    /// The first frame which will be redone after undoing all frames.
+    pub root_redo_child: Option<FrameId>,
+}
+
+impl History {
+    /// Add a new frame as a child of the current frame, and make it current.
+    ///
+    /// The returned id is valid until the history is [pruned](History::prune).
+    pub fn push(&mut self, frame: Frame) -> FrameId {
+        let id = self.nodes.len();
+        self.nodes.push(HistoryNode { frame, parent: self.current, redo_child: None });
+        self.set_redo_child(self.current, id);
+        self.current = Some(id);
+        id
+    }
+
+    /// Get the frame which would be undone next.
+    pub fn undo_frame(&self) -> Option<&Frame> {
+        self.current.map(|id| &self.nodes[id].frame)
+    }
+
+    /// Get the frame which would be redone next.
+    pub fn redo_frame(&self) -> Option<&Frame> {
+        self.redo_target().map(|id| &self.nodes[id].frame)
+    }
+
+    /// Mark the current frame as undone, making its parent current.
+    pub fn step_back(&mut self) {
+        if let Some(id) = self.current {
+            let parent = self.nodes[id].parent;
+            self.set_redo_child(parent, id);
+            self.current = parent;
+        }
+    }
+
+    /// Mark the next frame to redo as applied.
+    pub fn step_forward(&mut self) {
+        if let Some(id) = self.redo_target() {
+            self.current = Some(id);
+        }
+    }
+
+    /// Get all frames which may be redone from the current state. The most recently made frame is
+    /// the last one.
+    pub fn redo_branches(&self) -> Vec<FrameId> {
+        let is_branch = |(_, node): &(FrameId, &HistoryNode)| node.parent == self.current;
+        self.nodes.iter().enumerate().filter(is_branch).map(|(id, _)| id).collect()
+    }
+
+    /// Choose which of the [redo branches](History::redo_branches) will be redone next.
+    pub fn select_redo_branch(&mut self, id: FrameId) -> FallibleResult {
+        let is_branch = self.nodes.get(id).contains_if(|node| node.parent == self.current);
+        if is_branch {
+            self.set_redo_child(self.current, id);
+            Ok(())
+        } else {
+            Err(NotARedoBranch(id).into())
+        }
+    }
+
+    /// Get number of frames which may be undone.
+    pub fn undo_length(&self) -> usize {
+        iter::successors(self.current, |id| self.nodes[*id].parent).count()
+    }
+
+    /// Get number of frames which may be redone one after another.
+    pub fn redo_length(&self) -> usize {
+        iter::successors(self.redo_target(), |id| self.nodes[*id].redo_child).count()
+    }
+
+    /// Remove frames until at most `max_frames` are left. The frames are removed in order:
+    /// 1) the branches which are neither undone nor redone from the current state, starting from
+    ///    the least recently extended ones;
+    /// 2) the oldest frames which may be undone;
+    /// 3) the last frames which may be redone.
+    ///
+    /// The remaining frames are renumbered, keeping their order.
+    pub fn prune(&mut self, max_frames: usize) {
+        let count = self.nodes.len();
+        if count <= max_frames {
+            return;
+        }
+        let mut undo_path =
+            iter::successors(self.current, |id| self.nodes[*id].parent).collect_vec();
+        undo_path.reverse();
+        let redo_path = iter::successors(self.redo_target(), |id| self.nodes[*id].redo_child);
+        let mut active_path: VecDeque<_> = undo_path.iter().copied().chain(redo_path).collect();
+        let mut is_active = vec![false; count];
+        for id in &active_path {
+            is_active[*id] = true;
+        }
+        // The frames are made after their parents, so the descendants have greater ids.
+        let mut newest_descendant = (0..count).collect_vec();
+        for (id, node) in self.nodes.iter().enumerate().rev() {
+            if let Some(parent) = node.parent {
+                newest_descendant[parent] = newest_descendant[parent].max(newest_descendant[id]);
+            }
+        }
+        let is_branch_root = |id: &FrameId| {
+            let parent = self.nodes[*id].parent;
+            !is_active[*id] && parent.map_or(true, |parent| is_active[parent])
+        };
+        let mut branches = (0..count).filter(is_branch_root).collect_vec();
+        branches.sort_by_key(|id| newest_descendant[*id]);
+
+        let mut is_removed = vec![false; count];
+        let mut removed_count = 0;
+        for branch in branches {
+            if count - removed_count <= max_frames {
+                break;
+            }
+            is_removed[branch] = true;
+            removed_count += 1;
+            for id in branch + 1..count {
+                if self.nodes[id].parent.map_or(false, |parent| is_removed[parent]) {
+                    is_removed[id] = true;
+                    removed_count += 1;
+                }
+            }
+        }
+        let mut undoable_count = undo_path.len();
+        while count - removed_count > max_frames {
+            let removed = if undoable_count > 0 {
+                undoable_count -= 1;
+                active_path.pop_front()
+            } else {
+                active_path.pop_back()
+            };
+            if let Some(id) = removed {
+                is_removed[id] = true;
+                removed_count += 1;
+            }
+        }
+
+        let mut new_ids = vec![None; count];
+        let nodes = mem::take(&mut self.nodes).into_iter().enumerate();
+        for (id, node) in nodes.filter(|(id, _)| !is_removed[*id]) {
+            new_ids[id] = Some(self.nodes.len());
+            self.nodes.push(node);
+        }
+        let new_id = |id: Option<FrameId>| id.and_then(|id| new_ids[id]);
+        for node in &mut self.nodes {
+            node.parent = new_id(node.parent);
+            node.redo_child = new_id(node.redo_child);
+        }
+        self.current = new_id(self.current);
+        let new_root = active_path.front().and_then(|id| new_ids[*id]);
+        self.root_redo_child = new_id(self.root_redo_child).or(new_root);
+    }
+
+    fn redo_target(&self) -> Option<FrameId> {
+        match self.current {
+            Some(id) => self.nodes[id].redo_child,
+            None => self.root_redo_child,
+        }
+    }
+
+    fn set_redo_child(&mut self, parent: Option<FrameId>, child: FrameId) {
+        match parent {
+            Some(parent) => self.nodes[parent].redo_child = Some(child),
+            None => self.root_redo_child = Some(child),
+        }
+    }
+}
+
+
+
 // ==================
 // === Repository ===
 // ==================
 
-/// Identifies a stack in Undo-Redo repository.
+/// Identifies a direction of moving through the Undo-Redo history.
 #[derive(Clone, Copy, Debug, Display, Ord, PartialOrd, Eq, PartialEq)]
 #[allow(missing_docs)]
 pub enum Stack {
@@ -192,18 +454,20 @@ pub enum Stack {
     Redo,
 }
 
+/// Notification emitted by [`Repository`] whenever its [`History`] changes.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct HistoryChanged;
+
 /// The inner state of the Und-Redo repository.
 #[derive(Debug, Default)]
 pub struct Data {
-    /// Undo stack.
-    pub undo:                Vec<Frame>,
-    /// Redo stack.
-    pub redo:                Vec<Frame>,
+    /// The history of changes.
+    pub history:             History,
     /// Currently open transaction (if `Some` and alive).
     pub current_transaction: Option<Weak<Transaction>>,
 }
 
-/// `Repository` stores undo and redo stacks and provides transaction support.
+/// `Repository` stores the undo-redo history and provides transaction support.
 ///
 /// This is the primary type meant to be exposed to entities that want their actions to be
 /// undoable. They can group edits together by keeping a [`Transaction`] handle alive.
@@ -212,7 +476,8 @@ pub struct Data {
 /// can be shared with no consequence on project state.
 #[derive(Debug, Default)]
 pub struct Repository {
-    data: RefCell<Data>,
+    data:          RefCell<Data>,
+    notifications: notification::Publisher<HistoryChanged>,
 }
 
 impl Repository {
@@ -271,64 +536,89 @@ impl Repository {
         }
     }
 
-    /// Borrow given stack.
-    fn borrow(&self, stack: Stack) -> Ref<Vec<Frame>> {
-        let data = self.data.borrow();
-        match stack {
-            Stack::Undo => Ref::map(data, |d| &d.undo),
-            Stack::Redo => Ref::map(data, |d| &d.redo),
-        }
+    /// Subscribe to the changes of the history.
+    pub fn subscribe(&self) -> Subscriber<HistoryChanged> {
+        self.notifications.subscribe()
     }
 
-    /// Borrow given stack mutably.
-    fn borrow_mut(&self, stack: Stack) -> RefMut<Vec<Frame>> {
-        let data = self.data.borrow_mut();
-        match stack {
-            Stack::Undo => RefMut::map(data, |d| &mut d.undo),
-            Stack::Redo => RefMut::map(data, |d| &mut d.redo),
-        }
+    /// Get a copy of the whole history, e.g. to persist it.
+    pub fn history(&self) -> History {
+        self.data.borrow().history.clone()
     }
 
-    /// Push a new frame to the given stack.
-    fn push_to(&self, stack: Stack, frame: Frame) {
-        debug!("Pushing to {stack} stack a new frame: {frame}");
-        self.borrow_mut(stack).push(frame);
+    /// Replace the history with the given one, e.g. restored from the previous session.
+    pub fn restore_history(&self, history: History) {
+        debug!("Restoring history with {} frames.", history.nodes.len());
+        self.modify_history(|h| {
+            *h = history;
+            h.prune(MAX_HISTORY_FRAMES);
+        });
     }
 
-    /// Clear all frames from the given stack.
-    fn clear(&self, stack: Stack) {
-        debug!("Clearing {stack} stack.");
-        self.borrow_mut(stack).clear();
+    /// Add a new frame to the history, as a child of the current one. The history is then pruned
+    /// to [`MAX_HISTORY_FRAMES`].
+    fn push(&self, frame: Frame) {
+        debug!("Pushing to history a new frame: {frame}");
+        self.modify_history(|history| {
+            history.push(frame);
+            history.prune(MAX_HISTORY_FRAMES);
+        });
     }
 
-    /// Clear all frames from both undo and redo stacks.
+    /// Mark the frame on top of the given stack as undone or redone, respectively.
+    fn step(&self, stack: Stack) {
+        debug!("Moving the current frame in the {stack} direction.");
+        self.modify_history(|history| match stack {
+            Stack::Undo => history.step_back(),
+            Stack::Redo => history.step_forward(),
+        });
+    }
+
+    fn modify_history<R>(&self, f: impl FnOnce(&mut History) -> R) -> R {
+        let result = f(&mut self.data.borrow_mut().history);
+        self.notifications.notify(HistoryChanged);
+        result
+    }
+
+    /// Clear the whole history.
     pub fn clear_all(&self) {
-        for stack in [Stack::Undo, Stack::Redo] {
-            self.clear(stack)
-        }
+        debug!("Clearing history.");
+        self.modify_history(|history| *history = default());
     }
 
-    /// Get the top frame from a given stack. [`Err`] if the stack is empty.
-    ///
-    /// Does *not* pop.
-    pub fn last(&self, stack: Stack) -> FallibleResult<Frame> {
-        self.borrow(stack).last().cloned().ok_or_else(|| NoActionToUndo.into())
+    /// Get the frames which may be redone from the current state. See [`History::redo_branches`].
+    pub fn redo_branches(&self) -> Vec<(FrameId, Frame)> {
+        let data = self.data.borrow();
+        let history = &data.history;
+        let branches = history.redo_branches().into_iter();
+        branches.map(|id| (id, history.nodes[id].frame.clone())).collect()
+    }
+
+    /// Choose which branch will be redone next. See [`History::select_redo_branch`].
+    pub fn select_redo_branch(&self, id: FrameId) -> FallibleResult {
+        self.data.borrow_mut().history.select_redo_branch(id)?;
+        self.notifications.notify(HistoryChanged);
+        Ok(())
     }
 
-    /// Pop the top frame from a given stack. [`Err`] if there are no frames to pop.
-    fn pop(&self, stack: Stack) -> FallibleResult<Frame> {
-        let frame = self.borrow_mut(stack).pop().ok_or(NoFrameToPop(stack))?;
-        debug!(
-            "Popping a frame from {stack}. Remaining length: {}. Frame: {frame}",
-            self.len(stack)
-        );
-        Ok(frame)
+    /// Get the frame which would be undone or redone next. [`Err`] if there is none.
+    pub fn last(&self, stack: Stack) -> FallibleResult<Frame> {
+        let data = self.data.borrow();
+        let frame = match stack {
+            Stack::Undo => data.history.undo_frame(),
+            Stack::Redo => data.history.redo_frame(),
+        };
+        frame.cloned().ok_or_else(|| NoActionToUndo.into())
     }
 
-    /// Get number of frames on a given stack.
+    /// Get number of frames which may be undone or redone one after another.
     #[allow(clippy::len_without_is_empty)]
     pub fn len(&self, stack: Stack) -> usize {
-        self.borrow(stack).len()
+        let history = &self.data.borrow().history;
+        match stack {
+            Stack::Undo => history.undo_length(),
+            Stack::Redo => history.redo_length(),
+        }
     }
 }
 
@@ -344,10 +634,11 @@ impl Repository {
 #[derive(Debug, Default)]
 pub struct Manager {
     #[allow(missing_docs)]
-    /// Repository with undo and redo stacks.
+    /// Repository with the undo-redo history.
     pub repository: Rc<Repository>,
     /// Currently available modules.
     modules:        RefCell<BTreeMap<model::module::Id, model::Module>>,
+    parser:         Parser,
 }
 
 impl Aware for Manager {
@@ -376,77 +667,71 @@ impl Manager {
 
     /// Undo last operation.
     pub fn undo(&self) -> FallibleResult {
-        debug!("Undo requested, stack size is {}.", self.repository.len(Stack::Undo));
-        let frame = self.repository.last(Stack::Undo)?;
+        debug!("Undo requested, history depth is {}.", self.repository.len(Stack::Undo));
+        self.move_along(Stack::Undo)
+    }
 
-        // Before applying undo we create a special transaction. The purpose it two-fold:
+    /// Redo the last undone operation.
+    pub fn redo(&self) -> FallibleResult {
+        debug!("Redo requested, {} frames can be redone.", self.repository.len(Stack::Redo));
+        self.move_along(Stack::Redo)
+    }
+
+    /// Undo or redo the next frame in the history.
+    fn move_along(&self, stack: Stack) -> FallibleResult {
+        let frame = self.repository.last(stack)?;
+
+        // Before applying undo or redo we create a special transaction. The purpose it two-fold:
         // 1) We want to prevent any undo attempt if there is already an ongoing transaction;
         // 2) We want to make sure that any of undo consequences won't create a new transaction,
-        //    leading to a situation when undoing would re-add itself onto the undo stack.
+        //    leading to a situation when undoing would re-add itself to the history.
         // We mark transaction as ignored right after creating, as it is never intended to create a
-        // new undo frame. Instead, frame will be pushed to the redo stack manually.
-        let undo_transaction = self.repository.open_transaction("Undo faux transaction").map_err(
+        // new frame. Instead, the current frame is moved in the history.
+        let faux_transaction = self.repository.open_transaction("Undo faux transaction").map_err(
             |ongoing_transaction| {
                 let transaction_name = ongoing_transaction.name();
                 CannotUndoDuringTransaction { transaction_name }
             },
         )?;
-        undo_transaction.ignore();
-        self.reset_to(&frame)?;
-        let popped = self.repository.pop(Stack::Undo);
-
-        // Sanity check the we popped the same frame as we have just undone. What was on top is
-        // supposed to stay on top, as we maintain an open transaction while undoing.
-        if !popped.contains(&frame) {
-            // No reason to stop the world but should catch our eye in logs.
-            error!("Undone frame mismatch!");
-            debug_assert!(false, "Undone frame mismatch!");
-        }
-
-        let undo_transaction =
-            Rc::try_unwrap(undo_transaction).map_err(|_| FauxTransactionLeaked)?;
-        self.repository.data.borrow_mut().redo.push(undo_transaction.frame.borrow().clone());
-        Ok(())
-    }
-
-    /// Redo the last undone operation.
-    pub fn redo(&self) -> FallibleResult {
-        let frame = self.repository.data.borrow_mut().redo.pop().ok_or(NoActionToUndo)?;
-        let redo_transaction = self.get_or_open_transaction(&frame.name);
-        redo_transaction.ignore();
-        self.reset_to(&frame)?;
-        self.repository.push_to(Stack::Undo, redo_transaction.frame.borrow().clone());
+        faux_transaction.ignore();
+        self.reset_to(&frame, stack)?;
+        self.repository.step(stack);
         Ok(())
     }
 
-    /// Restore all modules affected by the [`Frame`] to their stored state.
-    fn reset_to(&self, frame: &Frame) -> FallibleResult {
-        info!("Resetting to initial state on frame {frame}");
+    /// Restore all modules affected by the [`Frame`] to their state from before (when undoing) or
+    /// after (when redoing) the frame.
+    fn reset_to(&self, frame: &Frame, stack: Stack) -> FallibleResult {
+        info!("Resetting modules ({stack}) on frame {frame}");
 
-        // First we must have all modules resolved. Only then we can start applying changes.
-        // Otherwise, if one of the modules could not be retrieved, we'd risk ending up with
-        // a partially undone operation and inconsistent state.
-        //
-        // In general this should never happen, as we store strong references to all opened modules
-        // and don't allow getting snapshots of modules that are not opened.
+        // First we must have all modules resolved and their new content computed. Only then we can
+        // start applying changes. Otherwise, if one of the modules could not be retrieved, or it
+        // was changed in a way the frame does not apply to it, we'd risk ending up with a partially
+        // undone operation and inconsistent state.
         let module_and_content = with(self.modules.borrow(), |modules| {
             frame
-                .snapshots
+                .changes
                 .iter()
-                .map(|(id, content)| -> FallibleResult<_> {
+                .map(|(id, diff)| -> FallibleResult<_> {
                     let err = || MissingModuleHandle(id.to_string());
                     let module = modules.get(id).cloned().ok_or_else(err)?;
-                    Ok((module, content.clone()))
+                    let current = module.serialized_content()?;
+                    let restored = match stack {
+                        Stack::Undo => diff.revert(&current)?,
+                        Stack::Redo => diff.apply(&current)?,
+                    };
+                    let content = self.parser.parse_with_metadata(restored);
+                    Ok((module, content))
                 })
                 .collect::<FallibleResult<Vec<_>>>()
         })?;
 
         for (module, content) in module_and_content {
-            info!("Undoing on module {}", module.path());
+            info!("Resetting module {}", module.path());
             // The below should never fail, because it can fail only if serialization to code fails.
             // And it cannot fail, as it already underwent this procedure successfully in the past
-            // (we are copying an old state, so it must ba a representable state).
-            module.update_whole(content.clone())?;
+            // (we are restoring an old state, so it must ba a representable state).
+            module.update_whole(content)?;
             // Temporary changes should not leave UR frames, but some frame could be created during
             // editing, so the temporary changes are in the snapshot. We need to remove them after
             // restoring that frame.
@@ -567,6 +852,12 @@ main =
         graph.set_node_position(node.id(), pos1).unwrap();
         graph.set_node_position(node.id(), pos2).unwrap();
 
+        // Moving a node changes only the metadata, so the frame stores just the changed position.
+        let frame = urm.repository.last(Stack::Undo).unwrap();
+        let (_, diff) = frame.changes.first().unwrap();
+        assert!(diff.code.is_empty() && diff.id_map.is_empty());
+        assert!(serde_json::to_string(diff).unwrap().len() < 200);
+
         assert_eq!(graph.node(node.id()).unwrap().position(), Some(pos2));
         urm.undo().unwrap();
         assert_eq!(graph.node(node.id()).unwrap().position(), Some(pos1));
@@ -621,4 +912,79 @@ main =
         executed_graph.graph().set_expression(node.info.id(), "4 * 20").unwrap();
         assert!(urm.redo().is_err());
     }
+
+    #[test]
+    fn prune_history() {
+        let frame = |name: &str| Frame { name: name.into(), ..default() };
+        let mut history = History::default();
+        history.push(frame("a"));
+        history.push(frame("b"));
+        history.step_back();
+        history.push(frame("c"));
+        history.step_back();
+        history.push(frame("d"));
+        history.push(frame("e"));
+        history.step_back();
+        history.step_back();
+        let names = |history: &History| history.nodes.iter().map(|n| n.frame.name.clone()).join("");
+        let lengths = |history: &History| (history.undo_length(), history.redo_length());
+
+        // The branches not on the current undo-redo path are removed first, the older one first.
+        history.prune(4);
+        assert_eq!(names(&history), "acde");
+        history.prune(3);
+        assert_eq!(names(&history), "ade");
+        assert_eq!(lengths(&history), (1, 2));
+        assert_eq!(history.redo_frame().map(|f| f.name.as_str()), Some("d"));
+
+        // Then the oldest frames which may be undone.
+        history.prune(2);
+        assert_eq!(names(&history), "de");
+        assert_eq!(lengths(&history), (0, 2));
+        history.step_forward();
+        history.prune(1);
+        assert_eq!(names(&history), "e");
+        assert_eq!(lengths(&history), (0, 1));
+
+        // Then the last frames which may be redone.
+        history.prune(0);
+        assert_eq!(history, History::default());
+    }
+
+    #[wasm_bindgen_test]
+    fn redo_branches() {
+        let fixture = Unified::new().fixture();
+        let Fixture { executed_graph, project, module, searcher, .. } = fixture;
+        drop(searcher);
+
+        let urm = project.urm();
+        let graph = executed_graph.graph();
+        let node_id = graph.nodes().unwrap()[0].info.id();
+
+        // Edit after undo starts a new branch, the undone change is not forgotten.
+        graph.set_expression(node_id, "5 * 20").unwrap();
+        urm.undo().unwrap();
+        graph.set_expression(node_id, "4 * 20").unwrap();
+        assert_eq!(urm.repository.len(Stack::Redo), 0);
+        urm.undo().unwrap();
+        assert_eq!(module.ast().to_string(), "main = \n    2 + 2");
+
+        let branches = urm.repository.redo_branches();
+        assert_eq!(branches.len(), 2);
+        urm.redo().unwrap();
+        assert_eq!(module.ast().to_string(), "main = \n    4 * 20");
+        urm.undo().unwrap();
+        urm.repository.select_redo_branch(branches[0].0).unwrap();
+        urm.redo().unwrap();
+        assert_eq!(module.ast().to_string(), "main = \n    5 * 20");
+
+        // The history may be restored, e.g. in the next session.
+        let json = serde_json::to_string(&urm.repository.history()).unwrap();
+        urm.repository.clear_all();
+        assert!(urm.undo().is_err());
+        urm.repository.restore_history(serde_json::from_str(&json).unwrap());
+        urm.undo().unwrap();
+        assert_eq!(module.ast().to_string(), "main = \n    2 + 2");
+        assert_eq!(urm.repository.len(Stack::Redo), 1);
+    }
 }
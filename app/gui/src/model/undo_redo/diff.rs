@@ -0,0 +1,451 @@
+//! Compact representation of changes made to the module's serialized content.
+//!
+//! Undo-redo frames store [`ContentDiff`]s instead of full module snapshots, so the history can be
+//! kept for the whole session and persisted in a reasonable size. The code is compared by lines.
+//! The id map and the metadata are each serialized as a single line of JSON, so they are compared
+//! by JSON tokens instead; otherwise, e.g. moving a node would store the whole metadata line.
+
+use crate::prelude::*;
+
+use parser::api::SourceFile;
+use serde::Deserialize;
+use serde::Serialize;
+
+
+
+// ==============
+// === Errors ===
+// ==============
+
+#[allow(missing_docs)]
+#[derive(Clone, Debug, Fail)]
+#[fail(display = "The module content does not match the stored change at {} {}.", unit, index)]
+pub struct ContentMismatch {
+    unit:  &'static str,
+    index: usize,
+}
+
+
+
+// ====================
+// === Segmentation ===
+// ====================
+
+/// The units in which the versions of a text are compared.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Segmentation {
+    /// Lines, each with its trailing newline character (if it has any).
+    Lines,
+    /// Fragments of JSON, each ending with a `,`, `:`, `{` or `[` character (except the last one).
+    Json,
+}
+
+impl Segmentation {
+    /// Split the text into segments. Joining the segments restores the text exactly.
+    pub fn split(self, text: &str) -> Vec<&str> {
+        match self {
+            Self::Lines => text.split_inclusive('\n').collect(),
+            Self::Json => text.split_inclusive([',', ':', '{', '[']).collect(),
+        }
+    }
+
+    fn mismatch(self, index: usize) -> ContentMismatch {
+        let unit = match self {
+            Self::Lines => "line",
+            Self::Json => "JSON token",
+        };
+        ContentMismatch { unit, index }
+    }
+}
+
+
+
+// ============
+// === Hunk ===
+// ============
+
+/// A single contiguous change: the segments at `start` of the old text replaced with new ones.
+#[derive(Clone, Debug, Default, Eq, PartialEq, Deserialize, Serialize)]
+pub struct Hunk {
+    /// Index of the first replaced segment in the old text.
+    pub start:    usize,
+    /// Segments removed from the old text.
+    pub removed:  Vec<String>,
+    /// Segments inserted in their place in the new text.
+    pub inserted: Vec<String>,
+}
+
+
+
+// ================
+// === TextDiff ===
+// ================
+
+/// The difference between two versions of a text, stored as a sequence of [`Hunk`]s ordered by
+/// their position. The texts are compared by segments (see [`Segmentation`]); the same
+/// segmentation must be used when applying the diff.
+///
+/// The diff can be applied both ways: [`TextDiff::apply`] turns the old text into the new one,
+/// and [`TextDiff::revert`] does the opposite. Both check that the replaced segments match the
+/// stored ones, so a diff is never applied to a text that changed in the meantime.
+#[derive(Clone, Debug, Default, Eq, PartialEq, Deserialize, Serialize)]
+pub struct TextDiff {
+    #[allow(missing_docs)]
+    pub hunks: Vec<Hunk>,
+}
+
+impl TextDiff {
+    /// Compute the difference between `old` and `new` text.
+    pub fn new(old: &str, new: &str, segmentation: Segmentation) -> Self {
+        let old = segmentation.split(old);
+        let new = segmentation.split(new);
+        let prefix = old.iter().zip(&new).take_while(|(a, b)| a == b).count();
+        let max_suffix = old.len().min(new.len()) - prefix;
+        let suffix =
+            old.iter().rev().zip(new.iter().rev()).take(max_suffix).take_while(|(a, b)| a == b);
+        let suffix = suffix.count();
+        let old_changed = &old[prefix..old.len() - suffix];
+        let new_changed = &new[prefix..new.len() - suffix];
+
+        let mut hunks: Vec<Hunk> = default();
+        let mut pending: Option<Hunk> = None;
+        let mut old_segment = prefix;
+        for operation in edit_script(old_changed, new_changed) {
+            match operation {
+                Operation::Keep => {
+                    hunks.extend(pending.take());
+                    old_segment += 1;
+                }
+                Operation::Remove(segment) => {
+                    let hunk =
+                        pending.get_or_insert_with(|| Hunk { start: old_segment, ..default() });
+                    hunk.removed.push(old_changed[segment].to_owned());
+                    old_segment += 1;
+                }
+                Operation::Insert(segment) => {
+                    let hunk =
+                        pending.get_or_insert_with(|| Hunk { start: old_segment, ..default() });
+                    hunk.inserted.push(new_changed[segment].to_owned());
+                }
+            }
+        }
+        hunks.extend(pending);
+        Self { hunks }
+    }
+
+    /// Check if the diff contains no changes.
+    pub fn is_empty(&self) -> bool {
+        self.hunks.is_empty()
+    }
+
+    /// Turn the old version of the text into the new one.
+    pub fn apply(&self, old: &str, segmentation: Segmentation) -> FallibleResult<String> {
+        let hunks = self.hunks.iter().map(|hunk| (hunk.start, &hunk.removed, &hunk.inserted));
+        Self::replace(old, segmentation, hunks)
+    }
+
+    /// Turn the new version of the text into the old one.
+    pub fn revert(&self, new: &str, segmentation: Segmentation) -> FallibleResult<String> {
+        let mut shift = 0_isize;
+        let hunks = self.hunks.iter().map(|hunk| {
+            let start = (hunk.start as isize + shift) as usize;
+            shift += hunk.inserted.len() as isize - hunk.removed.len() as isize;
+            (start, &hunk.inserted, &hunk.removed)
+        });
+        Self::replace(new, segmentation, hunks)
+    }
+
+    /// Replace the given segment ranges, checking that the replaced segments are as expected.
+    fn replace<'a>(
+        text: &str,
+        segmentation: Segmentation,
+        hunks: impl Iterator<Item = (usize, &'a Vec<String>, &'a Vec<String>)>,
+    ) -> FallibleResult<String> {
+        let segments = segmentation.split(text);
+        let mut result = String::with_capacity(text.len());
+        let mut next_segment = 0;
+        for (start, expected, replacement) in hunks {
+            let mismatch = || segmentation.mismatch(start);
+            let unchanged = segments.get(next_segment..start).ok_or_else(mismatch)?;
+            let replaced = segments.get(start..start + expected.len()).ok_or_else(mismatch)?;
+            if !replaced.iter().eq(expected.iter()) {
+                return Err(mismatch().into());
+            }
+            result.extend(unchanged.iter().copied());
+            result.extend(replacement.iter().map(String::as_str));
+            next_segment = start + expected.len();
+        }
+        result.extend(segments[next_segment..].iter().copied());
+        Ok(result)
+    }
+}
+
+
+
+// ===================
+// === ContentDiff ===
+// ===================
+
+/// The difference between two versions of a module's serialized content: the differences of its
+/// code, id map and metadata sections. The text around the sections is not stored; it is taken
+/// from the content the diff is applied to.
+#[derive(Clone, Debug, Default, Eq, PartialEq, Deserialize, Serialize)]
+pub struct ContentDiff {
+    /// Changes of the code, by lines.
+    pub code:     TextDiff,
+    /// Changes of the id map, by JSON tokens.
+    pub id_map:   TextDiff,
+    /// Changes of the metadata, by JSON tokens.
+    pub metadata: TextDiff,
+}
+
+impl ContentDiff {
+    /// Compute the difference between the `old` and `new` content.
+    pub fn new(old: &SourceFile, new: &SourceFile) -> Self {
+        let code = TextDiff::new(old.code_slice(), new.code_slice(), Segmentation::Lines);
+        let id_map = TextDiff::new(old.id_map_slice(), new.id_map_slice(), Segmentation::Json);
+        let metadata =
+            TextDiff::new(old.metadata_slice(), new.metadata_slice(), Segmentation::Json);
+        Self { code, id_map, metadata }
+    }
+
+    /// Check if the diff contains no changes.
+    pub fn is_empty(&self) -> bool {
+        self.code.is_empty() && self.id_map.is_empty() && self.metadata.is_empty()
+    }
+
+    /// Turn the old version of the content into the new one.
+    pub fn apply(&self, old: &SourceFile) -> FallibleResult<String> {
+        self.patch(old, TextDiff::apply)
+    }
+
+    /// Turn the new version of the content into the old one.
+    pub fn revert(&self, new: &SourceFile) -> FallibleResult<String> {
+        self.patch(new, TextDiff::revert)
+    }
+
+    /// Patch each section of the content with its diff.
+    fn patch(
+        &self,
+        file: &SourceFile,
+        patch: impl Fn(&TextDiff, &str, Segmentation) -> FallibleResult<String>,
+    ) -> FallibleResult<String> {
+        let code = patch(&self.code, file.code_slice(), Segmentation::Lines)?;
+        let id_map = patch(&self.id_map, file.id_map_slice(), Segmentation::Json)?;
+        let metadata = patch(&self.metadata, file.metadata_slice(), Segmentation::Json)?;
+        let content = &file.content;
+        let before_id_map = &content[file.code.end.value..file.id_map.start.value];
+        let before_metadata = &content[file.id_map.end.value..file.metadata.start.value];
+        let after_metadata = &content[file.metadata.end.value..];
+        Ok(format!("{code}{before_id_map}{id_map}{before_metadata}{metadata}{after_metadata}"))
+    }
+}
+
+
+
+// ===================
+// === Edit Script ===
+// ===================
+
+/// The maximum number of changed segments for which the shortest edit script is searched. Sequences
+/// differing more are replaced as a whole, which bounds the time and memory used by the search.
+const MAX_EDIT_SCRIPT_COST: isize = 1000;
+
+/// A single step of the edit script turning one sequence of segments into another.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+enum Operation {
+    /// Keep the next segment of both sequences.
+    Keep,
+    /// Remove the segment with the given index from the old sequence.
+    Remove(usize),
+    /// Insert the segment with the given index from the new sequence.
+    Insert(usize),
+}
+
+/// Compute the shortest edit script turning `old` into `new`, using the Myers' algorithm.
+///
+/// The cost is proportional to the sequences' length times the number of changed segments, which
+/// is small for the edits done in the IDE. If more than [`MAX_EDIT_SCRIPT_COST`] segments changed,
+/// the script removes all the old segments and inserts all the new ones.
+fn edit_script(old: &[&str], new: &[&str]) -> Vec<Operation> {
+    let (n, m) = (old.len() as isize, new.len() as isize);
+    let max = n + m;
+    // The furthest reaching `x` for each diagonal `k = x - y`, indexed by `k + max + 1`.
+    let mut furthest = vec![0_isize; 2 * max as usize + 3];
+    let index = |k: isize| (k + max + 1) as usize;
+    // The `furthest` values of diagonals `-d-1..=d+1` from before each step `d`.
+    let mut trace: Vec<Vec<isize>> = default();
+    let mut found = false;
+    'search: for d in 0..=max.min(MAX_EDIT_SCRIPT_COST) {
+        trace.push(furthest[index(-d - 1)..=index(d + 1)].to_vec());
+        for k in (-d..=d).step_by(2) {
+            let mut x = if goes_down(|k| furthest[index(k)], k, d) {
+                furthest[index(k + 1)]
+            } else {
+                furthest[index(k - 1)] + 1
+            };
+            let mut y = x - k;
+            while x < n && y < m && old[x as usize] == new[y as usize] {
+                x += 1;
+                y += 1;
+            }
+            furthest[index(k)] = x;
+            if x >= n && y >= m {
+                found = true;
+                break 'search;
+            }
+        }
+    }
+    if !found {
+        let removals = (0..old.len()).map(Operation::Remove);
+        return removals.chain((0..new.len()).map(Operation::Insert)).collect();
+    }
+
+    let mut script = Vec::new();
+    let (mut x, mut y) = (n, m);
+    for (d, stored) in trace.iter().enumerate().rev() {
+        let d = d as isize;
+        let furthest = |k: isize| stored[(k + d + 1) as usize];
+        let k = x - y;
+        let prev_k = if goes_down(furthest, k, d) { k + 1 } else { k - 1 };
+        let prev_x = furthest(prev_k);
+        let prev_y = prev_x - prev_k;
+        while x > prev_x && y > prev_y {
+            script.push(Operation::Keep);
+            x -= 1;
+            y -= 1;
+        }
+        if d > 0 {
+            if x == prev_x {
+                script.push(Operation::Insert(prev_y as usize));
+            } else {
+                script.push(Operation::Remove(prev_x as usize));
+            }
+        }
+        x = prev_x;
+        y = prev_y;
+    }
+    script.reverse();
+    script
+}
+
+/// Check if the path reaching diagonal `k` at step `d` comes from the diagonal `k + 1`, i.e. is
+/// an insertion, rather than from the diagonal `k - 1`, being a removal.
+fn goes_down(furthest: impl Fn(isize) -> isize, k: isize, d: isize) -> bool {
+    k == -d || (k != d && furthest(k - 1) < furthest(k + 1))
+}
+
+
+
+// =============
+// === Tests ===
+// =============
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn check_round_trip(old: &str, new: &str, segmentation: Segmentation) -> TextDiff {
+        let diff = TextDiff::new(old, new, segmentation);
+        assert_eq!(diff.apply(old, segmentation).unwrap(), new);
+        assert_eq!(diff.revert(new, segmentation).unwrap(), old);
+        diff
+    }
+
+    fn source_file(code: &str, id_map: &str, metadata: &str) -> SourceFile {
+        SourceFile::new(format!("{code}\n\n\n#### METADATA ####\n{id_map}\n{metadata}"))
+    }
+
+    #[test]
+    fn diff_round_trip() {
+        let lines = Segmentation::Lines;
+        check_round_trip("", "", lines);
+        check_round_trip("", "main = 2 + 2", lines);
+        check_round_trip("main = 2 + 2", "", lines);
+        check_round_trip("a\nb\nc", "a\nb\nc\n", lines);
+        check_round_trip("a\nb\nc\nd\ne\n", "x\nb\nd\ny\ne\nz", lines);
+        check_round_trip("a\na\nb\na\n", "b\na\na\nb\n", lines);
+
+        let diff = check_round_trip("a\nb\nc\nd\ne\n", "a\nB\nc\nd\nE\n", lines);
+        let expected = vec![
+            Hunk { start: 1, removed: vec!["b\n".into()], inserted: vec!["B\n".into()] },
+            Hunk { start: 4, removed: vec!["e\n".into()], inserted: vec!["E\n".into()] },
+        ];
+        assert_eq!(diff.hunks, expected);
+    }
+
+    #[test]
+    fn json_diff_round_trip() {
+        let json = Segmentation::Json;
+        check_round_trip("{}", r#"{"a":[1,2]}"#, json);
+        check_round_trip(r#"[[1,2],[3,4]]"#, r#"[[1,2],[5,4],[6]]"#, json);
+
+        let old = r#"{"a":{"x":1.0,"y":2.0},"b":{"x":3.0,"y":4.0}}"#;
+        let new = r#"{"a":{"x":1.0,"y":2.0},"b":{"x":3.5,"y":4.0}}"#;
+        let diff = check_round_trip(old, new, json);
+        let expected = vec![Hunk {
+            start:    10,
+            removed:  vec!["3.0,".into()],
+            inserted: vec!["3.5,".into()],
+        }];
+        assert_eq!(diff.hunks, expected);
+    }
+
+    #[test]
+    fn edit_script_cost_is_bounded() {
+        let old = (0..2 * MAX_EDIT_SCRIPT_COST).map(|i| format!("{i}\n")).collect::<String>();
+        let new = (0..2 * MAX_EDIT_SCRIPT_COST).map(|i| format!("{}\n", -i)).collect::<String>();
+        let diff = check_round_trip(&old, &new, Segmentation::Lines);
+        assert_eq!(diff.hunks.len(), 1);
+    }
+
+    #[test]
+    fn applying_diff_to_changed_content() {
+        let lines = Segmentation::Lines;
+        let diff = TextDiff::new("main =\n    foo = 2\n", "main =\n    foo = 3\n", lines);
+        assert!(diff.apply("main =\n    foo = 4\n", lines).is_err());
+        assert!(diff.revert("main =\n    foo = 2\n", lines).is_err());
+        assert!(diff.revert("main =\n", lines).is_err());
+    }
+
+    #[test]
+    fn content_diff_round_trip() {
+        let old = source_file("main =\n    foo = 2", "[]", r#"{"ide":{}}"#);
+        let new = source_file("main =\n    foo = 2\n    bar = 3", r#"[[1]]"#, r#"{"ide":{"a":1}}"#);
+        let diff = ContentDiff::new(&old, &new);
+        assert!(!diff.code.is_empty() && !diff.id_map.is_empty() && !diff.metadata.is_empty());
+        assert_eq!(diff.apply(&old).unwrap(), new.content);
+        assert_eq!(diff.revert(&new).unwrap(), old.content);
+        assert!(diff.apply(&new).is_err());
+    }
+
+    #[test]
+    fn position_only_frame_size() {
+        let node_count = 100;
+        let code = (0..node_count).map(|i| format!("    node{i} = {i}\n")).collect::<String>();
+        let code = format!("main =\n{code}");
+        let id = |i: usize| format!("00000000-0000-0000-0000-{i:012}");
+        let id_map = (0..node_count)
+            .map(|i| format!(r#"[{{"index":{{"value":{i}}},"size":{{"value":1}}}},"{}"]"#, id(i)));
+        let id_map = format!("[{}]", id_map.collect_vec().join(","));
+        let metadata = |moved_x: f32| {
+            let node = |i: usize| {
+                let x = if i == node_count / 2 { moved_x } else { i as f32 };
+                format!(r#""{}":{{"position":{{"vector":[{x:?},0.0]}}}}"#, id(i))
+            };
+            let nodes = (0..node_count).map(node).collect_vec().join(",");
+            format!(r#"{{"ide":{{"node":{{{nodes}}}}}}}"#)
+        };
+        let old = source_file(&code, &id_map, &metadata(50.0));
+        let new = source_file(&code, &id_map, &metadata(75.5));
+
+        let diff = ContentDiff::new(&old, &new);
+        assert!(diff.code.is_empty());
+        assert!(diff.id_map.is_empty());
+        assert_eq!(diff.metadata.hunks.len(), 1);
+        assert_eq!(diff.apply(&old).unwrap(), new.content);
+        let frame_size = serde_json::to_string(&diff).unwrap().len();
+        assert!(new.content.len() > 10_000);
+        assert!(frame_size < 200, "The diff of a node move takes {frame_size} bytes.");
+    }
+}
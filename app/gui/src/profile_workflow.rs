@@ -14,6 +14,7 @@ use enso_debug_api as debug_api;
 #[entry_point(profile)]
 #[allow(dead_code)] // Used from JavaScript.
 pub async fn main() {
+    crate::set_profiling_filter();
     // Run selected workflow.
     let need_workflow = "`profile` entry point requires --workflow argument. \
     Try --workflow=help to see a list of options.";
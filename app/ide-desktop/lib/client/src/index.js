@@ -176,6 +176,15 @@ optParser.options('workflow', {
     type: `string`,
 })
 
+optParser.options('profiling-filter', {
+    group: debugOptionsGroup,
+    describe:
+        'Select the profilers that are recorded, e.g. `detail,+*graph*,-*render*`: a profiling ' +
+        'level, and patterns of profiler labels to include (`+`) or exclude (`-`).',
+    requiresArg: true,
+    type: `string`,
+})
+
 // === Style Options ===
 
 let styleOptionsGroup = 'Style Options:'
@@ -612,6 +621,9 @@ function createWindow() {
     if (args.workflow) {
         urlCfg.test_workflow = args.workflow
     }
+    if (args.profilingFilter) {
+        urlCfg.profiling_filter = args.profilingFilter
+    }
 
     Electron.ipcMain.on('quit-ide', () => {
         Electron.app.quit()
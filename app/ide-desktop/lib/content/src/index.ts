@@ -159,6 +159,11 @@ class Config {
         'When profiling the application (e.g. with the `./run profile` command), this argument ' +
             'chooses what is profiled.'
     )
+    profilingFilter: config.Param<string | null> = new config.Param(
+        null,
+        'Selects the profilers that are recorded, e.g. `detail,+*graph*,-*render*`: a profiling ' +
+            'level, and patterns of profiler labels to include (`+`) or exclude (`-`).'
+    )
     skipMinVersionCheck: config.Param<boolean> = new config.Param(
         Version.isDev(),
         'Controls whether the minimum engine version check should be performed. It is set to ' +
@@ -432,6 +432,7 @@ pub fn gui() -> Result<Workflow> {
     workflow.add(PRIMARY_OS, job::Lint);
     workflow.add(PRIMARY_OS, job::WasmTest);
     workflow.add(PRIMARY_OS, job::NativeTest);
+    workflow.add(PRIMARY_OS, job::ParserBindingsTest);
 
     // FIXME: Integration tests are currently always failing.
     //        The should be reinstated when fixed.
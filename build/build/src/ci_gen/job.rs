@@ -100,6 +100,14 @@ impl JobArchetype for NativeTest {
     }
 }
 
+#[derive(Clone, Copy, Debug)]
+pub struct ParserBindingsTest;
+impl JobArchetype for ParserBindingsTest {
+    fn job(&self, os: OS) -> Job {
+        plain_job(&os, "Parser bindings tests", "java-gen test")
+    }
+}
+
 #[derive(Clone, Copy, Debug)]
 pub struct WasmTest;
 impl JobArchetype for WasmTest {
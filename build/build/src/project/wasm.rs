@@ -121,6 +121,18 @@ impl Profile {
         }
     }
 
+    /// The finest-grained profiling level compiled in, unless a level is given explicitly.
+    ///
+    /// Release builds include `Detail`-level profilers, so that a detailed profile of a release
+    /// build can be requested at runtime without shipping a special binary. They are recorded only
+    /// on request; see [`BuildInput::default_profiling_level`].
+    pub fn default_max_profiling_level(self) -> ProfilingLevel {
+        match self {
+            Profile::Dev | Profile::Profile => ProfilingLevel::Objective,
+            Profile::Release => ProfilingLevel::Detail,
+        }
+    }
+
     pub fn extra_rust_options(self) -> Vec<String> {
         match self {
             // Profile::Production => ["-Clto=fat", "-Ccodegen-units=1", "-Cincremental=false"]
@@ -156,6 +168,17 @@ pub struct BuildInput {
 }
 
 impl BuildInput {
+    /// The finest-grained profiling level compiled in.
+    pub fn max_profiling_level(&self) -> ProfilingLevel {
+        self.profiling_level.unwrap_or_else(|| self.profile.default_max_profiling_level())
+    }
+
+    /// The finest-grained profiling level recorded, unless changed at runtime. If a level is given
+    /// explicitly, it is recorded by default.
+    pub fn default_profiling_level(&self) -> ProfilingLevel {
+        self.profiling_level.unwrap_or(ProfilingLevel::Objective)
+    }
+
     pub async fn perhaps_check_size(&self, wasm_path: impl AsRef<Path>) -> Result {
         let compressed_size = compressed_size(&wasm_path).await?.get_appropriate_unit(true);
         info!("Compressed size of {} is {}.", wasm_path.as_ref().display(), compressed_size);
@@ -163,12 +186,12 @@ impl BuildInput {
             let wasm_size_limit = wasm_size_limit.get_appropriate_unit(true);
             if !self.profile.should_check_size() {
                 warn!("Skipping size check because profile is '{}'.", self.profile,);
-            } else if self.profiling_level.unwrap_or_default() != ProfilingLevel::Objective {
-                // TODO? additional leeway as sanity check
+            } else if self.max_profiling_level() != self.profile.default_max_profiling_level() {
+                // The limit is the budget of the profilers compiled into a standard build.
                 warn!(
-                    "Skipping size check because profiling level is {:?} rather than {}.",
-                    self.profiling_level,
-                    ProfilingLevel::Objective
+                    "Skipping size check because profiling level is {} rather than {}.",
+                    self.max_profiling_level(),
+                    self.profile.default_max_profiling_level()
                 );
             } else {
                 ensure!(
@@ -226,11 +249,13 @@ impl IsTarget for Wasm {
                 skip_wasm_opt,
                 extra_cargo_options,
                 profile,
-                profiling_level,
+                profiling_level: _,
                 log_level,
                 uncollapsed_log_level,
                 wasm_size_limit: _wasm_size_limit,
             } = &inner;
+            let max_profiling_level = inner.max_profiling_level();
+            let default_profiling_level = inner.default_profiling_level();
 
             cache::goodie::binaryen::Binaryen { version: BINARYEN_VERSION_TO_INSTALL }
                 .install_if_missing(&cache)
@@ -260,9 +285,9 @@ impl IsTarget for Wasm {
                         .apply(&cargo::Color::Always)
                         .args(extra_cargo_options);
 
-                    if let Some(profiling_level) = profiling_level {
-                        command.set_env(env::ENSO_MAX_PROFILING_LEVEL, &profiling_level)?;
-                    }
+                    command.set_env(env::ENSO_MAX_PROFILING_LEVEL, &max_profiling_level)?;
+                    command
+                        .set_env(env::ENSO_DEFAULT_PROFILING_LEVEL, &default_profiling_level)?;
                     command.set_env(env::ENSO_MAX_LOG_LEVEL, &log_level)?;
                     command.set_env(env::ENSO_MAX_UNCOLLAPSED_LOG_LEVEL, &uncollapsed_log_level)?;
                     Ok(command)
@@ -10,6 +10,9 @@ ide_ci::define_env_var! {
     /// implement the public interface to profiling-level configuration (see:
     /// https://github.com/enso-org/design/blob/main/epics/profiling/implementation.md)
     ENSO_MAX_PROFILING_LEVEL, ProfilingLevel;
+    /// The profiling level recorded unless changed at runtime. Must not be finer than
+    /// `ENSO_MAX_PROFILING_LEVEL`.
+    ENSO_DEFAULT_PROFILING_LEVEL, ProfilingLevel;
 
     /// Set the level of logging detail that will be enabled at compile-time.
     ENSO_MAX_LOG_LEVEL, LogLevel;
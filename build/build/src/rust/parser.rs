@@ -17,6 +17,7 @@ const GENERATOR_BIN_NAME: &str = GENERATOR_CRATE_NAME;
 const TEST_GENERATOR_BIN_NAME: &str = "java-tests";
 const GENERATED_CODE_NAMESPACE: [&str; 3] = ["org", "enso", "syntax2"];
 const GENERATED_TEST_CLASS: &str = "GeneratedFormatTests";
+const BINDINGS_TEST_NAME: &str = "bindings";
 const JAVA_EXTENSION: &str = ".java";
 
 pub fn cargo_run_generator_cmd(repo_root: &Path, binary_name: &str) -> Result<Command> {
@@ -84,3 +85,17 @@ pub async fn run_self_tests(repo_root: &RepoRoot) -> Result {
 
     Ok(())
 }
+
+/// Check that the TypeScript and Scala bindings deserialize the format test cases. Requires `tsc`,
+/// `node` and `scala-cli`.
+#[context("Running the tests of the TypeScript and Scala parser bindings failed.")]
+pub async fn run_binding_tests(repo_root: &Path) -> Result {
+    Cargo
+        .cmd()?
+        .current_dir(repo_root)
+        .apply(&cargo::Command::Test)
+        .apply(&cargo::Options::Package(GENERATOR_CRATE_NAME.into()))
+        .args(["--test", BINDINGS_TEST_NAME, "--", "--ignored"])
+        .run_ok()
+        .await
+}
@@ -61,7 +61,9 @@ pub struct BuildInput {
     #[clap(last = true, enso_env())]
     pub cargo_options: Vec<String>,
 
-    /// Compiles Enso with given profiling level. If not set, defaults to minimum.
+    /// Compiles Enso with given profiling level, and records it by default. If not set, release
+    /// builds include `detail`-level profilers but record only `objective` ones by default; other
+    /// builds include only `objective`-level profilers.
     #[clap(long, arg_enum, enso_env())]
     pub profiling_level: Option<ProfilingLevel>,
 
@@ -884,7 +884,8 @@ pub async fn main_internal(config: Option<enso_build::config::Config>) -> Result
                         generate_job.await?;
                         let backend_context = ctx.prepare_backend_context(default()).await?;
                         backend_context.prepare_build_env().await?;
-                        enso_build::rust::parser::run_self_tests(&repo_root).await
+                        enso_build::rust::parser::run_self_tests(&repo_root).await?;
+                        enso_build::rust::parser::run_binding_tests(&repo_root).await
                     }
                 }
             }
@@ -27,6 +27,7 @@ mod block;
 mod mark;
 
 use enso_profiler_flame_graph::Activity;
+use enso_profiler_flame_graph::PathSegment;
 use enso_profiler_flame_graph::Performance;
 use ensogl_core::data::color;
 use ensogl_core::display::shape::StyleWatchFrp;
@@ -62,6 +63,11 @@ pub const COLOR_PERFORMANCE_MEDIUM: &str = "flame_graph_block_color_performance_
 /// Theme path for the color of a performance block that indicates bad performance..
 pub const COLOR_PERFORMANCE_BAD: &str = "flame_graph_block_color_performance_bad";
 
+/// Theme path for the color of a block on a critical path that is active.
+pub const COLOR_CRITICAL_PATH_ACTIVE: &str = "flame_graph_block_color_critical_path_active";
+/// Theme path for the color of a block on a critical path that is waiting.
+pub const COLOR_CRITICAL_PATH_WAITING: &str = "flame_graph_block_color_critical_path_waiting";
+
 /// Theme path for the color that is sued to color a mark.
 pub const COLOR_MARK_DEFAULT: &str = "flame_graph_mark_color";
 
@@ -93,6 +99,16 @@ impl IntoThemePath for Performance {
     }
 }
 
+impl IntoThemePath for PathSegment {
+    fn theme_path(&self) -> style::Path {
+        match self {
+            PathSegment::Active => COLOR_CRITICAL_PATH_ACTIVE,
+            PathSegment::Waiting => COLOR_CRITICAL_PATH_WAITING,
+        }
+        .into()
+    }
+}
+
 impl<BlockType: IntoThemePath> IntoThemePath for profiler_flame_graph::Block<BlockType> {
     fn theme_path(&self) -> style::Path {
         self.block_type.theme_path()
@@ -195,6 +211,7 @@ impl FlameGraph {
         let activity_blocks =
             data.activity_blocks.into_iter().filter(|block| block.width() > MIN_INTERVAL_TIME_MS);
         let performance_blocks = data.performance_blocks.into_iter();
+        let critical_path = data.critical_path.into_iter();
         let marks = data.marks;
 
         let origin_x = activity_blocks
@@ -208,7 +225,13 @@ impl FlameGraph {
         let performance_block_shapes =
             performance_blocks.map(|block| shape_from_block(align_block(block, origin_x), app));
 
-        let blocks = activity_block_shapes.chain(performance_block_shapes).collect_vec();
+        let critical_path_shapes =
+            critical_path.map(|block| shape_from_block(align_block(block, origin_x), app));
+
+        let blocks = activity_block_shapes
+            .chain(performance_block_shapes)
+            .chain(critical_path_shapes)
+            .collect_vec();
         blocks.iter().for_each(|item| display_object.add_child(item));
 
         let marks: Vec<_> = marks
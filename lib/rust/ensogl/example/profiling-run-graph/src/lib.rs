@@ -39,6 +39,8 @@ use ensogl_core::frp;
 use ensogl_flame_graph as flame_graph;
 use ensogl_flame_graph::COLOR_BLOCK_ACTIVE;
 use ensogl_flame_graph::COLOR_BLOCK_PAUSED;
+use ensogl_flame_graph::COLOR_CRITICAL_PATH_ACTIVE;
+use ensogl_flame_graph::COLOR_CRITICAL_PATH_WAITING;
 use ensogl_flame_graph::COLOR_MARK_DEFAULT;
 use ensogl_flame_graph::COLOR_PERFORMANCE_BAD;
 use ensogl_flame_graph::COLOR_PERFORMANCE_GOOD;
@@ -125,6 +127,8 @@ fn init_theme(scene: &Scene) {
     theme.set(COLOR_PERFORMANCE_BAD, color::Lcha::red(0.4, 0.5));
     theme.set(COLOR_PERFORMANCE_GOOD, color::Lcha::green(0.8, 0.5));
     theme.set(COLOR_PERFORMANCE_MEDIUM, color::Lcha::yellow(0.6, 0.5));
+    theme.set(COLOR_CRITICAL_PATH_ACTIVE, color::Lcha::red(0.5, 0.8));
+    theme.set(COLOR_CRITICAL_PATH_WAITING, color::Lcha::red(0.8, 0.2));
     theme.set(COLOR_MARK_DEFAULT, color::Lcha::blue_green(0.9, 0.1));
     theme.set("component.label.text", color::Lcha::black());
 
@@ -170,6 +174,13 @@ fn profile_to_graph(profile: &Profile<Metadata>, app: &Application) -> flame_gra
     let performance_blocks = make_rendering_performance_blocks(profile);
     measurements.performance_blocks = performance_blocks;
 
+    let highlight = enso_profiler_data::critical_path::highlights(profile).next();
+    let critical_path = highlight
+        .and_then(|target| enso_profiler_data::critical_path::critical_path(profile, target));
+    if let Some(critical_path) = critical_path {
+        measurements.add_critical_path(profile, &critical_path);
+    }
+
     flame_graph::FlameGraph::from_data(measurements, app)
 }
 
@@ -0,0 +1,945 @@
+//! Converting values and their bincode serialization to and from a JSON representation, and
+//! producing JSON Schemas describing the JSON, based on reflection information.
+//!
+//! The conversions are driven by the `meta` model of a type, not by a format-specific serde derive:
+//! [`to_json`] encodes a Rust value by reading its data with [`Reflect::reflect_value`], so it
+//! works for any `#[derive(Reflect)]` type. [`from_bincode`] transcribes the bincode serialization
+//! of a value (see [`enso_metamodel::java::bincode`]) to JSON, and [`to_bincode`] transcribes JSON
+//! back to bincode; they don't depend on the types implementing any traits, so data can be
+//! converted by any holder of its bincode representation and the types' `meta` model--e.g. a tool
+//! receiving serialized data through an FFI boundary.
+//!
+//! # Format
+//!
+//! The format is similar to the one `serde_json` produces, with subtyping represented in the style
+//! of serde's *internally tagged* enums:
+//! - A struct is represented as an object, with a property for each of its fields, including the
+//!   fields inherited from its ancestors. Property names are field names in `snake_case`; an
+//!   unnamed field is identified by its position among all the fields of the type.
+//! - A type that has a parent has an additional property [`TAG`], whose value is the name of the
+//!   type. Values of abstract types are represented by the value of their concrete subtype.
+//! - Integers are numbers; a `char` is a string containing one code point.
+//! - A sequence is an array.
+//! - An optional value is `null`, or the contained value.
+//! - A `Result` is an object with a single property, `Ok` or `Err`.
+//!
+//! [`schema`] produces a JSON Schema (draft 2020-12) that validates the output for a type.
+//!
+//! # Example
+//!
+//! ```
+//! # use enso_reflect::prelude::*;
+//! use enso_reflect::Reflect;
+//! use serde_json::json;
+//!
+//! #[derive(Reflect)]
+//! struct A {
+//!     value: u32,
+//! }
+//! let value = A { value: 36 };
+//! let (graph, rust_to_meta) = enso_metamodel::rust::to_meta(value.reflect_type());
+//! let value_ty = rust_to_meta[&value.reflect_type().id];
+//! let json = enso_metamodel_json::to_json(&graph, value_ty, &value).unwrap();
+//! assert_eq!(json, json!({ "value": 36 }));
+//! let data = enso_metamodel_json::to_bincode(&graph, value_ty, &json).unwrap();
+//! assert_eq!(enso_metamodel_json::from_bincode(&graph, value_ty, &data).unwrap(), json);
+//! ```
+
+// === Features ===
+#![feature(let_chains)]
+// === Standard Linter Configuration ===
+#![deny(non_ascii_idents)]
+#![warn(unsafe_code)]
+#![allow(clippy::bool_to_int_with_if)]
+#![allow(clippy::let_and_return)]
+// === Non-Standard Linter Configuration ===
+#![allow(clippy::option_map_unit_fn)]
+#![allow(clippy::precedence)]
+#![allow(dead_code)]
+#![deny(unconditional_recursion)]
+#![warn(missing_copy_implementations)]
+#![warn(missing_debug_implementations)]
+#![warn(missing_docs)]
+#![warn(trivial_casts)]
+#![warn(trivial_numeric_casts)]
+#![warn(unused_import_braces)]
+#![warn(unused_qualifications)]
+
+use enso_metamodel::meta::*;
+
+use enso_reflect::Reflect;
+use serde_json::json;
+use serde_json::Map;
+use serde_json::Value;
+use std::collections::BTreeMap;
+use std::collections::BTreeSet;
+
+
+
+// =====================
+// === Configuration ===
+// =====================
+
+/// Name of the property that identifies the concrete type of an object. The name is not a valid
+/// identifier, so it cannot conflict with the name of a field.
+pub const TAG: &str = "$type";
+
+const SCHEMA_DIALECT: &str = "https://json-schema.org/draft/2020-12/schema";
+
+
+
+// =====================
+// === Value to JSON ===
+// =====================
+
+/// Convert a value to JSON, using its `meta` type info.
+///
+/// The data of the value is obtained by reflection, and linearized in the bincode format described
+/// by the `meta` model; an error indicates that the data visited doesn't match the type info.
+pub fn to_json<T: Reflect>(graph: &TypeGraph, id: TypeId, value: &T) -> Result<Value, FormatError> {
+    let mut encoder = Encoder::default();
+    value.reflect_value(&mut encoder);
+    from_bincode(graph, id, &encoder.output)
+}
+
+
+// === Implementation ===
+
+#[derive(Debug, Default)]
+struct Encoder {
+    output: Vec<u8>,
+}
+
+impl enso_reflect::Visitor for Encoder {
+    fn visit_bool(&mut self, value: bool) {
+        self.output.push(value as u8);
+    }
+
+    fn visit_u32(&mut self, value: u32) {
+        self.output.extend(value.to_le_bytes());
+    }
+
+    fn visit_i32(&mut self, value: i32) {
+        self.output.extend(value.to_le_bytes());
+    }
+
+    fn visit_usize(&mut self, value: usize) {
+        self.output.extend((value as u64).to_le_bytes());
+    }
+
+    fn visit_char(&mut self, value: char) {
+        self.visit_u32(value as u32);
+    }
+
+    fn visit_str(&mut self, value: &str) {
+        self.visit_sequence(value.len());
+        self.output.extend(value.as_bytes());
+    }
+
+    fn visit_variant(&mut self, index: u32) {
+        self.visit_u32(index);
+    }
+
+    fn visit_option(&mut self, is_some: bool) {
+        self.visit_bool(is_some);
+    }
+
+    fn visit_sequence(&mut self, len: usize) {
+        self.visit_usize(len);
+    }
+}
+
+
+
+// =======================
+// === Bincode to JSON ===
+// =======================
+
+/// Convert bincode-serialized data to JSON, using its `meta` type info.
+pub fn from_bincode(graph: &TypeGraph, id: TypeId, data: &[u8]) -> Result<Value, FormatError> {
+    let mut reader = Reader { graph, data, offset: 0 };
+    let value = reader.value(id)?;
+    match reader.offset == data.len() {
+        true => Ok(value),
+        false => Err(reader.error("Unexpected data after the end of the value.")),
+    }
+}
+
+/// The input to [`from_bincode`] is not valid data of the expected type.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FormatError {
+    /// The position in the input at which the error was detected.
+    pub offset:  usize,
+    #[allow(missing_docs)]
+    pub message: String,
+}
+
+impl std::fmt::Display for FormatError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} (at offset {})", self.message, self.offset)
+    }
+}
+
+impl std::error::Error for FormatError {}
+
+
+// === Implementation ===
+
+struct Reader<'g, 'd> {
+    graph:  &'g TypeGraph,
+    data:   &'d [u8],
+    offset: usize,
+}
+
+impl<'g, 'd> Reader<'g, 'd> {
+    fn error(&self, message: impl Into<String>) -> FormatError {
+        FormatError { offset: self.offset, message: message.into() }
+    }
+
+    fn value(&mut self, id: TypeId) -> Result<Value, FormatError> {
+        match &self.graph[id].data {
+            Data::Struct(_) => self.object(id),
+            Data::Primitive(primitive) => self.primitive(*primitive),
+        }
+    }
+
+    fn object(&mut self, id: TypeId) -> Result<Value, FormatError> {
+        let (pre, post) = self.graph.split_fields(id);
+        let mut values = vec![];
+        for field in pre {
+            values.push(self.value(field.type_)?);
+        }
+        let concrete = self.child(id, &mut values)?;
+        for field in post {
+            values.push(self.value(field.type_)?);
+        }
+        let mut object = Map::new();
+        if self.graph[concrete].parent.is_some() {
+            object.insert(TAG.to_owned(), Value::String(tag(self.graph, concrete)));
+        }
+        let fields = self.graph.all_fields(concrete).into_iter().enumerate();
+        for ((i, field), value) in fields.zip(values) {
+            object.insert(field_key(field, i), value);
+        }
+        Ok(Value::Object(object))
+    }
+
+    /// Read the data of the subtype of a type, if it has subtypes; return the concrete type.
+    fn child(&mut self, id: TypeId, values: &mut Vec<Value>) -> Result<TypeId, FormatError> {
+        let ty = &self.graph[id];
+        if ty.discriminants.is_empty() {
+            return Ok(id);
+        }
+        let discriminant = self.u32()?;
+        let child = match ty.discriminants.get(&(discriminant as usize)) {
+            Some(child) => *child,
+            None => {
+                let name = &ty.name;
+                return Err(self.error(format!("Unknown discriminant {discriminant} in {name}.")));
+            }
+        };
+        let fields = self.graph[child].data.as_struct().unwrap_or_default();
+        let split = self.graph[child].child_field.unwrap_or(fields.len());
+        for field in &fields[..split] {
+            values.push(self.value(field.type_)?);
+        }
+        let concrete = self.child(child, values)?;
+        for field in &fields[split..] {
+            values.push(self.value(field.type_)?);
+        }
+        Ok(concrete)
+    }
+
+    fn primitive(&mut self, primitive: Primitive) -> Result<Value, FormatError> {
+        Ok(match primitive {
+            Primitive::Bool => match self.u8()? {
+                0 => Value::Bool(false),
+                1 => Value::Bool(true),
+                value => return Err(self.error(format!("Invalid boolean {value}."))),
+            },
+            Primitive::U32 => self.u32()?.into(),
+            Primitive::I32 => (self.u32()? as i32).into(),
+            Primitive::U64 => self.u64()?.into(),
+            Primitive::I64 => (self.u64()? as i64).into(),
+            Primitive::Char => {
+                let value = self.u32()?;
+                match char::from_u32(value) {
+                    Some(c) => Value::String(c.to_string()),
+                    None => return Err(self.error(format!("Invalid code point {value}."))),
+                }
+            }
+            Primitive::String => {
+                let len = self.len()?;
+                let bytes = self.bytes(len)?.to_owned();
+                match String::from_utf8(bytes) {
+                    Ok(s) => Value::String(s),
+                    Err(_) => return Err(self.error("Invalid UTF-8.")),
+                }
+            }
+            Primitive::Sequence(t0) => {
+                let len = self.len()?;
+                let values: Result<_, _> = (0..len).map(|_| self.value(t0)).collect();
+                Value::Array(values?)
+            }
+            Primitive::Option(t0) => match self.u8()? {
+                0 => Value::Null,
+                1 => self.value(t0)?,
+                value => return Err(self.error(format!("Unknown discriminant {value} in Option."))),
+            },
+            Primitive::Result(t0, t1) => match self.u32()? {
+                0 => json!({ "Ok": self.value(t0)? }),
+                1 => json!({ "Err": self.value(t1)? }),
+                value => return Err(self.error(format!("Unknown discriminant {value} in Result."))),
+            },
+        })
+    }
+}
+
+
+// === Primitive Deserializers ===
+
+impl<'g, 'd> Reader<'g, 'd> {
+    fn bytes(&mut self, len: usize) -> Result<&'d [u8], FormatError> {
+        let end = self.offset.checked_add(len).filter(|end| *end <= self.data.len());
+        let end = end.ok_or_else(|| self.error("Unexpected end of input."))?;
+        let bytes = &self.data[self.offset..end];
+        self.offset = end;
+        Ok(bytes)
+    }
+
+    fn u8(&mut self) -> Result<u8, FormatError> {
+        Ok(self.bytes(1)?[0])
+    }
+
+    fn u32(&mut self) -> Result<u32, FormatError> {
+        let mut data = [0; 4];
+        data.copy_from_slice(self.bytes(4)?);
+        Ok(u32::from_le_bytes(data))
+    }
+
+    fn u64(&mut self) -> Result<u64, FormatError> {
+        let mut data = [0; 8];
+        data.copy_from_slice(self.bytes(8)?);
+        Ok(u64::from_le_bytes(data))
+    }
+
+    /// Read the length of a sequence or string.
+    fn len(&mut self) -> Result<usize, FormatError> {
+        let len = self.u64()?;
+        usize::try_from(len).map_err(|_| self.error(format!("Invalid length {len}.")))
+    }
+}
+
+
+
+// =======================
+// === JSON to Bincode ===
+// =======================
+
+/// Convert JSON in the format produced by [`from_bincode`] to bincode-serialized data, using its
+/// `meta` type info.
+pub fn to_bincode(graph: &TypeGraph, id: TypeId, value: &Value) -> Result<Vec<u8>, ValueError> {
+    let mut writer = Writer { graph, output: vec![], pointer: vec![] };
+    writer.value(id, value)?;
+    Ok(writer.output)
+}
+
+/// The input to [`to_bincode`] is not a valid JSON representation of the expected type.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ValueError {
+    /// The location in the input at which the error was detected, as a JSON Pointer.
+    pub pointer: String,
+    #[allow(missing_docs)]
+    pub message: String,
+}
+
+impl std::fmt::Display for ValueError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} (at {:?})", self.message, self.pointer)
+    }
+}
+
+impl std::error::Error for ValueError {}
+
+
+// === Implementation ===
+
+struct Writer<'g> {
+    graph:   &'g TypeGraph,
+    output:  Vec<u8>,
+    /// The keys and indices leading to the value being written.
+    pointer: Vec<String>,
+}
+
+impl<'g> Writer<'g> {
+    fn error(&self, message: impl Into<String>) -> ValueError {
+        let pointer = self.pointer.iter().map(|token| format!("/{token}")).collect();
+        ValueError { pointer, message: message.into() }
+    }
+
+    fn value(&mut self, id: TypeId, value: &Value) -> Result<(), ValueError> {
+        match &self.graph[id].data {
+            Data::Struct(_) => self.object(id, value),
+            Data::Primitive(primitive) => self.primitive(*primitive, value),
+        }
+    }
+
+    /// Write the value of a field, which is the property `key` of an object.
+    fn property(&mut self, id: TypeId, key: String, value: &Value) -> Result<(), ValueError> {
+        self.pointer.push(key);
+        self.value(id, value)?;
+        self.pointer.pop();
+        Ok(())
+    }
+
+    fn object(&mut self, id: TypeId, value: &Value) -> Result<(), ValueError> {
+        let name = self.graph.path(id);
+        let object = value.as_object().ok_or_else(|| self.error(format!("Expected {name}.")))?;
+        let concrete = self.concrete(id, object)?;
+        let mut properties = vec![];
+        for (i, field) in self.graph.all_fields(concrete).into_iter().enumerate() {
+            let key = field_key(field, i);
+            match object.get(&key) {
+                Some(value) => properties.push((field.type_, key, value)),
+                None => return Err(self.error(format!("Missing property {key:?}."))),
+            }
+        }
+        let expected = properties.len() + usize::from(object.contains_key(TAG));
+        if object.len() != expected {
+            let known = |key: &&String| *key == TAG || properties.iter().any(|p| &p.1 == *key);
+            let unknown = object.keys().find(|key| !known(key)).unwrap();
+            return Err(self.error(format!("Unknown property {unknown:?}.")));
+        }
+        let mut properties = properties.into_iter();
+        let (pre, post) = self.graph.split_fields(id);
+        for (type_, key, value) in properties.by_ref().take(pre.len()) {
+            self.property(type_, key, value)?;
+        }
+        self.child(id, concrete, &mut properties)?;
+        for (type_, key, value) in properties.take(post.len()) {
+            self.property(type_, key, value)?;
+        }
+        Ok(())
+    }
+
+    /// Identify the concrete type of an object that represents a value of a type.
+    fn concrete(&self, id: TypeId, object: &Map<String, Value>) -> Result<TypeId, ValueError> {
+        let tag_value = object.get(TAG);
+        if self.graph[id].parent.is_none() && self.graph[id].discriminants.is_empty() {
+            return match tag_value {
+                None => Ok(id),
+                Some(_) => Err(self.error(format!("Unknown property {TAG:?}."))),
+            };
+        }
+        let tag_value =
+            tag_value.ok_or_else(|| self.error(format!("Missing property {TAG:?}.")))?;
+        let mut candidates = vec![id];
+        while let Some(candidate) = candidates.pop() {
+            let ty = &self.graph[candidate];
+            if ty.discriminants.is_empty() {
+                if tag_value.as_str() == Some(tag(self.graph, candidate).as_str()) {
+                    return Ok(candidate);
+                }
+            } else {
+                candidates.extend(ty.discriminants.values().rev().copied());
+            }
+        }
+        let name = self.graph.path(id);
+        Err(self.error(format!("Type {tag_value} is not a concrete subtype of {name}.")))
+    }
+
+    /// Write the data of the subtype of a type that is an ancestor of the concrete type (or the
+    /// concrete type itself), if the type has subtypes.
+    fn child<'v>(
+        &mut self,
+        id: TypeId,
+        concrete: TypeId,
+        properties: &mut impl Iterator<Item = (TypeId, String, &'v Value)>,
+    ) -> Result<(), ValueError> {
+        let ty = &self.graph[id];
+        let hierarchy = self.graph.hierarchy(concrete);
+        let Some((key, child)) = ty.discriminants.iter().find(|(_, id)| hierarchy.contains(id))
+            else { return Ok(()) };
+        let (key, child) = (*key, *child);
+        self.u32(key as u32);
+        let fields = self.graph[child].data.as_struct().unwrap_or_default();
+        let split = self.graph[child].child_field.unwrap_or(fields.len());
+        let post = fields.len() - split;
+        for (type_, key, value) in properties.by_ref().take(split) {
+            self.property(type_, key, value)?;
+        }
+        self.child(child, concrete, properties)?;
+        for (type_, key, value) in properties.take(post) {
+            self.property(type_, key, value)?;
+        }
+        Ok(())
+    }
+
+    fn primitive(&mut self, primitive: Primitive, value: &Value) -> Result<(), ValueError> {
+        let expected = |description: &str| self.error(format!("Expected {description}."));
+        match primitive {
+            Primitive::Bool => {
+                let value = value.as_bool().ok_or_else(|| expected("a boolean"))?;
+                self.output.push(value as u8);
+            }
+            Primitive::U32 => {
+                let value = value.as_u64().and_then(|value| u32::try_from(value).ok());
+                self.u32(value.ok_or_else(|| expected("a 32-bit unsigned integer"))?);
+            }
+            Primitive::I32 => {
+                let value = value.as_i64().and_then(|value| i32::try_from(value).ok());
+                self.u32(value.ok_or_else(|| expected("a 32-bit signed integer"))? as u32);
+            }
+            Primitive::U64 =>
+                self.u64(value.as_u64().ok_or_else(|| expected("an unsigned integer"))?),
+            Primitive::I64 => {
+                let value = value.as_i64().ok_or_else(|| expected("a signed integer"))?;
+                self.u64(value as u64);
+            }
+            Primitive::Char => {
+                let c = value.as_str().and_then(|s| {
+                    let mut chars = s.chars();
+                    chars.next().filter(|_| chars.next().is_none())
+                });
+                self.u32(c.ok_or_else(|| expected("a string of one character"))? as u32);
+            }
+            Primitive::String => {
+                let value = value.as_str().ok_or_else(|| expected("a string"))?;
+                self.u64(value.len() as u64);
+                self.output.extend(value.as_bytes());
+            }
+            Primitive::Sequence(t0) => {
+                let values = value.as_array().ok_or_else(|| expected("an array"))?;
+                self.u64(values.len() as u64);
+                for (i, value) in values.iter().enumerate() {
+                    self.property(t0, i.to_string(), value)?;
+                }
+            }
+            Primitive::Option(t0) => match value {
+                Value::Null => self.output.push(0),
+                value => {
+                    self.output.push(1);
+                    self.value(t0, value)?;
+                }
+            },
+            Primitive::Result(t0, t1) => {
+                let object = value.as_object().filter(|object| object.len() == 1);
+                let variant = object.and_then(|object| object.iter().next());
+                match variant {
+                    Some((key, value)) if key == "Ok" => {
+                        self.u32(0);
+                        self.property(t0, key.clone(), value)?;
+                    }
+                    Some((key, value)) if key == "Err" => {
+                        self.u32(1);
+                        self.property(t1, key.clone(), value)?;
+                    }
+                    _ => return Err(expected("an object with a property \"Ok\" or \"Err\"")),
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+
+// === Primitive Serializers ===
+
+impl<'g> Writer<'g> {
+    fn u32(&mut self, value: u32) {
+        self.output.extend(value.to_le_bytes());
+    }
+
+    fn u64(&mut self, value: u64) {
+        self.output.extend(value.to_le_bytes());
+    }
+}
+
+
+// ===================
+// === JSON Schema ===
+// ===================
+
+/// Produce a JSON Schema describing the JSON representation of a type.
+///
+/// Each struct type is described by a definition in the `$defs` of the schema, named by the
+/// qualified name of the type (e.g. `Tree.Ident`).
+pub fn schema(graph: &TypeGraph, root: TypeId) -> Value {
+    let mut schema = Schema { graph, names: default(), used: default(), queue: default() };
+    let mut definitions = Map::new();
+    let root_schema = schema.value(root);
+    while let Some(id) = schema.queue.pop() {
+        let name = schema.names[&id].clone();
+        definitions.insert(name, schema.definition(id));
+    }
+    let mut root_schema = match root_schema {
+        Value::Object(object) => object,
+        _ => unreachable!(),
+    };
+    root_schema.insert("$schema".to_owned(), SCHEMA_DIALECT.into());
+    root_schema.insert("$defs".to_owned(), Value::Object(definitions));
+    Value::Object(root_schema)
+}
+
+fn default<T: Default>() -> T {
+    Default::default()
+}
+
+struct Schema<'g> {
+    graph: &'g TypeGraph,
+    names: BTreeMap<TypeId, String>,
+    used:  BTreeSet<String>,
+    queue: Vec<TypeId>,
+}
+
+impl<'g> Schema<'g> {
+    /// Produce a schema for a value of a type.
+    fn value(&mut self, id: TypeId) -> Value {
+        let primitive = match &self.graph[id].data {
+            Data::Struct(_) => return json!({ "$ref": format!("#/$defs/{}", self.name(id)) }),
+            Data::Primitive(primitive) => *primitive,
+        };
+        match primitive {
+            Primitive::Bool => json!({ "type": "boolean" }),
+            Primitive::U32 => json!({ "type": "integer", "minimum": 0, "maximum": u32::MAX }),
+            Primitive::I32 =>
+                json!({ "type": "integer", "minimum": i32::MIN, "maximum": i32::MAX }),
+            Primitive::U64 => json!({ "type": "integer", "minimum": 0 }),
+            Primitive::I64 => json!({ "type": "integer" }),
+            Primitive::Char => json!({ "type": "string", "minLength": 1, "maxLength": 1 }),
+            Primitive::String => json!({ "type": "string" }),
+            Primitive::Sequence(t0) => json!({ "type": "array", "items": self.value(t0) }),
+            Primitive::Option(t0) => json!({ "anyOf": [{ "type": "null" }, self.value(t0)] }),
+            Primitive::Result(t0, t1) => {
+                let variant = |name: &str, schema: Value| {
+                    json!({
+                        "type": "object",
+                        "properties": { name: schema },
+                        "required": [name],
+                        "additionalProperties": false,
+                    })
+                };
+                let ok = variant("Ok", self.value(t0));
+                let err = variant("Err", self.value(t1));
+                json!({ "oneOf": [ok, err] })
+            }
+        }
+    }
+
+    /// Return the name of the definition of a struct type, assigning a unique name and queueing the
+    /// type for definition if it has not been encountered yet.
+    fn name(&mut self, id: TypeId) -> &str {
+        let Self { graph, names, used, queue } = self;
+        names.entry(id).or_insert_with(|| {
+            let base = graph.path(id);
+            let mut name = base.clone();
+            let mut i = 1;
+            while used.contains(&name) {
+                i += 1;
+                name = format!("{base}{i}");
+            }
+            used.insert(name.clone());
+            queue.push(id);
+            name
+        })
+    }
+
+    /// Produce the definition of a struct type.
+    fn definition(&mut self, id: TypeId) -> Value {
+        let ty = &self.graph[id];
+        let title = self.graph.path(id);
+        if !ty.discriminants.is_empty() {
+            let children: Vec<_> = ty.discriminants.values().copied().collect();
+            let variants: Vec<_> = children.into_iter().map(|id| self.value(id)).collect();
+            return json!({ "title": title, "oneOf": variants });
+        }
+        let mut properties = Map::new();
+        let mut required = vec![];
+        if ty.parent.is_some() {
+            properties.insert(TAG.to_owned(), json!({ "const": tag(self.graph, id) }));
+            required.push(Value::from(TAG));
+        }
+        for (i, field) in self.graph.all_fields(id).into_iter().enumerate() {
+            let key = field_key(field, i);
+            properties.insert(key.clone(), self.value(field.type_));
+            required.push(Value::from(key));
+        }
+        json!({
+            "title": title,
+            "type": "object",
+            "properties": properties,
+            "required": required,
+            "additionalProperties": false,
+        })
+    }
+}
+
+
+
+// ===============
+// === Helpers ===
+// ===============
+
+/// Get the property name of a field; `index` is the position of the field among all the fields of
+/// the concrete type it occurs in, in serialization order.
+fn field_key(field: &Field, index: usize) -> String {
+    match field.name.is_empty() {
+        true => index.to_string(),
+        false => field.name.as_identifier().to_snake_case(),
+    }
+}
+
+/// Get the value of the [`TAG`] property of objects of a type.
+fn tag(graph: &TypeGraph, id: TypeId) -> String {
+    graph[id].name.to_string()
+}
+
+
+
+// =============
+// === Tests ===
+// =============
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use enso_reflect::prelude::*;
+    use enso_reflect::Reflect;
+
+    #[derive(serde::Serialize, Reflect)]
+    struct Drawing {
+        shapes:  Vec<Shape>,
+        label:   Option<String>,
+        outcome: Result<u32, String>,
+    }
+
+    #[derive(serde::Serialize, Reflect)]
+    enum Shape {
+        Circle { radius: u32 },
+        Polygon(Vec<i32>),
+        Empty,
+    }
+
+    fn drawing() -> Drawing {
+        let shapes = vec![Shape::Circle { radius: 2 }, Shape::Polygon(vec![-1, 1]), Shape::Empty];
+        Drawing { shapes, label: Some("é".to_owned()), outcome: Err("failed".to_owned()) }
+    }
+
+    fn graph() -> (TypeGraph, TypeId) {
+        let (graph, rust_to_meta) = enso_metamodel::rust::to_meta(Drawing::reflect());
+        let root = rust_to_meta[&Drawing::reflect().id];
+        (graph, root)
+    }
+
+    fn serialize(value: &impl serde::Serialize) -> Vec<u8> {
+        use bincode::Options;
+        bincode::DefaultOptions::new().with_fixint_encoding().serialize(value).unwrap()
+    }
+
+    #[test]
+    fn test_value() {
+        let (graph, root) = graph();
+        let expected = json!({
+            "shapes": [
+                { "$type": "Circle", "radius": 2 },
+                { "$type": "Polygon", "0": [-1, 1] },
+                { "$type": "Empty" },
+            ],
+            "label": "é",
+            "outcome": { "Err": "failed" },
+        });
+        assert_eq!(to_json(&graph, root, &drawing()).unwrap(), expected);
+        let data = serialize(&drawing());
+        assert_eq!(from_bincode(&graph, root, &data).unwrap(), expected);
+        assert_eq!(to_bincode(&graph, root, &expected).unwrap(), data);
+    }
+
+    /// A type using the `Reflect` attributes that affect how values are visited.
+    #[derive(serde::Serialize, Reflect)]
+    struct Node {
+        #[reflect(flatten)]
+        span:    Span,
+        #[reflect(subtype)]
+        variant: Box<NodeVariant>,
+    }
+
+    #[derive(serde::Serialize, Reflect)]
+    struct Span {
+        #[serde(serialize_with = "serialize_optional_int")]
+        #[reflect(as = "i32", visit_with = "visit_optional_int")]
+        offset: Option<u32>,
+        #[serde(skip)]
+        #[reflect(skip)]
+        cached: bool,
+        len:    usize,
+    }
+
+    #[derive(serde::Serialize, Reflect)]
+    enum NodeVariant {
+        Pair {
+            left:  Node,
+            right: Node,
+        },
+        #[reflect(inline)]
+        Leaf(Leaf),
+    }
+
+    #[derive(serde::Serialize, Reflect)]
+    struct Leaf {
+        text: String,
+    }
+
+    fn serialize_optional_int<S: serde::Serializer>(
+        x: &Option<u32>,
+        s: S,
+    ) -> Result<S::Ok, S::Error> {
+        s.serialize_i32(x.map(|x| x as i32).unwrap_or(-1))
+    }
+
+    fn visit_optional_int(x: &Option<u32>, visitor: &mut dyn enso_reflect::Visitor) {
+        visitor.visit_i32(x.map(|x| x as i32).unwrap_or(-1))
+    }
+
+    #[test]
+    fn test_reflected_value() {
+        let leaf = |offset, text: &str| Node {
+            span:    Span { offset, cached: true, len: text.len() },
+            variant: Box::new(NodeVariant::Leaf(Leaf { text: text.to_owned() })),
+        };
+        let variant =
+            Box::new(NodeVariant::Pair { left: leaf(Some(0), "a"), right: leaf(None, "") });
+        let node = Node { span: Span { offset: Some(0), cached: false, len: 1 }, variant };
+        let (graph, rust_to_meta) = enso_metamodel::rust::to_meta(Node::reflect());
+        let root = rust_to_meta[&Node::reflect().id];
+        let json = to_json(&graph, root, &node).unwrap();
+        let expected = json!({
+            "$type": "Pair",
+            "span_offset": 0,
+            "span_len": 1,
+            "left": { "$type": "Leaf", "span_offset": 0, "span_len": 1, "text": "a" },
+            "right": { "$type": "Leaf", "span_offset": -1, "span_len": 0, "text": "" },
+        });
+        assert_eq!(json, expected);
+        assert_eq!(to_bincode(&graph, root, &json).unwrap(), serialize(&node));
+    }
+
+    #[test]
+    fn test_invalid_data() {
+        let (graph, root) = graph();
+        let err = from_bincode(&graph, root, &[1, 0, 0, 0, 0, 0, 0, 0, 7, 0, 0, 0]).unwrap_err();
+        assert_eq!(err.offset, 12);
+        assert!(err.message.starts_with("Unknown discriminant 7"), "{err}");
+        let err = from_bincode(&graph, root, &[0, 0, 0]).unwrap_err();
+        assert_eq!(err.message, "Unexpected end of input.");
+    }
+
+    #[test]
+    fn test_invalid_value() {
+        let (graph, root) = graph();
+        let check = |value: Value, pointer: &str, message: &str| {
+            let err = to_bincode(&graph, root, &value).unwrap_err();
+            assert_eq!((err.pointer.as_str(), err.message.as_str()), (pointer, message));
+        };
+        let valid =
+            |shapes: Value| json!({ "shapes": shapes, "label": null, "outcome": { "Ok": 1 } });
+        check(json!({ "shapes": [] }), "", "Missing property \"label\".");
+        let mut extra = valid(json!([]));
+        extra["color"] = json!("red");
+        check(extra, "", "Unknown property \"color\".");
+        check(
+            valid(json!([{ "$type": "Square", "side": 1 }])),
+            "/shapes/0",
+            "Type \"Square\" is not a concrete subtype of Shape.",
+        );
+        check(
+            valid(json!([{ "$type": "Circle", "radius": -1 }])),
+            "/shapes/0/radius",
+            "Expected a 32-bit unsigned integer.",
+        );
+        check(
+            json!({ "shapes": [], "label": null, "outcome": { "Ok": 1, "Err": "" } }),
+            "/outcome",
+            "Expected an object with a property \"Ok\" or \"Err\".",
+        );
+    }
+
+    #[test]
+    fn test_schema() {
+        let (graph, root) = graph();
+        let schema = schema(&graph, root);
+        assert_eq!(schema["$ref"], "#/$defs/Drawing");
+        assert_eq!(schema["$defs"]["Shape"]["oneOf"].as_array().unwrap().len(), 3);
+        let circle = &schema["$defs"]["Shape.Circle"];
+        assert_eq!(circle["properties"]["$type"], json!({ "const": "Circle" }));
+        assert_eq!(circle["required"], json!(["$type", "radius"]));
+        let value = from_bincode(&graph, root, &serialize(&drawing())).unwrap();
+        validate(&schema, &schema, &value).unwrap();
+        let invalid = json!({ "shapes": [{ "$type": "Circle" }], "label": null, "outcome": {} });
+        assert!(validate(&schema, &schema, &invalid).is_err());
+    }
+
+    /// Validate a value against a schema, supporting the keywords used by [`schema`].
+    fn validate(root: &Value, schema: &Value, value: &Value) -> Result<(), String> {
+        let error = || Err(format!("{value} does not match {schema}"));
+        if let Some(reference) = schema["$ref"].as_str() {
+            let name = reference.trim_start_matches("#/$defs/");
+            validate(root, &root["$defs"][name], value)?;
+        }
+        if let Some(variants) = schema["oneOf"].as_array() {
+            let matches = variants.iter().filter(|s| validate(root, s, value).is_ok()).count();
+            if matches != 1 {
+                return error();
+            }
+        }
+        if let Some(variants) = schema["anyOf"].as_array() {
+            if !variants.iter().any(|s| validate(root, s, value).is_ok()) {
+                return error();
+            }
+        }
+        if let Some(expected) = schema.get("const") && expected != value {
+            return error();
+        }
+        let type_matches = match schema["type"].as_str() {
+            None => true,
+            Some("null") => value.is_null(),
+            Some("boolean") => value.is_boolean(),
+            Some("integer") => value.is_i64() || value.is_u64(),
+            Some("string") => value.is_string(),
+            Some("array") => value.is_array(),
+            Some("object") => value.is_object(),
+            Some(other) => panic!("Unsupported type: {other}"),
+        };
+        if !type_matches {
+            return error();
+        }
+        if let Some(minimum) = schema["minimum"].as_i64() && value.as_i64() < Some(minimum) {
+            return error();
+        }
+        if let Some(length) = value.as_str().map(|s| s.chars().count() as u64) {
+            let min = schema["minLength"].as_u64().unwrap_or(0);
+            let max = schema["maxLength"].as_u64().unwrap_or(u64::MAX);
+            if length < min || length > max {
+                return error();
+            }
+        }
+        if let Some(items) = value.as_array() {
+            for item in items {
+                validate(root, &schema["items"], item)?;
+            }
+        }
+        if let Some(object) = value.as_object() && schema.get("properties").is_some() {
+            for key in schema["required"].as_array().unwrap() {
+                if !object.contains_key(key.as_str().unwrap()) {
+                    return error();
+                }
+            }
+            for (key, property) in object {
+                match schema["properties"].get(key) {
+                    Some(property_schema) => validate(root, property_schema, property)?,
+                    None => return error(),
+                }
+            }
+        }
+        Ok(())
+    }
+}
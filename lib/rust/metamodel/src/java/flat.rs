@@ -0,0 +1,527 @@
+//! Derivation of accessor classes that read Java types in place from a flat binary format.
+//!
+//! # Layout
+//!
+//! Every value has a fixed-size *inline* representation:
+//! - A `boolean` is one byte; an `int` is four bytes; a `long` is eight bytes.
+//! - An object of a concrete class is the concatenation of its fields. If the class has a parent,
+//!   its fields are placed at the position of the parent's child field.
+//! - An object of an abstract class is the concatenation of the fields it owns, followed by the
+//!   address of a record containing the discriminant of the concrete class (as an `int`) and the
+//!   concrete class's fields.
+//! - A `String` is the address of a record containing its length in bytes, followed by its UTF-8
+//!   contents.
+//! - A `List` is the address of a record containing its number of elements, followed by the
+//!   elements.
+//! - A nullable field, or an `Optional`, is `0` if the value is absent; otherwise, it is the
+//!   address of the value.
+//! - An `Either` is the address of a record containing a discriminant (`0` for the right type, `1`
+//!   for the left type) followed by the value.
+//!
+//! All integers are little-endian, and addresses are `int` offsets from the start of the data.
+//! This is the layout produced by the `enso_parser::serialization::flat` serializer for the Rust
+//! types a [`TypeGraph`] is derived from.
+//!
+//! # Generated classes
+//!
+//! For each class in the [`TypeGraph`], a class of the same name is generated. An instance holds
+//! a `Buffer` (defined in the support package) and the address of the object's data; its getters
+//! read from the buffer each time they are called, so no part of the data is decoded until it is
+//! used. Getters are named as in the classes produced by [`to_syntax`]; fields without a getter
+//! get a package-private accessor named after the field.
+//!
+//! Every class has a static `read(Buffer, int)` method that constructs an accessor for an object
+//! stored inline at an address; for an abstract class, the discriminant determines the class of the
+//! result.
+//!
+//! # Limitations
+//!
+//! A class may have a parent, or children, but not both; and a class with children must not have
+//! any fields after its child field.
+
+use crate::java::implementation::*;
+use crate::java::*;
+
+use std::fmt::Write;
+
+
+
+// =================
+// === Constants ===
+// =================
+
+/// The size of an address or discriminant, in bytes.
+pub const ADDRESS_SIZE: usize = 4;
+
+const BUFFER: &str = "buffer";
+const ADDRESS: &str = "address";
+const CHILD_ADDRESS: &str = "childAddress";
+const MULTILEVEL: &str = "Flat accessors for multi-level class hierarchies are not implemented.";
+const FIELDS_AFTER_CHILD: &str =
+    "Flat accessors for fields after a child field are not implemented.";
+
+
+
+// ==============
+// === Layout ===
+// ==============
+
+/// The size of the inline representation of a field's data, in bytes.
+pub fn field_size(graph: &TypeGraph, data: &FieldData) -> usize {
+    match data {
+        FieldData::Primitive(Primitive::Bool) => 1,
+        FieldData::Primitive(Primitive::Int { .. }) => 4,
+        FieldData::Primitive(Primitive::Long { .. }) => 8,
+        FieldData::Object { non_null: false, .. } => ADDRESS_SIZE,
+        FieldData::Object { type_, non_null: true } => object_size(graph, *type_),
+    }
+}
+
+/// The size of the inline representation of an object of the specified class, in bytes.
+pub fn object_size(graph: &TypeGraph, id: ClassId) -> usize {
+    let class = &graph[id];
+    if class.builtin {
+        return ADDRESS_SIZE;
+    }
+    let mut size = own_size(graph, class);
+    if class.abstract_ {
+        size += ADDRESS_SIZE;
+    }
+    if let Some(parent) = class.parent {
+        size += own_size(graph, &graph[parent]);
+    }
+    size
+}
+
+/// The total size of the fields defined by a class, in bytes.
+fn own_size(graph: &TypeGraph, class: &Class) -> usize {
+    class.fields.iter().map(|field| field_size(graph, &field.data)).sum()
+}
+
+
+
+// ========================
+// === Derive Accessors ===
+// ========================
+
+/// Supports generating accessor classes for the types in a [`TypeGraph`].
+#[derive(Debug)]
+pub struct AccessorBuilder<'g> {
+    graph:       &'g TypeGraph,
+    package:     String,
+    support:     String,
+    either_type: String,
+    methods:     BTreeMap<ClassId, Vec<syntax::Method>>,
+}
+
+impl<'g> AccessorBuilder<'g> {
+    /// Create an accessor builder.
+    /// - `package`: The package for the generated classes.
+    /// - `support`: The package that defines the `Buffer` class.
+    /// - `either_type`: The fully-qualified name of the type that implements `Either`.
+    pub fn new(
+        graph: &'g TypeGraph,
+        package: impl Into<String>,
+        support: impl Into<String>,
+        either_type: impl Into<String>,
+    ) -> Self {
+        let package = package.into();
+        let support = support.into();
+        let either_type = either_type.into();
+        let methods = Default::default();
+        Self { graph, package, support, either_type, methods }
+    }
+
+    /// Add a method to the accessor generated for the specified class. The method may use the
+    /// `buffer` and `address` fields, and the accessors of the class.
+    pub fn method(&mut self, class: ClassId, method: syntax::Method) {
+        self.methods.entry(class).or_default().push(method);
+    }
+
+    /// Generate the accessor classes.
+    ///
+    /// Fails if the graph contains a class hierarchy outside the supported [limitations], or a
+    /// builtin class that has no flat representation.
+    ///
+    /// [limitations]: crate::java::flat#limitations
+    pub fn build(mut self) -> Result<Vec<syntax::Class>, String> {
+        let graph = self.graph;
+        let mut classes = BTreeMap::new();
+        for (id, class) in graph.classes.iter() {
+            if !class.builtin {
+                classes.insert(id, self.class(id)?);
+            }
+        }
+        for (id, class) in graph.classes.iter() {
+            if let Some(parent) = class.parent {
+                let mut inner = classes.remove(&id).unwrap();
+                inner.static_ = true;
+                classes.get_mut(&parent).unwrap().nested.push(inner);
+            }
+        }
+        for class in classes.values_mut() {
+            class.package = Some(self.package.clone());
+        }
+        Ok(classes.into_values().collect())
+    }
+}
+
+
+// === Classes ===
+
+impl<'g> AccessorBuilder<'g> {
+    fn class(&mut self, id: ClassId) -> Result<syntax::Class, String> {
+        let graph = self.graph;
+        let class = &graph[id];
+        let buffer = syntax::Type::named(format!("{}.Buffer", &self.support));
+        let int = syntax::Type::named("int");
+        let mut fields = vec![];
+        let mut constructor = syntax::Method::constructor(class.name.clone());
+        let base = match class.parent {
+            None => {
+                let type_ = buffer.clone();
+                fields.push(syntax::Field { type_, name: BUFFER.to_owned(), final_: true });
+                let type_ = int.clone();
+                fields.push(syntax::Field { type_, name: ADDRESS.to_owned(), final_: true });
+                constructor.arguments =
+                    vec![(buffer.clone(), BUFFER.to_owned()), (int, ADDRESS.to_owned())];
+                constructor.body =
+                    format!("this.{BUFFER} = {BUFFER};\nthis.{ADDRESS} = {ADDRESS};");
+                ADDRESS
+            }
+            Some(parent) => {
+                if graph[parent].parent.is_some() {
+                    return Err(format!("{MULTILEVEL} Class: {}.", class.name));
+                }
+                let type_ = int.clone();
+                fields.push(syntax::Field { type_, name: CHILD_ADDRESS.to_owned(), final_: true });
+                constructor.arguments = vec![
+                    (buffer.clone(), BUFFER.to_owned()),
+                    (int.clone(), ADDRESS.to_owned()),
+                    (int, CHILD_ADDRESS.to_owned()),
+                ];
+                constructor.body =
+                    format!("super({BUFFER}, {ADDRESS});\nthis.{CHILD_ADDRESS} = {CHILD_ADDRESS};");
+                CHILD_ADDRESS
+            }
+        };
+        if matches!(class.child_field, Some(child_field) if child_field != class.fields.len()) {
+            return Err(format!("{FIELDS_AFTER_CHILD} Class: {}.", class.name));
+        }
+        let mut methods = vec![constructor, self.read_method(id, buffer)];
+        let mut offset = 0;
+        for field in &class.fields {
+            methods.push(self.accessor(class, field, &format!("{base} + {offset}"))?);
+            offset += field_size(graph, &field.data);
+        }
+        methods.extend(self.methods.remove(&id).unwrap_or_default());
+        Ok(syntax::Class {
+            package: None,
+            name: class.name.clone(),
+            abstract_: class.abstract_,
+            final_: !class.abstract_,
+            static_: false,
+            parent: class.parent.map(|id| quote_class_type(graph, id)),
+            fields,
+            methods,
+            nested: vec![],
+            sealed: None,
+        })
+    }
+
+    /// Generate the static method that constructs an accessor for an object stored inline.
+    fn read_method(&self, id: ClassId, buffer: syntax::Type) -> syntax::Method {
+        let graph = self.graph;
+        let class = &graph[id];
+        let ty = quote_class_type(graph, id);
+        let mut body = String::new();
+        if class.abstract_ {
+            let child_offset = own_size(graph, class);
+            writeln!(body, "int child = {BUFFER}.getInt({ADDRESS} + {child_offset});").unwrap();
+            writeln!(body, "int discriminant = {BUFFER}.getInt(child);").unwrap();
+            writeln!(body, "switch (discriminant) {{").unwrap();
+            for (key, child) in &class.discriminants {
+                let child = path(graph, *child);
+                writeln!(body, "case {key}: return new {child}({BUFFER}, {ADDRESS}, child + 4);")
+                    .unwrap();
+            }
+            let err = format!("\"Unknown discriminant \" + discriminant + \" in {ty}.\"");
+            writeln!(body, "default: throw {BUFFER}.error({err}); }}").unwrap();
+        } else if let Some(parent) = class.parent {
+            let child_offset = own_size(graph, &graph[parent]);
+            writeln!(body, "return new {ty}({BUFFER}, {ADDRESS}, {ADDRESS} + {child_offset});")
+                .unwrap();
+        } else {
+            writeln!(body, "return new {ty}({BUFFER}, {ADDRESS});").unwrap();
+        }
+        let mut method = syntax::Method::new("read", ty);
+        method.static_ = true;
+        method.arguments =
+            vec![(buffer, BUFFER.to_owned()), (syntax::Type::named("int"), ADDRESS.to_owned())];
+        method.body = body;
+        method
+    }
+
+    /// Generate a method that reads a field.
+    fn accessor(
+        &self,
+        class: &Class,
+        field: &Field,
+        address: &str,
+    ) -> Result<syntax::Method, String> {
+        let getter = class.methods.iter().find_map(|method| match method {
+            Method::Dynamic(Dynamic::GetterNamed(id, name)) if *id == field.id() => Some(name),
+            Method::Dynamic(Dynamic::Getter(id)) if *id == field.id() => Some(&field.name),
+            _ => None,
+        });
+        let mut method = match getter {
+            Some(name) => syntax::Method::new(name, quote_type(self.graph, &field.data)),
+            None => {
+                let mut method =
+                    syntax::Method::new(&field.name, quote_type(self.graph, &field.data));
+                method.visibility = None;
+                method
+            }
+        };
+        method.body = format!("return {};", self.read(&field.data, address, 0)?);
+        Ok(method)
+    }
+}
+
+
+// === Reading Values ===
+
+impl<'g> AccessorBuilder<'g> {
+    /// Produce an expression reading a field's data from an address.
+    ///
+    /// Expressions nested within lambdas are distinguished by their `depth`, so that their
+    /// variables do not shadow each other.
+    fn read(&self, data: &FieldData, address: &str, depth: usize) -> Result<String, String> {
+        let buffer = buffer_variable(depth);
+        Ok(match data {
+            FieldData::Primitive(Primitive::Bool) => format!("{buffer}.getBoolean({address})"),
+            FieldData::Primitive(Primitive::Int { .. }) => format!("{buffer}.getInt({address})"),
+            FieldData::Primitive(Primitive::Long { .. }) => format!("{buffer}.getLong({address})"),
+            FieldData::Object { type_, non_null: true } =>
+                self.read_object(*type_, address, depth)?,
+            FieldData::Object { type_, non_null: false } => {
+                let reader = self.reader(*type_, depth + 1)?;
+                format!("{buffer}.nullable({address}, {reader})")
+            }
+        })
+    }
+
+    /// Produce an expression reading an object of the specified class from an address.
+    fn read_object(&self, id: ClassId, address: &str, depth: usize) -> Result<String, String> {
+        let graph = self.graph;
+        let buffer = buffer_variable(depth);
+        let class = &graph[id];
+        if !class.builtin {
+            return Ok(format!("{}.read({buffer}, {address})", path(graph, id)));
+        }
+        Ok(match class.name.as_str() {
+            STRING => format!("{buffer}.getString({address})"),
+            LIST => {
+                let element = class.params[0];
+                let size = object_size(graph, element);
+                let reader = self.reader(element, depth + 1)?;
+                format!("{buffer}.list({address}, {size}, {reader})")
+            }
+            OPTIONAL => {
+                let reader = self.reader(class.params[0], depth + 1)?;
+                format!("{buffer}.optional({address}, {reader})")
+            }
+            name if name == self.either_type => {
+                let left = self.reader(class.params[0], depth + 1)?;
+                let right = self.reader(class.params[1], depth + 1)?;
+                format!("{buffer}.either({address}, {left}, {right})")
+            }
+            name => return Err(format!("Unsupported builtin: {name}.")),
+        })
+    }
+
+    /// Produce a `Buffer.Reader` for objects of the specified class.
+    fn reader(&self, id: ClassId, depth: usize) -> Result<String, String> {
+        let graph = self.graph;
+        if !graph[id].builtin {
+            return Ok(format!("{}::read", path(graph, id)));
+        }
+        let buffer = buffer_variable(depth);
+        let address = format!("{ADDRESS}{depth}");
+        let value = self.read_object(id, &address, depth)?;
+        Ok(format!("({buffer}, {address}) -> {value}"))
+    }
+}
+
+fn buffer_variable(depth: usize) -> String {
+    match depth {
+        0 => BUFFER.to_owned(),
+        depth => format!("{BUFFER}{depth}"),
+    }
+}
+
+
+
+// ==================
+// === Validation ===
+// ==================
+
+/// Check that data is consistent with the layout of the specified class: every address must be in
+/// bounds, every discriminant valid, and every string valid UTF-8.
+///
+/// On success, returns the total size of the object's inline representation and of all records
+/// reachable from it. For data produced by a serializer, this is the size of the data excluding any
+/// header; a different value indicates that the serializer disagrees with the layout.
+pub fn validate(
+    graph: &TypeGraph,
+    root: ClassId,
+    data: &[u8],
+    address: usize,
+) -> Result<usize, String> {
+    let mut validator = Validator { graph, data, size: 0 };
+    validator.object(root, address)?;
+    Ok(validator.size + object_size(graph, root))
+}
+
+#[derive(Debug)]
+struct Validator<'a> {
+    graph: &'a TypeGraph,
+    data:  &'a [u8],
+    size:  usize,
+}
+
+impl<'a> Validator<'a> {
+    fn bytes(&self, address: usize, len: usize) -> Result<&'a [u8], String> {
+        let data = self.data;
+        data.get(address..address + len).ok_or_else(|| format!("Out of bounds: {address}+{len}."))
+    }
+
+    fn address(&self, address: usize) -> Result<usize, String> {
+        let bytes = self.bytes(address, ADDRESS_SIZE)?;
+        Ok(u32::from_le_bytes(bytes.try_into().unwrap()) as usize)
+    }
+
+    /// Check that a record is in bounds, and count its size.
+    fn record(&mut self, address: usize, len: usize) -> Result<(), String> {
+        self.bytes(address, len)?;
+        self.size += len;
+        Ok(())
+    }
+
+    fn fields(&mut self, class: &Class, address: usize) -> Result<(), String> {
+        let mut offset = 0;
+        for field in &class.fields {
+            self.field(&field.data, address + offset)?;
+            offset += field_size(self.graph, &field.data);
+        }
+        Ok(())
+    }
+
+    fn field(&mut self, data: &FieldData, address: usize) -> Result<(), String> {
+        match data {
+            FieldData::Primitive(Primitive::Bool) => match self.bytes(address, 1)?[0] {
+                0 | 1 => Ok(()),
+                x => Err(format!("Invalid boolean at {address}: {x}.")),
+            },
+            FieldData::Primitive(_) => self.bytes(address, field_size(self.graph, data)).map(drop),
+            FieldData::Object { type_, non_null: true } => self.object(*type_, address),
+            FieldData::Object { type_, non_null: false } => self.nullable(*type_, address),
+        }
+    }
+
+    fn nullable(&mut self, id: ClassId, address: usize) -> Result<(), String> {
+        match self.address(address)? {
+            0 => Ok(()),
+            record => {
+                self.record(record, object_size(self.graph, id))?;
+                self.object(id, record)
+            }
+        }
+    }
+
+    fn object(&mut self, id: ClassId, address: usize) -> Result<(), String> {
+        let graph = self.graph;
+        let class = &graph[id];
+        if class.builtin {
+            return self.builtin(class, address);
+        }
+        let mut fields_address = address;
+        if let Some(parent) = class.parent {
+            let parent = &graph[parent];
+            self.fields(parent, address)?;
+            fields_address += own_size(graph, parent);
+        }
+        self.fields(class, fields_address)?;
+        if class.abstract_ {
+            let record = self.address(fields_address + own_size(graph, class))?;
+            let discriminant = self.address(record)?;
+            let child = class.discriminants.get(&discriminant);
+            let child = child.ok_or_else(|| {
+                format!("Unknown discriminant at {record} in {}: {discriminant}.", class.name)
+            })?;
+            let child = &graph[*child];
+            self.record(record, ADDRESS_SIZE + own_size(graph, child))?;
+            self.fields(child, record + ADDRESS_SIZE)?;
+        }
+        Ok(())
+    }
+
+    fn builtin(&mut self, class: &Class, address: usize) -> Result<(), String> {
+        let graph = self.graph;
+        let record = self.address(address)?;
+        match class.name.as_str() {
+            STRING => {
+                let len = self.address(record)?;
+                self.record(record, ADDRESS_SIZE + len)?;
+                let bytes = self.bytes(record + ADDRESS_SIZE, len)?;
+                std::str::from_utf8(bytes)
+                    .map_err(|e| format!("Invalid string at {record}: {e}"))?;
+                Ok(())
+            }
+            LIST => {
+                let count = self.address(record)?;
+                let element = class.params[0];
+                let size = object_size(graph, element);
+                self.record(record, ADDRESS_SIZE + count * size)?;
+                for i in 0..count {
+                    self.object(element, record + ADDRESS_SIZE + i * size)?;
+                }
+                Ok(())
+            }
+            OPTIONAL => self.nullable(class.params[0], address),
+            // The only other builtin with two parameters is `Either`.
+            _ if class.params.len() == 2 => {
+                let value = match self.address(record)? {
+                    0 => class.params[1],
+                    1 => class.params[0],
+                    x => return Err(format!("Unknown discriminant at {record} in Either: {x}.")),
+                };
+                self.record(record, ADDRESS_SIZE + object_size(graph, value))?;
+                self.object(value, record + ADDRESS_SIZE)
+            }
+            name => Err(format!("Unsupported builtin: {name}.")),
+        }
+    }
+}
+
+
+
+// =============
+// === Tests ===
+// =============
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn unsupported_builtin() {
+        let mut graph = TypeGraph::default();
+        let map = graph.classes.insert(Class::builtin("java.util.Map", []));
+        let fields = vec![Field::object("map", map, true)];
+        let class = Class { name: "Holder".to_owned(), fields, ..Default::default() };
+        graph.classes.insert(class);
+        let builder = AccessorBuilder::new(&graph, "flat", "flat", "Either");
+        assert_eq!(builder.build().unwrap_err(), "Unsupported builtin: java.util.Map.");
+    }
+}
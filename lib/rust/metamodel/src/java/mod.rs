@@ -6,6 +6,7 @@
 // ==============
 
 pub mod bincode;
+pub mod flat;
 
 
 
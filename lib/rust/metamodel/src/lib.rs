@@ -10,6 +10,10 @@
 //! - [`meta`]: An abstract metamodel, used to perform language-independent analysis of data models,
 //!   and as an intermediate when translating data models between language-specific metamodels.
 //!
+//! Some backends generate code directly from the [`meta`] representation:
+//! - [`typescript`]: TypeScript type definitions, with derived deserialization.
+//! - [`scala`]: Scala case classes, with derived deserialization.
+//!
 //! Some accessory modules:
 //! - [`graphviz`]: Support for rendering graphical representations of data models with GraphViz.
 //!   This serves primarily to support developing and understanding transformations on and between
@@ -27,7 +31,13 @@
 //! Other use cases supported include:
 //! - Analyze a data model's serialization to generate exhaustive test cases, using
 //!   [`meta::serialization::testcases`].
+//! - Check whether changes to a data model break compatibility of its serialized format, using
+//!   [`meta::evolution::compare`].
 //! - Produce graphs of type relationships, using [graphviz::Graph]`
+//! - Generate TypeScript types and their deserialization for a data model, using
+//!   [`typescript::from_meta`] and [`typescript::bincode`].
+//! - Generate Scala case classes for a data model, and deserialization methods for them, using
+//!   [`scala::from_meta`] and [`scala::bincode`].
 //!
 //! # Design
 //!
@@ -73,3 +83,7 @@ pub mod java;
 pub mod meta;
 #[cfg(feature = "rust")]
 pub mod rust;
+#[cfg(feature = "scala")]
+pub mod scala;
+#[cfg(feature = "typescript")]
+pub mod typescript;
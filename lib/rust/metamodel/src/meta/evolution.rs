@@ -0,0 +1,663 @@
+//! Schema evolution checking on meta representations.
+//!
+//! The [`compare`] function finds the differences between two versions of a data model, and
+//! classifies each according to its effect on the serialized format. The format is bincode (see
+//! [`crate::java::bincode`]): it is not self-describing, so almost any change to the layout of a
+//! type is breaking--including adding an optional field, which changes the position of all
+//! following data. The changes that don't break the format are:
+//! - Renaming a type or field.
+//! - Adding a variant to a sum type with a discriminant that was not previously used. Data written
+//!   by the old version can be read by the new version, but not necessarily the reverse.
+//!
+//! # Schema Snapshots
+//!
+//! To compare versions of a data model from different builds (e.g. two git revisions), a model can
+//! be saved with [`to_schema`], and loaded with [`from_schema`]. A schema snapshot contains the
+//! information that affects the serialized format, and the names of types and fields.
+
+use crate::meta::*;
+
+use std::fmt;
+use std::fmt::Write;
+
+
+
+// ===============
+// === Changes ===
+// ===============
+
+/// The effect of a change on the compatibility of serialized data.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Compatibility {
+    /// Data is interpreted identically by the old and new versions.
+    Compatible,
+    /// Data written by the old version can be read by the new version, but not necessarily the
+    /// reverse.
+    BackwardCompatible,
+    /// Data written by the old version may be read incorrectly by the new version.
+    Breaking,
+}
+
+/// A difference between two versions of a data model.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Change {
+    /// Where the change occurs: the qualified name of the type in the new version of the model,
+    /// followed by the name of the field, if the change applies to a field.
+    pub location: String,
+    #[allow(missing_docs)]
+    pub kind:     ChangeKind,
+}
+
+/// The kinds of differences between two versions of a data model.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[allow(missing_docs)]
+pub enum ChangeKind {
+    /// A type has a different name.
+    TypeRenamed { old: String },
+    /// A field has a different name.
+    FieldRenamed { old: String },
+    /// A field has been added.
+    FieldAdded { optional: bool },
+    /// A field has been removed.
+    FieldRemoved,
+    /// Fields that occur in both versions occur in a different order.
+    FieldsReordered,
+    /// A field is serialized on the other side of the data of the child types.
+    ChildFieldMoved,
+    /// A value has an incompatible type.
+    TypeChanged { old: String, new: String },
+    /// A type has a different parent type, or has gained or lost a parent.
+    ParentChanged,
+    /// A subtype has been added, with a new discriminant.
+    VariantAdded { name: String, discriminant: usize },
+    /// A subtype has been removed.
+    VariantRemoved { name: String, discriminant: usize },
+    /// A subtype is identified by a different discriminant.
+    VariantMoved { name: String, old: usize, new: usize },
+}
+
+impl ChangeKind {
+    /// Return the effect of the change on the compatibility of serialized data.
+    pub fn compatibility(&self) -> Compatibility {
+        match self {
+            ChangeKind::TypeRenamed { .. } | ChangeKind::FieldRenamed { .. } =>
+                Compatibility::Compatible,
+            ChangeKind::VariantAdded { .. } => Compatibility::BackwardCompatible,
+            ChangeKind::FieldAdded { .. }
+            | ChangeKind::FieldRemoved
+            | ChangeKind::FieldsReordered
+            | ChangeKind::ChildFieldMoved
+            | ChangeKind::TypeChanged { .. }
+            | ChangeKind::ParentChanged
+            | ChangeKind::VariantRemoved { .. }
+            | ChangeKind::VariantMoved { .. } => Compatibility::Breaking,
+        }
+    }
+}
+
+impl Change {
+    /// Return the effect of the change on the compatibility of serialized data.
+    pub fn compatibility(&self) -> Compatibility {
+        self.kind.compatibility()
+    }
+}
+
+/// The result of comparing two versions of a data model.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Report {
+    /// The differences found, in the order they were encountered.
+    pub changes: Vec<Change>,
+}
+
+impl Report {
+    /// Return the overall compatibility of the new version of the model with the old version.
+    pub fn compatibility(&self) -> Compatibility {
+        let changes = self.changes.iter().map(|change| change.compatibility());
+        changes.max().unwrap_or(Compatibility::Compatible)
+    }
+
+    /// Return whether any of the changes is breaking.
+    pub fn is_breaking(&self) -> bool {
+        self.compatibility() == Compatibility::Breaking
+    }
+}
+
+impl fmt::Display for ChangeKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ChangeKind::TypeRenamed { old } => write!(f, "type renamed (from {old})"),
+            ChangeKind::FieldRenamed { old } => write!(f, "field renamed (from {old})"),
+            ChangeKind::FieldAdded { optional: true } => write!(f, "optional field added"),
+            ChangeKind::FieldAdded { optional: false } => write!(f, "field added"),
+            ChangeKind::FieldRemoved => write!(f, "field removed"),
+            ChangeKind::FieldsReordered => write!(f, "fields reordered"),
+            ChangeKind::ChildFieldMoved => write!(f, "field moved relative to child data"),
+            ChangeKind::TypeChanged { old, new } => write!(f, "type changed from {old} to {new}"),
+            ChangeKind::ParentChanged => write!(f, "parent type changed"),
+            ChangeKind::VariantAdded { name, discriminant } =>
+                write!(f, "variant {name} added (discriminant {discriminant})"),
+            ChangeKind::VariantRemoved { name, discriminant } =>
+                write!(f, "variant {name} removed (discriminant {discriminant})"),
+            ChangeKind::VariantMoved { name, old, new } =>
+                write!(f, "variant {name} moved from discriminant {old} to {new}"),
+        }
+    }
+}
+
+impl fmt::Display for Report {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for change in &self.changes {
+            let Change { location, kind } = change;
+            writeln!(f, "{:?}: {location}: {kind}", change.compatibility())?;
+        }
+        Ok(())
+    }
+}
+
+
+
+// ==================
+// === Comparison ===
+// ==================
+
+/// Find the differences between two versions of a data model, considering the types reachable
+/// from the given root types.
+pub fn compare(old: &TypeGraph, old_root: TypeId, new: &TypeGraph, new_root: TypeId) -> Report {
+    let visited = Default::default();
+    let report = Default::default();
+    let mut comparison = Comparison { old, new, visited, report };
+    comparison.values(old_root, new_root, &new.path(new_root));
+    comparison.report
+}
+
+struct Comparison<'g> {
+    old:     &'g TypeGraph,
+    new:     &'g TypeGraph,
+    visited: BTreeSet<(TypeId, TypeId)>,
+    report:  Report,
+}
+
+impl<'g> Comparison<'g> {
+    fn push(&mut self, location: impl Into<String>, kind: ChangeKind) {
+        let location = location.into();
+        self.report.changes.push(Change { location, kind });
+    }
+
+    /// Compare the types of corresponding values; `location` identifies the value.
+    fn values(&mut self, old: TypeId, new: TypeId, location: &str) {
+        let (old_ty, new_ty) = (&self.old[old], &self.new[new]);
+        match (&old_ty.data, &new_ty.data) {
+            (Data::Struct(_), Data::Struct(_)) => self.types(old, new),
+            (Data::Primitive(p0), Data::Primitive(p1)) => match (*p0, *p1) {
+                (Primitive::Sequence(t0), Primitive::Sequence(t1))
+                | (Primitive::Option(t0), Primitive::Option(t1)) => self.values(t0, t1, location),
+                (Primitive::Result(t0, e0), Primitive::Result(t1, e1)) => {
+                    self.values(t0, t1, location);
+                    self.values(e0, e1, location);
+                }
+                (p0, p1) if p0 == p1 => {}
+                _ => self.type_changed(old, new, location),
+            },
+            _ => self.type_changed(old, new, location),
+        }
+    }
+
+    fn type_changed(&mut self, old: TypeId, new: TypeId, location: &str) {
+        let old = describe(self.old, old);
+        let new = describe(self.new, new);
+        self.push(location, ChangeKind::TypeChanged { old, new });
+    }
+
+    /// Compare corresponding struct types.
+    fn types(&mut self, old: TypeId, new: TypeId) {
+        if !self.visited.insert((old, new)) {
+            return;
+        }
+        let (old_ty, new_ty) = (&self.old[old], &self.new[new]);
+        let location = self.new.path(new);
+        if old_ty.name != new_ty.name {
+            self.push(&location, ChangeKind::TypeRenamed { old: self.old.path(old) });
+        }
+        match (old_ty.parent, new_ty.parent) {
+            (Some(old_parent), Some(new_parent)) => self.types(old_parent, new_parent),
+            (None, None) => {}
+            _ => self.push(&location, ChangeKind::ParentChanged),
+        }
+        self.fields(old, new, &location);
+        self.variants(old, new, &location);
+    }
+
+    /// Compare the fields declared by corresponding types.
+    fn fields(&mut self, old: TypeId, new: TypeId, location: &str) {
+        let (old_ty, new_ty) = (&self.old[old], &self.new[new]);
+        let old_fields = old_ty.data.as_struct().unwrap_or_default();
+        let new_fields = new_ty.data.as_struct().unwrap_or_default();
+        let old_names: Vec<_> = old_fields.iter().enumerate().map(field_key).collect();
+        let new_names: Vec<_> = new_fields.iter().enumerate().map(field_key).collect();
+        let mut pairs = vec![];
+        let mut unmatched_old = vec![];
+        for (i, name) in old_names.iter().enumerate() {
+            match new_names.iter().position(|new_name| new_name == name) {
+                Some(j) => pairs.push((i, j)),
+                None => unmatched_old.push(i),
+            }
+        }
+        let mut unmatched_new: Vec<_> =
+            (0..new_fields.len()).filter(|j| !pairs.iter().any(|(_, j_)| j_ == j)).collect();
+        if pairs.windows(2).any(|pair| pair[0].1 > pair[1].1) {
+            self.push(location, ChangeKind::FieldsReordered);
+        }
+        // Fields with different names at the same positions are treated as renamed.
+        if unmatched_old == unmatched_new {
+            for &i in &unmatched_old {
+                let field_location = format!("{location}.{}", new_names[i]);
+                let old = old_names[i].clone();
+                self.push(field_location, ChangeKind::FieldRenamed { old });
+                pairs.push((i, i));
+            }
+            unmatched_old.clear();
+            unmatched_new.clear();
+        }
+        for i in unmatched_old {
+            self.push(format!("{location}.{}", old_names[i]), ChangeKind::FieldRemoved);
+        }
+        for j in unmatched_new {
+            let optional =
+                matches!(self.new[new_fields[j].type_].data, Data::Primitive(Primitive::Option(_)));
+            self.push(format!("{location}.{}", new_names[j]), ChangeKind::FieldAdded { optional });
+        }
+        let old_split = old_ty.child_field.unwrap_or(old_fields.len());
+        let new_split = new_ty.child_field.unwrap_or(new_fields.len());
+        pairs.sort();
+        for (i, j) in pairs {
+            let field_location = format!("{location}.{}", new_names[j]);
+            if (i < old_split) != (j < new_split) {
+                self.push(&field_location, ChangeKind::ChildFieldMoved);
+            }
+            self.values(old_fields[i].type_, new_fields[j].type_, &field_location);
+        }
+    }
+
+    /// Compare the subtypes of corresponding types.
+    fn variants(&mut self, old: TypeId, new: TypeId, location: &str) {
+        let name = |graph: &TypeGraph, id: &TypeId| graph[*id].name.to_string();
+        let old_variants: BTreeMap<_, _> = self.old[old]
+            .discriminants
+            .iter()
+            .map(|(key, id)| (*key, (name(self.old, id), *id)))
+            .collect();
+        let new_variants: BTreeMap<_, _> = self.new[new]
+            .discriminants
+            .iter()
+            .map(|(key, id)| (*key, (name(self.new, id), *id)))
+            .collect();
+        let mut pairs = vec![];
+        let mut unmatched_old = BTreeMap::new();
+        for (key, (name, id)) in &old_variants {
+            match new_variants.iter().find(|(_, (new_name, _))| new_name == name) {
+                Some((new_key, (_, new_id))) => {
+                    if key != new_key {
+                        let (name, old, new) = (name.clone(), *key, *new_key);
+                        self.push(location, ChangeKind::VariantMoved { name, old, new });
+                    }
+                    pairs.push((*id, *new_id));
+                }
+                None => {
+                    unmatched_old.insert(*key, (name, *id));
+                }
+            }
+        }
+        let matched_new: BTreeSet<_> = pairs.iter().map(|(_, new_id)| *new_id).collect();
+        for (key, (name, id)) in &new_variants {
+            if matched_new.contains(id) {
+                continue;
+            }
+            // A variant with a different name at the same discriminant is treated as renamed.
+            match unmatched_old.remove(key) {
+                Some((_, old_id)) => pairs.push((old_id, *id)),
+                None => {
+                    let (name, discriminant) = (name.clone(), *key);
+                    self.push(location, ChangeKind::VariantAdded { name, discriminant });
+                }
+            }
+        }
+        for (discriminant, (name, _)) in unmatched_old {
+            let name = name.clone();
+            self.push(location, ChangeKind::VariantRemoved { name, discriminant });
+        }
+        for (old, new) in pairs {
+            self.types(old, new);
+        }
+    }
+}
+
+/// Identify a field: by name if it has one, otherwise by position.
+fn field_key((index, field): (usize, &Field)) -> String {
+    field.name.to_camel_case().unwrap_or_else(|| index.to_string())
+}
+
+/// Produce a description of a type for use in messages.
+fn describe(graph: &TypeGraph, id: TypeId) -> String {
+    let primitive = match &graph[id].data {
+        Data::Struct(_) => return graph.path(id),
+        Data::Primitive(primitive) => primitive,
+    };
+    match *primitive {
+        Primitive::Sequence(t0) => format!("Sequence({})", describe(graph, t0)),
+        Primitive::Option(t0) => format!("Option({})", describe(graph, t0)),
+        Primitive::Result(t0, t1) =>
+            format!("Result({}, {})", describe(graph, t0), describe(graph, t1)),
+        primitive => format!("{primitive:?}"),
+    }
+}
+
+
+
+// ========================
+// === Schema Snapshots ===
+// ========================
+
+/// Produce a textual snapshot of the types reachable from some root type, which can be loaded with
+/// [`from_schema`].
+///
+/// Each line describes one type; types refer to each other by line number, and the root type is on
+/// the first line. For example:
+/// ```text
+/// Tree struct span:1 .. | 0:2 1:3
+/// U32 u32
+/// Ident ^0 struct token:1
+/// Number ^0 struct digits:1 base:4
+/// OptionU32 option 1
+/// ```
+/// A struct lists its fields as `name:type` (`_` for an unnamed field), with `..` marking the
+/// position of the data of its child types, and is followed by the discriminants of its child
+/// types, if any.
+pub fn to_schema(graph: &TypeGraph, root: TypeId) -> String {
+    let mut indices = BTreeMap::new();
+    let mut order = vec![];
+    let mut index = |id: TypeId, order: &mut Vec<TypeId>| {
+        *indices.entry(id).or_insert_with(|| {
+            order.push(id);
+            order.len() - 1
+        })
+    };
+    index(root, &mut order);
+    let mut schema = String::new();
+    let mut i = 0;
+    while let Some(&id) = order.get(i) {
+        i += 1;
+        let ty = &graph[id];
+        write!(schema, "{}", ty.name).unwrap();
+        if let Some(parent) = ty.parent {
+            write!(schema, " ^{}", index(parent, &mut order)).unwrap();
+        }
+        match &ty.data {
+            Data::Struct(fields) => {
+                schema.push_str(" struct");
+                for (i, field) in fields.iter().enumerate() {
+                    if ty.child_field == Some(i) {
+                        schema.push_str(" ..");
+                    }
+                    let name = match field.name.is_empty() {
+                        true => "_".to_owned(),
+                        false => field.name.as_identifier().to_snake_case(),
+                    };
+                    write!(schema, " {name}:{}", index(field.type_, &mut order)).unwrap();
+                }
+                if ty.child_field == Some(fields.len()) {
+                    schema.push_str(" ..");
+                }
+            }
+            Data::Primitive(primitive) => match *primitive {
+                Primitive::Bool => schema.push_str(" bool"),
+                Primitive::U32 => schema.push_str(" u32"),
+                Primitive::U64 => schema.push_str(" u64"),
+                Primitive::I32 => schema.push_str(" i32"),
+                Primitive::I64 => schema.push_str(" i64"),
+                Primitive::Char => schema.push_str(" char"),
+                Primitive::String => schema.push_str(" string"),
+                Primitive::Sequence(t0) =>
+                    write!(schema, " sequence {}", index(t0, &mut order)).unwrap(),
+                Primitive::Option(t0) =>
+                    write!(schema, " option {}", index(t0, &mut order)).unwrap(),
+                Primitive::Result(t0, t1) => {
+                    let t0 = index(t0, &mut order);
+                    let t1 = index(t1, &mut order);
+                    write!(schema, " result {t0} {t1}").unwrap();
+                }
+            },
+        }
+        if !ty.discriminants.is_empty() {
+            schema.push_str(" |");
+            for (key, id) in &ty.discriminants {
+                write!(schema, " {key}:{}", index(*id, &mut order)).unwrap();
+            }
+        }
+        schema.push('\n');
+    }
+    schema
+}
+
+/// Load a snapshot produced by [`to_schema`]. Returns the graph, and the root type.
+pub fn from_schema(schema: &str) -> Result<(TypeGraph, TypeId), String> {
+    let lines: Vec<_> = schema.lines().filter(|line| !line.trim().is_empty()).collect();
+    if lines.is_empty() {
+        return Err("Empty schema.".to_owned());
+    }
+    let mut graph = TypeGraph::new();
+    let keys: Vec<_> = lines.iter().map(|_| graph.types.unbound_key()).collect();
+    let ids: Vec<TypeId> = keys.iter().map(|key| key.into()).collect();
+    let mut types = Vec::with_capacity(lines.len());
+    for (i, line) in lines.iter().enumerate() {
+        let ty = parse_type(line, &ids).map_err(|e| format!("Line {}: {e}", i + 1))?;
+        types.push(ty);
+    }
+    for (key, ty) in keys.into_iter().zip(types) {
+        graph.types.bind(key, ty);
+    }
+    Ok((graph, ids[0]))
+}
+
+fn parse_type(line: &str, ids: &[TypeId]) -> Result<Type, String> {
+    let type_id = |s: &str| -> Result<TypeId, String> {
+        let index: usize = s.parse().map_err(|_| format!("Invalid type reference: {s:?}."))?;
+        ids.get(index).copied().ok_or_else(|| format!("Type reference out of range: {index}."))
+    };
+    let mut tokens = line.split_whitespace().peekable();
+    let name = tokens.next().ok_or("Missing type name.")?;
+    let name = TypeName::from_pascal_case(name);
+    let parent = match tokens.next_if(|token| token.starts_with('^')) {
+        Some(parent) => Some(type_id(&parent[1..])?),
+        None => None,
+    };
+    let mut next = || tokens.next().ok_or_else(|| "Unexpected end of line.".to_owned());
+    let mut child_field = None;
+    let data = match next()? {
+        "bool" => Data::Primitive(Primitive::Bool),
+        "u32" => Data::Primitive(Primitive::U32),
+        "u64" => Data::Primitive(Primitive::U64),
+        "i32" => Data::Primitive(Primitive::I32),
+        "i64" => Data::Primitive(Primitive::I64),
+        "char" => Data::Primitive(Primitive::Char),
+        "string" => Data::Primitive(Primitive::String),
+        "sequence" => Data::Primitive(Primitive::Sequence(type_id(next()?)?)),
+        "option" => Data::Primitive(Primitive::Option(type_id(next()?)?)),
+        "result" => Data::Primitive(Primitive::Result(type_id(next()?)?, type_id(next()?)?)),
+        "struct" => {
+            let mut fields = vec![];
+            while let Some(token) = tokens.next_if(|token| *token != "|") {
+                if token == ".." {
+                    child_field = Some(fields.len());
+                    continue;
+                }
+                let (name, type_) =
+                    token.split_once(':').ok_or_else(|| format!("Invalid field: {token:?}."))?;
+                let type_ = type_id(type_)?;
+                fields.push(match name {
+                    "_" => Field::unnamed(type_),
+                    name => Field::named(FieldName::from_snake_case(name), type_),
+                });
+            }
+            Data::Struct(fields)
+        }
+        token => return Err(format!("Unknown type: {token:?}.")),
+    };
+    let mut discriminants = BTreeMap::new();
+    if tokens.next_if_eq(&"|").is_some() {
+        for token in tokens.by_ref() {
+            let (key, id) =
+                token.split_once(':').ok_or_else(|| format!("Invalid discriminant: {token:?}."))?;
+            let key = key.parse().map_err(|_| format!("Invalid discriminant: {key:?}."))?;
+            discriminants.insert(key, type_id(id)?);
+        }
+    }
+    if let Some(token) = tokens.next() {
+        return Err(format!("Unexpected token: {token:?}."));
+    }
+    let mut ty = Type::new(name, data);
+    ty.parent = parent;
+    ty.child_field = child_field;
+    ty.abstract_ = !discriminants.is_empty();
+    ty.discriminants = discriminants;
+    Ok(ty)
+}
+
+
+
+// =============
+// === Tests ===
+// =============
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    /// The base version of a test model.
+    const BASE: &str = "\
+        Tree struct span:1 .. | 0:2 1:3
+        U32 u32
+        Ident ^0 struct token:1 lift_level:1
+        Number ^0 struct digits:4 base:5
+        String string
+        OptionTree option 0
+    ";
+
+    fn check(new: &str) -> Report {
+        let (old, old_root) = from_schema(BASE).unwrap();
+        let (new, new_root) = from_schema(new).unwrap();
+        compare(&old, old_root, &new, new_root)
+    }
+
+    fn kinds(report: &Report) -> Vec<(&str, &ChangeKind)> {
+        report.changes.iter().map(|change| (change.location.as_str(), &change.kind)).collect()
+    }
+
+    #[test]
+    fn schema_round_trip() {
+        let (graph, root) = from_schema(BASE).unwrap();
+        let schema = to_schema(&graph, root);
+        let expected: Vec<_> =
+            BASE.lines().map(str::trim).filter(|line| !line.is_empty()).collect();
+        assert_eq!(schema.lines().collect::<Vec<_>>(), expected);
+        assert_eq!(check(&schema), Report::default());
+    }
+
+    #[test]
+    fn renames_are_compatible() {
+        let report = check(
+            "Tree struct span:1 .. | 0:2 1:3
+             U32 u32
+             Identifier ^0 struct token:1 level:1
+             Number ^0 struct digits:4 base:5
+             String string
+             OptionTree option 0",
+        );
+        assert_eq!(kinds(&report), [
+            ("Tree.Identifier", &ChangeKind::TypeRenamed { old: "Tree.Ident".to_owned() }),
+            ("Tree.Identifier.level", &ChangeKind::FieldRenamed { old: "liftLevel".to_owned() }),
+        ]);
+        assert_eq!(report.compatibility(), Compatibility::Compatible);
+    }
+
+    #[test]
+    fn added_optional_field_is_breaking() {
+        let report = check(
+            "Tree struct span:1 .. | 0:2 1:3
+             U32 u32
+             Ident ^0 struct token:1 lift_level:1 is_free:5
+             Number ^0 struct digits:4 base:6
+             String string
+             OptionU32 option 1
+             OptionTree option 0",
+        );
+        assert_eq!(kinds(&report), [("Tree.Ident.isFree", &ChangeKind::FieldAdded {
+            optional: true,
+        })]);
+        assert!(report.is_breaking());
+    }
+
+    #[test]
+    fn added_variant_is_backward_compatible() {
+        let report = check(
+            "Tree struct span:1 .. | 0:2 1:3 2:6
+             U32 u32
+             Ident ^0 struct token:1 lift_level:1
+             Number ^0 struct digits:4 base:5
+             String string
+             OptionTree option 0
+             Wildcard ^0 struct token:1",
+        );
+        let name = "Wildcard".to_owned();
+        assert_eq!(kinds(&report), [("Tree", &ChangeKind::VariantAdded { name, discriminant: 2 })]);
+        assert_eq!(report.compatibility(), Compatibility::BackwardCompatible);
+    }
+
+    #[test]
+    fn reordered_variants_are_breaking() {
+        let report = check(
+            "Tree struct span:1 .. | 0:3 1:2
+             U32 u32
+             Ident ^0 struct token:1 lift_level:1
+             Number ^0 struct digits:4 base:5
+             String string
+             OptionTree option 0",
+        );
+        let ident = ChangeKind::VariantMoved { name: "Ident".to_owned(), old: 0, new: 1 };
+        let number = ChangeKind::VariantMoved { name: "Number".to_owned(), old: 1, new: 0 };
+        assert_eq!(kinds(&report), [("Tree", &ident), ("Tree", &number)]);
+        assert!(report.is_breaking());
+    }
+
+    #[test]
+    fn changed_field_type_is_breaking() {
+        let report = check(
+            "Tree struct span:1 .. | 0:2 1:3
+             U32 u32
+             Ident ^0 struct token:1 lift_level:1
+             Number ^0 struct digits:4 base:5
+             String string
+             SequenceTree sequence 0",
+        );
+        let old = "Option(Tree)".to_owned();
+        let new = "Sequence(Tree)".to_owned();
+        assert_eq!(kinds(&report), [("Tree.Number.base", &ChangeKind::TypeChanged { old, new })]);
+        assert!(report.is_breaking());
+    }
+
+    #[test]
+    fn moved_fields_are_breaking() {
+        let report = check(
+            "Tree struct .. span:1 | 0:2 1:3
+             U32 u32
+             Ident ^0 struct lift_level:1 token:1
+             Number ^0 struct digits:4
+             String string",
+        );
+        assert_eq!(kinds(&report), [
+            ("Tree.span", &ChangeKind::ChildFieldMoved),
+            ("Tree.Ident", &ChangeKind::FieldsReordered),
+            ("Tree.Number.base", &ChangeKind::FieldRemoved),
+        ]);
+        assert!(report.is_breaking());
+    }
+}
@@ -8,6 +8,7 @@
 
 
 
+pub mod evolution;
 #[cfg(feature = "graphviz")]
 mod graphviz;
 pub mod serialization;
@@ -447,6 +448,38 @@ impl TypeGraph {
         }
         hierarchy
     }
+
+    /// Get the qualified name of a type; for a subtype, this includes the names of its ancestors,
+    /// e.g. `Tree.Ident`.
+    pub fn path(&self, id: TypeId) -> String {
+        let names: Vec<_> =
+            self.hierarchy(id).iter().rev().map(|id| self[*id].name.to_string()).collect();
+        names.join(".")
+    }
+
+    /// Return the fields of a type, including the fields of its ancestors, in the order they are
+    /// serialized.
+    pub fn all_fields(&self, id: TypeId) -> Vec<&Field> {
+        let (mut fields, post) = self.split_fields(id);
+        fields.extend(post);
+        fields
+    }
+
+    /// Return the fields of a type and its ancestors, split into the fields serialized before the
+    /// type's child data (see [`Type::child_field`]), and those serialized after. For a type
+    /// without a `child_field`, all its own fields are serialized before the split.
+    pub fn split_fields(&self, id: TypeId) -> (Vec<&Field>, Vec<&Field>) {
+        let ty = &self[id];
+        let (mut pre, post) = match ty.parent {
+            Some(parent) => self.split_fields(parent),
+            None => Default::default(),
+        };
+        let fields = ty.data.as_struct().unwrap_or_default();
+        let index = ty.child_field.unwrap_or(fields.len());
+        pre.extend(&fields[..index]);
+        let own_post = fields[index..].iter();
+        (pre, own_post.chain(post).collect())
+    }
 }
 
 
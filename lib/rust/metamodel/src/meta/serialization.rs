@@ -1,5 +1,10 @@
 //! Serialization analysis on meta representations.
 //!
+//! # Schema Hash
+//!
+//! The [`schema_hash`] function computes a stable identifier for the structure of a data model, so
+//! that a serializer and deserializer generated from independent builds can check that they agree.
+//!
 //! # Test Case Generation
 //!
 //! The [`testcases`] function supports generation of deserialization test cases that cover all
@@ -658,3 +663,78 @@ fn collect_continuations(program: &[Op]) -> BTreeMap<usize, usize> {
     assert_eq!(&switch_stack, &[]);
     continuations
 }
+
+
+
+// ===================
+// === Schema Hash ===
+// ===================
+
+/// Compute a hash identifying the structure of the types reachable from some root type.
+///
+/// The hash is computed from the schema snapshot produced by [`super::evolution::to_schema`], so it
+/// covers everything that affects how data is laid out when serialized: type names, the names,
+/// types, and order of fields, primitive types, and the relationships between parent and child
+/// types. It does not depend on the [`TypeId`]s assigned in the graph, so two processes that build
+/// the same graph independently will compute the same hash; the producer and consumer of
+/// serialized data can use this to check that they agree on the format.
+pub fn schema_hash(graph: &TypeGraph, root: TypeId) -> u64 {
+    fnv1a(super::evolution::to_schema(graph, root).as_bytes())
+}
+
+/// The 64-bit FNV-1a hash. Unlike the hasher in the standard library, its output is specified, so
+/// it is suitable for values that must match across builds.
+fn fnv1a(data: &[u8]) -> u64 {
+    const OFFSET_BASIS: u64 = 0xcbf2_9ce4_8422_2325;
+    const PRIME: u64 = 0x0000_0100_0000_01b3;
+    data.iter().fold(OFFSET_BASIS, |hash, byte| (hash ^ *byte as u64).wrapping_mul(PRIME))
+}
+
+
+
+// =============
+// === Tests ===
+// =============
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn graph(inner_first: bool, field_name: &str) -> (TypeGraph, TypeId) {
+        let mut graph = TypeGraph::new();
+        let u32_name = TypeName::from_pascal_case("U32");
+        let u32_ = graph.types.insert(Type::new(u32_name, Data::Primitive(Primitive::U32)));
+        let inner_name = TypeName::from_pascal_case("Inner");
+        let inner_field = Field::named(FieldName::from_snake_case(field_name), u32_);
+        let inner = Type::new(inner_name, Data::Struct(vec![inner_field]));
+        let outer_name = TypeName::from_pascal_case("Outer");
+        let outer = |inner| {
+            let inner_field = Field::named(FieldName::from_snake_case("inner"), inner);
+            Type::new(outer_name, Data::Struct(vec![inner_field]))
+        };
+        if inner_first {
+            let inner = graph.types.insert(inner);
+            let outer = graph.types.insert(outer(inner));
+            (graph, outer)
+        } else {
+            let inner_ = graph.types.unbound_key();
+            let outer = graph.types.insert(outer((&inner_).into()));
+            graph.types.bind(inner_, inner);
+            (graph, outer)
+        }
+    }
+
+    #[test]
+    fn schema_hash_is_independent_of_ids() {
+        let (graph0, root0) = graph(true, "value");
+        let (graph1, root1) = graph(false, "value");
+        assert_eq!(schema_hash(&graph0, root0), schema_hash(&graph1, root1));
+    }
+
+    #[test]
+    fn schema_hash_reflects_fields() {
+        let (graph0, root0) = graph(true, "value");
+        let (graph1, root1) = graph(true, "other_value");
+        assert_ne!(schema_hash(&graph0, root0), schema_hash(&graph1, root1));
+    }
+}
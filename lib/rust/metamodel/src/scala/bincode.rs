@@ -0,0 +1,216 @@
+//! Derivation of bincode[1] deserialization for Scala types.
+//! [1]: https://github.com/bincode-org/bincode
+//!
+//! # Compatibility
+//!
+//! The generated functions read the same format as the deserializers derived by
+//! [`crate::typescript::bincode`], which is compatible with Rust's `serde-bincode` configured with
+//! `with_fixint_encoding`.
+//!
+//! # Interface
+//!
+//! For each type `T` (qualified name e.g. `Tree.Ident`), a method
+//! `readTreeIdent(reader: Reader): Tree.Ident` is generated in the [`OBJECT`] object. The `Reader`
+//! class, and a convenience method `deserialize(data, read)` of the object, are emitted as part of
+//! the file.
+//!
+//! # Deserialization errors
+//!
+//! If the input is not valid, a `FormatError` is thrown.
+
+use crate::scala::*;
+
+use std::fmt::Write;
+
+
+
+// ==========================
+// === Derive Deserialize ===
+// ==========================
+
+/// Name of the object containing the deserialization methods.
+pub const OBJECT: &str = "Bincode";
+
+/// Add deserialization methods for all the types in a data model to a file. The file should
+/// contain the definitions of the types (see [`from_meta`]).
+pub fn derive(graph: &meta::TypeGraph, file: &mut syntax::File) {
+    file.items.push(syntax::Item::Raw(SUPPORT.to_owned()));
+    let mut items = vec![syntax::Item::Raw(DESERIALIZE.to_owned())];
+    for (id, ty) in graph.types.iter() {
+        if ty.data.as_struct().is_some() {
+            items.push(syntax::Item::Raw(deserialize(graph, id)));
+        }
+    }
+    file.items.push(syntax::Item::Object { name: OBJECT.to_owned(), items });
+}
+
+/// Get the name of the method that reads a type.
+pub fn reader_name(graph: &meta::TypeGraph, id: meta::TypeId) -> String {
+    format!("read{}", graph.path(id).replace('.', ""))
+}
+
+/// Produce the deserialization method for a type.
+fn deserialize(graph: &meta::TypeGraph, id: meta::TypeId) -> String {
+    let ty = &graph[id];
+    let path = graph.path(id);
+    let mut body = String::new();
+    let mut values = 0;
+    if ty.discriminants.is_empty() {
+        for field in graph.all_fields(id) {
+            read_field(graph, field, &mut values, &mut body);
+        }
+        construct(&path, values, &mut body);
+    } else {
+        let (pre, post) = graph.split_fields(id);
+        for field in pre {
+            read_field(graph, field, &mut values, &mut body);
+        }
+        read_child(graph, id, &post, values, &mut body);
+    }
+    let name = reader_name(graph, id);
+    format!("def {name}(reader: Reader): {path} = {{\n{body}}}")
+}
+
+/// Read the discriminant identifying a subtype of a type, and the fields of the subtype. `post`
+/// are the fields that follow the data of the subtype, which are read before constructing it.
+fn read_child(
+    graph: &meta::TypeGraph,
+    id: meta::TypeId,
+    post: &[&meta::Field],
+    values: usize,
+    body: &mut String,
+) {
+    let ty = &graph[id];
+    writeln!(body, "reader.u32() match {{").unwrap();
+    for (key, child) in &ty.discriminants {
+        let mut values = values;
+        let child_ty = &graph[*child];
+        writeln!(body, "case {key} => {{").unwrap();
+        let fields = child_ty.data.as_struct().unwrap_or_default();
+        let split = child_ty.child_field.unwrap_or(fields.len());
+        for field in &fields[..split] {
+            read_field(graph, field, &mut values, body);
+        }
+        let post: Vec<_> = fields[split..].iter().chain(post.iter().copied()).collect();
+        if child_ty.discriminants.is_empty() {
+            for field in post {
+                read_field(graph, field, &mut values, body);
+            }
+            construct(&graph.path(*child), values, body);
+        } else {
+            read_child(graph, *child, &post, values, body);
+        }
+        writeln!(body, "}}").unwrap();
+    }
+    let path = graph.path(id);
+    let error = format!("s\"Unknown discriminant $discriminant in {path}.\"");
+    writeln!(body, "case discriminant => throw new FormatError({error})").unwrap();
+    writeln!(body, "}}").unwrap();
+}
+
+fn read_field(graph: &meta::TypeGraph, field: &meta::Field, values: &mut usize, body: &mut String) {
+    writeln!(body, "val value{values} = {}", read_value(graph, field.type_)).unwrap();
+    *values += 1;
+}
+
+/// Construct a value of a type from the values read, which are its fields in order.
+fn construct(path: &str, values: usize, body: &mut String) {
+    let arguments: Vec<_> = (0..values).map(|i| format!("value{i}")).collect();
+    writeln!(body, "{path}({})", arguments.join(", ")).unwrap();
+}
+
+/// Produce an expression that reads a value of a type.
+fn read_value(graph: &meta::TypeGraph, id: meta::TypeId) -> String {
+    let primitive = match &graph[id].data {
+        meta::Data::Struct(_) => return format!("{}(reader)", reader_name(graph, id)),
+        meta::Data::Primitive(primitive) => primitive,
+    };
+    match *primitive {
+        meta::Primitive::Bool => "reader.bool()".to_owned(),
+        meta::Primitive::U32 | meta::Primitive::I32 => "reader.u32()".to_owned(),
+        meta::Primitive::U64 | meta::Primitive::I64 => "reader.u64()".to_owned(),
+        meta::Primitive::Char => "reader.char()".to_owned(),
+        meta::Primitive::String => "reader.string()".to_owned(),
+        meta::Primitive::Sequence(t0) => format!("reader.sequence({})", read_value(graph, t0)),
+        meta::Primitive::Option(t0) => format!("reader.option({})", read_value(graph, t0)),
+        meta::Primitive::Result(t0, t1) =>
+            format!("reader.result({}, {})", read_value(graph, t0), read_value(graph, t1)),
+    }
+}
+
+
+
+// ======================
+// === Support Module ===
+// ======================
+
+/// Support code for the generated deserialization methods.
+const SUPPORT: &str = r#"/** The input to deserialization is not valid. */
+final class FormatError(message: String) extends RuntimeException(message)
+
+/** Reads data in the bincode format. */
+final class Reader(data: Array[Byte]) {
+private val buffer = java.nio.ByteBuffer.wrap(data).order(java.nio.ByteOrder.LITTLE_ENDIAN)
+
+/** Check that the given number of bytes remain to be read. */
+private def expect(size: Int): Unit =
+if (buffer.remaining < size) throw new FormatError("Unexpected end of input.")
+
+/** Read the length of a sequence or string. */
+private def length(): Int = {
+val length = u64()
+if (length < 0 || length > Int.MaxValue) throw new FormatError(s"Invalid length $length.")
+length.toInt
+}
+
+def u8(): Int = { expect(1); buffer.get() & 0xff }
+
+def u32(): Int = { expect(4); buffer.getInt() }
+
+def u64(): Long = { expect(8); buffer.getLong() }
+
+def bool(): Boolean = u8() match {
+case 0 => false
+case 1 => true
+case value => throw new FormatError(s"Invalid boolean $value.")
+}
+
+def char(): Int = {
+val value = u32()
+if (value < 0 || value > 0x10ffff || (value >= 0xd800 && value < 0xe000)) {
+throw new FormatError(s"Invalid code point $value.")
+}
+value
+}
+
+def string(): String = {
+val length = this.length()
+expect(length)
+val bytes = new Array[Byte](length)
+buffer.get(bytes)
+val decoder = java.nio.charset.StandardCharsets.UTF_8.newDecoder()
+try decoder.decode(java.nio.ByteBuffer.wrap(bytes)).toString
+catch {
+case _: java.nio.charset.CharacterCodingException => throw new FormatError("Invalid UTF-8.")
+}
+}
+
+def sequence[T](read: => T): Vector[T] = Vector.fill(length())(read)
+
+def option[T](read: => T): Option[T] = u8() match {
+case 0 => None
+case 1 => Some(read)
+case discriminant => throw new FormatError(s"Unknown discriminant $discriminant in Option.")
+}
+
+def result[T, E](ok: => T, err: => E): Either[E, T] = u32() match {
+case 0 => Right(ok)
+case 1 => Left(err)
+case discriminant => throw new FormatError(s"Unknown discriminant $discriminant in Result.")
+}
+}"#;
+
+/// The convenience entry point of the deserialization methods.
+const DESERIALIZE: &str = "/** Deserialize a value from the given data, using a generated method \
+                           like `readTree`. */
+def deserialize[T](data: Array[Byte], read: Reader => T): T = read(new Reader(data))";
@@ -0,0 +1,140 @@
+//! Generation of Scala case classes for data models in the [`meta`] representation.
+//!
+//! # Representation
+//!
+//! - A type with no subtypes is a `final case class`, with a parameter for each of its fields, and
+//!   for each field of its ancestors, in serialization order.
+//! - A type with subtypes is a `trait`, `sealed` if the type is closed. Its own fields are abstract
+//!   members, implemented by the parameters of its concrete subtypes. The subtypes are defined in
+//!   its companion `object`; e.g. a subtype `Ident` of `Tree` is referred to as `Tree.Ident`.
+//! - Integers of up to 32 bits, and `char`s, are `Int`s; 64-bit integers are `Long`s. As in the
+//!   [`crate::java`] model, unsigned integers are represented by signed types of the same size.
+//! - A sequence is a `Vector`; an optional value is an `Option`.
+//! - A `Result` is an `Either`, in which the success case is the `Right` value.
+//!
+//! Deserialization can be derived with [`bincode`].
+
+use crate::meta;
+
+
+// ==============
+// === Export ===
+// ==============
+
+pub mod bincode;
+pub mod syntax;
+
+
+
+// =======================
+// === Scala from Meta ===
+// =======================
+
+/// Produce a Scala source file defining the types in a data model in the [`meta`] representation.
+pub fn from_meta(graph: &meta::TypeGraph, package: impl Into<String>) -> syntax::File {
+    let package = package.into();
+    let mut items = vec![];
+    for (id, ty) in graph.types.iter() {
+        if ty.parent.is_none() && ty.data.as_struct().is_some() {
+            items.extend(define(graph, id));
+        }
+    }
+    syntax::File { package, items }
+}
+
+/// Define a type, and any subtypes.
+fn define(graph: &meta::TypeGraph, id: meta::TypeId) -> Vec<syntax::Item> {
+    let ty = &graph[id];
+    let name = ty.name.to_pascal_case();
+    let parent = ty.parent.map(|parent| graph.path(parent));
+    if ty.discriminants.is_empty() {
+        let fields = graph.all_fields(id).into_iter().enumerate();
+        let fields = fields.map(|(i, field)| quote_field(graph, field, i)).collect();
+        return vec![syntax::Item::CaseClass { name, fields, parent }];
+    }
+    let sealed = ty.closed;
+    let fields = ty.data.as_struct().unwrap_or_default().iter().enumerate();
+    let members = fields.map(|(i, field)| quote_field(graph, field, i)).collect();
+    let items = ty.discriminants.values().flat_map(|id| define(graph, *id)).collect();
+    vec![
+        syntax::Item::Trait { name: name.clone(), sealed, parent, members },
+        syntax::Item::Object { name, items },
+    ]
+}
+
+/// Produce a type expression for a type.
+pub fn quote_type(graph: &meta::TypeGraph, id: meta::TypeId) -> String {
+    let primitive = match &graph[id].data {
+        meta::Data::Struct(_) => return graph.path(id),
+        meta::Data::Primitive(primitive) => primitive,
+    };
+    match *primitive {
+        meta::Primitive::Bool => "Boolean".to_owned(),
+        meta::Primitive::U32 | meta::Primitive::I32 | meta::Primitive::Char => "Int".to_owned(),
+        meta::Primitive::U64 | meta::Primitive::I64 => "Long".to_owned(),
+        meta::Primitive::String => "String".to_owned(),
+        meta::Primitive::Sequence(t0) => format!("Vector[{}]", quote_type(graph, t0)),
+        meta::Primitive::Option(t0) => format!("Option[{}]", quote_type(graph, t0)),
+        meta::Primitive::Result(t0, t1) =>
+            format!("Either[{}, {}]", quote_type(graph, t1), quote_type(graph, t0)),
+    }
+}
+
+/// Produce a parameter or member definition for a field; `index` is the position of the field in
+/// the type it occurs in, used if the field is unnamed.
+fn quote_field(graph: &meta::TypeGraph, field: &meta::Field, index: usize) -> syntax::Field {
+    let name = field.name.to_camel_case().unwrap_or_else(|| format!("field{index}"));
+    let name = match KEYWORDS.split_whitespace().any(|keyword| keyword == name) {
+        true => format!("`{name}`"),
+        false => name,
+    };
+    let type_ = quote_type(graph, field.type_);
+    syntax::Field { name, type_ }
+}
+
+/// Reserved words, which must be quoted to be used as identifiers.
+const KEYWORDS: &str =
+    "abstract case catch class def do else enum export extends false final finally for forSome \
+     given if implicit import lazy match new null object override package private protected \
+     return sealed super then this throw trait true try type val var while with yield";
+
+
+
+// =============
+// === Tests ===
+// =============
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_definitions() {
+        let mut graph = meta::TypeGraph::new();
+        let u32_name = meta::TypeName::from_pascal_case("U32");
+        let u32_ty = meta::Type::new(u32_name, meta::Data::Primitive(meta::Primitive::U32));
+        let u32_ = graph.types.insert(u32_ty);
+        let option_name = meta::TypeName::from_pascal_case("OptionU32");
+        let option = meta::Data::Primitive(meta::Primitive::Option(u32_));
+        let option = graph.types.insert(meta::Type::new(option_name, option));
+        let tree_name = meta::TypeName::from_pascal_case("Tree");
+        let offset = meta::Field::named(meta::FieldName::from_snake_case("offset"), u32_);
+        let mut tree = meta::Type::new(tree_name, meta::Data::Struct(vec![offset]));
+        tree.abstract_ = true;
+        tree.closed = true;
+        tree.child_field = Some(0);
+        let tree = graph.types.insert(tree);
+        let ident_name = meta::TypeName::from_pascal_case("Ident");
+        let type_ = meta::Field::named(meta::FieldName::from_snake_case("type"), option);
+        let mut ident = meta::Type::new(ident_name, meta::Data::Struct(vec![type_]));
+        ident.parent = Some(tree);
+        let ident = graph.types.insert(ident);
+        graph[tree].discriminants.insert(0, ident);
+        let code = from_meta(&graph, "org.enso.syntax2").to_string();
+        let expected = "package org.enso.syntax2\n\n\
+                        sealed trait Tree {\ndef offset: Int\n}\n\n\
+                        object Tree {\n\
+                        final case class Ident(`type`: Option[Int], offset: Int) extends Tree\n}\n";
+        assert_eq!(code, expected);
+    }
+}
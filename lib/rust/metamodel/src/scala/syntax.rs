@@ -0,0 +1,125 @@
+//! Scala syntax.
+
+use std::fmt;
+
+
+
+// ===================
+// === Syntax Data ===
+// ===================
+
+/// A source file.
+#[derive(Debug)]
+pub struct File {
+    #[allow(missing_docs)]
+    pub package: String,
+    #[allow(missing_docs)]
+    pub items:   Vec<Item>,
+}
+
+/// A top-level definition, or a definition within an object.
+#[derive(Debug)]
+pub enum Item {
+    /// A `final case class`.
+    CaseClass {
+        #[allow(missing_docs)]
+        name:   String,
+        /// The constructor parameters.
+        fields: Vec<Field>,
+        /// The trait the class extends, if any.
+        parent: Option<String>,
+    },
+    /// A `trait`.
+    Trait {
+        #[allow(missing_docs)]
+        name:    String,
+        /// Whether the trait is `sealed`.
+        sealed:  bool,
+        /// The trait this trait extends, if any.
+        parent:  Option<String>,
+        /// Abstract members, implemented by the case classes extending the trait.
+        members: Vec<Field>,
+    },
+    /// An `object`.
+    Object {
+        #[allow(missing_docs)]
+        name:  String,
+        #[allow(missing_docs)]
+        items: Vec<Item>,
+    },
+    /// Literal code.
+    Raw(String),
+}
+
+/// A constructor parameter or abstract member.
+#[derive(Debug)]
+pub struct Field {
+    /// The name, escaped if necessary.
+    pub name:  String,
+    /// A type expression.
+    pub type_: String,
+}
+
+
+
+// =========================
+// === Rendering to Text ===
+// =========================
+
+impl fmt::Display for File {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let File { package, items } = self;
+        writeln!(f, "package {package}")?;
+        for item in items {
+            writeln!(f)?;
+            write!(f, "{item}")?;
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Display for Item {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Item::CaseClass { name, fields, parent } => {
+                let fields: Vec<_> = fields.iter().map(|field| field.to_string()).collect();
+                write!(f, "final case class {name}({})", fields.join(", "))?;
+                if let Some(parent) = parent {
+                    write!(f, " extends {parent}")?;
+                }
+                writeln!(f)
+            }
+            Item::Trait { name, sealed, parent, members } => {
+                if *sealed {
+                    write!(f, "sealed ")?;
+                }
+                write!(f, "trait {name}")?;
+                if let Some(parent) = parent {
+                    write!(f, " extends {parent}")?;
+                }
+                if members.is_empty() {
+                    return writeln!(f);
+                }
+                writeln!(f, " {{")?;
+                for member in members {
+                    writeln!(f, "def {member}")?;
+                }
+                writeln!(f, "}}")
+            }
+            Item::Object { name, items } => {
+                writeln!(f, "object {name} {{")?;
+                for item in items {
+                    write!(f, "{item}")?;
+                }
+                writeln!(f, "}}")
+            }
+            Item::Raw(code) => writeln!(f, "{code}"),
+        }
+    }
+}
+
+impl fmt::Display for Field {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.name, self.type_)
+    }
+}
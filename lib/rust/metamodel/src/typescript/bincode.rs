@@ -0,0 +1,246 @@
+//! Derivation of bincode[1] deserialization for TypeScript types.
+//! [1]: https://github.com/bincode-org/bincode
+//!
+//! # Compatibility
+//!
+//! The generated functions read the same format as the deserializers derived by
+//! [`crate::java::bincode`], which is compatible with Rust's `serde-bincode` configured with
+//! `with_fixint_encoding`. In particular, a `char` is encoded as a 32-bit code point.
+//!
+//! # Interface
+//!
+//! For each type `T` (qualified name e.g. `Tree.Ident`), a function `readTreeIdent(reader: Reader)`
+//! is generated. The `Reader` class, and a convenience function `deserialize(data, read)`, are
+//! emitted as part of the module.
+//!
+//! # Deserialization errors
+//!
+//! If the input is not valid, a `FormatError` is thrown.
+
+use crate::typescript::*;
+
+use std::fmt::Write;
+
+
+
+// ==========================
+// === Derive Deserialize ===
+// ==========================
+
+/// Add deserialization functions for all the types in a data model to a module. The module
+/// should contain the definitions of the types (see [`from_meta`]).
+pub fn derive(graph: &meta::TypeGraph, module: &mut syntax::Module) {
+    module.items.push(syntax::Item::Raw(SUPPORT.to_owned()));
+    for (id, ty) in graph.types.iter() {
+        if ty.data.as_struct().is_some() {
+            module.items.push(syntax::Item::Function(deserialize(graph, id)));
+        }
+    }
+}
+
+/// Get the name of the function that reads a type.
+pub fn reader_name(graph: &meta::TypeGraph, id: meta::TypeId) -> String {
+    format!("read{}", graph.path(id).replace('.', ""))
+}
+
+/// Produce the deserialization function for a type.
+fn deserialize(graph: &meta::TypeGraph, id: meta::TypeId) -> syntax::Function {
+    let ty = &graph[id];
+    let mut body = String::new();
+    writeln!(body, "const value: Record<string, unknown> = {{}}").unwrap();
+    let (pre, post) = graph.split_fields(id);
+    if ty.discriminants.is_empty() && ty.parent.is_some() {
+        writeln!(body, "value.{TAG} = {:?}", ty.name.to_pascal_case()).unwrap();
+    }
+    let mut index = 0;
+    for field in pre {
+        read_field(graph, field, &mut index, &mut body);
+    }
+    if !ty.discriminants.is_empty() {
+        read_child(graph, id, &mut index, &mut body);
+    }
+    for field in post {
+        read_field(graph, field, &mut index, &mut body);
+    }
+    writeln!(body, "return value as unknown as {}", graph.path(id)).unwrap();
+    let name = reader_name(graph, id);
+    let arguments = vec![("reader".to_owned(), "Reader".to_owned())];
+    let return_ = graph.path(id);
+    syntax::Function { name, arguments, return_, body }
+}
+
+/// Read the discriminant identifying a subtype of a type, and the fields of the subtype.
+fn read_child(graph: &meta::TypeGraph, id: meta::TypeId, index: &mut usize, body: &mut String) {
+    let ty = &graph[id];
+    let base = *index;
+    writeln!(body, "const discriminant = reader.u32()").unwrap();
+    writeln!(body, "switch (discriminant) {{").unwrap();
+    for (key, child) in &ty.discriminants {
+        *index = base;
+        let child_ty = &graph[*child];
+        writeln!(body, "case {key}: {{").unwrap();
+        if child_ty.discriminants.is_empty() {
+            writeln!(body, "value.{TAG} = {:?}", child_ty.name.to_pascal_case()).unwrap();
+        }
+        let fields = child_ty.data.as_struct().unwrap_or_default();
+        let split = child_ty.child_field.unwrap_or(fields.len());
+        for field in &fields[..split] {
+            read_field(graph, field, index, body);
+        }
+        if !child_ty.discriminants.is_empty() {
+            read_child(graph, *child, index, body);
+        }
+        for field in &fields[split..] {
+            read_field(graph, field, index, body);
+        }
+        writeln!(body, "break").unwrap();
+        writeln!(body, "}}").unwrap();
+    }
+    let path = graph.path(id);
+    let error = format!("`Unknown discriminant ${{discriminant}} in {path}.`");
+    writeln!(body, "default: throw new FormatError({error})").unwrap();
+    writeln!(body, "}}").unwrap();
+}
+
+fn read_field(graph: &meta::TypeGraph, field: &meta::Field, index: &mut usize, body: &mut String) {
+    let name = field_name(field, *index);
+    *index += 1;
+    writeln!(body, "value.{name} = {}", read_value(graph, field.type_)).unwrap();
+}
+
+/// Produce an expression that reads a value of a type.
+fn read_value(graph: &meta::TypeGraph, id: meta::TypeId) -> String {
+    let primitive = match &graph[id].data {
+        meta::Data::Struct(_) => return format!("{}(reader)", reader_name(graph, id)),
+        meta::Data::Primitive(primitive) => primitive,
+    };
+    match *primitive {
+        meta::Primitive::Bool => "reader.bool()".to_owned(),
+        meta::Primitive::U32 => "reader.u32()".to_owned(),
+        meta::Primitive::I32 => "reader.i32()".to_owned(),
+        meta::Primitive::U64 => "reader.u64()".to_owned(),
+        meta::Primitive::I64 => "reader.i64()".to_owned(),
+        meta::Primitive::Char => "reader.char()".to_owned(),
+        meta::Primitive::String => "reader.string()".to_owned(),
+        meta::Primitive::Sequence(t0) =>
+            format!("reader.sequence(() => {})", read_value(graph, t0)),
+        meta::Primitive::Option(t0) => format!("reader.option(() => {})", read_value(graph, t0)),
+        meta::Primitive::Result(t0, t1) => format!(
+            "reader.result(() => {}, () => {})",
+            read_value(graph, t0),
+            read_value(graph, t1)
+        ),
+    }
+}
+
+
+
+// ======================
+// === Support Module ===
+// ======================
+
+/// Support code for the generated deserialization functions.
+const SUPPORT: &str = r#"/** The input to deserialization is not valid. */
+export class FormatError extends Error {}
+
+const UTF8_DECODER = new TextDecoder('utf-8', { fatal: true })
+
+/** Reads data in the bincode format. */
+export class Reader {
+private readonly view: DataView
+private offset: number
+
+constructor(data: Uint8Array) {
+this.view = new DataView(data.buffer, data.byteOffset, data.byteLength)
+this.offset = 0
+}
+
+/** Consume the given number of bytes, and return the offset of the first. */
+private advance(size: number): number {
+const offset = this.offset
+if (offset + size > this.view.byteLength) throw new FormatError('Unexpected end of input.')
+this.offset += size
+return offset
+}
+
+/** Read the length of a sequence or string. */
+private length(): number {
+const length = this.u64()
+if (length > BigInt(Number.MAX_SAFE_INTEGER)) throw new FormatError(`Invalid length ${length}.`)
+return Number(length)
+}
+
+u8(): number {
+return this.view.getUint8(this.advance(1))
+}
+
+u32(): number {
+return this.view.getUint32(this.advance(4), true)
+}
+
+i32(): number {
+return this.view.getInt32(this.advance(4), true)
+}
+
+u64(): bigint {
+return this.view.getBigUint64(this.advance(8), true)
+}
+
+i64(): bigint {
+return this.view.getBigInt64(this.advance(8), true)
+}
+
+bool(): boolean {
+const value = this.u8()
+if (value > 1) throw new FormatError(`Invalid boolean ${value}.`)
+return value === 1
+}
+
+char(): string {
+const value = this.u32()
+if (value > 0x10ffff || (value >= 0xd800 && value < 0xe000)) {
+throw new FormatError(`Invalid code point ${value}.`)
+}
+return String.fromCodePoint(value)
+}
+
+string(): string {
+const length = this.length()
+const offset = this.view.byteOffset + this.advance(length)
+try {
+return UTF8_DECODER.decode(new Uint8Array(this.view.buffer, offset, length))
+} catch {
+throw new FormatError('Invalid UTF-8.')
+}
+}
+
+sequence<T>(read: () => T): T[] {
+const length = this.length()
+const values = []
+for (let i = 0; i < length; i++) values.push(read())
+return values
+}
+
+option<T>(read: () => T): T | undefined {
+const discriminant = this.u8()
+switch (discriminant) {
+case 0: return undefined
+case 1: return read()
+default: throw new FormatError(`Unknown discriminant ${discriminant} in Option.`)
+}
+}
+
+result<T, E>(ok: () => T, err: () => E): Result<T, E> {
+const discriminant = this.u32()
+switch (discriminant) {
+case 0: return { ok: ok() }
+case 1: return { err: err() }
+default: throw new FormatError(`Unknown discriminant ${discriminant} in Result.`)
+}
+}
+}
+
+/** Deserialize a value from the given data, using a generated function like `readTree`. */
+export function deserialize<T>(data: Uint8Array, read: (reader: Reader) => T): T {
+return read(new Reader(data))
+}"#;
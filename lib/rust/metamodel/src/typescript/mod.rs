@@ -0,0 +1,154 @@
+//! Generation of TypeScript type definitions for data models in the [`meta`] representation.
+//!
+//! # Representation
+//!
+//! Types are represented idiomatically in TypeScript:
+//! - A type with no subtypes is an `interface`, with a `readonly` property for each of its fields,
+//!   and for each field of its ancestors, in serialization order.
+//! - A type with subtypes is a union of its subtypes, which are declared in a `namespace` with the
+//!   same name as the type; e.g. a subtype `Ident` of `Tree` is referred to as `Tree.Ident`. Each
+//!   concrete subtype has a [`TAG`] property, whose value is the name of the subtype, so that the
+//!   union can be narrowed with a `switch`.
+//! - Integers of up to 32 bits are `number`s; 64-bit integers are `bigint`s.
+//! - A `char` is a `string` containing one code point.
+//! - A sequence is an array.
+//! - An optional value is a union with `undefined`. Note that this representation cannot
+//!   distinguish the levels of a nested option.
+//! - A `Result` is represented by a [`RESULT`] type defined in the generated module: an object with
+//!   an `ok` or `err` property.
+//!
+//! Deserialization can be derived with [`bincode`].
+
+use crate::meta;
+
+
+// ==============
+// === Export ===
+// ==============
+
+pub mod bincode;
+pub mod syntax;
+
+
+
+// ===========================
+// === TypeScript Builtins ===
+// ===========================
+
+/// Name of the type that represents a `Result` in the generated module.
+pub const RESULT: &str = "Result";
+/// Name of the property that identifies the concrete type of a value of a union type.
+pub const TAG: &str = "kind";
+
+const RESULT_DEFINITION: &str = "export type Result<T, E> = { ok: T } | { err: E }";
+
+
+
+// ============================
+// === TypeScript from Meta ===
+// ============================
+
+/// Produce TypeScript definitions of the types in a data model in the [`meta`] representation.
+pub fn from_meta(graph: &meta::TypeGraph) -> syntax::Module {
+    let mut items = vec![syntax::Item::Raw(RESULT_DEFINITION.to_owned())];
+    for (id, ty) in graph.types.iter() {
+        if ty.parent.is_none() && ty.data.as_struct().is_some() {
+            items.extend(declare(graph, id));
+        }
+    }
+    syntax::Module { items }
+}
+
+/// Declare a type, and any subtypes.
+fn declare(graph: &meta::TypeGraph, id: meta::TypeId) -> Vec<syntax::Item> {
+    let ty = &graph[id];
+    let name = ty.name.to_pascal_case();
+    if ty.discriminants.is_empty() {
+        let mut fields = vec![];
+        if ty.parent.is_some() {
+            fields.push(syntax::Field { name: TAG.to_owned(), type_: format!("{name:?}") });
+        }
+        for (i, field) in graph.all_fields(id).into_iter().enumerate() {
+            let name = field_name(field, i);
+            assert_ne!(name, TAG, "Field of {} conflicts with the type tag.", graph.path(id));
+            fields.push(syntax::Field { name, type_: quote_type(graph, field.type_) });
+        }
+        return vec![syntax::Item::Interface(syntax::Interface { name, fields })];
+    }
+    let variants: Vec<_> = ty.discriminants.values().map(|id| graph.path(*id)).collect();
+    let type_ = variants.join(" | ");
+    let items = ty.discriminants.values().flat_map(|id| declare(graph, *id)).collect();
+    vec![syntax::Item::TypeAlias { name: name.clone(), type_ }, syntax::Item::Namespace {
+        name,
+        items,
+    }]
+}
+
+/// Produce a type expression for a type.
+pub fn quote_type(graph: &meta::TypeGraph, id: meta::TypeId) -> String {
+    let primitive = match &graph[id].data {
+        meta::Data::Struct(_) => return graph.path(id),
+        meta::Data::Primitive(primitive) => primitive,
+    };
+    match *primitive {
+        meta::Primitive::Bool => "boolean".to_owned(),
+        meta::Primitive::U32 | meta::Primitive::I32 => "number".to_owned(),
+        meta::Primitive::U64 | meta::Primitive::I64 => "bigint".to_owned(),
+        meta::Primitive::Char | meta::Primitive::String => "string".to_owned(),
+        meta::Primitive::Sequence(t0) => match quote_type(graph, t0) {
+            t0 if t0.contains(' ') => format!("({t0})[]"),
+            t0 => format!("{t0}[]"),
+        },
+        meta::Primitive::Option(t0) => format!("{} | undefined", quote_type(graph, t0)),
+        meta::Primitive::Result(t0, t1) =>
+            format!("{RESULT}<{}, {}>", quote_type(graph, t0), quote_type(graph, t1)),
+    }
+}
+
+/// Get the name of a field; `index` is the position of the field in the serialization order of
+/// the type it occurs in, used if the field is unnamed.
+pub fn field_name(field: &meta::Field, index: usize) -> String {
+    field.name.to_camel_case().unwrap_or_else(|| format!("field{index}"))
+}
+
+
+
+// =============
+// === Tests ===
+// =============
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_declarations() {
+        let mut graph = meta::TypeGraph::new();
+        let u64_name = meta::TypeName::from_pascal_case("U64");
+        let u64_ = graph
+            .types
+            .insert(meta::Type::new(u64_name, meta::Data::Primitive(meta::Primitive::U64)));
+        let seq_name = meta::TypeName::from_pascal_case("VecU64");
+        let seq = meta::Data::Primitive(meta::Primitive::Sequence(u64_));
+        let seq = graph.types.insert(meta::Type::new(seq_name, seq));
+        let tree_name = meta::TypeName::from_pascal_case("Tree");
+        let span = meta::Field::named(meta::FieldName::from_snake_case("span_len"), u64_);
+        let mut tree = meta::Type::new(tree_name, meta::Data::Struct(vec![span]));
+        tree.abstract_ = true;
+        tree.child_field = Some(1);
+        let tree = graph.types.insert(tree);
+        let ident_name = meta::TypeName::from_pascal_case("Ident");
+        let ident_fields =
+            vec![meta::Field::named(meta::FieldName::from_snake_case("values"), seq)];
+        let mut ident = meta::Type::new(ident_name, meta::Data::Struct(ident_fields));
+        ident.parent = Some(tree);
+        let ident = graph.types.insert(ident);
+        graph[tree].discriminants.insert(0, ident);
+        let code = from_meta(&graph).to_string();
+        assert!(code.contains("export type Tree = Tree.Ident\n"));
+        assert!(code.contains("export namespace Tree {\n"));
+        let interface = "export interface Ident {\nreadonly kind: \"Ident\"\n\
+                         readonly spanLen: bigint\nreadonly values: bigint[]\n}";
+        assert!(code.contains(interface), "{code}");
+    }
+}
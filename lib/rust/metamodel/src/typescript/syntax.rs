@@ -0,0 +1,128 @@
+//! TypeScript syntax.
+
+use std::fmt;
+
+
+
+// ===================
+// === Syntax Data ===
+// ===================
+
+/// A module; the contents of a source file.
+#[derive(Debug, Default)]
+pub struct Module {
+    #[allow(missing_docs)]
+    pub items: Vec<Item>,
+}
+
+/// A top-level declaration, or a declaration within a namespace.
+#[derive(Debug)]
+pub enum Item {
+    /// An `interface` declaration.
+    Interface(Interface),
+    /// A `type` alias declaration.
+    TypeAlias {
+        #[allow(missing_docs)]
+        name:  String,
+        /// The aliased type expression.
+        type_: String,
+    },
+    /// A `namespace` declaration.
+    Namespace {
+        #[allow(missing_docs)]
+        name:  String,
+        #[allow(missing_docs)]
+        items: Vec<Item>,
+    },
+    /// A `function` declaration.
+    Function(Function),
+    /// Literal code.
+    Raw(String),
+}
+
+/// An interface declaration.
+#[derive(Debug)]
+pub struct Interface {
+    #[allow(missing_docs)]
+    pub name:   String,
+    /// The properties of the interface, which are all `readonly`.
+    pub fields: Vec<Field>,
+}
+
+/// A property of an interface.
+#[derive(Debug)]
+pub struct Field {
+    #[allow(missing_docs)]
+    pub name:  String,
+    /// A type expression.
+    pub type_: String,
+}
+
+/// A function declaration.
+#[derive(Debug)]
+pub struct Function {
+    #[allow(missing_docs)]
+    pub name:      String,
+    /// The parameters, as (name, type expression) pairs.
+    pub arguments: Vec<(String, String)>,
+    /// The return type expression.
+    pub return_:   String,
+    /// Literal body, not including brackets.
+    pub body:      String,
+}
+
+
+
+// =========================
+// === Rendering to Text ===
+// =========================
+
+impl fmt::Display for Module {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for item in &self.items {
+            writeln!(f, "{item}")?;
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Display for Item {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Item::Interface(interface) => write!(f, "{interface}"),
+            Item::TypeAlias { name, type_ } => writeln!(f, "export type {name} = {type_}"),
+            Item::Namespace { name, items } => {
+                writeln!(f, "export namespace {name} {{")?;
+                for item in items {
+                    write!(f, "{item}")?;
+                }
+                writeln!(f, "}}")
+            }
+            Item::Function(function) => write!(f, "{function}"),
+            Item::Raw(code) => writeln!(f, "{code}"),
+        }
+    }
+}
+
+impl fmt::Display for Interface {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let Interface { name, fields } = self;
+        writeln!(f, "export interface {name} {{")?;
+        for Field { name, type_ } in fields {
+            writeln!(f, "readonly {name}: {type_}")?;
+        }
+        writeln!(f, "}}")
+    }
+}
+
+impl fmt::Display for Function {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let Function { name, arguments, return_, body } = self;
+        let arguments: Vec<_> =
+            arguments.iter().map(|(name, ty)| format!("{name}: {ty}")).collect();
+        let arguments = arguments.join(", ");
+        writeln!(f, "export function {name}({arguments}): {return_} {{")?;
+        writeln!(f, "{body}")?;
+        writeln!(f, "}}")
+    }
+}
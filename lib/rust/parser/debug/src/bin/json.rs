@@ -0,0 +1,88 @@
+//! Print the AST of Enso source code as JSON, in the format documented in [`enso_metamodel_json`].
+//!
+//! Usage:
+//! ```console
+//! json < input.enso > tree.json
+//! json --schema > tree.schema.json
+//! ```
+//! With the `--schema` flag, a JSON Schema describing the output is printed instead.
+//!
+//! The parser's reflected data refers to source code by memory address; in the output, these
+//! references (the `code_repr_begin` fields) are converted to byte offsets in the input.
+
+// === Features ===
+#![feature(exact_size_is_empty)]
+#![feature(let_chains)]
+#![feature(if_let_guard)]
+// === Standard Linter Configuration ===
+#![deny(non_ascii_idents)]
+#![warn(unsafe_code)]
+#![allow(clippy::bool_to_int_with_if)]
+#![allow(clippy::let_and_return)]
+// === Non-Standard Linter Configuration ===
+#![allow(clippy::option_map_unit_fn)]
+#![allow(clippy::precedence)]
+#![allow(dead_code)]
+#![deny(unconditional_recursion)]
+#![warn(missing_copy_implementations)]
+#![warn(missing_debug_implementations)]
+#![warn(missing_docs)]
+#![warn(trivial_casts)]
+#![warn(trivial_numeric_casts)]
+#![warn(unused_import_braces)]
+#![warn(unused_qualifications)]
+
+use enso_reflect::Reflect;
+use serde_json::Value;
+
+
+
+// ===================
+// === JSON Output ===
+// ===================
+
+fn main() {
+    let tree = enso_parser::syntax::Tree::reflect();
+    let tree_id = tree.id;
+    let (graph, rust_to_meta) = enso_metamodel::rust::to_meta(tree);
+    let root = rust_to_meta[&tree_id];
+    let json = match std::env::args().nth(1).as_deref() {
+        Some("--schema") => enso_metamodel_json::schema(&graph, root),
+        Some(_) => {
+            eprintln!("Usage: json [--schema]");
+            std::process::exit(2);
+        }
+        None => {
+            use std::io::Read;
+            let mut input = String::new();
+            std::io::stdin().read_to_string(&mut input).unwrap();
+            let (code, _metadata) = enso_parser::metadata::extract(&input);
+            let ast = enso_parser::Parser::new().run(code);
+            let mut json = enso_metamodel_json::to_json(&graph, root, &ast).unwrap();
+            relativize_code_references(&mut json, code);
+            json
+        }
+    };
+    println!("{}", serde_json::to_string_pretty(&json).unwrap());
+}
+
+/// Replace the addresses of source code slices with offsets from the start of the code.
+fn relativize_code_references(value: &mut Value, code: &str) {
+    let base = code.as_ptr() as u32;
+    match value {
+        Value::Object(object) =>
+            for (key, value) in object {
+                if key.ends_with("code_repr_begin") && let Some(address) = value.as_u64() {
+                    let offset = (address as u32).wrapping_sub(base);
+                    if offset as usize <= code.len() {
+                        *value = offset.into();
+                    }
+                } else {
+                    relativize_code_references(value, code);
+                }
+            },
+        Value::Array(values) =>
+            values.iter_mut().for_each(|value| relativize_code_references(value, code)),
+        _ => {}
+    }
+}
@@ -0,0 +1,66 @@
+//! Check that the parser represents source code losslessly: parse every Enso source file in the
+//! given files and directories, regenerate the code from the resulting trees, and report every
+//! place where the input is not reproduced exactly, or where the spans of the tree are not
+//! consistent with its code.
+//!
+//! Usage: `round_trip PATH...`. Exits with a nonzero status if any discrepancy is found.
+
+// === Features ===
+#![feature(exact_size_is_empty)]
+#![feature(let_chains)]
+#![feature(if_let_guard)]
+// === Standard Linter Configuration ===
+#![deny(non_ascii_idents)]
+#![warn(unsafe_code)]
+#![allow(clippy::bool_to_int_with_if)]
+#![allow(clippy::let_and_return)]
+// === Non-Standard Linter Configuration ===
+#![allow(clippy::option_map_unit_fn)]
+#![allow(clippy::precedence)]
+#![allow(dead_code)]
+#![deny(unconditional_recursion)]
+#![warn(missing_copy_implementations)]
+#![warn(missing_debug_implementations)]
+#![warn(missing_docs)]
+#![warn(trivial_casts)]
+#![warn(trivial_numeric_casts)]
+#![warn(unused_import_braces)]
+#![warn(unused_qualifications)]
+
+use enso_parser::source::files::enso_files;
+use enso_parser::source::files::line_column;
+use std::path::Path;
+
+
+
+/// Check all the files in the paths given as arguments.
+pub fn main() {
+    let args: Vec<_> = std::env::args().skip(1).collect();
+    if args.is_empty() {
+        eprintln!("Usage: round_trip PATH...");
+        std::process::exit(2);
+    }
+    let parser = enso_parser::Parser::new();
+    let mut files = 0;
+    let mut failed = 0;
+    for arg in args {
+        let paths =
+            enso_files(Path::new(&arg)).unwrap_or_else(|e| panic!("Failed to read {arg}: {e}"));
+        for path in paths {
+            let input = std::fs::read_to_string(&path)
+                .unwrap_or_else(|e| panic!("Failed to read {}: {e}", path.display()));
+            let (code, _metadata) = enso_parser::metadata::extract(&input);
+            let discrepancies = parser.run(code).check_code(code);
+            for discrepancy in &discrepancies {
+                let (line, column) = line_column(code, discrepancy.offset());
+                println!("{}:{line}:{column}: {discrepancy}", path.display());
+            }
+            files += 1;
+            failed += usize::from(!discrepancies.is_empty());
+        }
+    }
+    println!("Checked {files} files; {failed} not reproduced losslessly.");
+    if failed != 0 {
+        std::process::exit(1);
+    }
+}
@@ -593,6 +593,14 @@ fn multiple_operator_error() {
     test(&code.join("\n"), expected);
 }
 
+#[test]
+fn operator_after_assignment() {
+    test!("x = , 1", (Assignment (Ident x) "=" (Invalid)));
+    test!("f x = = 1", (Function (Ident f) #((() (Ident x) () ())) "=" (Invalid)));
+    test!("x = * 2 + 3",
+        (Assignment (Ident x) "=" (OprApp (Invalid) (Ok "+") (Number () "3" ()))));
+}
+
 #[test]
 fn precedence() {
     #[rustfmt::skip]
@@ -1316,6 +1324,60 @@ fn bad_case() {
     test_invalid("foo = case x of\n 4->");
 }
 
+#[test]
+fn invalid_case_is_isolated() {
+    #[rustfmt::skip]
+    let code = [
+        "case x of",
+        "    foo",
+        "    Bar -> y",
+        "    Baz ->",
+    ];
+    #[rustfmt::skip]
+    let expected = block![
+        (CaseOf (Ident x) #(
+         ((() () () (Invalid)))
+         ((() (Ident Bar) "->" (Ident y)))
+         ((() (Invalid) "->" ()))))];
+    test(&code.join("\n"), expected);
+}
+
+#[test]
+fn invalid_type_header_is_isolated() {
+    #[rustfmt::skip]
+    let code = [
+        "type 4",
+        "    A",
+        "    f x = x",
+    ];
+    #[rustfmt::skip]
+    let expected = block![
+        (ArgumentBlockApplication (Invalid) #(
+         (ConstructorDefinition A #() #())
+         (Function (Ident f) #((() (Ident x) () ())) "=" (Ident x))))];
+    test(&code.join("\n"), expected);
+}
+
+#[test]
+fn independent_errors_in_block() {
+    #[rustfmt::skip]
+    let code = [
+        "main =",
+        "    x = (1",
+        "    y = 2",
+        "    z = 3)",
+        "    w = 4",
+    ];
+    #[rustfmt::skip]
+    let expected = block![
+        (Function (Ident main) #() "=" (BodyBlock #(
+         (Assignment (Ident x) "=" (App (Invalid) (Number () "1" ())))
+         (Assignment (Ident y) "=" (Number () "2" ()))
+         (Assignment (Ident z) "=" (App (Number () "3" ()) (Invalid)))
+         (Assignment (Ident w) "=" (Number () "4" ())))))];
+    test(&code.join("\n"), expected);
+}
+
 #[test]
 fn malformed_sequence() {
     test_invalid("(1, )");
@@ -0,0 +1,41 @@
+//! Check that the parser represents the standard library losslessly.
+
+// === Non-Standard Linter Configuration ===
+#![allow(clippy::option_map_unit_fn)]
+#![allow(clippy::precedence)]
+#![allow(dead_code)]
+#![deny(non_ascii_idents)]
+#![deny(unconditional_recursion)]
+#![warn(unsafe_code)]
+#![warn(missing_copy_implementations)]
+#![warn(missing_debug_implementations)]
+#![warn(missing_docs)]
+#![warn(trivial_casts)]
+#![warn(trivial_numeric_casts)]
+#![warn(unused_import_braces)]
+#![warn(unused_qualifications)]
+
+use enso_parser::source::files::enso_files;
+use enso_parser::source::files::line_column;
+use std::path::Path;
+
+
+
+const STANDARD_LIBRARY: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/../../../../distribution/lib");
+
+#[test]
+fn standard_library_round_trip() {
+    let parser = enso_parser::Parser::new();
+    let paths = enso_files(Path::new(STANDARD_LIBRARY)).unwrap();
+    assert!(!paths.is_empty(), "No sources found in {STANDARD_LIBRARY}.");
+    let mut failures = vec![];
+    for path in paths {
+        let input = std::fs::read_to_string(&path).unwrap();
+        let (code, _) = enso_parser::metadata::extract(&input);
+        for discrepancy in parser.run(code).check_code(code) {
+            let (line, column) = line_column(code, discrepancy.offset());
+            failures.push(format!("{}:{line}:{column}: {discrepancy}", path.display()));
+        }
+    }
+    assert!(failures.is_empty(), "{}", failures.join("\n"));
+}
@@ -0,0 +1,575 @@
+//! Canonical formatter for Enso source code.
+//!
+//! The formatter walks the [`Tree`] produced by [`enso_parser::Parser`], and reprints the code of
+//! its tokens with normalized whitespace:
+//! - Blocks are indented by a configurable number of spaces per level.
+//! - Runs of whitespace between tokens on the same line are collapsed to a single space. Whether
+//!   tokens are separated by whitespace at all is significant in Enso (it affects operator
+//!   precedence), so in general it is not changed.
+//! - Binding operators are surrounded by single spaces: the `=` of assignments and function
+//!   definitions, and the `->` of case branches. Their operands are delimited by the statement, so
+//!   spacing them does not change the parse; spacing other operators could.
+//! - Trailing whitespace is removed.
+//! - Runs of empty lines are limited to a configurable length.
+//! - Definitions of types and functions spanning multiple lines are separated from preceding
+//!   statements by a configurable number of empty lines.
+//!
+//! Comments are kept, and reindented along with the code they appear in. Text literals and
+//! documentation comments (the text of [`Documented`] nodes) are kept verbatim: when their first
+//! line is reindented, their following lines are shifted by the same amount, so that their
+//! content does not change. The metadata section of a file is preserved unchanged.
+//!
+//! [`Documented`]: enso_parser::syntax::tree::Documented
+
+// === Features ===
+#![feature(let_chains)]
+// === Standard Linter Configuration ===
+#![deny(non_ascii_idents)]
+#![warn(unsafe_code)]
+#![allow(clippy::bool_to_int_with_if)]
+#![allow(clippy::let_and_return)]
+// === Non-Standard Linter Configuration ===
+#![allow(clippy::option_map_unit_fn)]
+#![allow(clippy::precedence)]
+#![allow(dead_code)]
+#![deny(unconditional_recursion)]
+#![warn(missing_copy_implementations)]
+#![warn(missing_debug_implementations)]
+#![warn(missing_docs)]
+#![warn(trivial_casts)]
+#![warn(trivial_numeric_casts)]
+#![warn(unused_import_braces)]
+#![warn(unused_qualifications)]
+
+use enso_parser::lexer;
+use enso_parser::syntax::item;
+use enso_parser::syntax::token;
+use enso_parser::syntax::tree;
+use enso_parser::syntax::tree::ItemVisitable;
+use enso_parser::syntax::Tree;
+use std::collections::HashSet;
+use std::ops::Range;
+
+
+
+// ==============
+// === Config ===
+// ==============
+
+/// Formatting rules.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Config {
+    /// Number of spaces by which each block is indented relative to its parent.
+    pub indent_width:                   usize,
+    /// Maximum number of consecutive empty lines.
+    pub max_blank_lines:                usize,
+    /// Minimum number of empty lines before a multi-line type or function definition that follows
+    /// another statement of the same block. Limited by `max_blank_lines`.
+    pub blank_lines_before_definitions: usize,
+    /// Whether whitespace between tokens of the same line is collapsed to a single space.
+    pub collapse_spaces:                bool,
+    /// Whether the `=` of assignments and function definitions, and the `->` of case branches, are
+    /// surrounded by single spaces.
+    pub space_binding_operators:        bool,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            indent_width:                   4,
+            max_blank_lines:                2,
+            blank_lines_before_definitions: 1,
+            collapse_spaces:                true,
+            space_binding_operators:        true,
+        }
+    }
+}
+
+
+
+// =================
+// === Formatter ===
+// =================
+
+/// Formats Enso source code.
+#[derive(Debug)]
+pub struct Formatter {
+    config: Config,
+    parser: enso_parser::Parser,
+}
+
+impl Formatter {
+    /// Constructor.
+    pub fn new(config: Config) -> Self {
+        let parser = enso_parser::Parser::new();
+        Self { config, parser }
+    }
+
+    /// Format the contents of an Enso source file. A metadata section, if present, is preserved
+    /// unchanged.
+    pub fn format(&self, input: &str) -> String {
+        let (code, metadata) = enso_parser::metadata::extract(input);
+        let mut out = self.format_code(code);
+        if let Some(metadata) = metadata {
+            let marker = &input[code.len()..input.len() - metadata.len()];
+            out.push_str(marker);
+            out.push_str(metadata);
+        }
+        out
+    }
+
+    /// Format Enso code, not including a metadata section.
+    pub fn format_code(&self, code: &str) -> String {
+        let tree = self.parser.run(code);
+        let mut collector = PieceCollector::new(code);
+        tree.visit_item(&mut collector);
+        let line_break = if code.contains("\r\n") { "\r\n" } else { "\n" };
+        let printer = Printer::new(&self.config, line_break);
+        printer.print(&collector.pieces, &collector.definitions, &collector.binding_operators)
+    }
+}
+
+impl Default for Formatter {
+    fn default() -> Self {
+        Self::new(Config::default())
+    }
+}
+
+
+
+// ==============
+// === Pieces ===
+// ==============
+
+/// The code of a token, with the whitespace preceding it.
+#[derive(Debug)]
+struct Piece<'a> {
+    whitespace: String,
+    code:       &'a str,
+    /// Offset of the code in the source, in bytes.
+    offset:     usize,
+    kind:       PieceKind,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum PieceKind {
+    Code,
+    /// A line break.
+    Newline,
+    /// A comment; the lexer represents it as a newline token.
+    Comment,
+    /// The first token of a text literal or documentation comment.
+    TextStart,
+    /// A token that is part of a text literal or documentation comment.
+    Text,
+}
+
+/// Collects the tokens of a [`Tree`] as [`Piece`]s, the locations of the definitions that should be
+/// separated from preceding statements, and the locations of the binding operators.
+#[derive(Debug)]
+struct PieceCollector<'a> {
+    code:              &'a str,
+    pieces:            Vec<Piece<'a>>,
+    /// Whitespace that has not been attached to a piece yet.
+    whitespace:        String,
+    offset:            usize,
+    /// The range of the outermost text literal containing the current item.
+    text:              Range<usize>,
+    definitions:       HashSet<usize>,
+    /// Offsets of the definitions that are part of a documented or annotated definition.
+    wrapped:           HashSet<usize>,
+    /// Offsets of the newline characters in the source, in ascending order.
+    newlines:          Vec<usize>,
+    /// Offsets of the `=` of assignments and function definitions, and the `->` of case branches.
+    binding_operators: HashSet<usize>,
+}
+
+impl<'a> PieceCollector<'a> {
+    fn new(code: &'a str) -> Self {
+        let newlines = code.match_indices(lexer::is_newline_char).map(|(i, _)| i).collect();
+        Self {
+            code,
+            pieces: Default::default(),
+            whitespace: Default::default(),
+            offset: Default::default(),
+            text: Default::default(),
+            definitions: Default::default(),
+            wrapped: Default::default(),
+            newlines,
+            binding_operators: Default::default(),
+        }
+    }
+
+    /// Check whether the given range of the source contains a line break.
+    fn spans_lines(&self, range: Range<usize>) -> bool {
+        let next_newline = self.newlines.partition_point(|&newline| newline < range.start);
+        self.newlines.get(next_newline).map_or(false, |&newline| newline < range.end)
+    }
+
+    /// The offset of a token's code in the source.
+    fn token_offset(&self, token: &token::Operator) -> usize {
+        token.code.repr.as_ptr() as usize - self.code.as_ptr() as usize
+    }
+
+    fn visit_tree(&mut self, tree: &'a Tree) {
+        self.whitespace.push_str(&tree.span.left_offset.code);
+        self.offset += tree.span.left_offset.code.repr.len();
+        let start = self.offset;
+        let end = start + tree.span.code_length.utf8_bytes();
+        if let tree::Variant::TextLiteral(_) = &*tree.variant && start >= self.text.end {
+            self.text = start..end;
+        }
+        let binding_operators = match &*tree.variant {
+            tree::Variant::Assignment(tree::Assignment { equals, .. })
+            | tree::Variant::Function(tree::Function { equals, .. }) => vec![equals],
+            tree::Variant::CaseOf(case_of) => case_of
+                .cases
+                .iter()
+                .filter_map(|line| line.case.as_ref().and_then(|case| case.arrow.as_ref()))
+                .collect(),
+            _ => vec![],
+        };
+        for operator in binding_operators {
+            self.binding_operators.insert(self.token_offset(operator));
+        }
+        if let Some(definition) = definition(tree) && !self.wrapped.contains(&start) {
+            // The wrapped expressions end where the documented or annotated tree does.
+            let definition_start = end - definition.span.code_length.utf8_bytes();
+            if self.spans_lines(definition_start..end) {
+                self.definitions.insert(start);
+                let mut wrapped = tree;
+                while let Some(inner) = wrapped_expression(wrapped) {
+                    self.wrapped.insert(end - inner.span.code_length.utf8_bytes());
+                    wrapped = inner;
+                }
+            }
+        }
+    }
+
+    fn visit_token(&mut self, token: token::Ref<'_, 'a>) {
+        self.whitespace.push_str(&token.left_offset.code);
+        self.offset += token.left_offset.code.repr.len();
+        let offset = self.offset;
+        let code: &'a str = &token.code.repr;
+        self.offset += code.len();
+        if code.is_empty() {
+            return;
+        }
+        let kind = if self.text.contains(&offset) {
+            match offset == self.text.start {
+                true => PieceKind::TextStart,
+                false => PieceKind::Text,
+            }
+        } else {
+            match token.data {
+                token::Variant::TextStart(_) => PieceKind::TextStart,
+                token::Variant::TextEnd(_)
+                | token::Variant::TextSection(_)
+                | token::Variant::TextEscape(_)
+                | token::Variant::TextInitialNewline(_)
+                | token::Variant::TextNewline(_) => PieceKind::Text,
+                token::Variant::Newline(_) if is_line_break(code) => PieceKind::Newline,
+                token::Variant::Newline(_) => PieceKind::Comment,
+                _ => PieceKind::Code,
+            }
+        };
+        // Comments include any whitespace that follows them on their line.
+        let code = if kind == PieceKind::Comment { code.trim_end() } else { code };
+        let whitespace = std::mem::take(&mut self.whitespace);
+        self.pieces.push(Piece { whitespace, code, offset, kind });
+    }
+}
+
+impl<'a> tree::Visitor for PieceCollector<'a> {}
+impl<'a, 's> tree::ItemVisitor<'s, 'a> for PieceCollector<'a> {
+    fn visit_item(&mut self, item: item::Ref<'s, 'a>) -> bool {
+        match item {
+            item::Ref::Tree(tree) => self.visit_tree(tree),
+            item::Ref::Token(token) => self.visit_token(token),
+        }
+        true
+    }
+}
+
+/// If the tree is a type or function definition, possibly with documentation or annotations,
+/// return the definition.
+fn definition<'a, 's>(tree: &'a Tree<'s>) -> Option<&'a Tree<'s>> {
+    match &*tree.variant {
+        tree::Variant::TypeDef(_)
+        | tree::Variant::Function(_)
+        | tree::Variant::ForeignFunction(_) => Some(tree),
+        _ => wrapped_expression(tree).and_then(definition),
+    }
+}
+
+/// If the tree attaches documentation or an annotation to an expression, return the expression.
+fn wrapped_expression<'a, 's>(tree: &'a Tree<'s>) -> Option<&'a Tree<'s>> {
+    match &*tree.variant {
+        tree::Variant::Documented(tree::Documented { expression, .. })
+        | tree::Variant::Annotated(tree::Annotated { expression, .. })
+        | tree::Variant::AnnotatedBuiltin(tree::AnnotatedBuiltin { expression, .. }) =>
+            expression.as_ref(),
+        _ => None,
+    }
+}
+
+fn is_line_break(code: &str) -> bool {
+    !code.is_empty() && code.chars().all(lexer::is_newline_char)
+}
+
+/// The width of whitespace, as measured by the lexer.
+fn visible_width(whitespace: &str) -> usize {
+    whitespace
+        .chars()
+        .map(|c| lexer::space_char_visible_size(c).map_or(0, |size| size.width_in_spaces))
+        .sum()
+}
+
+
+
+// ===============
+// === Printer ===
+// ===============
+
+/// Prints [`Piece`]s, applying the formatting rules.
+#[derive(Debug)]
+struct Printer<'c> {
+    config:        &'c Config,
+    line_break:    &'c str,
+    out:           String,
+    /// The indentation of the enclosing blocks, innermost last.
+    blocks:        Vec<Indentation>,
+    /// The number of empty lines printed since the last non-empty line.
+    blank_lines:   usize,
+    previous_line: Option<Line>,
+}
+
+/// The indentation of a block, before and after formatting.
+#[derive(Clone, Copy, Debug, Default)]
+struct Indentation {
+    old: usize,
+    new: usize,
+}
+
+/// Information about a printed non-empty line.
+#[derive(Clone, Copy, Debug)]
+struct Line {
+    indent:     usize,
+    is_comment: bool,
+}
+
+impl<'c> Printer<'c> {
+    fn new(config: &'c Config, line_break: &'c str) -> Self {
+        Self {
+            config,
+            line_break,
+            out: Default::default(),
+            blocks: vec![Default::default()],
+            blank_lines: Default::default(),
+            previous_line: Default::default(),
+        }
+    }
+
+    fn print(
+        mut self,
+        pieces: &[Piece],
+        definitions: &HashSet<usize>,
+        binding_operators: &HashSet<usize>,
+    ) -> String {
+        let is_binding_operator = |piece: &Piece| {
+            self.config.space_binding_operators && binding_operators.contains(&piece.offset)
+        };
+        let mut previous = None;
+        for (i, piece) in pieces.iter().enumerate() {
+            let at_line_start = self.out.is_empty() || self.out.ends_with(lexer::is_newline_char);
+            if at_line_start {
+                let next_line = match piece.kind {
+                    PieceKind::Comment => pieces[i + 1..]
+                        .iter()
+                        .find(|piece| matches!(piece.kind, PieceKind::Code | PieceKind::TextStart))
+                        .map(|piece| visible_width(&piece.whitespace)),
+                    _ => None,
+                };
+                self.print_line_start(piece, definitions.contains(&piece.offset), next_line);
+            } else {
+                let spaced =
+                    is_binding_operator(piece) || previous.map_or(false, is_binding_operator);
+                let whitespace = match piece.kind {
+                    PieceKind::Newline => "",
+                    PieceKind::Text => piece.whitespace.as_str(),
+                    _ if spaced => " ",
+                    _ if self.config.collapse_spaces && !piece.whitespace.is_empty() => " ",
+                    _ => piece.whitespace.as_str(),
+                };
+                self.out.push_str(whitespace);
+                self.out.push_str(piece.code);
+            }
+            previous = Some(piece);
+        }
+        self.out
+    }
+
+    fn print_line_start(&mut self, piece: &Piece, is_definition: bool, next_line: Option<usize>) {
+        match piece.kind {
+            PieceKind::Newline => {
+                if self.previous_line.is_some() && self.blank_lines < self.config.max_blank_lines {
+                    self.out.push_str(piece.code);
+                    self.blank_lines += 1;
+                }
+                return;
+            }
+            PieceKind::Text if is_line_break(piece.code) || piece.whitespace.is_empty() => {
+                self.out.push_str(&piece.whitespace);
+                self.out.push_str(piece.code);
+            }
+            PieceKind::Text | PieceKind::Comment => {
+                let whitespace = self.shift(&piece.whitespace, next_line);
+                let indent = visible_width(&whitespace);
+                let is_comment = piece.kind == PieceKind::Comment;
+                self.out.push_str(&whitespace);
+                self.out.push_str(piece.code);
+                self.previous_line = Some(Line { indent, is_comment });
+            }
+            PieceKind::Code | PieceKind::TextStart => {
+                let indent = self.indent(visible_width(&piece.whitespace));
+                if is_definition
+                        && let Some(previous) = self.previous_line
+                        && !previous.is_comment && previous.indent >= indent {
+                    let blank_lines =
+                        self.config.blank_lines_before_definitions.min(self.config.max_blank_lines);
+                    while self.blank_lines < blank_lines {
+                        self.out.push_str(self.line_break);
+                        self.blank_lines += 1;
+                    }
+                }
+                self.out.push_str(&" ".repeat(indent));
+                self.out.push_str(piece.code);
+                self.previous_line = Some(Line { indent, is_comment: false });
+            }
+        }
+        self.blank_lines = 0;
+    }
+
+    /// Return the new indentation of a line that does not affect the block structure, such as a
+    /// comment or a continuation line of a text literal. Its indentation relative to the innermost
+    /// block it is not outdented from is kept. A comment may precede the first line of a block;
+    /// `next_line` is the original indentation of the next line of code, if any.
+    fn shift(&self, whitespace: &str, next_line: Option<usize>) -> String {
+        let width = visible_width(whitespace);
+        let current = *self.blocks.last().unwrap();
+        let new = current.new + self.config.indent_width;
+        let opened = next_line.filter(|&old| old > current.old).map(|old| Indentation { old, new });
+        let mut blocks = self.blocks.iter().copied().chain(opened).rev();
+        let block = blocks.find(|block| block.old <= width).unwrap_or_default();
+        match block.old == block.new {
+            true => whitespace.to_owned(),
+            false => " ".repeat(width - block.old + block.new),
+        }
+    }
+
+    /// Return the new indentation of a line of code, given its original indentation. The block
+    /// structure is tracked the same way as by the lexer, so that it is not changed by reindenting.
+    fn indent(&mut self, width: usize) -> usize {
+        let current = *self.blocks.last().unwrap();
+        if width > current.old {
+            let new = current.new + self.config.indent_width;
+            self.blocks.push(Indentation { old: width, new });
+        } else {
+            while width < self.blocks.last().unwrap().old && self.blocks.len() > 1 {
+                let previous = self.blocks[self.blocks.len() - 2];
+                if width > previous.old {
+                    break;
+                }
+                self.blocks.pop();
+            }
+        }
+        self.blocks.last().unwrap().new
+    }
+}
+
+
+
+// =============
+// === Tests ===
+// =============
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn test(input: &str, expected: &str) {
+        let formatter = Formatter::default();
+        let formatted = formatter.format(input);
+        assert_eq!(formatted, expected);
+        assert_eq!(formatter.format(&formatted), formatted);
+    }
+
+    #[test]
+    fn indentation() {
+        test("main =\n  x = 1\n  x\n", "main =\n    x = 1\n    x\n");
+        test("foo =\n        a\n            b\n        c\n", "foo =\n    a\n        b\n    c\n");
+    }
+
+    #[test]
+    fn spacing() {
+        test("main  =   foo  x+1    y  \n", "main = foo x+1 y\n");
+        test("x = 1 +   2 # Comment.  \n", "x = 1 + 2 # Comment.\n");
+    }
+
+    #[test]
+    fn binding_operators() {
+        test("main=\n  x=1+2\n  x\n", "main =\n    x = 1+2\n    x\n");
+        test("foo=\n  1\n", "foo =\n    1\n");
+        test("x = case y of\n  A->1\n  _  ->0\n", "x = case y of\n    A -> 1\n    _ -> 0\n");
+        // Not bindings: a named argument, an `=` bound within an expression, and a lambda.
+        test("x = f a=1\n", "x = f a=1\n");
+        test("x=1 + 2\n", "x=1 + 2\n");
+        test("f = x->x\n", "f = x->x\n");
+    }
+
+    #[test]
+    fn comments() {
+        test("main =\n  # Comment.\n  x\n", "main =\n    # Comment.\n    x\n");
+        test("main =\n        # Comment.\n      x\n", "main =\n      # Comment.\n    x\n");
+    }
+
+    #[test]
+    fn blank_lines() {
+        test("\n\na = 1\n\n\n\n\nb = 2\n", "a = 1\n\n\nb = 2\n");
+    }
+
+    #[test]
+    fn definitions() {
+        test("a = 1\nfoo x =\n  x\n", "a = 1\n\nfoo x =\n    x\n");
+        test("a = 1\n## Doc.\nfoo x =\n  x\n", "a = 1\n\n## Doc.\nfoo x =\n    x\n");
+        test("a = 1\n# Comment.\nfoo x =\n  x\n", "a = 1\n# Comment.\nfoo x =\n    x\n");
+        test("a = 1\n## Doc.\n@a x\nfoo x =\n  x\n", "a = 1\n\n## Doc.\n@a x\nfoo x =\n    x\n");
+        test("a = 1\n## Doc.\n   More.\nfoo = 1\n", "a = 1\n## Doc.\n   More.\nfoo = 1\n");
+        test(
+            "type T\n  A\n  foo self =\n    self\n",
+            "type T\n    A\n\n    foo self =\n        self\n",
+        );
+    }
+
+    #[test]
+    fn verbatim_text() {
+        let input = "main =\n  x = '''\n     text\n       indented\n  x\n";
+        let expected = "main =\n    x = '''\n       text\n         indented\n    x\n";
+        test(input, expected);
+        test("x = 'a   b'\n", "x = 'a   b'\n");
+    }
+
+    #[test]
+    fn documentation() {
+        let input = "type T\n  ## Doc  with   spaces.\n     More.\n  foo = 1\n";
+        let expected = "type T\n    ## Doc  with   spaces.\n       More.\n    foo = 1\n";
+        test(input, expected);
+    }
+
+    #[test]
+    fn metadata() {
+        let input = "main =\n  1\n\n\n#### METADATA ####\n[[{}, []]]\n{}\n";
+        let expected = "main =\n    1\n\n\n#### METADATA ####\n[[{}, []]]\n{}\n";
+        test(input, expected);
+    }
+}
@@ -0,0 +1,98 @@
+//! Format Enso source files.
+//!
+//! Usage: `enso-parser-format [--check] [--indent-width N] [--max-blank-lines N] [FILE]...`
+//!
+//! With no files, formats standard input to standard output. Otherwise, rewrites each file in
+//! place; with `--check`, files are not modified, and the process fails if any file is not
+//! formatted.
+
+// === Features ===
+#![feature(let_chains)]
+// === Standard Linter Configuration ===
+#![deny(non_ascii_idents)]
+#![warn(unsafe_code)]
+#![allow(clippy::bool_to_int_with_if)]
+#![allow(clippy::let_and_return)]
+// === Non-Standard Linter Configuration ===
+#![allow(clippy::option_map_unit_fn)]
+#![allow(clippy::precedence)]
+#![allow(dead_code)]
+#![deny(unconditional_recursion)]
+#![warn(missing_copy_implementations)]
+#![warn(missing_debug_implementations)]
+#![warn(missing_docs)]
+#![warn(trivial_casts)]
+#![warn(trivial_numeric_casts)]
+#![warn(unused_import_braces)]
+#![warn(unused_qualifications)]
+
+use enso_parser_format::Config;
+use enso_parser_format::Formatter;
+
+
+
+// ============
+// === Main ===
+// ============
+
+fn main() {
+    let mut config = Config::default();
+    let mut check = false;
+    let mut paths = vec![];
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--check" => check = true,
+            "--indent-width" => config.indent_width = numeric_argument(&arg, args.next()),
+            "--max-blank-lines" => config.max_blank_lines = numeric_argument(&arg, args.next()),
+            _ if arg.starts_with("--") => usage(&format!("Unknown option: {arg}")),
+            _ => paths.push(arg),
+        }
+    }
+    let formatter = Formatter::new(config);
+    if paths.is_empty() {
+        use std::io::Read;
+        let mut input = String::new();
+        std::io::stdin().read_to_string(&mut input).unwrap();
+        let output = formatter.format(&input);
+        if check {
+            std::process::exit(if output == input { 0 } else { 1 });
+        }
+        print!("{output}");
+        return;
+    }
+    let mut unformatted = 0;
+    for path in &paths {
+        let input =
+            std::fs::read_to_string(path).unwrap_or_else(|e| panic!("Failed to read {path}: {e}"));
+        let output = formatter.format(&input);
+        if output == input {
+            continue;
+        }
+        if check {
+            println!("{path}");
+            unformatted += 1;
+        } else {
+            std::fs::write(path, output).unwrap_or_else(|e| panic!("Failed to write {path}: {e}"));
+        }
+    }
+    if unformatted != 0 {
+        eprintln!("{unformatted} file(s) would be reformatted.");
+        std::process::exit(1);
+    }
+}
+
+fn numeric_argument(option: &str, value: Option<String>) -> usize {
+    match value.as_deref().map(str::parse) {
+        Some(Ok(value)) => value,
+        _ => usage(&format!("{option} expects a non-negative integer.")),
+    }
+}
+
+fn usage(error: &str) -> ! {
+    eprintln!("{error}");
+    eprintln!(
+        "Usage: enso-parser-format [--check] [--indent-width N] [--max-blank-lines N] [FILE]..."
+    );
+    std::process::exit(2)
+}
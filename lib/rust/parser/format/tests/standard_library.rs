@@ -0,0 +1,97 @@
+//! Format the standard library, and check that formatting is idempotent and preserves meaning.
+
+// === Non-Standard Linter Configuration ===
+#![allow(clippy::option_map_unit_fn)]
+#![allow(clippy::precedence)]
+#![allow(dead_code)]
+#![deny(non_ascii_idents)]
+#![deny(unconditional_recursion)]
+#![warn(unsafe_code)]
+#![warn(missing_copy_implementations)]
+#![warn(missing_debug_implementations)]
+#![warn(missing_docs)]
+#![warn(trivial_casts)]
+#![warn(trivial_numeric_casts)]
+#![warn(unused_import_braces)]
+#![warn(unused_qualifications)]
+
+use enso_parser::source::files::enso_files;
+use enso_parser::syntax::item;
+use enso_parser::syntax::tree;
+use enso_parser::syntax::tree::ItemVisitable;
+use enso_parser::syntax::Tree;
+use enso_parser_format::Formatter;
+use std::fmt::Write;
+use std::path::Path;
+
+
+
+// =============
+// === Tests ===
+// =============
+
+const STANDARD_LIBRARY: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/../../../../distribution/lib");
+
+#[test]
+fn format_standard_library_twice() {
+    let formatter = Formatter::default();
+    let parser = enso_parser::Parser::new();
+    let paths = enso_files(Path::new(STANDARD_LIBRARY)).unwrap();
+    assert!(!paths.is_empty(), "No sources found in {STANDARD_LIBRARY}.");
+    for path in paths {
+        let input = std::fs::read_to_string(&path).unwrap();
+        let once = formatter.format(&input);
+        let twice = formatter.format(&once);
+        assert_eq!(once, twice, "Formatting is not idempotent: {}", path.display());
+        let (code, _) = enso_parser::metadata::extract(&input);
+        let (formatted, _) = enso_parser::metadata::extract(&once);
+        let original = parser.run(code);
+        let reformatted = parser.run(formatted);
+        assert_eq!(reformatted.code(), formatted, "Tokens dropped: {}", path.display());
+        let changed = structure(&original) != structure(&reformatted);
+        assert!(!changed, "Formatting changed the parse of: {}", path.display());
+    }
+}
+
+
+
+// =================
+// === Structure ===
+// =================
+
+/// Print the structure of a tree, without the whitespace between tokens: each tree is printed as a
+/// parenthesized list of its items, and each token as its kind and its code. Text literal tokens
+/// are printed with their whitespace removed, as continuation lines are reindented.
+fn structure(tree: &Tree) -> String {
+    let mut printer = StructurePrinter::default();
+    tree.visit_item(&mut printer);
+    printer.out
+}
+
+#[derive(Debug, Default)]
+struct StructurePrinter {
+    out: String,
+}
+
+impl tree::Visitor for StructurePrinter {}
+impl<'s, 'a> tree::ItemVisitor<'s, 'a> for StructurePrinter {
+    fn visit_item(&mut self, item: item::Ref<'s, 'a>) -> bool {
+        match item {
+            item::Ref::Tree(tree) => {
+                self.out.push('(');
+                tree.variant.visit_item(self);
+                self.out.push(')');
+            }
+            item::Ref::Token(token) => {
+                let code = token.code.repr.trim_end();
+                if code.is_empty() {
+                    return false;
+                }
+                let kind = format!("{:?}", token.data);
+                let kind = kind.split(|c: char| !c.is_alphanumeric()).next().unwrap_or_default();
+                write!(self.out, " {kind}:{code:?}").unwrap();
+            }
+        }
+        false
+    }
+}
@@ -64,6 +64,10 @@ public final class Parser implements AutoCloseable {
 
   private static native ByteBuffer parseInput(long state, ByteBuffer input);
 
+  private static native ByteBuffer parseInputFlat(long state, ByteBuffer input, long schemaHash);
+
+  private static native ByteBuffer getDiagnostics(long state);
+
   private static native long getLastInputBase(long state);
 
   private static native long getMetadata(long state);
@@ -89,6 +93,35 @@ public final class Parser implements AutoCloseable {
     return Tree.deserialize(message);
   }
 
+  /**
+   * Parse the input, returning accessors that read the tree in place from the parser's output.
+   *
+   * <p>The result MUST NOT be used after another call to a {@code parse} method, or to {@link
+   * #close}.
+   */
+  public org.enso.syntax2.flat.Tree parseFlat(CharSequence input) {
+    byte[] inputBytes = input.toString().getBytes(StandardCharsets.UTF_8);
+    ByteBuffer inputBuf = ByteBuffer.allocateDirect(inputBytes.length);
+    inputBuf.put(inputBytes);
+    var schemaHash = org.enso.syntax2.flat.Schema.hash();
+    var serializedTree = parseInputFlat(state, inputBuf, schemaHash);
+    var base = getLastInputBase(state);
+    var buffer = new org.enso.syntax2.flat.Buffer(serializedTree, inputBuf, base, schemaHash);
+    return org.enso.syntax2.flat.Tree.read(buffer, buffer.root());
+  }
+
+  public java.util.List<Diagnostic> diagnostics() {
+    var serializedDiagnostics = getDiagnostics(state);
+    serializedDiagnostics.order(ByteOrder.LITTLE_ENDIAN);
+    var message = new Message(serializedDiagnostics, null, 0, 0);
+    var count = (int) message.get64();
+    var diagnostics = new java.util.ArrayList<Diagnostic>(count);
+    for (var i = 0; i < count; i++) {
+      diagnostics.add(Diagnostic.deserialize(message));
+    }
+    return diagnostics;
+  }
+
   @Override
   public void close() {
     freeState(state);
@@ -0,0 +1,150 @@
+package org.enso.syntax2.flat;
+
+import java.nio.ByteBuffer;
+import java.nio.ByteOrder;
+import java.nio.charset.StandardCharsets;
+import org.enso.syntax2.Either;
+import org.enso.syntax2.FormatException;
+
+public final class Buffer {
+  /** The version of the layout this class reads; must match `flat::VERSION` in the parser. */
+  public static final int VERSION = 1;
+
+  private static final byte[] MAGIC = {'E', 'N', 'S', 'F'};
+  private static final int HEADER_LEN = 20;
+
+  private final ByteBuffer data;
+  private final ByteBuffer source;
+  private final int base;
+
+  /**
+   * Wrap serialized data, checking that it was produced in the expected layout and schema.
+   *
+   * @param dataIn the serialized data.
+   * @param sourceIn the UTF-8 source code the data was parsed from.
+   * @param baseIn the address of the source code in the parser's memory.
+   * @param schemaHash the schema hash of the generated accessor classes.
+   */
+  public Buffer(ByteBuffer dataIn, ByteBuffer sourceIn, long baseIn, long schemaHash) {
+    data = dataIn.order(ByteOrder.LITTLE_ENDIAN);
+    source = sourceIn;
+    base = (int) baseIn;
+    if (data.limit() < HEADER_LEN) {
+      throw new FormatException("Flat data is truncated.");
+    }
+    for (int i = 0; i < MAGIC.length; i++) {
+      if (data.get(i) != MAGIC[i]) {
+        throw new FormatException("Data is not in the flat format.");
+      }
+    }
+    int version = data.getInt(4);
+    if (version != VERSION) {
+      throw new FormatException(
+          "Flat format version " + version + " is not supported; expected " + VERSION + ".");
+    }
+    long hash = data.getLong(8);
+    if (hash != schemaHash) {
+      throw new FormatException(
+          "Parser library schema "
+              + Long.toHexString(hash)
+              + " does not match the Java classes' schema "
+              + Long.toHexString(schemaHash)
+              + "; the parser library and the generated Java sources must be built from the"
+              + " same version.");
+    }
+  }
+
+  /** The address of the root object. */
+  public int root() {
+    return data.getInt(16);
+  }
+
+  boolean getBoolean(int address) {
+    switch (data.get(address)) {
+      case 0:
+        return false;
+      case 1:
+        return true;
+      default:
+        throw error("Boolean out of range at " + address + ".");
+    }
+  }
+
+  int getInt(int address) {
+    return data.getInt(address);
+  }
+
+  long getLong(int address) {
+    return data.getLong(address);
+  }
+
+  String getString(int address) {
+    int record = data.getInt(address);
+    int len = data.getInt(record);
+    byte[] dst = new byte[len];
+    data.get(record + 4, dst);
+    return new String(dst, StandardCharsets.UTF_8);
+  }
+
+  <T> java.util.List<T> list(int address, int elementSize, Reader<T> reader) {
+    int record = data.getInt(address);
+    int count = data.getInt(record);
+    return new java.util.AbstractList<T>() {
+      @Override
+      public T get(int index) {
+        java.util.Objects.checkIndex(index, count);
+        return reader.read(Buffer.this, record + 4 + index * elementSize);
+      }
+
+      @Override
+      public int size() {
+        return count;
+      }
+    };
+  }
+
+  <T> T nullable(int address, Reader<T> reader) {
+    int record = data.getInt(address);
+    return record == 0 ? null : reader.read(this, record);
+  }
+
+  <T> java.util.Optional<T> optional(int address, Reader<T> reader) {
+    return java.util.Optional.ofNullable(nullable(address, reader));
+  }
+
+  <L, R> Either<L, R> either(int address, Reader<L> left, Reader<R> right) {
+    int record = data.getInt(address);
+    int discriminant = data.getInt(record);
+    switch (discriminant) {
+      case 0:
+        return Either.right(right.read(this, record + 4));
+      case 1:
+        return Either.left(left.read(this, record + 4));
+      default:
+        throw error("Unknown discriminant " + discriminant + " in Either.");
+    }
+  }
+
+  /** Return the source code at an address in the parser's memory. */
+  String code(int begin, long len) {
+    // Given the low bits of `begin`, the low bits of `base`, and the invariant `begin >= base`,
+    // compute `begin - base`.
+    int offset = begin - base;
+    if (offset < 0) {
+      offset += Integer.MAX_VALUE;
+      offset += 1;
+    }
+    byte[] dst = new byte[(int) len];
+    source.get(offset, dst);
+    return new String(dst, StandardCharsets.UTF_8);
+  }
+
+  FormatException error(String message) {
+    return new FormatException(message);
+  }
+
+  /** Constructs an object of some type stored inline at an address. */
+  interface Reader<T> {
+    T read(Buffer buffer, int address);
+  }
+}
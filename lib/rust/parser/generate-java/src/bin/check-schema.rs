@@ -0,0 +1,43 @@
+//! Compare a snapshot of the serialization schema of the parser's AST types with the current
+//! schema, or with another snapshot; report the differences, and exit with an error status if any
+//! of them are breaking.
+//!
+//! Usage:
+//! ```console
+//! git checkout <old-revision>
+//! tree-schema > old.txt
+//! git checkout <new-revision>
+//! check-schema old.txt
+//! ```
+
+// === Standard Linter Configuration ===
+#![deny(non_ascii_idents)]
+#![warn(unsafe_code)]
+#![allow(clippy::bool_to_int_with_if)]
+#![allow(clippy::let_and_return)]
+
+use enso_metamodel::meta::evolution;
+
+
+
+// =======================
+// === Schema Checking ===
+// =======================
+
+fn main() {
+    let mut args = std::env::args().skip(1);
+    let usage = "Usage: check-schema <old-schema> [<new-schema>]";
+    let old = std::fs::read_to_string(args.next().expect(usage)).unwrap();
+    let new = match args.next() {
+        Some(path) => std::fs::read_to_string(path).unwrap(),
+        None => enso_parser_generate_java::tree_schema(),
+    };
+    let (old, old_root) = evolution::from_schema(&old).unwrap();
+    let (new, new_root) = evolution::from_schema(&new).unwrap();
+    let report = evolution::compare(&old, old_root, &new, new_root);
+    print!("{report}");
+    println!("Overall: {:?}", report.compatibility());
+    if report.is_breaking() {
+        std::process::exit(1);
+    }
+}
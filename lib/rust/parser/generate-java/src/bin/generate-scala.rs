@@ -0,0 +1,23 @@
+//! Generate Scala case classes corresponding to `enso-parser`'s AST types, and methods that
+//! deserialize them.
+//!
+//! Usage:
+//! ```console
+//! generate-scala > Tree.scala
+//! ```
+
+// === Standard Linter Configuration ===
+#![deny(non_ascii_idents)]
+#![warn(unsafe_code)]
+#![allow(clippy::bool_to_int_with_if)]
+#![allow(clippy::let_and_return)]
+
+
+
+// ========================
+// === Scala Generation ===
+// ========================
+
+fn main() {
+    println!("{}", enso_parser_generate_java::bindings::scala());
+}
@@ -0,0 +1,23 @@
+//! Generate TypeScript types corresponding to `enso-parser`'s AST types, and functions that
+//! deserialize them.
+//!
+//! Usage:
+//! ```console
+//! generate-typescript > ast.ts
+//! ```
+
+// === Standard Linter Configuration ===
+#![deny(non_ascii_idents)]
+#![warn(unsafe_code)]
+#![allow(clippy::bool_to_int_with_if)]
+#![allow(clippy::let_and_return)]
+
+
+
+// =============================
+// === TypeScript Generation ===
+// =============================
+
+fn main() {
+    println!("{}", enso_parser_generate_java::bindings::typescript());
+}
@@ -0,0 +1,25 @@
+//! Generates Scala format tests.
+//!
+//! Usage:
+//! ```console
+//! generate-scala > Tree.scala
+//! scala-tests > FormatTests.scala
+//! scala-cli run Tree.scala FormatTests.scala
+//! ```
+
+// === Standard Linter Configuration ===
+#![deny(non_ascii_idents)]
+#![warn(unsafe_code)]
+#![allow(clippy::bool_to_int_with_if)]
+#![allow(clippy::let_and_return)]
+
+
+
+// =============================
+// === Scala Test Generation ===
+// =============================
+
+fn main() {
+    let cases = enso_parser_generate_java::generate_testcases();
+    print!("{}", enso_parser_generate_java::bindings::scala_tests(&cases));
+}
@@ -0,0 +1,22 @@
+//! Print a snapshot of the serialization schema of the parser's AST types.
+//!
+//! Usage:
+//! ```console
+//! tree-schema > tree-schema.txt
+//! ```
+
+// === Standard Linter Configuration ===
+#![deny(non_ascii_idents)]
+#![warn(unsafe_code)]
+#![allow(clippy::bool_to_int_with_if)]
+#![allow(clippy::let_and_return)]
+
+
+
+// =======================
+// === Schema Snapshot ===
+// =======================
+
+fn main() {
+    print!("{}", enso_parser_generate_java::tree_schema());
+}
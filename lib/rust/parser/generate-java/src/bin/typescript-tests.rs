@@ -0,0 +1,25 @@
+//! Generates TypeScript format tests.
+//!
+//! Usage:
+//! ```console
+//! generate-typescript > ast.ts
+//! typescript-tests > formatTests.ts
+//! tsc --target es2020 --module commonjs formatTests.ts && node formatTests.js
+//! ```
+
+// === Standard Linter Configuration ===
+#![deny(non_ascii_idents)]
+#![warn(unsafe_code)]
+#![allow(clippy::bool_to_int_with_if)]
+#![allow(clippy::let_and_return)]
+
+
+
+// ==================================
+// === TypeScript Test Generation ===
+// ==================================
+
+fn main() {
+    let cases = enso_parser_generate_java::generate_testcases();
+    print!("{}", enso_parser_generate_java::bindings::typescript_tests(&cases));
+}
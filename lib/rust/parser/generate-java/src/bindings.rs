@@ -0,0 +1,149 @@
+//! Generation of the TypeScript and Scala bindings for the parser types rooted at `syntax::Tree`,
+//! and of programs testing their deserialization against the format test cases.
+
+use enso_metamodel::meta;
+use enso_metamodel::scala;
+use enso_metamodel::typescript;
+use enso_reflect::Reflect;
+
+
+
+// ================
+// === Bindings ===
+// ================
+
+/// Generate a TypeScript module defining the AST types, and functions that deserialize them.
+pub fn typescript() -> String {
+    let (graph, _) = enso_metamodel::rust::to_meta(enso_parser::syntax::Tree::reflect());
+    let mut module = typescript::from_meta(&graph);
+    typescript::bincode::derive(&graph, &mut module);
+    module.to_string()
+}
+
+/// Generate a Scala file defining the AST types as case classes, and methods that deserialize
+/// them.
+pub fn scala() -> String {
+    let (graph, _) = enso_metamodel::rust::to_meta(enso_parser::syntax::Tree::reflect());
+    let mut file = scala::from_meta(&graph, crate::PACKAGE);
+    scala::bincode::derive(&graph, &mut file);
+    file.to_string()
+}
+
+
+
+// =============
+// === Tests ===
+// =============
+
+/// Generate a TypeScript program that deserializes the test cases with the module produced by
+/// [`typescript`], which it imports from `./ast`. The program exits with a nonzero status if any
+/// case is not handled as expected.
+pub fn typescript_tests(cases: &meta::serialization::TestCases) -> String {
+    let fmt_cases = |cases: &[Vec<u8>]| {
+        let cases = cases.iter().map(|case| {
+            let case: Vec<_> = case.iter().map(|byte| byte.to_string()).collect();
+            format!("  new Uint8Array([{}]),\n", case.join(", "))
+        });
+        cases.collect::<String>()
+    };
+    let accept = fmt_cases(&cases.accept);
+    let reject = fmt_cases(&cases.reject);
+    format!(
+        "\
+import {{ deserialize, FormatError, readTree }} from './ast'
+
+const accept: Uint8Array[] = [
+{accept}]
+const reject: Uint8Array[] = [
+{reject}]
+
+let result = 0
+for (const testCase of accept) {{
+  try {{
+    deserialize(testCase, readTree)
+    console.log('- pass')
+  }} catch (e) {{
+    console.log('- fail:', e)
+    result = 1
+  }}
+}}
+for (const testCase of reject) {{
+  try {{
+    deserialize(testCase, readTree)
+    console.log('- fail: accepted')
+    result = 1
+  }} catch (e) {{
+    if (e instanceof FormatError) {{
+      console.log('- pass: (rejected)')
+    }} else {{
+      console.log('- fail: wrong exception:', e)
+      result = 1
+    }}
+  }}
+}}
+process.exit(result)
+"
+    )
+}
+
+/// Generate a Scala program, `FormatTests`, that deserializes the test cases with the file
+/// produced by [`scala`]. The program exits with a nonzero status if any case is not handled as
+/// expected.
+///
+/// The cases are embedded as hexadecimal strings, because large array literals exceed the size
+/// limits of JVM methods.
+pub fn scala_tests(cases: &meta::serialization::TestCases) -> String {
+    let fmt_cases = |cases: &[Vec<u8>]| {
+        let cases = cases.iter().map(|case| {
+            let case: String = case.iter().map(|byte| format!("{byte:02x}")).collect();
+            format!("    \"{case}\",\n")
+        });
+        cases.collect::<String>()
+    };
+    let package = crate::PACKAGE;
+    let object = scala::bincode::OBJECT;
+    let accept = fmt_cases(&cases.accept);
+    let reject = fmt_cases(&cases.reject);
+    format!(
+        "\
+package {package}
+
+object FormatTests {{
+  private val accept = Vector(
+{accept}  )
+  private val reject = Vector(
+{reject}  )
+
+  private def decode(hex: String): Array[Byte] =
+    hex.grouped(2).map(Integer.parseInt(_, 16).toByte).toArray
+
+  def main(args: Array[String]): Unit = {{
+    var result = 0
+    for (testCase <- accept) {{
+      try {{
+        {object}.deserialize(decode(testCase), {object}.readTree)
+        println(\"- pass\")
+      }} catch {{
+        case e: Exception =>
+          println(s\"- fail: $e\")
+          result = 1
+      }}
+    }}
+    for (testCase <- reject) {{
+      try {{
+        {object}.deserialize(decode(testCase), {object}.readTree)
+        println(\"- fail: accepted\")
+        result = 1
+      }} catch {{
+        case _: FormatError => println(\"- pass: (rejected)\")
+        case e: Exception =>
+          println(s\"- fail: wrong exception: $e\")
+          result = 1
+      }}
+    }}
+    sys.exit(result)
+  }}
+}}
+"
+    )
+}
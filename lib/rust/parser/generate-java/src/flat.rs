@@ -0,0 +1,89 @@
+//! Accessors reading the `enso_parser` types in place from the flat serialization format.
+
+use enso_metamodel::java::*;
+
+use enso_metamodel::java::flat::AccessorBuilder;
+use enso_metamodel::meta;
+use enso_reflect::Reflect;
+
+
+
+// =================
+// === Accessors ===
+// =================
+
+const CODE_GETTER: &str = "codeRepr";
+const WHITESPACE_GETTER: &str = "getWhitespace";
+const TREE_BEGIN: &str = "fieldSpanLeftOffsetCodeReprBegin";
+const TREE_WHITESPACE_LEN: &str = "fieldSpanLeftOffsetCodeReprLen";
+const TREE_LEN: &str = "fieldSpanCodeLengthUtf8";
+const TOKEN_WHITESPACE_BEGIN: &str = "fieldLeftOffsetCodeReprBegin";
+const TOKEN_WHITESPACE_LEN: &str = "fieldLeftOffsetCodeReprLen";
+const TOKEN_BEGIN: &str = "fieldCodeReprBegin";
+const TOKEN_LEN: &str = "fieldCodeReprLen";
+
+/// Generate accessor classes for all types in the typegraph, and a `Schema` class identifying the
+/// schema they were generated from.
+///
+/// The graph must not yet have been modified by [`crate::serialization::derive`], which adds fields
+/// that are not present in the serialized data.
+pub fn derive(graph: &TypeGraph, tree: ClassId, token: ClassId) -> Vec<syntax::Class> {
+    for (class, name) in [(tree, TREE_BEGIN), (tree, TREE_WHITESPACE_LEN), (tree, TREE_LEN)] {
+        assert!(graph[class].find_field(name).is_some(), "Missing field: {name}");
+    }
+    for name in [TOKEN_WHITESPACE_BEGIN, TOKEN_WHITESPACE_LEN, TOKEN_BEGIN, TOKEN_LEN] {
+        assert!(graph[token].find_field(name).is_some(), "Missing field: {name}");
+    }
+    let mut accessors =
+        AccessorBuilder::new(graph, crate::FLAT_PACKAGE, crate::FLAT_PACKAGE, crate::EITHER_TYPE);
+    let tree_code = format!("{TREE_BEGIN}() + {TREE_WHITESPACE_LEN}(), {TREE_LEN}()");
+    accessors.method(tree, code_getter(CODE_GETTER, &tree_code));
+    let tree_whitespace = format!("{TREE_BEGIN}(), {TREE_WHITESPACE_LEN}()");
+    accessors.method(tree, code_getter(WHITESPACE_GETTER, &tree_whitespace));
+    let token_code = format!("{TOKEN_BEGIN}(), {TOKEN_LEN}()");
+    accessors.method(token, code_getter(CODE_GETTER, &token_code));
+    let token_whitespace = format!("{TOKEN_WHITESPACE_BEGIN}(), {TOKEN_WHITESPACE_LEN}()");
+    accessors.method(token, code_getter(WHITESPACE_GETTER, &token_whitespace));
+    let mut classes =
+        accessors.build().unwrap_or_else(|e| panic!("Failed to derive flat accessors: {e}"));
+    classes.push(schema_class(schema_hash()));
+    classes
+}
+
+fn code_getter(name: &str, arguments: &str) -> syntax::Method {
+    let mut method = syntax::Method::new(name, syntax::Type::named("String"));
+    method.body = format!("return buffer.code({arguments});");
+    method
+}
+
+
+
+// ===================
+// === Schema Hash ===
+// ===================
+
+/// Compute the hash identifying the schema of `enso_parser::syntax::Tree`.
+pub fn schema_hash() -> u64 {
+    let tree = enso_parser::syntax::Tree::reflect();
+    let tree_id = tree.id;
+    let (graph, rust_to_meta) = enso_metamodel::rust::to_meta(tree);
+    meta::serialization::schema_hash(&graph, rust_to_meta[&tree_id])
+}
+
+fn schema_class(hash: u64) -> syntax::Class {
+    let mut method = syntax::Method::new("hash", syntax::Type::named("long"));
+    method.static_ = true;
+    method.body = format!("return {hash:#x}L;");
+    syntax::Class {
+        package:   Some(crate::FLAT_PACKAGE.to_owned()),
+        name:      "Schema".to_owned(),
+        abstract_: false,
+        final_:    true,
+        static_:   false,
+        parent:    None,
+        fields:    vec![],
+        methods:   vec![method],
+        nested:    vec![],
+        sealed:    None,
+    }
+}
@@ -27,6 +27,8 @@ use enso_reflect::Reflect;
 // === Export ===
 // ==============
 
+pub mod bindings;
+pub mod flat;
 pub mod serialization;
 
 
@@ -39,6 +41,8 @@ pub mod serialization;
 pub const PACKAGE: &str = "org.enso.syntax2";
 /// The package for the non-generated serialization support code.
 pub const SERIALIZATION_SUPPORT: &str = "org.enso.syntax2";
+/// The package for the generated flat-format accessors, and their non-generated support code.
+pub const FLAT_PACKAGE: &str = "org.enso.syntax2.flat";
 /// The fully-qualified name of an `Either` type.
 pub const EITHER_TYPE: &str = "org.enso.syntax2.Either";
 
@@ -61,6 +65,21 @@ pub fn generate_testcases() -> meta::serialization::TestCases {
 
 
 
+// ==============
+// === Schema ===
+// ==============
+
+/// Produce a snapshot of the serialization schema of the parser types rooted at `syntax::Tree`. See
+/// [`meta::evolution`].
+pub fn tree_schema() -> String {
+    let root = syntax::Tree::reflect();
+    let root_id = root.id;
+    let (graph, rust_to_meta) = enso_metamodel::rust::to_meta(root);
+    meta::evolution::to_schema(&graph, rust_to_meta[&root_id])
+}
+
+
+
 // ===========================
 // === Rust Format Testing ===
 // ===========================
@@ -82,4 +101,57 @@ mod test {
             }
         }
     }
+
+    /// Check that the serialization format is compatible with the recorded snapshot. If a change
+    /// to the format is intended, the snapshot can be updated with:
+    /// ```console
+    /// cargo run -p enso-parser-generate-java --bin tree-schema > tree-schema.txt
+    /// ```
+    #[test]
+    fn test_schema_compatibility() {
+        use enso_metamodel::meta::evolution;
+        let (old, old_root) = evolution::from_schema(include_str!("../tree-schema.txt")).unwrap();
+        let (new, new_root) = evolution::from_schema(&super::tree_schema()).unwrap();
+        let report = evolution::compare(&old, old_root, &new, new_root);
+        assert!(!report.is_breaking(), "Breaking changes to the serialization format:\n{report}");
+    }
+
+    /// Check the flat serialization of a parsed tree against the layout read by the accessors.
+    #[test]
+    fn test_flat_format() {
+        use enso_metamodel::java;
+        use enso_parser::serialization::flat;
+        use enso_reflect::Reflect;
+        let tree = enso_parser::syntax::Tree::reflect();
+        let tree_id = tree.id;
+        let (graph, rust_to_meta) = enso_metamodel::rust::to_meta(tree);
+        let (graph, meta_to_java) = java::from_meta(&graph, crate::EITHER_TYPE);
+        let graph = java::transform::optional_to_null(graph);
+        let root = meta_to_java[&rust_to_meta[&tree_id]];
+        let code = [
+            "from Standard.Base import all",
+            "import Standard.Base.Data.Vector",
+            "type Maybe a",
+            "    Nothing",
+            "    Some (value : a)",
+            "    map self f = case self of",
+            "        Maybe.Some x -> Maybe.Some (f x)",
+            "        _ -> self",
+            "@Builtin_Type",
+            "main =",
+            "    x = 0x1F + 1.5 * -3",
+            "    text = 'Hello, ' + \"World\" + '''\n        block text",
+            "    list = [1, 2, 3] . map (x -> x + 1)",
+            "    result = list.at 0 . catch Any (_ -> Nothing)",
+            "    invalid = + +",
+            "    IO.println text",
+        ];
+        let code = code.join("\n");
+        let tree = enso_parser::Parser::new().run(&code);
+        let data = flat::serialize(&tree, crate::flat::schema_hash()).unwrap();
+        let root_address = &data[flat::HEADER_LEN - 4..flat::HEADER_LEN];
+        let root_address = u32::from_le_bytes(root_address.try_into().unwrap()) as usize;
+        let size = java::flat::validate(&graph, root, &data, root_address).unwrap();
+        assert_eq!(size, data.len() - flat::HEADER_LEN);
+    }
 }
@@ -1,4 +1,4 @@
-//! Generate the Java types corresponding to `enso-parser`'s AST types.
+//! Generate the Java types corresponding to `enso-parser`'s AST and diagnostic types.
 //!
 //! # Usage
 //!
@@ -6,6 +6,8 @@
 //! ```console
 //! generate-java org/enso/syntax2/
 //! ```
+//!
+//! The accessors for the flat format are placed in the `flat` subdirectory.
 
 // === Standard Linter Configuration ===
 #![deny(non_ascii_idents)]
@@ -27,6 +29,7 @@
 
 use enso_metamodel::java;
 use enso_metamodel::rust;
+use enso_parser_generate_java::flat;
 use enso_parser_generate_java::serialization;
 use enso_reflect::Reflect;
 
@@ -45,13 +48,34 @@ fn main() {
     let mut graph = java::transform::optional_to_null(graph);
     let rust_to_java = |id| meta_to_java[&rust_to_meta[&id]];
     let (tree, token) = (rust_to_java(tree), rust_to_java(token));
+    let flat_classes = flat::derive(&graph, tree, token);
     serialization::derive(&mut graph, tree, token);
     let graph = java::to_syntax(&graph, enso_parser_generate_java::PACKAGE);
+    let diagnostic = enso_parser::syntax::tree::diagnostic::Diagnostic::reflect();
+    let (diagnostic_graph, _) = rust::to_meta(diagnostic);
+    let (diagnostic_graph, _) =
+        java::from_meta(&diagnostic_graph, enso_parser_generate_java::EITHER_TYPE);
+    let mut diagnostic_graph = java::transform::optional_to_null(diagnostic_graph);
+    serialization::derive_plain(&mut diagnostic_graph);
+    let diagnostic_graph = java::to_syntax(&diagnostic_graph, enso_parser_generate_java::PACKAGE);
     let mut args = std::env::args();
     args.next().unwrap();
     let dir = args.next().expect("Usage: generate-java <output-dir>");
-    for class in graph {
+    // Types used by both the tree and the diagnostics (e.g. `ErrorCode`) appear in both graphs.
+    let mut written = std::collections::HashMap::new();
+    for class in graph.into_iter().chain(diagnostic_graph) {
+        let code = class.to_string();
+        if let Some(previous) = written.get(&class.name) {
+            assert_eq!(previous, &code, "Conflicting definitions of class {}.", class.name);
+            continue;
+        }
+        std::fs::write(format!("{}/{}.java", &dir, &class.name), &code).unwrap();
+        written.insert(class.name, code);
+    }
+    let flat_dir = format!("{dir}/flat");
+    std::fs::create_dir_all(&flat_dir).unwrap();
+    for class in flat_classes {
         let code = class.to_string();
-        std::fs::write(format!("{}/{}.java", &dir, &class.name), code).unwrap();
+        std::fs::write(format!("{}/{}.java", &flat_dir, &class.name), code).unwrap();
     }
 }
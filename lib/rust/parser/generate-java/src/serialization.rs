@@ -30,6 +30,17 @@ pub fn derive(graph: &mut TypeGraph, tree: ClassId, token: ClassId) {
     graph[tree].methods.push(impl_whitespace_getter(WHITESPACE_GETTER));
 }
 
+/// Derive deserialization for all types in a typegraph that does not contain any syntax types.
+pub fn derive_plain(graph: &mut TypeGraph) {
+    let ids: Vec<_> = graph.classes.keys().collect();
+    for id in ids {
+        let deserialization =
+            bincode::DeserializerBuilder::new(id, crate::SERIALIZATION_SUPPORT, crate::EITHER_TYPE);
+        let deserializer = deserialization.build(graph);
+        graph[id].methods.push(deserializer);
+    }
+}
+
 
 // === Deserialization Methods ===
 
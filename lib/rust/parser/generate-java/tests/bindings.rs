@@ -0,0 +1,93 @@
+//! Check that the TypeScript and Scala bindings deserialize the format test cases: every accepted
+//! case is read, and every rejected case fails with a `FormatError`.
+//!
+//! The tests need the languages' toolchains (`tsc` and `node`; `scala-cli`), so they are ignored by
+//! default; run them with `cargo test -p enso-parser-generate-java --test bindings -- --ignored`.
+//! CI runs them as part of `./run java-gen test`. A test fails if its toolchain is not installed.
+
+// === Non-Standard Linter Configuration ===
+#![allow(clippy::option_map_unit_fn)]
+#![allow(clippy::precedence)]
+#![allow(dead_code)]
+#![deny(non_ascii_idents)]
+#![deny(unconditional_recursion)]
+#![warn(unsafe_code)]
+#![warn(missing_copy_implementations)]
+#![warn(missing_debug_implementations)]
+#![warn(missing_docs)]
+#![warn(trivial_casts)]
+#![warn(trivial_numeric_casts)]
+#![warn(unused_import_braces)]
+#![warn(unused_qualifications)]
+
+use enso_parser_generate_java::bindings;
+use std::path::Path;
+use std::path::PathBuf;
+use std::process::Command;
+
+
+
+// =============
+// === Tests ===
+// =============
+
+#[test]
+#[ignore = "requires `tsc` and `node`"]
+fn typescript_round_trip() {
+    let dir = output_dir("typescript");
+    let cases = enso_parser_generate_java::generate_testcases();
+    std::fs::write(dir.join("ast.ts"), bindings::typescript()).unwrap();
+    std::fs::write(dir.join("formatTests.ts"), bindings::typescript_tests(&cases)).unwrap();
+    let mut tsc = Command::new("tsc");
+    tsc.args(["--target", "es2020", "--module", "commonjs", "--outDir"]);
+    tsc.arg(dir.join("js")).arg(dir.join("formatTests.ts"));
+    check(tsc);
+    let mut node = Command::new("node");
+    node.arg(dir.join("js").join("formatTests.js"));
+    check(node);
+}
+
+#[test]
+#[ignore = "requires `scala-cli`"]
+fn scala_round_trip() {
+    let dir = output_dir("scala");
+    let cases = enso_parser_generate_java::generate_testcases();
+    std::fs::write(dir.join("Tree.scala"), bindings::scala()).unwrap();
+    std::fs::write(dir.join("FormatTests.scala"), bindings::scala_tests(&cases)).unwrap();
+    let mut scala = Command::new("scala-cli");
+    scala.args(["run", "--server=false", "--main-class"]);
+    scala.arg(format!("{}.FormatTests", enso_parser_generate_java::PACKAGE));
+    scala.arg(dir.join("Tree.scala")).arg(dir.join("FormatTests.scala"));
+    check(scala);
+}
+
+
+
+// ===============
+// === Helpers ===
+// ===============
+
+/// Create an empty directory for the generated files of a test.
+fn output_dir(name: &str) -> PathBuf {
+    let dir = Path::new(env!("CARGO_TARGET_TMPDIR")).join("bindings").join(name);
+    if dir.exists() {
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+    std::fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+/// Run a command to completion, and check that it succeeded, showing its output otherwise.
+fn check(mut command: Command) {
+    let output = match command.output() {
+        Ok(output) => output,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+            let program = command.get_program().to_string_lossy();
+            panic!("`{program}` is required by this test, but is not installed.")
+        }
+        Err(e) => panic!("Failed to run {command:?}: {e}"),
+    };
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(output.status.success(), "{}\n{stdout}\n{stderr}", output.status);
+}
@@ -20,8 +20,10 @@
 
 use enso_prelude::*;
 
+use enso_reflect::Reflect;
 use jni::objects::JByteBuffer;
 use jni::objects::JClass;
+use jni::objects::JObject;
 use jni::sys::jobject;
 use jni::JNIEnv;
 
@@ -49,24 +51,8 @@ pub extern "system" fn Java_org_enso_syntax2_Parser_parseInput(
     input: JByteBuffer,
 ) -> jobject {
     let state = unsafe { &mut *(state as usize as *mut State) };
-    let direct_allocated = "Internal Error: ByteBuffer must be direct-allocated.";
-    let input = env.get_direct_buffer_address(input).expect(direct_allocated);
-    let input = if cfg!(debug_assertions) {
-        std::str::from_utf8(input).unwrap()
-    } else {
-        unsafe { std::str::from_utf8_unchecked(input) }
-    };
-    let mut code = input;
-    let mut meta = None;
-    if let Some((meta_, code_)) = enso_parser::metadata::parse(input) {
-        match meta_ {
-            Ok(meta_) => meta = Some(meta_),
-            Err(e) => error!("Ignoring invalid metadata: {e}."),
-        }
-        code = code_;
-    }
-    state.base = str::as_ptr(code) as usize as u64;
-    let tree = enso_parser::Parser::new().run(code);
+    let input = input_str(&env, input);
+    let tree = parse(state, input);
     state.output = match enso_parser::serialization::serialize_tree(&tree) {
         Ok(tree) => tree,
         // `Tree` does not contain any types with fallible `serialize` implementations, so this
@@ -76,11 +62,78 @@ pub extern "system" fn Java_org_enso_syntax2_Parser_parseInput(
             default()
         }
     };
-    state.metadata = meta;
     let result = env.new_direct_byte_buffer(&mut state.output);
     result.unwrap().into_inner()
 }
 
+/// Parse the input. Returns a representation of the parse tree in the flat format, which can be
+/// read in place. The caller is responsible for freeing the memory associated with the returned
+/// buffer.
+///
+/// If the `schema_hash` expected by the caller differs from the hash of the parser's types, throws
+/// an `IllegalStateException` and returns `null`.
+///
+/// # Safety
+///
+/// The state MUST be a value returned by `allocState` that has not been passed to `freeState`.
+/// The input buffer contents MUST be valid UTF-8.
+/// The contents of the returned buffer MUST not be accessed after another call to `parseInput` or
+/// `parseInputFlat`, or a call to `freeState`.
+#[allow(unsafe_code)]
+#[no_mangle]
+pub extern "system" fn Java_org_enso_syntax2_Parser_parseInputFlat(
+    env: JNIEnv,
+    _class: JClass,
+    state: u64,
+    input: JByteBuffer,
+    schema_hash: u64,
+) -> jobject {
+    let state = unsafe { &mut *(state as usize as *mut State) };
+    if schema_hash != *SCHEMA_HASH {
+        let message = format!(
+            "Java classes were generated for parser schema {schema_hash:x}, but the parser library \
+            has schema {:x}; the parser library and the generated Java sources must be built from \
+            the same version.",
+            *SCHEMA_HASH
+        );
+        env.throw_new("java/lang/IllegalStateException", message).unwrap();
+        return JObject::null().into_inner();
+    }
+    let input = input_str(&env, input);
+    let tree = parse(state, input);
+    state.output = match enso_parser::serialization::flat::serialize(&tree, *SCHEMA_HASH) {
+        Ok(tree) => tree,
+        // `Tree` contains only types supported by the flat format, and source code is limited to
+        // 4 GiB by the 32-bit addresses in the output of `getLastInputBase`, so this cannot fail.
+        Err(_) => {
+            debug_assert!(false);
+            default()
+        }
+    };
+    let result = env.new_direct_byte_buffer(&mut state.output);
+    result.unwrap().into_inner()
+}
+
+/// Return a serialized representation of the diagnostics of the errors found by the most recent
+/// call to `parseInput`.
+///
+/// # Safety
+///
+/// The state MUST be a value returned by `allocState` that has not been passed to `freeState`.
+/// The contents of the returned buffer MUST not be accessed after another call to `parseInput`, or
+/// a call to `freeState`.
+#[allow(unsafe_code)]
+#[no_mangle]
+pub extern "system" fn Java_org_enso_syntax2_Parser_getDiagnostics(
+    env: JNIEnv,
+    _class: JClass,
+    state: u64,
+) -> jobject {
+    let state = unsafe { &mut *(state as usize as *mut State) };
+    let result = env.new_direct_byte_buffer(&mut state.diagnostics);
+    result.unwrap().into_inner()
+}
+
 /// Return the `base` parameter to pass to the `Message` class along with the other output of the
 /// most recent call to `parseInput`.
 ///
@@ -190,6 +243,45 @@ pub extern "system" fn Java_org_enso_syntax2_Parser_getUuidLow(
     get_uuid(metadata, code_offset, code_length).1
 }
 
+#[allow(unsafe_code)]
+fn input_str<'a>(env: &'a JNIEnv, input: JByteBuffer) -> &'a str {
+    let direct_allocated = "Internal Error: ByteBuffer must be direct-allocated.";
+    let input = env.get_direct_buffer_address(input).expect(direct_allocated);
+    if cfg!(debug_assertions) {
+        std::str::from_utf8(input).unwrap()
+    } else {
+        unsafe { std::str::from_utf8_unchecked(input) }
+    }
+}
+
+/// Parse the input, and store the information about the parse that is not part of the tree in the
+/// state.
+fn parse<'s>(state: &mut State, input: &'s str) -> enso_parser::syntax::Tree<'s> {
+    let mut code = input;
+    let mut meta = None;
+    if let Some((meta_, code_)) = enso_parser::metadata::parse(input) {
+        match meta_ {
+            Ok(meta_) => meta = Some(meta_),
+            Err(e) => error!("Ignoring invalid metadata: {e}."),
+        }
+        code = code_;
+    }
+    state.base = str::as_ptr(code) as usize as u64;
+    let tree = enso_parser::Parser::new().run(code);
+    let diagnostics = tree.diagnostics();
+    state.diagnostics = match enso_parser::serialization::serialize_diagnostics(&diagnostics) {
+        Ok(diagnostics) => diagnostics,
+        // `Diagnostic` does not contain any types with fallible `serialize` implementations, so
+        // this cannot fail.
+        Err(_) => {
+            debug_assert!(false);
+            default()
+        }
+    };
+    state.metadata = meta;
+    tree
+}
+
 #[allow(unsafe_code)]
 fn get_uuid(metadata: u64, code_offset: u64, code_length: u64) -> (u64, u64) {
     if metadata == 0 {
@@ -205,13 +297,30 @@ fn get_uuid(metadata: u64, code_offset: u64, code_length: u64) -> (u64, u64) {
 
 
 
+// =====================
+// === Schema Hashes ===
+// =====================
+
+lazy_static! {
+    /// The hash identifying the schema of the parser's types, as used in the flat format.
+    static ref SCHEMA_HASH: u64 = {
+        let tree = enso_parser::syntax::Tree::reflect();
+        let tree_id = tree.id;
+        let (graph, rust_to_meta) = enso_metamodel::rust::to_meta(tree);
+        enso_metamodel::meta::serialization::schema_hash(&graph, rust_to_meta[&tree_id])
+    };
+}
+
+
+
 // ====================
 // === Parser state ===
 // ====================
 
 #[derive(Default, Debug)]
 struct State {
-    base:     u64,
-    output:   Vec<u8>,
-    metadata: Option<enso_parser::metadata::Metadata>,
+    base:        u64,
+    output:      Vec<u8>,
+    diagnostics: Vec<u8>,
+    metadata:    Option<enso_parser::metadata::Metadata>,
 }
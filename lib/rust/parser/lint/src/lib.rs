@@ -0,0 +1,458 @@
+//! Lints for Enso source code.
+//!
+//! A [`Linter`] parses code with [`enso_parser`], and runs a set of [`Rule`]s over the resulting
+//! [`Tree`]. Each rule reports [`Violation`]s, which the linter turns into [`Finding`]s with the
+//! rule's configured [`Severity`]. The built-in rules are defined in [`rules`]; more can be added
+//! with [`Linter::register`].
+//!
+//! # Suppression
+//! Findings can be suppressed by annotating a statement with `@allow` followed by the names of the
+//! rules to suppress, or `all`:
+//! ```text
+//! @allow unused_assignment shadowed_binding
+//! main =
+//!     x = 1
+//!     42
+//! ```
+//! A finding is suppressed if it starts within the annotated statement.
+
+// === Features ===
+#![feature(let_chains)]
+// === Standard Linter Configuration ===
+#![deny(non_ascii_idents)]
+#![warn(unsafe_code)]
+#![allow(clippy::bool_to_int_with_if)]
+#![allow(clippy::let_and_return)]
+// === Non-Standard Linter Configuration ===
+#![allow(clippy::option_map_unit_fn)]
+#![allow(clippy::precedence)]
+#![allow(dead_code)]
+#![deny(unconditional_recursion)]
+#![warn(missing_copy_implementations)]
+#![warn(missing_debug_implementations)]
+#![warn(missing_docs)]
+#![warn(trivial_casts)]
+#![warn(trivial_numeric_casts)]
+#![warn(unused_import_braces)]
+#![warn(unused_qualifications)]
+
+use enso_parser::source::Code;
+use enso_parser::syntax::item;
+use enso_parser::syntax::token::Token;
+use enso_parser::syntax::tree;
+use enso_parser::syntax::tree::ItemVisitable;
+use enso_parser::syntax::Tree;
+use serde::Serialize;
+use std::collections::HashMap;
+use std::fmt::Debug;
+use std::ops::Range;
+
+
+// ==============
+// === Export ===
+// ==============
+
+pub mod report;
+pub mod rules;
+
+
+
+// ============
+// === Rule ===
+// ============
+
+/// The severity of a finding.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize)]
+#[serde(rename_all = "lowercase")]
+#[allow(missing_docs)]
+pub enum Severity {
+    Info,
+    Warning,
+    Error,
+}
+
+impl Severity {
+    /// The name of the severity, as used in reports.
+    pub fn name(self) -> &'static str {
+        match self {
+            Severity::Info => "info",
+            Severity::Warning => "warning",
+            Severity::Error => "error",
+        }
+    }
+}
+
+/// A check of Enso code.
+pub trait Rule: Debug {
+    /// The name used to refer to the rule in configuration and suppression annotations. By
+    /// convention, names are in `snake_case`.
+    fn name(&self) -> &'static str;
+    /// A one-line description of what the rule checks.
+    fn description(&self) -> &'static str;
+    /// The severity of the rule's findings, unless otherwise configured.
+    fn default_severity(&self) -> Severity {
+        Severity::Warning
+    }
+    /// Check the code, returning any violations of the rule.
+    fn check(&self, context: &Context) -> Vec<Violation>;
+}
+
+/// A violation of a [`Rule`], as reported by the rule.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Violation {
+    /// The location of the offending code, in bytes.
+    pub span:    Range<usize>,
+    /// A description of the problem.
+    pub message: String,
+}
+
+impl Violation {
+    /// Constructor.
+    pub fn new(span: Range<usize>, message: impl Into<String>) -> Self {
+        let message = message.into();
+        Self { span, message }
+    }
+}
+
+/// A violation of a [`Rule`], as reported by the [`Linter`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Finding {
+    /// The name of the rule violated.
+    pub rule:     &'static str,
+    /// The configured severity of the rule.
+    pub severity: Severity,
+    /// A description of the problem.
+    pub message:  String,
+    /// The location of the offending code, in bytes.
+    pub span:     Range<usize>,
+}
+
+
+
+// ===============
+// === Context ===
+// ===============
+
+/// The code being checked, and the tree parsed from it.
+#[derive(Debug)]
+pub struct Context<'s, 'a> {
+    /// The code.
+    pub code: &'s str,
+    /// The tree parsed from the code.
+    pub tree: &'a Tree<'s>,
+    /// The span of each node, by address.
+    trees:    HashMap<*const Tree<'s>, Range<usize>>,
+    /// The span of each token, by the address of its code.
+    tokens:   HashMap<*const Code<'s>, Range<usize>>,
+}
+
+impl<'s, 'a> Context<'s, 'a> {
+    /// Constructor. The tree must have been parsed from the code.
+    pub fn new(code: &'s str, tree: &'a Tree<'s>) -> Self {
+        let mut spans = SpanCollector::default();
+        tree.visit_item(&mut spans);
+        let SpanCollector { trees, tokens, .. } = spans;
+        Self { code, tree, trees, tokens }
+    }
+
+    /// Return all the nodes of the tree, in pre-order.
+    pub fn nodes(&self) -> Vec<&'a Tree<'s>> {
+        self.tree.collect_vec_ref()
+    }
+
+    /// Return the location of the code of a node of the tree, not including its left offset.
+    /// Panics if the node is not part of [`Self::tree`].
+    pub fn span(&self, tree: &Tree<'s>) -> Range<usize> {
+        let key: *const Tree<'s> = tree;
+        self.trees.get(&key).cloned().expect("The node is not part of the checked tree.")
+    }
+
+    /// Return the location of the code of a token of the tree, not including its left offset.
+    /// Panics if the token is not part of [`Self::tree`].
+    pub fn token_span<T>(&self, token: &Token<'s, T>) -> Range<usize> {
+        let key: *const Code<'s> = &token.code;
+        self.tokens.get(&key).cloned().expect("The token is not part of the checked tree.")
+    }
+}
+
+/// Records the span of every node and token.
+#[derive(Debug, Default)]
+struct SpanCollector<'s> {
+    offset: usize,
+    trees:  HashMap<*const Tree<'s>, Range<usize>>,
+    tokens: HashMap<*const Code<'s>, Range<usize>>,
+}
+
+impl<'s> tree::Visitor for SpanCollector<'s> {}
+impl<'s, 'a> tree::ItemVisitor<'s, 'a> for SpanCollector<'s> {
+    fn visit_item(&mut self, item: item::Ref<'s, 'a>) -> bool {
+        match item {
+            item::Ref::Tree(tree) => {
+                self.offset += tree.span.left_offset.code.repr.len();
+                let start = self.offset;
+                let end = start + tree.span.code_length.utf8_bytes();
+                self.trees.insert(tree, start..end);
+                tree.variant.visit_item(self);
+                self.offset = end;
+                // The contents have been visited above.
+                false
+            }
+            item::Ref::Token(token) => {
+                self.offset += token.left_offset.code.repr.len();
+                let start = self.offset;
+                self.offset += token.code.repr.len();
+                self.tokens.insert(token.code, start..self.offset);
+                true
+            }
+        }
+    }
+}
+
+
+
+// ==============
+// === Linter ===
+// ==============
+
+/// The name of the annotation suppressing findings.
+pub const SUPPRESSION_ANNOTATION: &str = "allow";
+
+/// The rule name that can be used in a suppression annotation to suppress all rules.
+pub const ALL_RULES: &str = "all";
+
+/// Runs [`Rule`]s over Enso code.
+#[derive(Debug)]
+pub struct Linter {
+    parser: enso_parser::Parser,
+    /// Each rule, with its configured severity; rules with no severity are disabled.
+    rules:  Vec<(Box<dyn Rule>, Option<Severity>)>,
+}
+
+impl Default for Linter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Linter {
+    /// Constructor. The linter runs all the built-in [`rules`], at their default severities.
+    pub fn new() -> Self {
+        let mut linter = Self::empty();
+        for rule in rules::all() {
+            linter.register_boxed(rule);
+        }
+        linter
+    }
+
+    /// Constructor. The linter has no rules.
+    pub fn empty() -> Self {
+        Self { parser: enso_parser::Parser::new(), rules: vec![] }
+    }
+
+    /// Add a rule, at its default severity.
+    pub fn register(&mut self, rule: impl Rule + 'static) {
+        self.register_boxed(Box::new(rule));
+    }
+
+    fn register_boxed(&mut self, rule: Box<dyn Rule>) {
+        let severity = Some(rule.default_severity());
+        self.rules.push((rule, severity));
+    }
+
+    /// Return the registered rules, with their configured severities.
+    pub fn rules(&self) -> impl Iterator<Item = (&dyn Rule, Option<Severity>)> + '_ {
+        self.rules.iter().map(|(rule, severity)| (&**rule, *severity))
+    }
+
+    /// Set the severity of the named rule; [`None`] disables it. Returns `false` if no rule has
+    /// the given name.
+    pub fn set_severity(&mut self, rule: &str, severity: Option<Severity>) -> bool {
+        let mut found = false;
+        for (_, configured) in self.rules.iter_mut().filter(|(r, _)| r.name() == rule) {
+            *configured = severity;
+            found = true;
+        }
+        found
+    }
+
+    /// Parse and check the contents of a source file. Findings are ordered by location.
+    pub fn lint(&self, source: &str) -> Vec<Finding> {
+        let (code, _metadata) = enso_parser::metadata::extract(source);
+        let tree = self.parser.run(code);
+        self.lint_tree(&Context::new(code, &tree))
+    }
+
+    /// Check a parsed tree. Findings are ordered by location.
+    pub fn lint_tree(&self, context: &Context) -> Vec<Finding> {
+        let suppressions = suppressions(context);
+        let mut findings = vec![];
+        for (rule, severity) in &self.rules {
+            let Some(severity) = *severity else { continue };
+            let rule_name = rule.name();
+            let is_suppressed = |span: &Range<usize>| {
+                suppressions.iter().any(|(range, rules)| {
+                    range.contains(&span.start)
+                        && rules.iter().any(|name| name == rule_name || name == ALL_RULES)
+                })
+            };
+            for Violation { span, message } in rule.check(context) {
+                if !is_suppressed(&span) {
+                    findings.push(Finding { rule: rule_name, severity, message, span });
+                }
+            }
+        }
+        findings.sort_by_key(|finding| (finding.span.start, finding.span.end));
+        findings
+    }
+}
+
+/// Return the location of each statement annotated with a suppression, and the names of the rules
+/// suppressed.
+fn suppressions(context: &Context) -> Vec<(Range<usize>, Vec<String>)> {
+    let mut suppressions = vec![];
+    for node in context.nodes() {
+        match &*node.variant {
+            tree::Variant::Annotated(annotated) =>
+                if let Some(rules) = suppressed_rules(annotated, &[]) {
+                    suppressions.push((context.span(node), rules));
+                },
+            // An annotation with more than one argument is parsed as an application of the
+            // annotation, on a line of its own; it applies to the next statement of the block.
+            tree::Variant::BodyBlock(block) => {
+                let mut statements =
+                    block.statements.iter().filter_map(|line| line.expression.as_ref()).peekable();
+                while let Some(statement) = statements.next() {
+                    if let Some(rules) = applied_suppression(statement)
+                        && let Some(next) = statements.peek() {
+                        suppressions.push((context.span(next), rules));
+                    }
+                }
+            }
+            _ => (),
+        }
+    }
+    suppressions
+}
+
+/// If the tree is a suppression annotation applied to additional arguments, return the names of
+/// the rules suppressed.
+fn applied_suppression(tree: &Tree) -> Option<Vec<String>> {
+    let mut arguments = vec![];
+    let mut tree = tree;
+    while let tree::Variant::App(app) = &*tree.variant {
+        arguments.push(&app.arg);
+        tree = &app.func;
+    }
+    match &*tree.variant {
+        tree::Variant::Annotated(annotated)
+            if annotated.expression.is_none() && !arguments.is_empty() =>
+        {
+            arguments.reverse();
+            suppressed_rules(annotated, &arguments)
+        }
+        _ => None,
+    }
+}
+
+/// If the annotation is a suppression, return the names of the rules suppressed by its argument
+/// and any additional arguments it is applied to.
+fn suppressed_rules(annotated: &tree::Annotated, arguments: &[&Tree]) -> Option<Vec<String>> {
+    if annotated.annotation.code.repr != SUPPRESSION_ANNOTATION {
+        return None;
+    }
+    let arguments = annotated.argument.iter().chain(arguments.iter().copied());
+    let rules = arguments.flat_map(|argument| argument.collect_vec_ref());
+    let rules = rules.filter_map(|tree| match &*tree.variant {
+        tree::Variant::Ident(ident) => Some(ident.token.code.repr.to_string()),
+        _ => None,
+    });
+    Some(rules.collect())
+}
+
+
+
+// =============
+// === Tests ===
+// =============
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rules_found(linter: &Linter, code: &str) -> Vec<&'static str> {
+        linter.lint(code).into_iter().map(|finding| finding.rule).collect()
+    }
+
+    const CODE: &str = "main =\n    x = 1\n    x = 2\n    42\n";
+
+    #[test]
+    fn findings() {
+        let findings = Linter::new().lint(CODE);
+        let first = &findings[0];
+        assert_eq!(first.rule, "unused_assignment");
+        assert_eq!(first.severity, Severity::Warning);
+        assert_eq!(&CODE[first.span.clone()], "x");
+        assert_eq!(first.span.start, CODE.find("x = 1").unwrap());
+    }
+
+    #[test]
+    fn suppression() {
+        let linter = Linter::new();
+        assert_eq!(rules_found(&linter, CODE), [
+            "unused_assignment",
+            "unused_assignment",
+            "shadowed_binding"
+        ]);
+        let suppressed = format!("@allow unused_assignment\n{CODE}");
+        assert_eq!(rules_found(&linter, &suppressed), ["shadowed_binding"]);
+        let suppressed = format!("@allow unused_assignment shadowed_binding\n{CODE}");
+        assert_eq!(rules_found(&linter, &suppressed), Vec::<&str>::new());
+        let suppressed = format!("@allow all\n{CODE}");
+        assert_eq!(rules_found(&linter, &suppressed), Vec::<&str>::new());
+        let other = format!("@allow unused_import\n{CODE}");
+        assert_eq!(rules_found(&linter, &other).len(), 3);
+    }
+
+    #[test]
+    fn configuration() {
+        let mut linter = Linter::new();
+        assert!(linter.set_severity("shadowed_binding", None));
+        assert!(linter.set_severity("unused_assignment", Some(Severity::Error)));
+        assert!(!linter.set_severity("no_such_rule", None));
+        let findings = linter.lint(CODE);
+        let found: Vec<_> =
+            findings.iter().map(|finding| (finding.rule, finding.severity)).collect();
+        let unused = ("unused_assignment", Severity::Error);
+        assert_eq!(found, [unused, unused]);
+    }
+
+    #[test]
+    fn custom_rules() {
+        #[derive(Debug)]
+        struct NoMain;
+        impl Rule for NoMain {
+            fn name(&self) -> &'static str {
+                "no_main"
+            }
+            fn description(&self) -> &'static str {
+                "Reports functions named `main`."
+            }
+            fn check(&self, context: &Context) -> Vec<Violation> {
+                let functions =
+                    context.nodes().into_iter().filter_map(|node| match &*node.variant {
+                        tree::Variant::Function(function)
+                            if function.name.trimmed_code() == "main" =>
+                            Some(Violation::new(context.span(&function.name), "Found `main`.")),
+                        _ => None,
+                    });
+                functions.collect()
+            }
+        }
+        let mut linter = Linter::empty();
+        linter.register(NoMain);
+        let findings = linter.lint("foo = 1\nmain x = x\n");
+        assert_eq!(findings.len(), 1);
+        assert_eq!(findings[0].span, 8..12);
+        assert_eq!(findings[0].message, "Found `main`.");
+    }
+}
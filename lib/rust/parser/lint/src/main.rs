@@ -0,0 +1,104 @@
+//! Check Enso source files for problems.
+//!
+//! Usage:
+//! `enso-parser-lint [--format text|json] [--allow RULE] [--warn RULE] [--deny RULE] PATH...`
+//!
+//! Each path is a source file, or a directory that is searched recursively for `.enso` files, such
+//! as the root of a project. `--allow`, `--warn` and `--deny` disable a rule, or set its severity
+//! to warning or error; they can be repeated. `--list-rules` prints the available rules. The
+//! process fails if any finding has error severity.
+
+// === Features ===
+#![feature(let_chains)]
+// === Standard Linter Configuration ===
+#![deny(non_ascii_idents)]
+#![warn(unsafe_code)]
+#![allow(clippy::bool_to_int_with_if)]
+#![allow(clippy::let_and_return)]
+// === Non-Standard Linter Configuration ===
+#![allow(clippy::option_map_unit_fn)]
+#![allow(clippy::precedence)]
+#![allow(dead_code)]
+#![deny(unconditional_recursion)]
+#![warn(missing_copy_implementations)]
+#![warn(missing_debug_implementations)]
+#![warn(missing_docs)]
+#![warn(trivial_casts)]
+#![warn(trivial_numeric_casts)]
+#![warn(unused_import_braces)]
+#![warn(unused_qualifications)]
+
+use enso_parser::source::files::enso_files;
+use enso_parser_lint::report::Entry;
+use enso_parser_lint::report::Format;
+use enso_parser_lint::Linter;
+use enso_parser_lint::Severity;
+use std::path::PathBuf;
+
+
+
+// ============
+// === Main ===
+// ============
+
+fn main() {
+    let mut linter = Linter::new();
+    let mut format = Format::default();
+    let mut paths = vec![];
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        let severity = match arg.as_str() {
+            "--allow" => None,
+            "--warn" => Some(Severity::Warning),
+            "--deny" => Some(Severity::Error),
+            "--format" => {
+                let name = args.next().unwrap_or_default();
+                format = Format::from_name(&name)
+                    .unwrap_or_else(|| usage(&format!("Unknown format: {name:?}.")));
+                continue;
+            }
+            "--list-rules" => {
+                for (rule, severity) in linter.rules() {
+                    let severity = severity.map_or("disabled", Severity::name);
+                    println!("{} ({severity}): {}", rule.name(), rule.description());
+                }
+                return;
+            }
+            _ if arg.starts_with("--") => usage(&format!("Unknown option: {arg}")),
+            _ => {
+                paths.push(PathBuf::from(arg));
+                continue;
+            }
+        };
+        let rule = args.next().unwrap_or_else(|| usage(&format!("{arg} expects a rule name.")));
+        if !linter.set_severity(&rule, severity) {
+            usage(&format!("Unknown rule: {rule}"));
+        }
+    }
+    if paths.is_empty() {
+        usage("No paths given.");
+    }
+    let mut entries = vec![];
+    for path in &paths {
+        let files = enso_files(path).unwrap_or_else(|e| panic!("Failed to read {path:?}: {e}"));
+        for file in files {
+            let code = std::fs::read_to_string(&file)
+                .unwrap_or_else(|e| panic!("Failed to read {file:?}: {e}"));
+            let findings = linter.lint(&code);
+            entries.extend(Entry::locate(&file.to_string_lossy(), &code, &findings));
+        }
+    }
+    format.write(&entries, &mut std::io::stdout().lock()).unwrap();
+    if entries.iter().any(|entry| entry.severity == Severity::Error) {
+        std::process::exit(1);
+    }
+}
+
+fn usage(error: &str) -> ! {
+    eprintln!("{error}");
+    eprintln!(
+        "Usage: enso-parser-lint [--format text|json] [--allow RULE] [--warn RULE] [--deny RULE] \
+         PATH..."
+    );
+    std::process::exit(2)
+}
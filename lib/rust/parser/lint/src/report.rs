@@ -0,0 +1,133 @@
+//! Reporting [`Finding`]s as text or JSON.
+
+use crate::Finding;
+use crate::Severity;
+
+use enso_parser::source::files::line_column;
+use serde::Serialize;
+use std::io;
+use std::io::Write;
+
+
+
+// =============
+// === Entry ===
+// =============
+
+/// A finding, located in a file by line and column. Lines and columns are one-based; columns are
+/// counted in characters.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+#[allow(missing_docs)]
+pub struct Entry {
+    pub path:       String,
+    pub line:       usize,
+    pub column:     usize,
+    pub end_line:   usize,
+    pub end_column: usize,
+    pub rule:       &'static str,
+    pub severity:   Severity,
+    pub message:    String,
+}
+
+impl Entry {
+    /// Locate the findings in a file with the given path and contents.
+    pub fn locate(path: &str, code: &str, findings: &[Finding]) -> Vec<Self> {
+        let entry = |finding: &Finding| {
+            let (line, column) = line_column(code, finding.span.start);
+            let (end_line, end_column) = line_column(code, finding.span.end);
+            Entry {
+                path: path.to_owned(),
+                line,
+                column,
+                end_line,
+                end_column,
+                rule: finding.rule,
+                severity: finding.severity,
+                message: finding.message.clone(),
+            }
+        };
+        findings.iter().map(entry).collect()
+    }
+}
+
+
+
+// ==============
+// === Format ===
+// ==============
+
+/// An output format for findings.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Format {
+    /// One line per finding: `path:line:column: severity[rule]: message`.
+    #[default]
+    Text,
+    /// A JSON array of [`Entry`] objects.
+    Json,
+}
+
+impl Format {
+    /// Look up a format by name.
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "text" => Some(Format::Text),
+            "json" => Some(Format::Json),
+            _ => None,
+        }
+    }
+
+    /// Write the entries in this format.
+    pub fn write(self, entries: &[Entry], output: &mut impl Write) -> io::Result<()> {
+        match self {
+            Format::Text => {
+                for entry in entries {
+                    let Entry { path, line, column, rule, severity, message, .. } = entry;
+                    let severity = severity.name();
+                    writeln!(output, "{path}:{line}:{column}: {severity}[{rule}]: {message}")?;
+                }
+                Ok(())
+            }
+            Format::Json => {
+                serde_json::to_writer_pretty(&mut *output, entries)?;
+                writeln!(output)
+            }
+        }
+    }
+}
+
+
+
+// =============
+// === Tests ===
+// =============
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn formats() {
+        let code = "main =\n    value = 1\n    42\n";
+        let findings = crate::Linter::new().lint(code);
+        let entries = Entry::locate("src/Main.enso", code, &findings);
+        let mut text = vec![];
+        Format::Text.write(&entries, &mut text).unwrap();
+        let expected = "src/Main.enso:2:5: warning[unused_assignment]: \
+                        The value assigned to `value` is never used.\n";
+        assert_eq!(String::from_utf8(text).unwrap(), expected);
+        let mut json = vec![];
+        Format::Json.write(&entries, &mut json).unwrap();
+        let json: serde_json::Value = serde_json::from_slice(&json).unwrap();
+        let expected = serde_json::json!([{
+            "path": "src/Main.enso",
+            "line": 2,
+            "column": 5,
+            "end_line": 2,
+            "end_column": 10,
+            "rule": "unused_assignment",
+            "severity": "warning",
+            "message": "The value assigned to `value` is never used.",
+        }]);
+        assert_eq!(json, expected);
+    }
+}
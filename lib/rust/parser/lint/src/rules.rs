@@ -0,0 +1,438 @@
+//! The built-in [`Rule`]s.
+
+use crate::Context;
+use crate::Rule;
+use crate::Violation;
+
+use enso_parser::scope_analysis;
+use enso_parser::scope_analysis::Binding;
+use enso_parser::scope_analysis::ScopeKind;
+use enso_parser::syntax::tree;
+use enso_parser::syntax::tree::Variant;
+use enso_parser::syntax::Tree;
+use std::collections::HashSet;
+use std::ptr;
+
+
+
+// ===========
+// === All ===
+// ===========
+
+/// Return all the built-in rules.
+pub fn all() -> Vec<Box<dyn Rule>> {
+    vec![
+        Box::new(UnusedAssignment),
+        Box::new(ShadowedBinding),
+        Box::new(CaseWithoutWildcard),
+        Box::new(UnusedImport),
+        Box::new(NonSnakeCaseFunction),
+    ]
+}
+
+
+
+// ========================
+// === UnusedAssignment ===
+// ========================
+
+/// Reports assignments in a function body whose variable is not referenced by any later statement
+/// of the body.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct UnusedAssignment;
+
+impl Rule for UnusedAssignment {
+    fn name(&self) -> &'static str {
+        "unused_assignment"
+    }
+
+    fn description(&self) -> &'static str {
+        "Assignments to variables that are never used."
+    }
+
+    fn check(&self, context: &Context) -> Vec<Violation> {
+        let mut violations = vec![];
+        for node in context.nodes() {
+            // Assignments at the top level of a module define module-level values.
+            let Variant::BodyBlock(block) = &*node.variant else { continue };
+            if ptr::eq(node, context.tree) {
+                continue;
+            }
+            let statements: Vec<_> =
+                block.statements.iter().filter_map(|line| line.expression.as_ref()).collect();
+            for (i, statement) in statements.iter().enumerate() {
+                let Variant::Assignment(assignment) = &*statement_body(statement).variant
+                    else { continue };
+                let Some(name) = bound_name(&assignment.pattern) else { continue };
+                let mut references = HashSet::new();
+                for statement in &statements[i + 1..] {
+                    // Reassigning the variable is not a use of its value.
+                    match &*statement_body(statement).variant {
+                        Variant::Assignment(later) =>
+                            referenced_names(&later.expr, &mut references),
+                        _ => referenced_names(statement, &mut references),
+                    }
+                }
+                if !references.contains(name) {
+                    let message = format!("The value assigned to `{name}` is never used.");
+                    violations.push(Violation::new(context.span(&assignment.pattern), message));
+                }
+            }
+        }
+        violations
+    }
+}
+
+
+
+// =======================
+// === ShadowedBinding ===
+// =======================
+
+/// Reports variables bound with the same name as a variable in an enclosing scope, or earlier in
+/// the same block. Variables are bound by function arguments, assignments in function bodies,
+/// lambdas, and `case` branch patterns; names are resolved by [`scope_analysis`].
+#[derive(Clone, Copy, Debug, Default)]
+pub struct ShadowedBinding;
+
+impl Rule for ShadowedBinding {
+    fn name(&self) -> &'static str {
+        "shadowed_binding"
+    }
+
+    fn description(&self) -> &'static str {
+        "Variables hiding other variables with the same name."
+    }
+
+    fn check(&self, context: &Context) -> Vec<Violation> {
+        let table = scope_analysis::analyze(context.tree);
+        // Names bound at the top level of a module or type are members, not variables.
+        let is_variable = |binding: &Binding| {
+            !matches!(table.scopes[binding.scope].kind, ScopeKind::Module | ScopeKind::Type)
+        };
+        let shadowing = table.bindings.iter().filter(|binding| match binding.shadows {
+            Some(shadowed) => is_variable(binding) && is_variable(&table.bindings[shadowed]),
+            None => false,
+        });
+        let violation = |binding: &Binding| {
+            let message =
+                format!("`{}` shadows another variable with the same name.", binding.name);
+            Violation::new(binding.range.clone(), message)
+        };
+        shadowing.map(violation).collect()
+    }
+}
+
+
+
+// ===========================
+// === CaseWithoutWildcard ===
+// ===========================
+
+/// Reports `case` expressions with no branch matching every value, such as `_ -> ...`.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct CaseWithoutWildcard;
+
+impl Rule for CaseWithoutWildcard {
+    fn name(&self) -> &'static str {
+        "case_without_wildcard"
+    }
+
+    fn description(&self) -> &'static str {
+        "`case` expressions without a catch-all branch."
+    }
+
+    fn check(&self, context: &Context) -> Vec<Violation> {
+        let mut violations = vec![];
+        for node in context.nodes() {
+            let Variant::CaseOf(case_of) = &*node.variant else { continue };
+            let patterns = case_of.cases.iter().filter_map(|line| line.case.as_ref());
+            let mut patterns = patterns.filter_map(|case| case.pattern.as_ref());
+            let is_catch_all = |pattern: &Tree| match &*pattern.variant {
+                Variant::Wildcard(_) => true,
+                Variant::Ident(ident) => !ident.token.variant.is_type,
+                _ => false,
+            };
+            if !patterns.any(is_catch_all) {
+                let message = "This `case` expression has no catch-all branch, such as `_ -> ...`.";
+                violations.push(Violation::new(context.token_span(&case_of.case), message));
+            }
+        }
+        violations
+    }
+}
+
+
+
+// ====================
+// === UnusedImport ===
+// ====================
+
+/// Reports names imported into a module that are not referenced by the module. Imports of `all`
+/// the members of a module are not checked.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct UnusedImport;
+
+impl Rule for UnusedImport {
+    fn name(&self) -> &'static str {
+        "unused_import"
+    }
+
+    fn description(&self) -> &'static str {
+        "Imported names that are never used."
+    }
+
+    fn check(&self, context: &Context) -> Vec<Violation> {
+        let Variant::BodyBlock(module) = &*context.tree.variant else { return vec![] };
+        let statements = module.statements.iter().filter_map(|line| line.expression.as_ref());
+        let mut imports = vec![];
+        let mut references = HashSet::new();
+        for statement in statements {
+            match &*statement.variant {
+                Variant::Import(import) => imports.extend(imported_names(import)),
+                Variant::Export(_) => (),
+                _ => referenced_names(statement, &mut references),
+            }
+        }
+        let unused = imports.into_iter().filter(|name| !references.contains(&*name.trimmed_code()));
+        let violation = |name: &Tree| {
+            let message = format!("`{}` is imported but never used.", name.trimmed_code());
+            Violation::new(context.span(name), message)
+        };
+        unused.map(violation).collect()
+    }
+}
+
+/// Return the identifiers that an import statement brings into scope.
+fn imported_names<'a, 's>(import: &'a tree::Import<'s>) -> Vec<&'a Tree<'s>> {
+    if import.all.is_some() {
+        return vec![];
+    }
+    if let Some(alias) = &import.as_ {
+        return alias.body.iter().collect();
+    }
+    let Some(body) = &import.import.body else { return vec![] };
+    if import.from.is_some() {
+        // `from Module import A, B`
+        let mut names = vec![];
+        let mut list = Some(body);
+        while let Some(tree) = list {
+            match &*tree.variant {
+                Variant::OprApp(tree::OprApp { lhs, opr: Ok(opr), rhs: Some(rhs) })
+                    if opr.properties.is_sequence() =>
+                {
+                    names.push(last_segment(rhs));
+                    list = lhs.as_ref();
+                }
+                _ => {
+                    names.push(last_segment(tree));
+                    list = None;
+                }
+            }
+        }
+        names.reverse();
+        names
+    } else {
+        // `import Qualified.Name`
+        vec![last_segment(body)]
+    }
+}
+
+/// Return the last segment of a qualified name.
+fn last_segment<'a, 's>(name: &'a Tree<'s>) -> &'a Tree<'s> {
+    match &*name.variant {
+        Variant::OprApp(tree::OprApp { opr: Ok(opr), rhs: Some(rhs), .. })
+            if opr.properties.is_dot() =>
+            rhs,
+        _ => name,
+    }
+}
+
+
+
+// ============================
+// === NonSnakeCaseFunction ===
+// ============================
+
+/// Reports functions and methods whose names are not in `snake_case`.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct NonSnakeCaseFunction;
+
+impl Rule for NonSnakeCaseFunction {
+    fn name(&self) -> &'static str {
+        "non_snake_case_function"
+    }
+
+    fn description(&self) -> &'static str {
+        "Function names not in `snake_case`."
+    }
+
+    fn check(&self, context: &Context) -> Vec<Violation> {
+        let mut violations = vec![];
+        for node in context.nodes() {
+            let Variant::Function(function) = &*node.variant else { continue };
+            let name = last_segment(&function.name);
+            let Variant::Ident(ident) = &*name.variant else { continue };
+            if ident.token.variant.is_operator_lexically {
+                continue;
+            }
+            let code = &ident.token.code.repr;
+            if !is_snake_case(code) {
+                let suggestion = to_snake_case(code);
+                let message = format!("The name `{code}` should be in snake_case: `{suggestion}`.");
+                violations.push(Violation::new(context.span(name), message));
+            }
+        }
+        violations
+    }
+}
+
+fn is_snake_case(name: &str) -> bool {
+    let name = name.trim_end_matches('\'');
+    let mut chars = name.chars();
+    chars.next().map_or(false, |c| c.is_ascii_lowercase())
+        && chars.all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '_')
+        && !name.contains("__")
+        && !name.ends_with('_')
+}
+
+/// Convert a name to snake_case. A word starts at an uppercase letter following a lowercase letter
+/// or a digit, or at the last uppercase letter of an acronym followed by a lowercase letter.
+fn to_snake_case(name: &str) -> String {
+    let mut snake = String::with_capacity(name.len() + 4);
+    let mut previous: Option<char> = None;
+    let mut chars = name.chars().peekable();
+    while let Some(c) = chars.next() {
+        let after_word = previous.map_or(false, |p| p.is_lowercase() || p.is_ascii_digit());
+        let ends_acronym = previous.map_or(false, char::is_uppercase)
+            && chars.peek().map_or(false, |next| next.is_lowercase());
+        if c.is_uppercase() && (after_word || ends_acronym) {
+            snake.push('_');
+        }
+        if !(c == '_' && snake.ends_with('_')) {
+            snake.extend(c.to_lowercase());
+        }
+        previous = Some(c);
+    }
+    snake
+}
+
+
+
+// ===============
+// === Helpers ===
+// ===============
+
+/// Return the statement within any annotations and documentation.
+fn statement_body<'a, 's>(statement: &'a Tree<'s>) -> &'a Tree<'s> {
+    let inner = match &*statement.variant {
+        Variant::Documented(documented) => documented.expression.as_ref(),
+        Variant::Annotated(annotated) => annotated.expression.as_ref(),
+        Variant::AnnotatedBuiltin(annotated) => annotated.expression.as_ref(),
+        _ => None,
+    };
+    inner.map_or(statement, statement_body)
+}
+
+/// If the pattern binds a single variable, return its name.
+fn bound_name<'a>(pattern: &'a Tree) -> Option<&'a str> {
+    match &*pattern.variant {
+        Variant::Ident(ident) if !ident.token.variant.is_type => Some(&ident.token.code.repr),
+        _ => None,
+    }
+}
+
+/// Add the names of all the identifiers in the tree to the set.
+fn referenced_names(tree: &Tree, names: &mut HashSet<String>) {
+    for node in tree.collect_vec_ref() {
+        if let Variant::Ident(ident) = &*node.variant {
+            names.insert(ident.token.code.repr.to_string());
+        }
+    }
+}
+
+
+
+// =============
+// === Tests ===
+// =============
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Run a single rule over the code, returning the code of each violation's span.
+    fn violations(rule: impl Rule, code: &str) -> Vec<String> {
+        let tree = enso_parser::Parser::new().run(code);
+        let context = Context::new(code, &tree);
+        let violations = rule.check(&context);
+        violations.into_iter().map(|violation| code[violation.span].to_owned()).collect()
+    }
+
+    #[test]
+    fn unused_assignment() {
+        let code = "value = 1\nmain =\n    x = 1\n    y = 2\n    z = y + 1\n    z\n";
+        assert_eq!(violations(UnusedAssignment, code), ["x"]);
+        let code = "main =\n    x = 1\n    f = a -> a + x\n    f 1\n";
+        assert!(violations(UnusedAssignment, code).is_empty());
+    }
+
+    #[test]
+    fn shadowed_binding() {
+        let code = "f x =\n    y = 1\n    g y = y\n    x = 2\n    h = x -> x\n    g x\n";
+        assert_eq!(violations(ShadowedBinding, code), ["y", "x", "x"]);
+        let code = "f a =\n    b = a + 1\n    c = b -> b\n    c a\ng a = a\n";
+        assert_eq!(violations(ShadowedBinding, code), ["b"]);
+    }
+
+    #[test]
+    fn shadowed_binding_in_case() {
+        let code = "f x = case x of\n    Cons x xs -> xs\n    _ -> x\n";
+        assert_eq!(violations(ShadowedBinding, code), ["x"]);
+    }
+
+    #[test]
+    fn case_without_wildcard() {
+        let code = "f x = case x of\n    1 -> 'one'\n    2 -> 'two'\n";
+        assert_eq!(violations(CaseWithoutWildcard, code), ["case"]);
+        let code = "f x = case x of\n    1 -> 'one'\n    _ -> 'many'\n";
+        assert!(violations(CaseWithoutWildcard, code).is_empty());
+        let code = "f x = case x of\n    Nil -> 0\n    other -> other\n";
+        assert!(violations(CaseWithoutWildcard, code).is_empty());
+    }
+
+    #[test]
+    fn unused_import() {
+        let code = "import Standard.Base.Data.Vector
+import Standard.Base.Data.Map as Dictionary
+from Standard.Base import Integer, Text, Boolean
+from Standard.Table import all
+polyglot java import java.lang.Float
+
+main : Integer -> Text
+main n = Vector.new n (_ -> Float.parse '1')
+";
+        assert_eq!(violations(UnusedImport, code), ["Dictionary", "Boolean"]);
+    }
+
+    #[test]
+    fn non_snake_case_function() {
+        let code = "fooBar = 1
+main =
+    doThings 1
+type T
+    Foo_Bar x
+    getX self = self.x
+    to_text self = 'T'
+T.aB1c self = 1
+";
+        assert_eq!(violations(NonSnakeCaseFunction, code), ["getX", "aB1c"]);
+        assert_eq!(to_snake_case("getX"), "get_x");
+        assert_eq!(to_snake_case("parseHTTPHeader"), "parse_http_header");
+        assert_eq!(to_snake_case("HTTPServer"), "http_server");
+        assert_eq!(to_snake_case("toJSON"), "to_json");
+        assert_eq!(to_snake_case("Bad__Name"), "bad_name");
+        assert!(is_snake_case("to_text'"));
+        assert!(!is_snake_case("snake_"));
+    }
+}
@@ -0,0 +1,456 @@
+//! Computing the information served by the language server from a parsed document.
+
+use enso_parser::syntax::item;
+use enso_parser::syntax::token;
+use enso_parser::syntax::tree;
+use enso_parser::syntax::tree::ItemVisitable;
+use enso_parser::syntax::Tree;
+use serde::Serialize;
+
+
+
+// =================
+// === LineIndex ===
+// =================
+
+/// Converts byte offsets in a document to LSP positions, which are zero-based lines and UTF-16
+/// columns.
+#[derive(Debug)]
+pub struct LineIndex<'c> {
+    code:        &'c str,
+    line_starts: Vec<usize>,
+}
+
+impl<'c> LineIndex<'c> {
+    /// Constructor.
+    pub fn new(code: &'c str) -> Self {
+        let bytes = code.as_bytes();
+        let mut line_starts = vec![0];
+        for (i, byte) in bytes.iter().enumerate() {
+            let is_line_end = match byte {
+                b'\n' => true,
+                b'\r' => bytes.get(i + 1) != Some(&b'\n'),
+                _ => false,
+            };
+            if is_line_end {
+                line_starts.push(i + 1);
+            }
+        }
+        Self { code, line_starts }
+    }
+
+    /// Return the position of the given byte offset.
+    pub fn position(&self, offset: usize) -> Position {
+        let line = self.line_starts.partition_point(|&start| start <= offset) - 1;
+        let line_start = self.line_starts[line];
+        let character = self.code[line_start..offset].encode_utf16().count();
+        Position { line, character }
+    }
+
+    /// Return the range between the given byte offsets.
+    pub fn range(&self, start: usize, end: usize) -> Range {
+        Range { start: self.position(start), end: self.position(end) }
+    }
+}
+
+/// A position in a document.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+#[allow(missing_docs)]
+pub struct Position {
+    pub line:      usize,
+    pub character: usize,
+}
+
+/// A range in a document.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+#[allow(missing_docs)]
+pub struct Range {
+    pub start: Position,
+    pub end:   Position,
+}
+
+
+
+// ================
+// === Analysis ===
+// ================
+
+/// LSP symbol kinds.
+mod symbol_kind {
+    pub const CLASS: u32 = 5;
+    pub const METHOD: u32 = 6;
+    pub const CONSTRUCTOR: u32 = 9;
+    pub const FUNCTION: u32 = 12;
+    pub const VARIABLE: u32 = 13;
+}
+
+/// The semantic token types reported, in the order of their indices.
+pub const SEMANTIC_TOKEN_TYPES: &[&str] =
+    &["keyword", "type", "variable", "operator", "number", "string", "comment"];
+
+mod token_type {
+    pub const KEYWORD: u32 = 0;
+    pub const TYPE: u32 = 1;
+    pub const VARIABLE: u32 = 2;
+    pub const OPERATOR: u32 = 3;
+    pub const NUMBER: u32 = 4;
+    pub const STRING: u32 = 5;
+    pub const COMMENT: u32 = 6;
+}
+
+/// A symbol defined in a document.
+#[derive(Clone, Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+#[allow(missing_docs)]
+pub struct DocumentSymbol {
+    pub name:            String,
+    pub kind:            u32,
+    pub range:           Range,
+    pub selection_range: Range,
+    pub children:        Vec<DocumentSymbol>,
+}
+
+/// A foldable range of lines.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+#[allow(missing_docs)]
+pub struct FoldingRange {
+    pub start_line: usize,
+    pub end_line:   usize,
+}
+
+/// A parse error.
+#[derive(Clone, Debug, Serialize)]
+#[allow(missing_docs)]
+pub struct Diagnostic {
+    pub range:    Range,
+    pub severity: u32,
+    pub code:     &'static str,
+    pub source:   &'static str,
+    pub message:  String,
+}
+
+/// A classified token.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+struct SemanticToken {
+    position:   Position,
+    length:     usize,
+    token_type: u32,
+}
+
+/// The information about a document served by the language server.
+#[derive(Debug, Default)]
+pub struct Analysis {
+    /// The symbols defined at the top level of the document; other symbols are their children.
+    pub symbols:         Vec<DocumentSymbol>,
+    /// The foldable blocks.
+    pub folding_ranges:  Vec<FoldingRange>,
+    /// The classified tokens, in the relative encoding of the LSP `semanticTokens` response.
+    pub semantic_tokens: Vec<u32>,
+    /// The parse errors.
+    pub diagnostics:     Vec<Diagnostic>,
+}
+
+impl Analysis {
+    /// Parse and analyze a document.
+    pub fn new(parser: &enso_parser::Parser, document: &str) -> Self {
+        let (code, _metadata) = enso_parser::metadata::extract(document);
+        let tree = parser.run(code);
+        let index = LineIndex::new(code);
+        let mut collector = Collector::new(&index);
+        tree.visit_item(&mut collector);
+        let symbols = collector.symbols.pop().unwrap_or_default();
+        let folding_ranges = collector.folding_ranges;
+        let semantic_tokens = encode_semantic_tokens(&collector.tokens);
+        let diagnostics = tree
+            .diagnostics()
+            .into_iter()
+            .map(|diagnostic| {
+                let range = diagnostic.primary.range;
+                Diagnostic {
+                    range:    index.range(range.start.utf8, range.end.utf8),
+                    severity: 1,
+                    code:     diagnostic.code.id(),
+                    source:   "enso",
+                    message:  diagnostic.message,
+                }
+            })
+            .collect();
+        Self { symbols, folding_ranges, semantic_tokens, diagnostics }
+    }
+}
+
+/// Encode tokens as a sequence of `[line delta, start delta, length, type, modifiers]` tuples.
+fn encode_semantic_tokens(tokens: &[SemanticToken]) -> Vec<u32> {
+    let mut data = Vec::with_capacity(tokens.len() * 5);
+    let mut previous = Position { line: 0, character: 0 };
+    for token in tokens {
+        let Position { line, character } = token.position;
+        let delta_line = line - previous.line;
+        let delta_start = if delta_line == 0 { character - previous.character } else { character };
+        data.extend([delta_line as u32, delta_start as u32, token.length as u32]);
+        data.extend([token.token_type, 0]);
+        previous = token.position;
+    }
+    data
+}
+
+
+
+// =================
+// === Collector ===
+// =================
+
+/// Visits a tree, collecting the information served about it.
+#[derive(Debug)]
+struct Collector<'c> {
+    index:          &'c LineIndex<'c>,
+    offset:         usize,
+    /// The symbols found so far within each enclosing symbol, outermost first.
+    symbols:        Vec<Vec<DocumentSymbol>>,
+    /// The kinds of the enclosing symbols, outermost first.
+    symbol_kinds:   Vec<u32>,
+    folding_ranges: Vec<FoldingRange>,
+    tokens:         Vec<SemanticToken>,
+    depth:          usize,
+    in_ident:       bool,
+}
+
+impl<'c> Collector<'c> {
+    fn new(index: &'c LineIndex<'c>) -> Self {
+        Self {
+            index,
+            offset: 0,
+            symbols: vec![vec![]],
+            symbol_kinds: vec![],
+            folding_ranges: vec![],
+            tokens: vec![],
+            depth: 0,
+            in_ident: false,
+        }
+    }
+
+    fn visit_tree(&mut self, tree: &Tree) {
+        self.offset += tree.span.left_offset.code.repr.len();
+        let start = self.offset;
+        let symbol = self.symbol(tree);
+        if let Some((_, kind, _)) = &symbol {
+            self.symbols.push(vec![]);
+            self.symbol_kinds.push(*kind);
+        }
+        let in_ident = std::mem::replace(&mut self.in_ident, is_ident(tree));
+        self.depth += 1;
+        tree.variant.visit_item(self);
+        self.depth -= 1;
+        self.in_ident = in_ident;
+        let end = self.offset;
+        let code = &self.index.code[start..end];
+        let trimmed_end = start + code.trim_end().len();
+        if let Some((name, kind, name_range)) = symbol {
+            let children = self.symbols.pop().unwrap();
+            self.symbol_kinds.pop();
+            let range = self.index.range(start, trimmed_end);
+            let selection_range =
+                self.index.range(start + name_range.start, start + name_range.end);
+            let symbol = DocumentSymbol { name, kind, range, selection_range, children };
+            self.symbols.last_mut().unwrap().push(symbol);
+        }
+        if self.depth > 0 && is_foldable(tree) && trimmed_end > start {
+            let start_line = self.index.position(start).line;
+            let end_line = self.index.position(trimmed_end - 1).line;
+            if end_line > start_line {
+                self.folding_ranges.push(FoldingRange { start_line, end_line });
+            }
+        }
+    }
+
+    fn visit_token(&mut self, token: token::Ref) {
+        self.offset += token.left_offset.code.repr.len();
+        let start = self.offset;
+        let code: &str = &token.code.repr;
+        self.offset += code.len();
+        if code.is_empty() || code.contains(['\n', '\r']) {
+            return;
+        }
+        if let Some(token_type) = self.token_type(&token) {
+            // A comment token includes any whitespace following the comment on its line.
+            let code = if token_type == token_type::COMMENT { code.trim_end() } else { code };
+            let position = self.index.position(start);
+            let length = code.encode_utf16().count();
+            self.tokens.push(SemanticToken { position, length, token_type });
+        }
+    }
+
+    /// If the tree defines a symbol, return its name, kind, and the range of the name's code
+    /// relative to the start of the tree's code.
+    fn symbol(&self, tree: &Tree) -> Option<(String, u32, std::ops::Range<usize>)> {
+        let in_type = self.symbol_kinds.last() == Some(&symbol_kind::CLASS);
+        let tree_range = |tree: &Tree| 0..tree.span.code_length.utf8_bytes();
+        match &*tree.variant {
+            tree::Variant::TypeDef(def) => {
+                let name_start = def.keyword.code.repr.len() + def.name.left_offset.code.repr.len();
+                let name_range = name_start..name_start + def.name.code.repr.len();
+                Some((def.name.code.to_string(), symbol_kind::CLASS, name_range))
+            }
+            tree::Variant::ConstructorDefinition(def) => {
+                let name_range = 0..def.constructor.code.repr.len();
+                Some((def.constructor.code.to_string(), symbol_kind::CONSTRUCTOR, name_range))
+            }
+            tree::Variant::Function(function) => {
+                let kind = if in_type { symbol_kind::METHOD } else { symbol_kind::FUNCTION };
+                Some((function.name.trimmed_code(), kind, tree_range(&function.name)))
+            }
+            tree::Variant::Assignment(assignment) => {
+                let name = assignment.pattern.trimmed_code();
+                Some((name, symbol_kind::VARIABLE, tree_range(&assignment.pattern)))
+            }
+            _ => None,
+        }
+    }
+
+    /// Classify a token. Identifiers that are not the content of an [`tree::Ident`] node are part
+    /// of the syntax of a construct: they are keywords, or names of types being defined.
+    fn token_type(&self, token: &token::Ref) -> Option<u32> {
+        Some(match &token.data {
+            token::Variant::Ident(ident) if ident.is_operator_lexically => token_type::OPERATOR,
+            token::Variant::Ident(ident) if ident.is_type => token_type::TYPE,
+            token::Variant::Ident(_) if self.in_ident => token_type::VARIABLE,
+            token::Variant::Ident(_) | token::Variant::AutoScope(_) => token_type::KEYWORD,
+            token::Variant::Wildcard(_) => token_type::VARIABLE,
+            token::Variant::Operator(_) => token_type::OPERATOR,
+            token::Variant::Digits(_) | token::Variant::NumberBase(_) => token_type::NUMBER,
+            token::Variant::TextStart(_)
+            | token::Variant::TextEnd(_)
+            | token::Variant::TextSection(_)
+            | token::Variant::TextEscape(_) => token_type::STRING,
+            token::Variant::Newline(_) if token.code.starts_with('#') => token_type::COMMENT,
+            _ => return None,
+        })
+    }
+}
+
+fn is_ident(tree: &Tree) -> bool {
+    matches!(&*tree.variant, tree::Variant::Ident(_))
+}
+
+fn is_foldable(tree: &Tree) -> bool {
+    matches!(
+        &*tree.variant,
+        tree::Variant::BodyBlock(_)
+            | tree::Variant::ArgumentBlockApplication(_)
+            | tree::Variant::OperatorBlockApplication(_)
+            | tree::Variant::TypeDef(_)
+    )
+}
+
+impl<'c> tree::Visitor for Collector<'c> {}
+impl<'s, 'a, 'c> tree::ItemVisitor<'s, 'a> for Collector<'c> {
+    fn visit_item(&mut self, item: item::Ref<'s, 'a>) -> bool {
+        match item {
+            item::Ref::Tree(tree) => self.visit_tree(tree),
+            item::Ref::Token(token) => self.visit_token(token),
+        }
+        // The contents of trees are visited by `visit_tree`.
+        false
+    }
+}
+
+
+
+// =============
+// === Tests ===
+// =============
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn analyze(code: &str) -> Analysis {
+        Analysis::new(&enso_parser::Parser::new(), code)
+    }
+
+    #[test]
+    fn positions() {
+        let index = LineIndex::new("a\r\nßb\n\n𝄞c");
+        assert_eq!(index.position(0), Position { line: 0, character: 0 });
+        assert_eq!(index.position(3), Position { line: 1, character: 0 });
+        assert_eq!(index.position(5), Position { line: 1, character: 1 });
+        assert_eq!(index.position(8), Position { line: 3, character: 0 });
+        assert_eq!(index.position(12), Position { line: 3, character: 2 });
+    }
+
+    #[test]
+    fn symbols() {
+        let code = "type Point\n    Xy x y\n\n    norm self = self.x\n\nmain =\n    p = 1\n    p\n";
+        let analysis = analyze(code);
+        let describe = |symbol: &DocumentSymbol| (symbol.name.clone(), symbol.kind);
+        let top: Vec<_> = analysis.symbols.iter().map(describe).collect();
+        let point = ("Point".to_owned(), symbol_kind::CLASS);
+        assert_eq!(top, vec![point, ("main".to_owned(), symbol_kind::FUNCTION)]);
+        let members: Vec<_> = analysis.symbols[0].children.iter().map(describe).collect();
+        let constructor = ("Xy".to_owned(), symbol_kind::CONSTRUCTOR);
+        assert_eq!(members, vec![constructor, ("norm".to_owned(), symbol_kind::METHOD)]);
+        let main = &analysis.symbols[1];
+        assert_eq!(main.children[0].name, "p");
+        assert_eq!(main.range.start, Position { line: 5, character: 0 });
+        assert_eq!(main.range.end, Position { line: 7, character: 5 });
+        let selection = main.children[0].selection_range;
+        assert_eq!(selection.start, Position { line: 6, character: 4 });
+        assert_eq!(selection.end, Position { line: 6, character: 5 });
+        let selection = analysis.symbols[0].selection_range;
+        assert_eq!(selection.start, Position { line: 0, character: 5 });
+        assert_eq!(selection.end, Position { line: 0, character: 10 });
+        let selection = analysis.symbols[0].children[0].selection_range;
+        assert_eq!(selection.start, Position { line: 1, character: 4 });
+        assert_eq!(selection.end, Position { line: 1, character: 6 });
+    }
+
+    #[test]
+    fn symbol_name_in_keyword() {
+        let analysis = analyze("type ype\n");
+        let selection = analysis.symbols[0].selection_range;
+        assert_eq!(selection.start, Position { line: 0, character: 5 });
+        assert_eq!(selection.end, Position { line: 0, character: 8 });
+    }
+
+    #[test]
+    fn folding_ranges() {
+        let code = "type Point\n    Xy x y\n\nmain =\n    p = 1\n    p\n";
+        let analysis = analyze(code);
+        let expected = [FoldingRange { start_line: 0, end_line: 1 }, FoldingRange {
+            start_line: 3,
+            end_line:   5,
+        }];
+        assert_eq!(analysis.folding_ranges, expected);
+    }
+
+    #[test]
+    fn semantic_tokens() {
+        let analysis = analyze("main = 1 + x # Comment.\n    'text'");
+        #[rustfmt::skip]
+        let expected = [
+            0, 0, 4, token_type::VARIABLE, 0,
+            0, 5, 1, token_type::OPERATOR, 0,
+            0, 2, 1, token_type::NUMBER, 0,
+            0, 2, 1, token_type::OPERATOR, 0,
+            0, 2, 1, token_type::VARIABLE, 0,
+            0, 2, 10, token_type::COMMENT, 0,
+            1, 4, 1, token_type::STRING, 0,
+            0, 1, 4, token_type::STRING, 0,
+            0, 4, 1, token_type::STRING, 0,
+        ];
+        assert_eq!(analysis.semantic_tokens, expected);
+    }
+
+    #[test]
+    fn diagnostics() {
+        let analysis = analyze("main =\n    x + + x\n");
+        let ranges: Vec<_> = analysis.diagnostics.iter().map(|d| d.range).collect();
+        let range = |line, start, end| Range {
+            start: Position { line, character: start },
+            end:   Position { line, character: end },
+        };
+        assert_eq!(ranges, vec![range(1, 8, 9)]);
+        assert_eq!(analysis.diagnostics[0].code, "multiple-operators");
+    }
+}
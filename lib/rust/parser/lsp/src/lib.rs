@@ -0,0 +1,218 @@
+//! Language Server Protocol front-end for the Enso parser.
+//!
+//! The [`Server`] communicates with an editor over the base protocol of the LSP (JSON-RPC messages
+//! with `Content-Length` headers), and serves information derived from the [`Tree`] produced by
+//! [`enso_parser::Parser`] for each open document:
+//! - `textDocument/documentSymbol`: type definitions, constructors, functions, methods and
+//!   assignments, nested according to the structure of the code.
+//! - `textDocument/foldingRange`: blocks spanning multiple lines.
+//! - `textDocument/semanticTokens/full`: a classification of every token.
+//! - `textDocument/publishDiagnostics`: parse errors, sent whenever a document is opened or
+//!   changed.
+//!
+//! Documents are synchronized in full on every change. The server does not require the engine.
+//!
+//! [`Tree`]: enso_parser::syntax::Tree
+
+// === Features ===
+#![feature(let_chains)]
+// === Standard Linter Configuration ===
+#![deny(non_ascii_idents)]
+#![warn(unsafe_code)]
+#![allow(clippy::bool_to_int_with_if)]
+#![allow(clippy::let_and_return)]
+// === Non-Standard Linter Configuration ===
+#![allow(clippy::option_map_unit_fn)]
+#![allow(clippy::precedence)]
+#![allow(dead_code)]
+#![deny(unconditional_recursion)]
+#![warn(missing_copy_implementations)]
+#![warn(missing_debug_implementations)]
+#![warn(missing_docs)]
+#![warn(trivial_casts)]
+#![warn(trivial_numeric_casts)]
+#![warn(unused_import_braces)]
+#![warn(unused_qualifications)]
+
+use serde_json::json;
+use serde_json::Value;
+use std::collections::HashMap;
+use std::io;
+use std::io::BufRead;
+use std::io::Write;
+
+
+// ==============
+// === Export ===
+// ==============
+
+pub mod analysis;
+pub mod protocol;
+
+pub use analysis::Analysis;
+
+
+
+// ==============
+// === Server ===
+// ==============
+
+/// JSON-RPC error code of a message that is not valid JSON.
+const PARSE_ERROR: i64 = -32700;
+/// JSON-RPC error code of a request that is not valid in the current state of the server.
+const INVALID_REQUEST: i64 = -32600;
+/// JSON-RPC error code of a request for a method the server does not implement.
+const METHOD_NOT_FOUND: i64 = -32601;
+/// JSON-RPC error code of a request with invalid parameters.
+const INVALID_PARAMS: i64 = -32602;
+
+/// Language server for Enso source files.
+#[derive(Debug, Default)]
+pub struct Server {
+    parser:             enso_parser::Parser,
+    /// The text of each open document, by URI.
+    documents:          HashMap<String, String>,
+    shutdown_requested: bool,
+    exit_requested:     bool,
+}
+
+impl Server {
+    /// Constructor.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Serve requests read from the input until an `exit` notification is received, or the input
+    /// ends. Returns the exit code the process should use: `0` if the client requested a shutdown
+    /// before exiting, otherwise `1`.
+    ///
+    /// A malformed message is answered with a parse error, and does not stop the server.
+    pub fn run(mut self, mut input: impl BufRead, mut output: impl Write) -> io::Result<i32> {
+        loop {
+            let responses = match protocol::read_message(&mut input) {
+                Ok(Some(message)) => self.handle(&message),
+                Ok(None) => break,
+                Err(e) if e.kind() == io::ErrorKind::InvalidData => vec![json!({
+                    "jsonrpc": "2.0",
+                    "id": null,
+                    "error": { "code": PARSE_ERROR, "message": e.to_string() },
+                })],
+                Err(e) => return Err(e),
+            };
+            for response in responses {
+                protocol::write_message(&mut output, &response)?;
+            }
+            if self.exit_requested {
+                break;
+            }
+        }
+        Ok(if self.shutdown_requested && self.exit_requested { 0 } else { 1 })
+    }
+
+    /// Handle a message from the client. Returns the messages to send in reply: the response, if
+    /// the message is a request, and any notifications. Responses from the client are ignored, as
+    /// the server does not send requests.
+    pub fn handle(&mut self, message: &Value) -> Vec<Value> {
+        let Some(method) = message["method"].as_str() else { return vec![] };
+        let params = &message["params"];
+        match message.get("id") {
+            Some(id) => {
+                let response = match self.request(method, params) {
+                    Ok(result) => json!({ "jsonrpc": "2.0", "id": id, "result": result }),
+                    Err((code, message)) => json!({
+                        "jsonrpc": "2.0",
+                        "id": id,
+                        "error": { "code": code, "message": message },
+                    }),
+                };
+                vec![response]
+            }
+            None => self.notification(method, params),
+        }
+    }
+
+    fn request(&mut self, method: &str, params: &Value) -> Result<Value, (i64, String)> {
+        if self.shutdown_requested {
+            return Err((INVALID_REQUEST, format!("Request after shutdown: {method}.")));
+        }
+        match method {
+            "initialize" => Ok(capabilities()),
+            "shutdown" => {
+                self.shutdown_requested = true;
+                Ok(Value::Null)
+            }
+            "textDocument/documentSymbol" => Ok(json!(self.analyze(params)?.symbols)),
+            "textDocument/foldingRange" => Ok(json!(self.analyze(params)?.folding_ranges)),
+            "textDocument/semanticTokens/full" =>
+                Ok(json!({ "data": self.analyze(params)?.semantic_tokens })),
+            _ => Err((METHOD_NOT_FOUND, format!("Unsupported method: {method}."))),
+        }
+    }
+
+    fn notification(&mut self, method: &str, params: &Value) -> Vec<Value> {
+        if method == "exit" {
+            self.exit_requested = true;
+        }
+        // After a shutdown, only the `exit` notification is meaningful.
+        if self.shutdown_requested {
+            return vec![];
+        }
+        let document = &params["textDocument"];
+        let Some(uri) = document["uri"].as_str() else { return vec![] };
+        let text = match method {
+            "textDocument/didOpen" => document["text"].as_str(),
+            // With full synchronization, the last change contains the whole text.
+            "textDocument/didChange" => params["contentChanges"]
+                .as_array()
+                .and_then(|changes| changes.last())
+                .and_then(|change| change["text"].as_str()),
+            "textDocument/didClose" => {
+                self.documents.remove(uri);
+                return vec![publish_diagnostics(uri, json!([]))];
+            }
+            _ => None,
+        };
+        match text {
+            Some(text) => {
+                self.documents.insert(uri.to_owned(), text.to_owned());
+                let diagnostics = Analysis::new(&self.parser, text).diagnostics;
+                vec![publish_diagnostics(uri, json!(diagnostics))]
+            }
+            None => vec![],
+        }
+    }
+
+    fn analyze(&self, params: &Value) -> Result<Analysis, (i64, String)> {
+        let uri = params["textDocument"]["uri"].as_str().unwrap_or_default();
+        match self.documents.get(uri) {
+            Some(text) => Ok(Analysis::new(&self.parser, text)),
+            None => Err((INVALID_PARAMS, format!("Unknown document: {uri}."))),
+        }
+    }
+}
+
+fn capabilities() -> Value {
+    json!({
+        "capabilities": {
+            "textDocumentSync": 1,
+            "documentSymbolProvider": true,
+            "foldingRangeProvider": true,
+            "semanticTokensProvider": {
+                "legend": {
+                    "tokenTypes": analysis::SEMANTIC_TOKEN_TYPES,
+                    "tokenModifiers": [],
+                },
+                "full": true,
+            },
+        },
+        "serverInfo": { "name": env!("CARGO_PKG_NAME"), "version": env!("CARGO_PKG_VERSION") },
+    })
+}
+
+fn publish_diagnostics(uri: &str, diagnostics: Value) -> Value {
+    json!({
+        "jsonrpc": "2.0",
+        "method": "textDocument/publishDiagnostics",
+        "params": { "uri": uri, "diagnostics": diagnostics },
+    })
+}
@@ -0,0 +1,41 @@
+//! Run the Enso language server, communicating over standard input and output.
+//!
+//! Usage: `enso-parser-lsp`
+
+// === Features ===
+#![feature(let_chains)]
+// === Standard Linter Configuration ===
+#![deny(non_ascii_idents)]
+#![warn(unsafe_code)]
+#![allow(clippy::bool_to_int_with_if)]
+#![allow(clippy::let_and_return)]
+// === Non-Standard Linter Configuration ===
+#![allow(clippy::option_map_unit_fn)]
+#![allow(clippy::precedence)]
+#![allow(dead_code)]
+#![deny(unconditional_recursion)]
+#![warn(missing_copy_implementations)]
+#![warn(missing_debug_implementations)]
+#![warn(missing_docs)]
+#![warn(trivial_casts)]
+#![warn(trivial_numeric_casts)]
+#![warn(unused_import_braces)]
+#![warn(unused_qualifications)]
+
+use enso_parser_lsp::Server;
+
+
+
+// ============
+// === Main ===
+// ============
+
+fn main() {
+    let input = std::io::stdin().lock();
+    let output = std::io::stdout().lock();
+    let code = Server::new().run(input, output).unwrap_or_else(|e| {
+        eprintln!("Communication with the client failed: {e}");
+        1
+    });
+    std::process::exit(code)
+}
@@ -0,0 +1,84 @@
+//! The base protocol: JSON-RPC messages framed by HTTP-style headers.
+
+use serde_json::Value;
+use std::io;
+use std::io::BufRead;
+use std::io::Write;
+
+
+
+// ===============
+// === Reading ===
+// ===============
+
+/// Read a message. Returns [`None`] at the end of the input.
+///
+/// A malformed message results in an error of kind [`io::ErrorKind::InvalidData`]. The headers
+/// and content of the message have then been consumed, so reading can continue with the next
+/// message.
+pub fn read_message(input: &mut impl BufRead) -> io::Result<Option<Value>> {
+    let mut content_length = None;
+    let mut line = String::new();
+    loop {
+        line.clear();
+        if input.read_line(&mut line)? == 0 {
+            return Ok(None);
+        }
+        let header = line.trim_end_matches(['\r', '\n']);
+        if header.is_empty() {
+            break;
+        }
+        if let Some((name, value)) = header.split_once(':')
+            && name.eq_ignore_ascii_case("Content-Length") {
+            let value = value.trim().parse().map_err(|e| invalid_data(format!("{e}")))?;
+            content_length = Some(value);
+        }
+    }
+    let content_length =
+        content_length.ok_or_else(|| invalid_data("Missing Content-Length header."))?;
+    let mut content = vec![0; content_length];
+    input.read_exact(&mut content)?;
+    serde_json::from_slice(&content).map(Some).map_err(|e| invalid_data(format!("{e}")))
+}
+
+fn invalid_data(message: impl Into<String>) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message.into())
+}
+
+
+
+// ===============
+// === Writing ===
+// ===============
+
+/// Write a message.
+pub fn write_message(output: &mut impl Write, message: &Value) -> io::Result<()> {
+    let content = message.to_string();
+    write!(output, "Content-Length: {}\r\n\r\n{content}", content.len())?;
+    output.flush()
+}
+
+
+
+// =============
+// === Tests ===
+// =============
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn round_trip() {
+        let messages = [serde_json::json!({"id": 1, "method": "ü"}), serde_json::json!(null)];
+        let mut buffer = vec![];
+        for message in &messages {
+            write_message(&mut buffer, message).unwrap();
+        }
+        let mut input = &buffer[..];
+        for message in &messages {
+            assert_eq!(read_message(&mut input).unwrap().as_ref(), Some(message));
+        }
+        assert!(read_message(&mut input).unwrap().is_none());
+    }
+}
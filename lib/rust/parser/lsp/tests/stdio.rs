@@ -0,0 +1,207 @@
+//! Drive the language server binary over standard input and output, as an editor would.
+
+// === Non-Standard Linter Configuration ===
+#![allow(clippy::option_map_unit_fn)]
+#![allow(clippy::precedence)]
+#![allow(dead_code)]
+#![deny(non_ascii_idents)]
+#![deny(unconditional_recursion)]
+#![warn(unsafe_code)]
+#![warn(missing_copy_implementations)]
+#![warn(missing_debug_implementations)]
+#![warn(missing_docs)]
+#![warn(trivial_casts)]
+#![warn(trivial_numeric_casts)]
+#![warn(unused_import_braces)]
+#![warn(unused_qualifications)]
+
+use enso_parser_lsp::protocol;
+use serde_json::json;
+use serde_json::Value;
+use std::io::Write;
+use std::process::Command;
+use std::process::Stdio;
+
+
+
+// ===============
+// === Running ===
+// ===============
+
+/// Encode messages as the input of the server.
+fn encode(messages: &[Value]) -> Vec<u8> {
+    let mut input = vec![];
+    for message in messages {
+        protocol::write_message(&mut input, message).unwrap();
+    }
+    input
+}
+
+/// Run the server on the given input, check that it exits successfully, and return its replies.
+fn run(input: &[u8]) -> Vec<Value> {
+    let mut server = Command::new(env!("CARGO_BIN_EXE_enso-parser-lsp"))
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .unwrap();
+    server.stdin.take().unwrap().write_all(input).unwrap();
+    let output = server.wait_with_output().unwrap();
+    assert!(output.status.success(), "{:?}", output.status);
+    let mut replies = vec![];
+    let mut output = &output.stdout[..];
+    while let Some(message) = protocol::read_message(&mut output).unwrap() {
+        replies.push(message);
+    }
+    replies
+}
+
+
+
+// =============
+// === Tests ===
+// =============
+
+const URI: &str = "file:///project/src/Main.enso";
+
+const CODE: &str = "type Point
+    Xy x y
+
+    norm self = self.x
+
+main =
+    p = Point.Xy 1 2
+    p + + p
+";
+
+#[test]
+fn scripted_session() {
+    let document = json!({ "uri": URI });
+    let script = [
+        json!({ "jsonrpc": "2.0", "id": 1, "method": "initialize", "params": {} }),
+        json!({ "jsonrpc": "2.0", "method": "initialized", "params": {} }),
+        json!({
+            "jsonrpc": "2.0",
+            "method": "textDocument/didOpen",
+            "params": {
+                "textDocument": { "uri": URI, "languageId": "enso", "version": 1, "text": CODE },
+            },
+        }),
+        json!({
+            "jsonrpc": "2.0",
+            "id": 2,
+            "method": "textDocument/documentSymbol",
+            "params": { "textDocument": document },
+        }),
+        json!({
+            "jsonrpc": "2.0",
+            "id": 3,
+            "method": "textDocument/foldingRange",
+            "params": { "textDocument": document },
+        }),
+        json!({
+            "jsonrpc": "2.0",
+            "id": 4,
+            "method": "textDocument/semanticTokens/full",
+            "params": { "textDocument": document },
+        }),
+        json!({ "jsonrpc": "2.0", "id": 5, "method": "textDocument/hover", "params": {} }),
+        // A response to a request of the server must not be mistaken for a request.
+        json!({ "jsonrpc": "2.0", "id": 7, "result": null }),
+        json!({ "jsonrpc": "2.0", "id": 6, "method": "shutdown" }),
+        json!({ "jsonrpc": "2.0", "method": "exit" }),
+    ];
+    let replies = run(&encode(&script));
+    let response = |id: u64| replies.iter().find(|reply| reply["id"] == id).unwrap();
+
+    let capabilities = &response(1)["result"]["capabilities"];
+    assert_eq!(capabilities["documentSymbolProvider"], true);
+    assert_eq!(capabilities["foldingRangeProvider"], true);
+    let legend = &capabilities["semanticTokensProvider"]["legend"]["tokenTypes"];
+    assert!(legend.as_array().unwrap().contains(&json!("comment")));
+
+    let notification = replies.iter().find(|reply| reply.get("id").is_none()).unwrap();
+    assert_eq!(notification["method"], "textDocument/publishDiagnostics");
+    assert_eq!(notification["params"]["uri"], URI);
+    let diagnostics = notification["params"]["diagnostics"].as_array().unwrap();
+    assert_eq!(diagnostics.len(), 1);
+    let range = json!({
+        "start": { "line": 7, "character": 8 },
+        "end": { "line": 7, "character": 9 },
+    });
+    assert_eq!(diagnostics[0]["range"], range);
+    assert_eq!(diagnostics[0]["severity"], 1);
+
+    let symbols = response(2)["result"].as_array().unwrap();
+    let names = |symbols: &[Value]| -> Vec<String> {
+        symbols.iter().map(|symbol| symbol["name"].as_str().unwrap().to_owned()).collect()
+    };
+    assert_eq!(names(symbols), ["Point", "main"]);
+    assert_eq!(names(symbols[0]["children"].as_array().unwrap()), ["Xy", "norm"]);
+    assert_eq!(names(symbols[1]["children"].as_array().unwrap()), ["p"]);
+
+    let folding_ranges = &response(3)["result"];
+    let expected = json!([{ "startLine": 0, "endLine": 3 }, { "startLine": 5, "endLine": 7 }]);
+    assert_eq!(folding_ranges, &expected);
+
+    let tokens = response(4)["result"]["data"].as_array().unwrap();
+    assert_eq!(tokens.len() % 5, 0);
+    // The first token is the `type` keyword.
+    assert_eq!(tokens[..5], [json!(0), json!(0), json!(4), json!(0), json!(0)]);
+
+    assert_eq!(response(5)["error"]["code"], -32601);
+    assert_eq!(response(6)["result"], Value::Null);
+    assert!(replies.iter().all(|reply| reply["id"] != 7));
+}
+
+#[test]
+fn malformed_messages() {
+    let initialize = json!({ "jsonrpc": "2.0", "id": 1, "method": "initialize", "params": {} });
+    let shutdown = json!({ "jsonrpc": "2.0", "id": 2, "method": "shutdown" });
+    let exit = json!({ "jsonrpc": "2.0", "method": "exit" });
+    let mut input = encode(&[initialize]);
+    // Content that is not valid JSON.
+    input.extend(b"Content-Length: 5\r\n\r\n{\"id\"");
+    // A message without a `Content-Length` header.
+    input.extend(b"Content-Type: application/vscode-jsonrpc\r\n\r\n");
+    input.extend(encode(&[shutdown, exit]));
+    let replies = run(&input);
+    assert_eq!(replies.len(), 4);
+    assert_eq!(replies[0]["id"], 1);
+    for reply in &replies[1..3] {
+        assert_eq!(reply["id"], Value::Null);
+        assert_eq!(reply["error"]["code"], -32700);
+    }
+    assert_eq!(replies[3]["id"], 2);
+    assert_eq!(replies[3]["result"], Value::Null);
+}
+
+#[test]
+fn requests_after_shutdown() {
+    let script = [
+        json!({ "jsonrpc": "2.0", "id": 1, "method": "initialize", "params": {} }),
+        json!({ "jsonrpc": "2.0", "id": 2, "method": "shutdown" }),
+        json!({
+            "jsonrpc": "2.0",
+            "method": "textDocument/didOpen",
+            "params": {
+                "textDocument": { "uri": URI, "languageId": "enso", "version": 1, "text": CODE },
+            },
+        }),
+        json!({
+            "jsonrpc": "2.0",
+            "id": 3,
+            "method": "textDocument/foldingRange",
+            "params": { "textDocument": { "uri": URI } },
+        }),
+        json!({ "jsonrpc": "2.0", "id": 4, "method": "shutdown" }),
+        json!({ "jsonrpc": "2.0", "method": "exit" }),
+    ];
+    let replies = run(&encode(&script));
+    // The notification after the shutdown is dropped, so no diagnostics are published.
+    assert!(replies.iter().all(|reply| reply.get("id").is_some()));
+    assert_eq!(replies.len(), 4);
+    assert_eq!(replies[1]["result"], Value::Null);
+    for reply in &replies[2..] {
+        assert_eq!(reply["error"]["code"], -32600);
+    }
+}
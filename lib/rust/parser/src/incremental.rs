@@ -0,0 +1,348 @@
+//! Incremental reparsing: updating the [`Tree`] of a module after an edit of its source code,
+//! reusing the top-level block lines that the edit could not have affected.
+//!
+//! # Anchors
+//! A top-level line is an *anchor* if its content begins in the first column of a physical line,
+//! with a character that is not whitespace. At the beginning of an anchor the lexer has closed
+//! every block and every text literal, and the macro resolver has finished every macro of the
+//! previous lines. As a result, lexing and parsing code starting at an anchor does not depend on
+//! the code preceding it. There is one exception: an interpolation splice left open earlier in the
+//! module would be closed by a backtick appearing later, so regions involving backticks are never
+//! reparsed in isolation.
+//!
+//! # Reparsing
+//! The edited region is extended to the closest anchors around it: the last anchor starting before
+//! the edit, and the first anchor starting after it. The new code of the region is parsed on its
+//! own, and the resulting lines replace the lines of the region in the previous tree. If the
+//! conditions required for the result to be equal to a full reparse cannot be established (for
+//! example, when the edit leaves the region beginning with whitespace, or ending with a
+//! documentation comment that would be attached to the following line), the whole module is parsed
+//! again instead.
+
+use crate::prelude::*;
+
+use crate::lexer;
+use crate::macros;
+use crate::syntax::tree::block;
+use crate::syntax::tree::BodyBlock;
+use crate::syntax::tree::Variant;
+use crate::syntax::Tree;
+use crate::Parser;
+
+
+
+// ============
+// === Edit ===
+// ============
+
+/// A change of source code: the bytes in `range` of the old code are replaced with `text`.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Edit {
+    /// The replaced range, as byte offsets into the code before the edit.
+    pub range: Range<usize>,
+    /// The text inserted in place of the range.
+    pub text:  String,
+}
+
+impl Edit {
+    /// Constructor.
+    pub fn new(range: Range<usize>, text: impl Into<String>) -> Self {
+        let text = text.into();
+        Self { range, text }
+    }
+
+    /// Return the result of applying the edit to the given code.
+    pub fn apply(&self, code: &str) -> String {
+        let mut out = String::with_capacity(code.len() - self.range.len() + self.text.len());
+        out.push_str(&code[..self.range.start]);
+        out.push_str(&self.text);
+        out.push_str(&code[self.range.end..]);
+        out
+    }
+
+    /// Translate a byte offset into the code before the edit, located after the replaced range,
+    /// into the corresponding offset in the code after the edit.
+    fn shift(&self, offset: usize) -> usize {
+        debug_assert!(offset >= self.range.end);
+        offset - self.range.len() + self.text.len()
+    }
+}
+
+
+
+// ===============
+// === Reparse ===
+// ===============
+
+/// Update the `tree` of a module to reflect the `edit`. The `code` is the whole source of the
+/// module after the edit has been applied. The result is always equal to `parser.run(code)`.
+pub fn reparse<'s>(parser: &Parser, tree: Tree<'s>, code: &'s str, edit: &Edit) -> Tree<'s> {
+    try_reparse(parser, tree, code, edit).unwrap_or_else(|| parser.run(code))
+}
+
+/// Reparse only the region of the module affected by the edit. Returns [`None`] if the result
+/// could not be guaranteed to be equal to a full reparse.
+fn try_reparse<'s>(
+    parser: &Parser,
+    tree: Tree<'s>,
+    code: &'s str,
+    edit: &Edit,
+) -> Option<Tree<'s>> {
+    // A module starting with whitespace has a root indentation level other than zero; the old root
+    // span also owns the leading whitespace that has been trimmed from its first lines.
+    if !tree.span.left_offset.is_empty() || code.chars().next().map_or(false, is_space) {
+        return None;
+    }
+    let box Variant::BodyBlock(BodyBlock { statements: mut lines }) = tree.variant else {
+        return None;
+    };
+    let positions = line_positions(&lines);
+    let old_len = positions.last().map_or(0, |line| line.end);
+    if edit.range.start > edit.range.end
+        || edit.range.end > old_len
+        || code.len() != old_len - edit.range.len() + edit.text.len()
+    {
+        return None;
+    }
+    let is_anchor_before = |i: &usize| {
+        let line = &positions[*i];
+        line.is_anchor && line.content_start <= edit.range.start
+    };
+    let is_anchor_after = |i: &usize| {
+        let line = &positions[*i];
+        line.is_anchor && line.content_start > edit.range.end
+    };
+    let first = (0..lines.len()).rev().find(is_anchor_before)?;
+    let end = (first + 1..lines.len()).find(is_anchor_after);
+    let region_start = positions[first].content_start;
+    let region_end = end.map_or(code.len(), |end| edit.shift(positions[end].content_start));
+    let region = code.get(region_start..region_end)?;
+    if !region.chars().next().map_or(false, |c| !is_space(c) && !lexer::is_newline_char(c)) {
+        return None;
+    }
+    let old_region_end = end.unwrap_or(lines.len());
+    if region.contains('`') || lines[first..old_region_end].iter().any(has_backtick) {
+        return None;
+    }
+    let mut new_lines = parse_lines(&parser.macros, region)?;
+    let mut tail = match end {
+        Some(end) => {
+            // Unless a trailing documentation comment or annotation absorbed it, the newline
+            // introducing the first reused line has been parsed as an empty line.
+            let newline = match new_lines.pop()? {
+                block::Line { newline, expression: None } => newline,
+                _ => return None,
+            };
+            let mut tail = lines.split_off(end);
+            tail[0].newline = newline;
+            tail
+        }
+        None => default(),
+    };
+    let replaced = lines.split_off(first);
+    new_lines.first_mut()?.newline = replaced.into_iter().next()?.newline;
+    lines.append(&mut new_lines);
+    lines.append(&mut tail);
+    Some(Tree::body_block(lines))
+}
+
+/// Parse the given code as a module, and return its top-level lines. Returns [`None`] if an
+/// internal error occurred.
+fn parse_lines<'s>(
+    macros: &macros::resolver::MacroMap,
+    code: &'s str,
+) -> Option<Vec<block::Line<'s>>> {
+    let resolver = macros::resolver::Resolver::new_statement();
+    let result = lexer::run(code).map(|tokens| resolver.run(macros, tokens));
+    if result.internal_error.is_some() {
+        return None;
+    }
+    match result.value.variant {
+        box Variant::BodyBlock(BodyBlock { statements }) => Some(statements),
+        _ => None,
+    }
+}
+
+fn is_space(c: char) -> bool {
+    lexer::space_char_visible_size(c).is_some()
+}
+
+fn has_backtick(line: &block::Line) -> bool {
+    line.newline.left_offset.code.contains('`')
+        || line.newline.code.contains('`')
+        || line.expression.as_ref().map_or(false, |expression| expression.code().contains('`'))
+}
+
+
+// === Line positions ===
+
+/// The location of a top-level line in the source code.
+#[derive(Clone, Copy, Debug)]
+struct LinePosition {
+    /// Byte offset of the beginning of the line's content, following its newline token.
+    content_start: usize,
+    /// Byte offset of the end of the line.
+    end:           usize,
+    /// Whether the line is an anchor; see the module documentation.
+    is_anchor:     bool,
+}
+
+fn line_positions(lines: &[block::Line]) -> Vec<LinePosition> {
+    let mut offset = 0;
+    let mut positions = Vec::with_capacity(lines.len());
+    for (i, line) in lines.iter().enumerate() {
+        let newline = &line.newline;
+        let content_start = offset + newline.left_offset.code.repr.len() + newline.code.repr.len();
+        let starts_physical_line = i == 0 || newline.code.ends_with(lexer::is_newline_char);
+        let expression = line.expression.as_ref();
+        let is_anchor = starts_physical_line
+            && expression.map_or(false, |expression| expression.span.left_offset.is_empty());
+        let content_len = expression.map_or(0, |expression| {
+            expression.span.left_offset.code.repr.len() + expression.span.code_length.utf8_bytes()
+        });
+        let end = content_start + content_len;
+        positions.push(LinePosition { content_start, end, is_anchor });
+        offset = end;
+    }
+    positions
+}
+
+
+
+// =============
+// === Tests ===
+// =============
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::prelude::*;
+    use rand_chacha::ChaCha8Rng;
+
+    const MODULES: &[&str] = &[
+        "main =\n    x = 1\n    y = x + 2\n    y\n",
+        concat!(
+            "## A type.\ntype Option a\n    Some value\n    None\n\n",
+            "    is_some self = case self of\n        Some _ -> True\n        _ -> False\n",
+        ),
+        concat!(
+            "import Standard.Base\nfrom Standard.Base import all\n\n",
+            "@a foo\nbar x = x\nbaz = 'text `bar` more'\n",
+        ),
+        concat!(
+            "foo x =\n    \"\"\"\n        multiline\n        text\n    x\n",
+            "# Comment.\nquux = [1, 2, 3]\n",
+        ),
+        "a = 1\r\nb = 2\r\n\r\nc = a +\r\n    b\r\n",
+        "",
+    ];
+
+    const INSERTIONS: &[&str] = &[
+        "",
+        "x",
+        " ",
+        "  ",
+        "\n",
+        "\n    ",
+        "\nfoo = 1\n",
+        "=",
+        "foo = bar",
+        "## Doc.\n",
+        "@a ",
+        "\"",
+        "'''",
+        "`",
+        "(",
+        ")",
+        "type T",
+        "case x of",
+        "->",
+        "# comment",
+        "\r\n",
+        "y\n    z",
+    ];
+
+    fn random_boundary(rng: &mut ChaCha8Rng, code: &str) -> usize {
+        loop {
+            let i = rng.gen_range(0..=code.len());
+            if code.is_char_boundary(i) {
+                return i;
+            }
+        }
+    }
+
+    fn random_edit(rng: &mut ChaCha8Rng, code: &str) -> Edit {
+        let a = random_boundary(rng, code);
+        let b = if rng.gen_range(0..3) == 0u32 { random_boundary(rng, code) } else { a };
+        let text = INSERTIONS[rng.gen_range(0..INSERTIONS.len())];
+        Edit::new(a.min(b)..a.max(b), text)
+    }
+
+    #[test]
+    fn reparse_equals_full_parse() {
+        let parser = Parser::new();
+        let mut rng = ChaCha8Rng::seed_from_u64(0);
+        let mut incremental = 0;
+        for module in MODULES {
+            let mut code = module.to_string();
+            for _ in 0..200 {
+                let edit = random_edit(&mut rng, &code);
+                let new_code = edit.apply(&code);
+                {
+                    let expected = parser.run(&new_code);
+                    let tree = parser.run(&code);
+                    if let Some(tree) = try_reparse(&parser, tree.clone(), &new_code, &edit) {
+                        incremental += 1;
+                        assert_eq!(tree, expected, "Edit {edit:?} of code {code:?}.");
+                    }
+                    assert_eq!(parser.reparse(tree, &new_code, &edit), expected);
+                }
+                code = new_code;
+            }
+        }
+        assert!(incremental > 0);
+    }
+
+    #[test]
+    fn reuses_unaffected_lines() {
+        let parser = Parser::new();
+        let code = "a = 1\nb = 2\nc = 3\n";
+        let edit = Edit::new(6..7, "bb");
+        let new_code = edit.apply(code);
+        let tree = parser.run(code);
+        let reparsed = try_reparse(&parser, tree, &new_code, &edit).unwrap();
+        assert_eq!(reparsed, parser.run(&new_code));
+        assert_eq!(reparsed.code(), new_code);
+    }
+
+    #[test]
+    fn arrow_section_followed_by_operator() {
+        // An arrow section without a body, followed by a binary operator, used to crash the parser.
+        let parser = Parser::new();
+        let code = "a = 1\nf T = y\n";
+        let edit = Edit::new(9..9, "->");
+        let new_code = edit.apply(code);
+        assert_eq!(new_code, "a = 1\nf T-> = y\n");
+        let tree = parser.run(code);
+        let expected = parser.run(&new_code);
+        assert_eq!(expected.code(), new_code);
+        assert_eq!(parser.reparse(tree, &new_code, &edit), expected);
+    }
+
+    #[test]
+    fn edits_requiring_full_reparse() {
+        let parser = Parser::new();
+        let cases = [
+            // Indenting a line makes it a child of the previous line.
+            ("a =\nb\n", Edit::new(4..4, "    ")),
+            // A backtick could close a splice opened on a previous line.
+            ("a\nb\n", Edit::new(2..3, "`")),
+        ];
+        for (code, edit) in cases {
+            let new_code = edit.apply(code);
+            let tree = parser.run(code);
+            assert!(try_reparse(&parser, tree.clone(), &new_code, &edit).is_none());
+            assert_eq!(parser.reparse(tree, &new_code, &edit), parser.run(&new_code));
+        }
+    }
+}
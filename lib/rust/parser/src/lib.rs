@@ -8,8 +8,9 @@
 //! # Building macro registry.
 //! Macros in Enso are a very powerful mechanism and are used to transform group of tokens into
 //! almost any statement. First, macros need to be discovered and registered. Currently, there is no
-//! real macro discovery process, as there is no support for user-defined macros. Instead, there is
-//! a set of hardcoded macros defined in the compiler.
+//! real macro discovery process. Instead, there is a set of hardcoded macros defined in the
+//! compiler, which can be extended with macros described declaratively at runtime (see
+//! [`macros::declarative`]).
 //!
 //! Each macro defines one or more segments. Every segment starts with a predefined token and can
 //! contain any number of other tokens. For example, the macro `if ... then ... else ...` contains
@@ -106,14 +107,18 @@
 
 use crate::prelude::*;
 
+use crate::syntax::tree::diagnostic::ErrorCode;
+
 
 // ==============
 // === Export ===
 // ==============
 
+pub mod incremental;
 pub mod lexer;
 pub mod macros;
 pub mod metadata;
+pub mod scope_analysis;
 pub mod serialization;
 pub mod source;
 pub mod syntax;
@@ -181,6 +186,12 @@ impl Parser {
         Self { macros }
     }
 
+    /// Constructor. Use the given macros instead of the built-in ones. To add macros to the
+    /// built-in ones, extend the result of [`macros::built_in::all`].
+    pub fn with_macros(macros: macros::resolver::MacroMap) -> Self {
+        Self { macros }
+    }
+
     /// Main entry point.
     pub fn run<'s>(&self, code: &'s str) -> syntax::Tree<'s> {
         let tokens = lexer::run(code);
@@ -188,10 +199,24 @@ impl Parser {
         let result = tokens.map(|tokens| resolver.run(&self.macros, tokens));
         let value = result.value;
         if let Some(error) = result.internal_error {
-            return value.with_error(format!("Internal error: {error}"));
+            let message = format!("Internal error: {error}");
+            return value.with_error(syntax::tree::Error::new(ErrorCode::Internal, message));
         }
         value
     }
+
+    /// Update a tree previously produced by [`Self::run`] to reflect an edit of its source. The
+    /// `code` is the whole source after the edit has been applied. The result is equal to
+    /// `self.run(code)`, but top-level lines that the edit could not have affected are reused
+    /// instead of being parsed again; see the [`incremental`] module for details.
+    pub fn reparse<'s>(
+        &self,
+        tree: syntax::Tree<'s>,
+        code: &'s str,
+        edit: &incremental::Edit,
+    ) -> syntax::Tree<'s> {
+        incremental::reparse(self, tree, code, edit)
+    }
 }
 
 impl Default for Parser {
@@ -210,16 +235,27 @@ impl Default for Parser {
 fn expression_to_statement(mut tree: syntax::Tree<'_>) -> syntax::Tree<'_> {
     use syntax::tree::*;
     let mut left_offset = source::span::Offset::default();
-    if let Tree { variant: box Variant::Annotated(annotated), .. } = &mut tree {
-        annotated.expression = annotated.expression.take().map(expression_to_statement);
+    // The span of a multi-line statement is rebuilt, as reinterpreting its expression can change
+    // the expression's span.
+    if let Tree { variant: box Variant::Annotated(annotated), span } = tree {
+        let Annotated { token, annotation, argument, newlines, expression } = annotated;
+        let expression = expression.map(expression_to_statement);
+        let mut tree = Tree::annotated(token, annotation, argument, newlines, expression);
+        tree.span.left_offset += span.left_offset;
         return tree;
     }
-    if let Tree { variant: box Variant::AnnotatedBuiltin(annotated), .. } = &mut tree {
-        annotated.expression = annotated.expression.take().map(expression_to_statement);
+    if let Tree { variant: box Variant::AnnotatedBuiltin(annotated), span } = tree {
+        let AnnotatedBuiltin { token, annotation, newlines, expression } = annotated;
+        let expression = expression.map(expression_to_statement);
+        let mut tree = Tree::annotated_builtin(token, annotation, newlines, expression);
+        tree.span.left_offset += span.left_offset;
         return tree;
     }
-    if let Tree { variant: box Variant::Documented(documented), .. } = &mut tree {
-        documented.expression = documented.expression.take().map(expression_to_statement);
+    if let Tree { variant: box Variant::Documented(documented), span } = tree {
+        let Documented { documentation, expression } = documented;
+        let expression = expression.map(expression_to_statement);
+        let mut tree = Tree::documented(documentation, expression);
+        tree.span.left_offset += span.left_offset;
         return tree;
     }
     if let Tree { variant: box Variant::TypeAnnotated(annotated), span } = tree {
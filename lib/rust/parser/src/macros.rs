@@ -15,6 +15,7 @@ use enso_data_structures::im_list;
 // ==============
 
 pub mod built_in;
+pub mod declarative;
 pub mod expand;
 pub mod pattern;
 pub mod resolver;
@@ -58,13 +59,14 @@ pub type DefinitionBody = dyn for<'s> Fn(pattern::MatchedSegments<'s>) -> syntax
 #[derive(Clone, Debug)]
 #[allow(missing_docs)]
 pub struct SegmentDefinition<'a> {
-    pub header:  &'a str,
+    pub header:  Cow<'a, str>,
     pub pattern: Pattern,
 }
 
 impl<'a> SegmentDefinition<'a> {
     /// Constructor.
-    pub fn new(header: &'a str, pattern: Pattern) -> Self {
+    pub fn new(header: impl Into<Cow<'a, str>>, pattern: Pattern) -> Self {
+        let header = header.into();
         Self { header, pattern }
     }
 }
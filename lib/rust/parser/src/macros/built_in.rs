@@ -4,6 +4,7 @@ use crate::macros::pattern::*;
 use crate::macros::*;
 
 use crate::syntax::operator;
+use crate::syntax::tree::diagnostic::ErrorCode;
 
 
 
@@ -122,7 +123,7 @@ fn import_body(segments: NonEmptyVec<MatchedSegment>) -> syntax::Tree {
     }
     let import = syntax::Tree::import(polyglot, from, import.unwrap(), all, as_, hiding);
     if incomplete_import {
-        return import.with_error("Expected name or `all` keyword following `import` keyword.");
+        return import.with_error(ErrorCode::ExpectedImportTarget);
     }
     import
 }
@@ -201,7 +202,7 @@ fn export_body(segments: NonEmptyVec<MatchedSegment>) -> syntax::Tree {
     }
     let export = syntax::Tree::export(from, export.unwrap(), all, as_, hiding);
     if incomplete_export {
-        return export.with_error("Expected name or `all` keyword following `export` keyword.");
+        return export.with_error(ErrorCode::ExpectedExportTarget);
     }
     export
 }
@@ -279,13 +280,35 @@ fn type_def_body(matched_segments: NonEmptyVec<MatchedSegment>) -> syntax::Tree
             code,
             variant: syntax::token::Variant::Ident(ident),
         })) => syntax::Token(left_offset, code, ident),
-        _ => return Tree::ident(header).with_error("Expected identifier after `type` keyword."),
+        first => {
+            // Report the error on the header line only; the body is parsed as usual, so that its
+            // definitions remain available.
+            let header = Tree::ident(header);
+            let rest =
+                operator::resolve_operator_precedence_if_non_empty(first.into_iter().chain(tokens));
+            let header = match rest {
+                Some(rest) => Tree::app(header, rest),
+                None => header,
+            };
+            let header = header.with_error(ErrorCode::ExpectedTypeName);
+            return match block.is_empty() {
+                true => header,
+                false => Tree::argument_block_application(Some(header), type_def_lines(block)),
+            };
+        }
     };
     let mut precedence = operator::Precedence::new();
     let params = precedence
         .resolve_non_section(tokens)
         .map(crate::collect_arguments_inclusive)
         .unwrap_or_default();
+    let body = type_def_lines(block);
+    Tree::type_def(header, name, params, body)
+}
+
+/// Parse the lines of the body of a type definition.
+fn type_def_lines(mut block: Vec<syntax::item::Line>) -> Vec<syntax::tree::block::Line> {
+    use syntax::tree::*;
     for line in &mut block {
         if let Some(syntax::Item::Token(syntax::Token { variant, .. })) = line.items.first_mut()
             && let syntax::token::Variant::Operator(operator) = variant
@@ -295,14 +318,14 @@ fn type_def_body(matched_segments: NonEmptyVec<MatchedSegment>) -> syntax::Tree
             *variant = syntax::token::Variant::Ident(opr_ident);
         }
     }
+    let mut precedence = operator::Precedence::new();
     let parse_line = |syntax::item::Line { newline, items }| block::Line {
         newline,
         expression: precedence.resolve(items),
     };
-    let body = block::compound_lines(block.into_iter().map(parse_line))
+    block::compound_lines(block.into_iter().map(parse_line))
         .map(|line| line.map_expression(to_body_statement))
-        .collect();
-    Tree::type_def(header, name, params, body)
+        .collect()
 }
 
 fn to_body_statement(mut line_expression: syntax::Tree<'_>) -> syntax::Tree<'_> {
@@ -421,7 +444,7 @@ fn case_body(segments: NonEmptyVec<MatchedSegment>) -> syntax::Tree {
     let (case_lines, any_invalid) = case_builder.finish();
     let tree = Tree::case_of(case_, expression, of_, case_lines);
     if any_invalid {
-        return tree.with_error("Invalid case expression.");
+        return tree.with_error(ErrorCode::InvalidCaseExpression);
     }
     tree
 }
@@ -513,8 +536,18 @@ impl<'s> CaseBuilder<'s> {
         if pattern.is_none() && arrow.is_none() && expression.is_none() {
             return;
         }
-        self.any_invalid =
-            self.any_invalid || pattern.is_none() || arrow.is_none() || expression.is_none();
+        let (mut pattern, mut expression) = (pattern, expression);
+        if pattern.is_none() || arrow.is_none() || expression.is_none() {
+            // Mark only this case as invalid, so that the other cases are not affected.
+            let error = ErrorCode::InvalidCaseExpression;
+            if let Some(tree) = expression.take() {
+                expression = Some(tree.with_error(error));
+            } else if let Some(tree) = pattern.take() {
+                pattern = Some(tree.with_error(error));
+            } else {
+                self.any_invalid = true;
+            }
+        }
         if self.case_lines.is_empty() {
             self.case_lines.push(default());
         }
@@ -614,7 +647,7 @@ fn sequence_tree<'s>(
         tree = Tree::opr_app(tree, Ok(operator), body.map(&mut f)).into();
     }
     if invalid {
-        tree = tree.map(|tree| tree.with_error("Malformed comma-delimited sequence."));
+        tree = tree.map(|tree| tree.with_error(ErrorCode::MalformedSequence));
     }
     tree
 }
@@ -675,17 +708,17 @@ fn foreign_body(segments: NonEmptyVec<MatchedSegment>) -> syntax::Tree {
 fn try_foreign_body<'s>(
     keyword: syntax::token::Ident<'s>,
     tokens: impl IntoIterator<Item = syntax::Item<'s>>,
-) -> Result<syntax::Tree, &'static str> {
+) -> Result<syntax::Tree, ErrorCode> {
     let mut tokens = tokens.into_iter();
     let language = tokens
         .next()
         .and_then(try_into_token)
         .and_then(try_token_into_ident)
-        .ok_or("Expected an identifier specifying foreign method's language.")?;
-    let expected_name = "Expected an identifier specifying foreign function's name.";
+        .ok_or(ErrorCode::ExpectedForeignLanguage)?;
+    let expected_name = ErrorCode::ExpectedForeignName;
     let function =
         operator::resolve_operator_precedence_if_non_empty(tokens).ok_or(expected_name)?;
-    let expected_function = "Expected a function definition after foreign declaration.";
+    let expected_function = ErrorCode::ExpectedForeignFunction;
     let box syntax::tree::Variant::OprApp(
             syntax::tree::OprApp { lhs: Some(lhs), opr: Ok(equals), rhs: Some(body) }) = function.variant else {
         return Err(expected_function)
@@ -694,7 +727,7 @@ fn try_foreign_body<'s>(
         return Err(expected_function);
     };
     if !matches!(body.variant, box syntax::tree::Variant::TextLiteral(_)) {
-        return Err("Expected a text literal as body of `foreign` declaration.");
+        return Err(ErrorCode::ExpectedForeignBody);
     }
     let (name, args) = crate::collect_arguments(lhs);
     let mut name = try_tree_into_ident(name).ok_or(expected_name)?;
@@ -750,7 +783,7 @@ fn expect_ident(tree: syntax::Tree) -> syntax::Tree {
     if matches!(&*tree.variant, syntax::tree::Variant::Ident(_)) {
         tree
     } else {
-        tree.with_error("Expected identifier.")
+        tree.with_error(ErrorCode::ExpectedIdentifier)
     }
 }
 
@@ -758,11 +791,11 @@ fn expect_qualified(tree: syntax::Tree) -> syntax::Tree {
     if crate::is_qualified_name(&tree) {
         tree
     } else {
-        tree.with_error("Expected qualified name.")
+        tree.with_error(ErrorCode::ExpectedQualifiedName)
     }
 }
 
 fn expected_nonempty<'s>() -> syntax::Tree<'s> {
     let empty = syntax::Tree::ident(syntax::token::ident("", "", false, 0, false, false, false));
-    empty.with_error("Expected tokens.")
+    empty.with_error(ErrorCode::ExpectedTokens)
 }
@@ -0,0 +1,241 @@
+//! Macros defined at runtime from a declarative [`Description`], rather than in Rust code.
+//!
+//! A description lists the segments of the macro, each with its header and [`Pattern`], and a
+//! [`Template`] describing the tree the macro expands to. Descriptions are registered in a
+//! [`MacroMap`], which can be passed to [`crate::Parser::with_macros`]:
+//!
+//! ```
+//! use enso_parser::macros::declarative::Description;
+//! use enso_parser::macros::pattern;
+//! use enso_parser::macros::resolver::Context;
+//!
+//! let mut macros = enso_parser::macros::built_in::all();
+//! Description::new(Context::Expression)
+//!     .segment("with", pattern::everything())
+//!     .segment("do", pattern::everything())
+//!     .register(&mut macros)
+//!     .unwrap();
+//! let parser = enso_parser::Parser::with_macros(macros);
+//! let tree = parser.run("with file do read");
+//! ```
+
+use crate::prelude::*;
+
+use crate::lexer;
+use crate::macros;
+use crate::macros::pattern;
+use crate::macros::resolver::Context;
+use crate::macros::resolver::MacroMap;
+use crate::macros::Pattern;
+use crate::syntax;
+
+use enso_data_structures::im_list;
+
+
+
+// ===================
+// === Description ===
+// ===================
+
+/// Declarative description of a macro.
+#[derive(Clone, Debug)]
+#[allow(missing_docs)]
+pub struct Description {
+    pub context:  Context,
+    pub segments: Vec<SegmentDescription>,
+    pub template: Template,
+}
+
+/// Declarative description of a macro segment.
+#[derive(Clone, Debug)]
+#[allow(missing_docs)]
+pub struct SegmentDescription {
+    pub header:  String,
+    pub pattern: Pattern,
+}
+
+/// The tree a macro expands to. Every token of the macro invocation is kept in the resulting tree.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Template {
+    /// A [`MultiSegmentApp`], with the tokens matched by each segment's pattern resolved to an
+    /// expression.
+    ///
+    /// [`MultiSegmentApp`]: syntax::tree::MultiSegmentApp
+    #[default]
+    MultiSegmentApp,
+    /// All the tokens of the invocation, including the segment headers, resolved to an expression.
+    /// The macro delimits the expression and reserves its headers, but does not otherwise change
+    /// how its tokens are parsed.
+    Expression,
+}
+
+impl Description {
+    /// Constructor. The macro has no segments, and uses the default [`Template`].
+    pub fn new(context: Context) -> Self {
+        let segments = default();
+        let template = default();
+        Self { context, segments, template }
+    }
+
+    /// Add a segment.
+    pub fn segment(mut self, header: impl Into<String>, pattern: Pattern) -> Self {
+        let header = header.into();
+        self.segments.push(SegmentDescription { header, pattern });
+        self
+    }
+
+    /// Set the template.
+    pub fn template(mut self, template: Template) -> Self {
+        self.template = template;
+        self
+    }
+
+    /// Create the macro [`Definition`](macros::Definition) described.
+    pub fn to_definition(&self) -> Result<macros::Definition<'static>, Error> {
+        let mut segments = Vec::with_capacity(self.segments.len());
+        for segment in &self.segments {
+            validate_header(&segment.header)?;
+            let header = segment.header.clone();
+            segments.push(macros::SegmentDefinition::new(header, segment.pattern.clone()));
+        }
+        let segments = im_list::NonEmpty::try_from(segments).map_err(|_| Error::NoSegments)?;
+        let body: Rc<macros::DefinitionBody> = match self.template {
+            Template::MultiSegmentApp => Rc::new(macros::matched_segments_into_multi_segment_app),
+            Template::Expression => Rc::new(expression_body),
+        };
+        Ok(macros::Definition { segments, body })
+    }
+
+    /// Register the macro described in the given [`MacroMap`].
+    pub fn register(&self, macros: &mut MacroMap) -> Result<(), Error> {
+        macros.register(self.context, self.to_definition()?);
+        Ok(())
+    }
+}
+
+/// Check that the header would be lexed as a single token that can start a macro segment.
+fn validate_header(header: &str) -> Result<(), Error> {
+    let tokens = lexer::run(header).value;
+    match &tokens[..] {
+        [token] if &**token.code == header && token.variant.can_start_macro_segment() => Ok(()),
+        _ => Err(Error::InvalidHeader(header.to_owned())),
+    }
+}
+
+fn expression_body(segments: pattern::MatchedSegments) -> syntax::Tree {
+    let mut items = vec![];
+    for segment in segments {
+        items.push(syntax::Item::Token(segment.header));
+        segment.result.get_tokens(&mut items);
+    }
+    syntax::operator::resolve_operator_precedence(NonEmptyVec::try_from(items).unwrap())
+}
+
+
+
+// =============
+// === Error ===
+// =============
+
+/// A reason a [`Description`] does not describe a valid macro.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Error {
+    /// A macro must have at least one segment.
+    NoSegments,
+    /// A segment header must be a single token that can start a macro segment.
+    InvalidHeader(String),
+}
+
+impl Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::NoSegments => write!(f, "A macro must have at least one segment."),
+            Error::InvalidHeader(header) => write!(
+                f,
+                "Invalid macro segment header: {header:?}. A header must be a single token."
+            ),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+
+
+// =============
+// === Tests ===
+// =============
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::syntax::tree;
+
+    fn with_do(template: Template) -> crate::Parser {
+        let mut macros = macros::built_in::all();
+        Description::new(Context::Expression)
+            .segment("with", pattern::everything())
+            .segment("do", pattern::everything())
+            .template(template)
+            .register(&mut macros)
+            .unwrap();
+        crate::Parser::with_macros(macros)
+    }
+
+    fn expression<'s>(parser: &crate::Parser, code: &'s str) -> syntax::Tree<'s> {
+        let tree = parser.run(code);
+        assert_eq!(tree.code(), code);
+        match *tree.variant {
+            tree::Variant::BodyBlock(tree::BodyBlock { mut statements }) =>
+                statements.remove(0).expression.unwrap(),
+            _ => unreachable!(),
+        }
+    }
+
+    #[test]
+    fn multi_segment_app() {
+        let parser = with_do(Template::MultiSegmentApp);
+        let tree = expression(&parser, "with open file do read it");
+        let tree::Variant::MultiSegmentApp(app) = *tree.variant else { unreachable!() };
+        let headers: Vec<_> = app.segments.iter().map(|s| s.header.code.to_string()).collect();
+        assert_eq!(headers, ["with", "do"]);
+        let bodies: Vec<_> = app.segments.iter().map(|s| s.body.as_ref().unwrap().code()).collect();
+        assert_eq!(bodies, [" open file", " read it"]);
+    }
+
+    #[test]
+    fn expression_template() {
+        let parser = with_do(Template::Expression);
+        let tree = expression(&parser, "with file do read");
+        assert!(matches!(*tree.variant, tree::Variant::App(_)), "{tree:?}");
+        assert!(tree.diagnostics().is_empty());
+    }
+
+    #[test]
+    fn nesting() {
+        let parser = with_do(Template::MultiSegmentApp);
+        let tree = expression(&parser, "with (with a do b) do c");
+        assert!(matches!(*tree.variant, tree::Variant::MultiSegmentApp(_)), "{tree:?}");
+        assert!(tree.diagnostics().is_empty());
+    }
+
+    #[test]
+    fn not_registered() {
+        let tree = expression(&crate::Parser::new(), "with open file do read it");
+        assert!(matches!(*tree.variant, tree::Variant::App(_)), "{tree:?}");
+    }
+
+    #[test]
+    fn invalid_descriptions() {
+        let mut macros = macros::built_in::all();
+        let empty = Description::new(Context::Statement);
+        assert_eq!(empty.register(&mut macros), Err(Error::NoSegments));
+        for header in ["", "with do", "\"text\""] {
+            let description =
+                Description::new(Context::Statement).segment(header, pattern::nothing());
+            let expected = Err(Error::InvalidHeader(header.to_owned()));
+            assert_eq!(description.register(&mut macros), expected);
+        }
+    }
+}
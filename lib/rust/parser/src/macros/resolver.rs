@@ -21,6 +21,26 @@
 //! This is implemented by starting a new macro resolution [`Scope`] at the beginning of every
 //! block; the new scope is initialized with only the root macro. Within a scope the state of all
 //! macros defined in parent scopes will never be advanced.
+//!
+//! # Error recovery
+//!
+//! Errors are reported by wrapping a subtree in an [`Invalid`] node; the code around it is parsed
+//! as usual, so that independent errors are reported separately:
+//! - Macro resolution is limited to a line (including any child block of the line), so a malformed
+//!   line does not affect its siblings.
+//! - A token expected by an enclosing macro (such as the `)` closing a group) ends any nested macro
+//!   invocations, so an error within a group does not extend past it.
+//! - When a macro's first segment is found but its invocation cannot be completed, only the first
+//!   segment's header is marked invalid; the following tokens are resolved as if the header were
+//!   absent.
+//! - Macros that parse a block, such as `type` and `case`, mark only the invalid line of the block
+//!   or the invalid header, so that the rest of the block is usable.
+//!
+//! Similarly, when an assignment is followed by another operator, the operator precedence resolver
+//! marks only the assigned expression invalid (see [`syntax::operator`]), so that the statement is
+//! still recognized as an assignment.
+//!
+//! [`Invalid`]: syntax::tree::Invalid
 
 use crate::prelude::*;
 
@@ -29,6 +49,7 @@ use crate::macros::pattern;
 use crate::syntax;
 use crate::syntax::token;
 use crate::syntax::token::Token;
+use crate::syntax::tree::diagnostic::ErrorCode;
 
 use enso_data_structures::im_list;
 use enso_data_structures::im_list::List;
@@ -41,7 +62,7 @@ use std::collections::VecDeque;
 // ================
 
 /// Represents the sets of macros defined in different contexts.
-#[derive(Default, Debug)]
+#[derive(Clone, Default, Debug)]
 pub struct MacroMap {
     /// Macros that can occur anywhere in an expression.
     pub expression: SegmentMap<'static>,
@@ -56,11 +77,22 @@ impl MacroMap {
         let expression_result = || self.expression.get(key);
         (context == Context::Statement).then(statement_result).flatten().or_else(expression_result)
     }
+
+    /// Register a new macro definition, matching in the given context.
+    pub fn register(&mut self, context: Context, definition: macros::Definition<'static>) {
+        match context {
+            Context::Expression => self.expression.register(definition),
+            Context::Statement => self.statement.register(definition),
+        }
+    }
 }
 
+/// The context in which a macro can occur.
 #[derive(Debug, PartialEq, Eq, Copy, Clone)]
-enum Context {
+pub enum Context {
+    /// Anywhere in an expression.
     Expression,
+    /// Only at the start of a statement.
     Statement,
 }
 
@@ -77,9 +109,9 @@ enum Context {
 /// after matching the "if" keyword, this struct will contain one entry "then" with two values, one
 /// for the required "else" section, and one without a required section (for the "if ... then ..."
 /// case).
-#[derive(Default, Debug, Deref, DerefMut)]
+#[derive(Clone, Default, Debug, Deref, DerefMut)]
 pub struct SegmentMap<'s> {
-    map: HashMap<&'s str, NonEmptyVec<SegmentEntry<'s>>>,
+    map: HashMap<Cow<'s, str>, NonEmptyVec<SegmentEntry<'s>>>,
 }
 
 /// Partially matched macro info. See docs of [`SegmentMap`] to learn more.
@@ -98,12 +130,12 @@ pub struct SegmentEntry<'s> {
 impl<'a> SegmentMap<'a> {
     /// Register a new macro definition in this macro tree.
     pub fn register(&mut self, definition: macros::Definition<'a>) {
-        let header = definition.segments.head.header;
+        let header = definition.segments.head.header.clone();
         let entry = SegmentEntry {
             required_segments: definition.segments.tail.clone(),
             definition:        Rc::new(definition),
         };
-        if let Some(node) = self.get_mut(header) {
+        if let Some(node) = self.get_mut(&header) {
             node.push(entry);
         } else {
             self.insert(header, NonEmptyVec::singleton(entry));
@@ -141,7 +173,7 @@ impl<'a> PartiallyMatchedMacro<'a> {
         let possible_next_segments = default();
         let matched_macro_def = Some(Rc::new(macros::Definition {
             segments: im_list::NonEmpty::singleton(macros::SegmentDefinition {
-                header:  "__ROOT__",
+                header:  "__ROOT__".into(),
                 pattern: pattern::everything(),
             }),
             body:     Rc::new(|v| {
@@ -478,7 +510,7 @@ impl<'s> Resolver<'s> {
                     if let Some(excess) =
                         syntax::operator::resolve_operator_precedence_if_non_empty(excess)
                     {
-                        let excess = excess.with_error("Unexpected tokens in macro invocation.");
+                        let excess = excess.with_error(ErrorCode::UnexpectedMacroTokens);
                         tokens.push(excess.into());
                     }
                     let body = syntax::operator::resolve_operator_precedence_if_non_empty(tokens);
@@ -499,7 +531,8 @@ impl<'s> Resolver<'s> {
                 items.push_back(syntax::Item::Token(header));
                 items.append(&mut segment);
             }
-            let header0 = syntax::tree::to_ast(header0).with_error("Invalid macro invocation.");
+            let header0 =
+                syntax::tree::to_ast(header0).with_error(ErrorCode::InvalidMacroInvocation);
             (header0, items)
         }
     }
@@ -521,7 +554,7 @@ impl<'s> Resolver<'s> {
                 if let Some(node) = new_section_tree.get_mut(&first.header) {
                     node.push(entry);
                 } else {
-                    new_section_tree.insert(first.header, NonEmptyVec::singleton(entry));
+                    new_section_tree.insert(first.header.clone(), NonEmptyVec::singleton(entry));
                 }
             } else {
                 *matched_macro_def = Some(segment_entry.definition.clone_ref());
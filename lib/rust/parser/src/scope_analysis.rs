@@ -0,0 +1,733 @@
+//! Scope analysis of the parsed code — resolves each identifier usage in a [`Tree`] to the binding
+//! it refers to.
+//!
+//! The analysis works on the whole tree and follows the Enso scoping rules:
+//! - the definitions in modules and type bodies are visible in the whole body, regardless of their
+//!   order;
+//! - the local variables in blocks are visible only in the lines following the assignment;
+//! - the function arguments, lambda arguments and variables bound by the case patterns are visible
+//!   only in the body of the function, lambda or case branch, respectively;
+//! - a binding shadows any binding of the same name from the enclosing scopes.
+
+use crate::prelude::*;
+
+use crate::source::Code;
+use crate::syntax::item;
+use crate::syntax::tree;
+use crate::syntax::tree::ItemVisitable;
+use crate::syntax::tree::ItemVisitor;
+use crate::syntax::tree::Visitor;
+use crate::syntax::Tree;
+
+use std::ptr;
+
+
+
+// ==================
+// === ScopeTable ===
+// ==================
+
+/// Index of a scope in [`ScopeTable::scopes`].
+pub type ScopeId = usize;
+
+/// Index of a binding in [`ScopeTable::bindings`].
+pub type BindingId = usize;
+
+/// Byte range in the analyzed code. The offsets are counted from the beginning of the root tree,
+/// including its left offset.
+pub type CodeRange = Range<usize>;
+
+/// The syntactic construct introducing a scope.
+#[allow(missing_docs)]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum ScopeKind {
+    Module,
+    Type,
+    Function,
+    Lambda,
+    CaseBranch,
+    Block,
+}
+
+/// The syntactic construct introducing a binding.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum BindingKind {
+    /// A method defined in a module or type body, including the foreign ones.
+    Method,
+    /// A type definition.
+    Type,
+    /// A type's constructor.
+    Constructor,
+    /// An argument of a function, lambda or type constructor, or a type parameter.
+    Argument,
+    /// A local variable introduced by an assignment or a local function definition.
+    Variable,
+    /// A variable introduced by a case branch pattern.
+    PatternVariable,
+}
+
+/// A lexical scope.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Scope {
+    #[allow(missing_docs)]
+    pub kind:   ScopeKind,
+    /// The enclosing scope. Only the module scope has none.
+    pub parent: Option<ScopeId>,
+    /// The code covered by the construct introducing the scope.
+    pub range:  CodeRange,
+}
+
+/// A place where a name is introduced.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Binding {
+    #[allow(missing_docs)]
+    pub name:    String,
+    #[allow(missing_docs)]
+    pub kind:    BindingKind,
+    /// The range of the bound identifier.
+    pub range:   CodeRange,
+    /// The scope the name is introduced into.
+    pub scope:   ScopeId,
+    /// The binding of the same name which this one shadows: from an enclosing scope, or bound
+    /// earlier in the same scope.
+    pub shadows: Option<BindingId>,
+}
+
+/// A usage of a name.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Reference {
+    #[allow(missing_docs)]
+    pub name:    String,
+    /// The range of the identifier.
+    pub range:   CodeRange,
+    /// The innermost scope containing the usage.
+    pub scope:   ScopeId,
+    /// The binding the name refers to, or [`None`] if the name is not bound in the analyzed code
+    /// (e.g. it is imported from other module).
+    pub binding: Option<BindingId>,
+}
+
+/// The result of scope analysis: all scopes, binding sites and references in the code. The
+/// elements of each table are ordered by their position in the code, except the module-level and
+/// type-level definitions, which are bound before anything else in their scope.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct ScopeTable {
+    #[allow(missing_docs)]
+    pub scopes:     Vec<Scope>,
+    #[allow(missing_docs)]
+    pub bindings:   Vec<Binding>,
+    #[allow(missing_docs)]
+    pub references: Vec<Reference>,
+}
+
+impl ScopeTable {
+    /// All usages of the given binding.
+    pub fn references_to(&self, binding: BindingId) -> impl Iterator<Item = &Reference> {
+        self.references.iter().filter(move |reference| reference.binding == Some(binding))
+    }
+
+    /// Usages of names not bound in the analyzed code.
+    pub fn free_references(&self) -> impl Iterator<Item = &Reference> {
+        self.references.iter().filter(|reference| reference.binding.is_none())
+    }
+
+    /// Bindings which are never used.
+    pub fn unused_bindings(&self) -> impl Iterator<Item = BindingId> + '_ {
+        let used = self.references.iter().filter_map(|r| r.binding).collect::<HashSet<_>>();
+        (0..self.bindings.len()).filter(move |id| !used.contains(id))
+    }
+
+    /// Check if `scope` is `ancestor` or is nested in it.
+    pub fn is_within(&self, scope: ScopeId, ancestor: ScopeId) -> bool {
+        iter::successors(Some(scope), |id| self.scopes[*id].parent).contains(&ancestor)
+    }
+
+    /// Bindings from the enclosing function-like scopes which are captured by the code in the given
+    /// scope, e.g. the variables a lambda closes over. The module and type level definitions are
+    /// not considered captured.
+    pub fn captured_bindings(&self, scope: ScopeId) -> Vec<BindingId> {
+        let references = self.references.iter().filter(|r| self.is_within(r.scope, scope));
+        let bindings = references.filter_map(|r| r.binding).filter(|binding| {
+            let binding_scope = self.bindings[*binding].scope;
+            let is_definition =
+                matches!(self.scopes[binding_scope].kind, ScopeKind::Module | ScopeKind::Type);
+            !is_definition && !self.is_within(binding_scope, scope)
+        });
+        bindings.sorted().dedup().collect()
+    }
+
+    /// The binding at the given code offset: either the binding site at this offset or the binding
+    /// of the name usage at this offset.
+    pub fn binding_at(&self, offset: usize) -> Option<BindingId> {
+        let contains = |range: &CodeRange| range.start <= offset && offset < range.end;
+        let binding_site = self.bindings.iter().position(|binding| contains(&binding.range));
+        let usage = || self.references.iter().find(|r| contains(&r.range))?.binding;
+        binding_site.or_else(usage)
+    }
+}
+
+
+
+// ================
+// === Analysis ===
+// ================
+
+/// Run the scope analysis on a parsed module (or any other tree, which is then treated as the
+/// module's body).
+pub fn analyze(tree: &Tree) -> ScopeTable {
+    let mut analyzer = Analyzer::default();
+    let root = Located::root(tree);
+    analyzer.with_scope(ScopeKind::Module, root.range(), |this| match &*tree.variant {
+        tree::Variant::BodyBlock(block) => this.analyze_definitions(root, &block.statements),
+        _ => {
+            this.declare_definition(root);
+            this.analyze_statement(root, true);
+        }
+    });
+    analyzer.table
+}
+
+
+// === Located Trees ===
+
+/// A tree with the absolute offset of its code. The tree spans store only lengths, so the offsets
+/// are computed during the traversal: the offset of a child is found by adding up the lengths of
+/// the items preceding it in its parent.
+#[derive(Clone, Copy, Debug)]
+struct Located<'s, 'a> {
+    tree:  &'a Tree<'s>,
+    start: usize,
+}
+
+impl<'s, 'a> Located<'s, 'a> {
+    fn root(tree: &'a Tree<'s>) -> Self {
+        Self { tree, start: tree.span.left_offset.code.repr.len() }
+    }
+
+    fn range(&self) -> CodeRange {
+        self.start..self.start + self.tree.span.code_length.utf8_bytes()
+    }
+
+    /// The trees and tokens directly contained in the tree, with their offsets.
+    fn items(&self) -> Vec<(item::Ref<'s, 'a>, usize)> {
+        let mut visitor = Items { items: default(), position: self.start };
+        self.tree.variant.visit_item(&mut visitor);
+        visitor.items
+    }
+
+    /// The trees directly contained in the tree.
+    fn children(&self) -> impl Iterator<Item = Located<'s, 'a>> {
+        self.items().into_iter().filter_map(|(item, start)| match item {
+            item::Ref::Tree(tree) => Some(Located { tree, start }),
+            item::Ref::Token(_) => None,
+        })
+    }
+
+    /// Locate a tree directly contained in this one.
+    ///
+    /// # Panics
+    /// If `child` is not a child of this tree.
+    fn child(&self, child: &Tree<'s>) -> Located<'s, 'a> {
+        let located = self.children().find(|located| ptr::eq(located.tree, child));
+        located.expect("The tree to locate is not a child of the parent tree.")
+    }
+
+    /// The range of a token directly contained in the tree.
+    ///
+    /// # Panics
+    /// If `code` is not the code of a token in this tree.
+    fn token_range(&self, code: &Code<'s>) -> CodeRange {
+        let start = self.items().into_iter().find_map(|(item, start)| match item {
+            item::Ref::Token(token) => ptr::eq(token.code, code).then_some(start),
+            item::Ref::Tree(_) => None,
+        });
+        let start = start.expect("The token to locate is not a child of the parent tree.");
+        start..start + code.repr.len()
+    }
+}
+
+/// Collects the trees and tokens directly contained in a tree node, computing their offsets.
+#[derive(Debug)]
+struct Items<'s, 'a> {
+    items:    Vec<(item::Ref<'s, 'a>, usize)>,
+    position: usize,
+}
+
+impl<'s, 'a> Visitor for Items<'s, 'a> {}
+
+impl<'s, 'a> ItemVisitor<'s, 'a> for Items<'s, 'a> {
+    fn visit_item(&mut self, item: item::Ref<'s, 'a>) -> bool {
+        let (left_offset, length) = match item {
+            item::Ref::Token(token) => (token.left_offset.code.repr.len(), token.code.repr.len()),
+            item::Ref::Tree(tree) =>
+                (tree.span.left_offset.code.repr.len(), tree.span.code_length.utf8_bytes()),
+        };
+        self.position += left_offset;
+        self.items.push((item, self.position));
+        self.position += length;
+        false
+    }
+}
+
+/// The definition wrapped by the documentation comment or annotations, if any.
+fn documented_item<'s, 'a>(tree: Located<'s, 'a>) -> Option<Located<'s, 'a>> {
+    let expression = match &*tree.tree.variant {
+        tree::Variant::Documented(documented) => &documented.expression,
+        tree::Variant::Annotated(annotated) => &annotated.expression,
+        tree::Variant::AnnotatedBuiltin(annotated) => &annotated.expression,
+        _ => return Some(tree),
+    };
+    expression.as_ref().and_then(|expression| documented_item(tree.child(expression)))
+}
+
+
+// === Analyzer ===
+
+/// A scope on the analyzer's stack.
+#[derive(Debug)]
+struct ActiveScope {
+    id:    ScopeId,
+    names: HashMap<String, BindingId>,
+}
+
+/// Traverses the tree, keeping the stack of the scopes and filling the [`ScopeTable`].
+#[derive(Debug, Default)]
+struct Analyzer {
+    scopes: Vec<ActiveScope>,
+    table:  ScopeTable,
+}
+
+impl Analyzer {
+    // === Scopes and Names ===
+
+    fn with_scope(&mut self, kind: ScopeKind, range: CodeRange, f: impl FnOnce(&mut Self)) {
+        let id = self.table.scopes.len();
+        let parent = self.scopes.last().map(|scope| scope.id);
+        self.table.scopes.push(Scope { kind, parent, range });
+        self.scopes.push(ActiveScope { id, names: default() });
+        f(self);
+        self.scopes.pop();
+    }
+
+    fn current_scope(&self) -> ScopeId {
+        self.scopes.last().map_or(0, |scope| scope.id)
+    }
+
+    fn resolve(&self, name: &str) -> Option<BindingId> {
+        self.scopes.iter().rev().find_map(|scope| scope.names.get(name).copied())
+    }
+
+    fn bind(&mut self, name: &str, kind: BindingKind, range: CodeRange) {
+        let id = self.table.bindings.len();
+        let scope = self.current_scope();
+        let shadows = self.resolve(name);
+        let name = name.to_owned();
+        self.table.bindings.push(Binding { name: name.clone(), kind, range, scope, shadows });
+        if let Some(active) = self.scopes.last_mut() {
+            active.names.insert(name, id);
+        }
+    }
+
+    fn bind_identifier(&mut self, tree: Located, kind: BindingKind) {
+        if let tree::Variant::Ident(ident) = &*tree.tree.variant {
+            self.bind(&ident.token.code.repr, kind, tree.range());
+        }
+    }
+
+    /// Bind the name of a token directly contained in the `parent` tree.
+    fn bind_token(&mut self, parent: Located, code: &Code, kind: BindingKind) {
+        self.bind(&code.repr, kind, parent.token_range(code));
+    }
+
+    fn refer(&mut self, name: &str, range: CodeRange) {
+        let binding = self.resolve(name);
+        let scope = self.current_scope();
+        self.table.references.push(Reference { name: name.to_owned(), range, scope, binding });
+    }
+
+    // === Definitions ===
+
+    /// Analyze the lines of a module or type body: first bind all the definitions, then analyze
+    /// them.
+    fn analyze_definitions(&mut self, parent: Located, lines: &[tree::block::Line]) {
+        let statements = lines.iter().filter_map(|line| line.expression.as_ref());
+        let statements = statements.map(|statement| parent.child(statement)).collect_vec();
+        for statement in &statements {
+            self.declare_definition(*statement);
+        }
+        for statement in statements {
+            self.analyze_statement(statement, true);
+        }
+    }
+
+    /// Bind the name introduced by a module or type level definition.
+    fn declare_definition(&mut self, tree: Located) {
+        let Some(item) = documented_item(tree) else { return };
+        match &*item.tree.variant {
+            tree::Variant::Function(function) =>
+                self.bind_identifier(item.child(&function.name), BindingKind::Method),
+            tree::Variant::ForeignFunction(function) =>
+                self.bind_token(item, &function.name.code, BindingKind::Method),
+            tree::Variant::Assignment(assignment) =>
+                self.bind_pattern(item.child(&assignment.pattern), BindingKind::Method),
+            tree::Variant::TypeDef(type_def) =>
+                self.bind_token(item, &type_def.name.code, BindingKind::Type),
+            tree::Variant::ConstructorDefinition(constructor) =>
+                self.bind_token(item, &constructor.constructor.code, BindingKind::Constructor),
+            _ => {}
+        }
+    }
+
+    // === Statements ===
+
+    /// Analyze a line of a block. The `is_declared` flag means that the name introduced by the
+    /// statement was already bound by [`Self::declare_definition`].
+    fn analyze_statement(&mut self, tree: Located, is_declared: bool) {
+        match &*tree.tree.variant {
+            tree::Variant::Assignment(assignment) => {
+                self.analyze_expression(tree.child(&assignment.expr));
+                if !is_declared {
+                    self.bind_pattern(tree.child(&assignment.pattern), BindingKind::Variable);
+                }
+            }
+            tree::Variant::Function(function) => {
+                let name = tree.child(&function.name);
+                if !is_declared {
+                    self.bind_identifier(name, BindingKind::Variable);
+                }
+                if !matches!(&*function.name.variant, tree::Variant::Ident(_)) {
+                    // The extension method, like `Integer.foo`.
+                    self.analyze_expression(name);
+                }
+                self.with_scope(ScopeKind::Function, tree.range(), |this| {
+                    this.bind_arguments(tree, &function.args);
+                    if let Some(body) = &function.body {
+                        this.analyze_body(tree.child(body));
+                    }
+                });
+            }
+            tree::Variant::ForeignFunction(function) => {
+                if !is_declared {
+                    self.bind_token(tree, &function.name.code, BindingKind::Method);
+                }
+                // The body is a code in a foreign language.
+                self.with_scope(ScopeKind::Function, tree.range(), |this| {
+                    this.bind_arguments(tree, &function.args)
+                });
+            }
+            tree::Variant::TypeDef(type_def) => {
+                if !is_declared {
+                    self.bind_token(tree, &type_def.name.code, BindingKind::Type);
+                }
+                self.with_scope(ScopeKind::Type, tree.range(), |this| {
+                    this.bind_arguments(tree, &type_def.params);
+                    this.analyze_definitions(tree, &type_def.body);
+                });
+            }
+            tree::Variant::ConstructorDefinition(constructor) => {
+                if !is_declared {
+                    let name = &constructor.constructor.code;
+                    self.bind_token(tree, name, BindingKind::Constructor);
+                }
+                let block_arguments = constructor.block.iter().filter_map(|l| l.argument.as_ref());
+                let arguments = constructor.arguments.iter().chain(block_arguments);
+                self.with_scope(ScopeKind::Function, tree.range(), |this| {
+                    this.bind_arguments(tree, arguments)
+                });
+            }
+            tree::Variant::TypeSignature(signature) => {
+                self.analyze_expression(tree.child(&signature.variable));
+                self.analyze_expression(tree.child(&signature.type_));
+            }
+            tree::Variant::Documented(documented) =>
+                if let Some(expression) = &documented.expression {
+                    self.analyze_statement(tree.child(expression), is_declared);
+                },
+            tree::Variant::Annotated(annotated) => {
+                if let Some(argument) = &annotated.argument {
+                    self.analyze_expression(tree.child(argument));
+                }
+                if let Some(expression) = &annotated.expression {
+                    self.analyze_statement(tree.child(expression), is_declared);
+                }
+            }
+            tree::Variant::AnnotatedBuiltin(annotated) =>
+                if let Some(expression) = &annotated.expression {
+                    self.analyze_statement(tree.child(expression), is_declared);
+                },
+            // The names brought by imports are reported as free references.
+            tree::Variant::Import(_) | tree::Variant::Export(_) => {}
+            _ => self.analyze_expression(tree),
+        }
+    }
+
+    /// Analyze the body of a function, lambda or case branch. The block's variables are bound in
+    /// the current scope.
+    fn analyze_body(&mut self, tree: Located) {
+        match &*tree.tree.variant {
+            tree::Variant::BodyBlock(block) => self.analyze_block(tree, &block.statements),
+            _ => self.analyze_expression(tree),
+        }
+    }
+
+    fn analyze_block(&mut self, parent: Located, lines: &[tree::block::Line]) {
+        for statement in lines.iter().filter_map(|line| line.expression.as_ref()) {
+            self.analyze_statement(parent.child(statement), false);
+        }
+    }
+
+    // === Expressions ===
+
+    fn analyze_expression(&mut self, tree: Located) {
+        match &*tree.tree.variant {
+            tree::Variant::Ident(ident) => self.refer(&ident.token.code.repr, tree.range()),
+            tree::Variant::OprApp(tree::OprApp { lhs, opr: Ok(opr), rhs })
+                if opr.properties.is_arrow() =>
+                self.with_scope(ScopeKind::Lambda, tree.range(), |this| {
+                    if let Some(lhs) = lhs {
+                        this.bind_pattern(tree.child(lhs), BindingKind::Argument);
+                    }
+                    if let Some(rhs) = rhs {
+                        this.analyze_body(tree.child(rhs));
+                    }
+                }),
+            tree::Variant::OprApp(tree::OprApp { lhs, opr: Ok(opr), rhs })
+                if opr.properties.is_dot() =>
+            {
+                if let Some(lhs) = lhs {
+                    self.analyze_expression(tree.child(lhs));
+                }
+                // The right operand of the access operator names a member, not a binding.
+                let member =
+                    rhs.as_ref().filter(|rhs| !matches!(&*rhs.variant, tree::Variant::Ident(_)));
+                if let Some(rhs) = member {
+                    self.analyze_expression(tree.child(rhs));
+                }
+            }
+            tree::Variant::BodyBlock(block) =>
+                self.with_scope(ScopeKind::Block, tree.range(), |this| {
+                    this.analyze_block(tree, &block.statements)
+                }),
+            tree::Variant::CaseOf(case_of) => {
+                if let Some(expression) = &case_of.expression {
+                    self.analyze_expression(tree.child(expression));
+                }
+                for case in case_of.cases.iter().filter_map(|line| line.case.as_ref()) {
+                    self.analyze_case(tree, case);
+                }
+            }
+            tree::Variant::Assignment(_)
+            | tree::Variant::Function(_)
+            | tree::Variant::ForeignFunction(_)
+            | tree::Variant::TypeDef(_)
+            | tree::Variant::ConstructorDefinition(_)
+            | tree::Variant::TypeSignature(_)
+            | tree::Variant::Documented(_)
+            | tree::Variant::Annotated(_)
+            | tree::Variant::AnnotatedBuiltin(_)
+            | tree::Variant::Import(_)
+            | tree::Variant::Export(_) => self.analyze_statement(tree, false),
+            _ =>
+                for child in tree.children() {
+                    self.analyze_expression(child);
+                },
+        }
+    }
+
+    /// Analyze a branch of the `case_of` expression. A line with only a documentation comment is
+    /// not a branch.
+    fn analyze_case(&mut self, case_of: Located, case: &tree::Case) {
+        let pattern = case.pattern.as_ref().map(|pattern| case_of.child(pattern));
+        let expression = case.expression.as_ref().map(|expression| case_of.child(expression));
+        let (Some(first), Some(last)) = (pattern.or(expression), expression.or(pattern))
+            else { return };
+        let range = first.range().start..last.range().end;
+        self.with_scope(ScopeKind::CaseBranch, range, |this| {
+            if let Some(pattern) = pattern {
+                this.bind_pattern(pattern, BindingKind::PatternVariable);
+            }
+            if let Some(expression) = expression {
+                this.analyze_body(expression);
+            }
+        });
+    }
+
+    // === Patterns ===
+
+    /// Bind the arguments in order, so the default values and types may refer to the preceding
+    /// arguments. The arguments are contained in the `parent` tree.
+    fn bind_arguments<'s: 'a, 'a>(
+        &mut self,
+        parent: Located<'s, '_>,
+        arguments: impl IntoIterator<Item = &'a tree::ArgumentDefinition<'s>>,
+    ) {
+        for argument in arguments {
+            if let Some(type_) = &argument.type_ {
+                self.analyze_expression(parent.child(&type_.type_));
+            }
+            if let Some(default) = &argument.default {
+                self.analyze_expression(parent.child(&default.expression));
+            }
+            self.bind_pattern(parent.child(&argument.pattern), BindingKind::Argument);
+        }
+    }
+
+    /// Bind the variables of a pattern. The type and constructor names in the pattern are usages.
+    fn bind_pattern(&mut self, tree: Located, kind: BindingKind) {
+        match &*tree.tree.variant {
+            tree::Variant::Ident(ident) if ident.token.is_type => self.analyze_expression(tree),
+            tree::Variant::Ident(_) => self.bind_identifier(tree, kind),
+            tree::Variant::App(app) => {
+                self.bind_pattern(tree.child(&app.func), kind);
+                self.bind_pattern(tree.child(&app.arg), kind);
+            }
+            tree::Variant::Group(group) =>
+                if let Some(body) = &group.body {
+                    self.bind_pattern(tree.child(body), kind);
+                },
+            tree::Variant::TypeAnnotated(annotated) => {
+                self.analyze_expression(tree.child(&annotated.type_));
+                self.bind_pattern(tree.child(&annotated.expression), kind);
+            }
+            _ => self.analyze_expression(tree),
+        }
+    }
+}
+
+
+
+// ============
+// === Test ===
+// ============
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Analyze the code and describe each reference as `name@offset -> binding offset`, with `_`
+    /// for the free references.
+    fn resolved(code: &str) -> Vec<String> {
+        let tree = crate::Parser::new().run(code);
+        let table = analyze(&tree);
+        let describe = |reference: &Reference| {
+            let binding = reference.binding.map(|id| table.bindings[id].range.start.to_string());
+            let binding = binding.unwrap_or_else(|| "_".into());
+            format!("{}@{} -> {}", reference.name, reference.range.start, binding)
+        };
+        table.references.iter().map(describe).collect()
+    }
+
+    #[test]
+    fn test_function_arguments_and_shadowing() {
+        // 0         1         2
+        // 012345678901234567890123456789
+        // f x (y = x) = x -> y + x + z
+        let code = "f x (y = x) = x -> y + x + z";
+        let expected = ["x@9 -> 2", "y@19 -> 5", "x@23 -> 14", "z@27 -> _"];
+        assert_eq!(resolved(code), expected);
+
+        let table = analyze(&crate::Parser::new().run(code));
+        let lambda_argument = table.bindings.iter().find(|b| b.range.start == 14).unwrap();
+        assert_eq!(lambda_argument.kind, BindingKind::Argument);
+        assert_eq!(lambda_argument.shadows.map(|id| table.bindings[id].range.start), Some(2));
+
+        // Rebinding a name in the same scope shadows the earlier binding.
+        let code = "f x =\n    x = x + 1\n    x";
+        assert_eq!(resolved(code), ["x@14 -> 2", "x@24 -> 10"]);
+        let table = analyze(&crate::Parser::new().run(code));
+        let variable = table.bindings.iter().find(|b| b.kind == BindingKind::Variable).unwrap();
+        assert_eq!(variable.shadows.map(|id| table.bindings[id].range.start), Some(2));
+    }
+
+    #[test]
+    fn test_blocks_and_definitions() {
+        let code = r"main =
+    a = foo b
+    b = a
+    b
+foo x = main";
+        let expected = ["foo@15 -> 37", "b@19 -> _", "a@29 -> 11", "b@35 -> 25", "main@45 -> 0"];
+        assert_eq!(resolved(code), expected);
+
+        let code = r"type Maybe a
+    Some value
+    None
+
+    get self (default = None) = case self of
+        Some value -> value
+        _ -> default";
+        let table = analyze(&crate::Parser::new().run(code));
+        let names = |kind| {
+            let bindings = table.bindings.iter().filter(|b| b.kind == kind);
+            bindings.map(|b| b.name.as_str()).collect_vec()
+        };
+        assert_eq!(names(BindingKind::Type), ["Maybe"]);
+        assert_eq!(names(BindingKind::Constructor), ["Some", "None"]);
+        assert_eq!(names(BindingKind::Method), ["get"]);
+        assert_eq!(names(BindingKind::Argument), ["a", "value", "self", "default"]);
+        assert_eq!(names(BindingKind::PatternVariable), ["value"]);
+        let references = table.references.iter().map(|r| {
+            let binding =
+                r.binding.map(|id| (table.bindings[id].name.as_str(), table.bindings[id].kind));
+            (r.name.as_str(), binding)
+        });
+        let expected = [
+            ("None", Some(("None", BindingKind::Constructor))),
+            ("self", Some(("self", BindingKind::Argument))),
+            ("Some", Some(("Some", BindingKind::Constructor))),
+            ("value", Some(("value", BindingKind::PatternVariable))),
+            ("default", Some(("default", BindingKind::Argument))),
+        ];
+        assert_eq!(references.collect_vec(), expected);
+    }
+
+    #[test]
+    fn test_closures() {
+        let code = r"main =
+    x = 1
+    f y = y -> x + y
+    z = f x
+    Vector.map [1] (v -> v + z)";
+        let table = analyze(&crate::Parser::new().run(code));
+        let captured = |kind| {
+            let scopes = table.scopes.iter().positions(|scope| scope.kind == kind).collect_vec();
+            let captured = scopes.into_iter().map(|scope| table.captured_bindings(scope));
+            let names =
+                captured.map(|ids| ids.iter().map(|id| &table.bindings[*id].name).join(" "));
+            names.collect_vec()
+        };
+        assert_eq!(captured(ScopeKind::Function), ["", "x"]);
+        assert_eq!(captured(ScopeKind::Lambda), ["x", "z"]);
+        let unused = table.unused_bindings().map(|id| &table.bindings[id].name).collect_vec();
+        assert_eq!(unused, ["main", "y"]);
+        let free = table.free_references().map(|r| r.name.as_str()).collect_vec();
+        assert_eq!(free, ["Vector"]);
+        let x_usage = code.find("x + y").unwrap();
+        assert_eq!(table.binding_at(x_usage), table.binding_at(code.find("x = 1").unwrap()));
+    }
+
+    #[test]
+    fn test_ranges() {
+        let code = r#"## Doc.
+foo x = @Builtin_Method "foo"
+
+type T
+    A
+    e = 2.718
+
+main = case foo 0x1F of
+    ## The first branch.
+    T.A -> foo main
+    y -> T.e + y"#;
+        let table = analyze(&crate::Parser::new().run(code));
+        for binding in &table.bindings {
+            assert_eq!(code[binding.range.clone()], binding.name);
+        }
+        for reference in &table.references {
+            assert_eq!(code[reference.range.clone()], reference.name);
+        }
+        let branches = table.scopes.iter().filter(|scope| scope.kind == ScopeKind::CaseBranch);
+        let branches = branches.map(|scope| &code[scope.range.clone()]).collect_vec();
+        assert_eq!(branches, ["T.A -> foo main", "y -> T.e + y"]);
+    }
+}
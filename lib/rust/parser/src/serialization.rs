@@ -6,6 +6,13 @@
 use crate::prelude::*;
 
 
+// ==============
+// === Export ===
+// ==============
+
+pub mod flat;
+
+
 
 // ============
 // === Tree ===
@@ -27,6 +34,30 @@ pub fn deserialize_tree(data: &[u8]) -> Result<crate::syntax::tree::Tree, bincod
 
 
 
+// ===================
+// === Diagnostics ===
+// ===================
+
+/// Serialize a sequence of `Diagnostic`s to their binary representation.
+pub fn serialize_diagnostics(
+    data: &[crate::syntax::tree::diagnostic::Diagnostic],
+) -> Result<Vec<u8>, bincode::Error> {
+    use bincode::Options;
+    let options = bincode::DefaultOptions::new().with_fixint_encoding();
+    options.serialize(data)
+}
+
+/// Deserialize a sequence of `Diagnostic`s from their binary representation.
+pub fn deserialize_diagnostics(
+    data: &[u8],
+) -> Result<Vec<crate::syntax::tree::diagnostic::Diagnostic>, bincode::Error> {
+    use bincode::Options;
+    let options = bincode::DefaultOptions::new().with_fixint_encoding();
+    options.deserialize(data)
+}
+
+
+
 // ============
 // === Code ===
 // ============
@@ -40,20 +71,32 @@ pub(crate) struct Code {
     len:   u32,
 }
 
+impl Code {
+    #[allow(clippy::ptr_arg)]
+    fn new(cow: &Cow<'_, str>) -> Self {
+        match cow {
+            Cow::Borrowed(s) => {
+                let begin = str::as_ptr(s) as u32;
+                let len = s.len() as u32;
+                Code { begin, len }
+            }
+            Cow::Owned(s) if s.is_empty() => Code { begin: 0, len: 0 },
+            Cow::Owned(_) => panic!(),
+        }
+    }
+}
+
 /// Serde wrapper to serialize a `Cow` as the `Code` representation.
 #[allow(clippy::ptr_arg)] // This is the signature required by serde.
 pub(crate) fn serialize_cow<S>(cow: &Cow<'_, str>, ser: S) -> Result<S::Ok, S::Error>
 where S: serde::Serializer {
-    let s = match cow {
-        Cow::Borrowed(s) => {
-            let begin = str::as_ptr(s) as u32;
-            let len = s.len() as u32;
-            Code { begin, len }
-        }
-        Cow::Owned(s) if s.is_empty() => Code { begin: 0, len: 0 },
-        Cow::Owned(_) => panic!(),
-    };
-    s.serialize(ser)
+    Code::new(cow).serialize(ser)
+}
+
+/// Reflection counterpart of [`serialize_cow`].
+#[allow(clippy::ptr_arg)] // This is the signature required by `#[reflect(visit_with)]`.
+pub(crate) fn visit_cow(cow: &Cow<'_, str>, visitor: &mut dyn enso_reflect::Visitor) {
+    enso_reflect::Reflect::reflect_value(&Code::new(cow), visitor)
 }
 
 pub(crate) fn deserialize_cow<'c, 'de, D>(deserializer: D) -> Result<Cow<'c, str>, D::Error>
@@ -74,6 +117,15 @@ where S: serde::Serializer {
     s.serialize_u32(value)
 }
 
+/// Reflection counterpart of [`serialize_optional_char`]. An absent value is visited as the
+/// serialized sentinel, which is not a valid `char`.
+pub(crate) fn visit_optional_char(c: &Option<char>, visitor: &mut dyn enso_reflect::Visitor) {
+    match c {
+        Some(c) => visitor.visit_char(*c),
+        None => visitor.visit_u32(0xFFFF_FFFF),
+    }
+}
+
 pub(crate) fn deserialize_optional_char<'c, 'de, D>(
     deserializer: D,
 ) -> Result<Option<char>, D::Error>
@@ -93,12 +145,16 @@ where D: serde::Deserializer<'de> {
 
 /// Deserialization type for `crate::syntax::tree::Error`.
 #[derive(Deserialize, Debug, Clone)]
-pub(crate) struct Error(String);
+pub(crate) struct Error {
+    code:    crate::syntax::tree::diagnostic::ErrorCode,
+    message: String,
+}
 
 impl From<Error> for crate::syntax::tree::Error {
     fn from(error: Error) -> Self {
-        let message = error.0.into();
-        crate::syntax::tree::Error { message }
+        let Error { code, message } = error;
+        let message = message.into();
+        crate::syntax::tree::Error { code, message }
     }
 }
 
@@ -149,6 +205,11 @@ where S: serde::Serializer {
     s.serialize_u32(x.unwrap_or(0xFFFF_FFFF))
 }
 
+/// Reflection counterpart of [`serialize_optional_int`].
+pub(crate) fn visit_optional_int(x: &Option<u32>, visitor: &mut dyn enso_reflect::Visitor) {
+    visitor.visit_i32(x.map(|x| x as i32).unwrap_or(-1))
+}
+
 pub(crate) fn deserialize_optional_int<'c, 'de, D>(
     deserializer: D,
 ) -> Result<Option<u32>, D::Error>
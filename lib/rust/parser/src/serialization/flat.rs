@@ -0,0 +1,487 @@
+//! A binary format that can be read in place, without deserializing it first.
+//!
+//! # Layout
+//!
+//! The data begins with a header:
+//! - The [`MAGIC`] bytes.
+//! - The format [`VERSION`], as a `u32`.
+//! - A hash identifying the schema of the serialized types, as a `u64`. The serializer doesn't
+//!   interpret this value; it is computed by the caller from the reflection information of the root
+//!   type (see `enso_metamodel::meta::serialization::schema_hash`), so that a reader built from a
+//!   different version of the types can detect the mismatch.
+//! - The address of the root value, as a `u32`.
+//!
+//! All integers are little-endian. An *address* is an offset from the beginning of the data. Every
+//! value has a fixed-size *inline* representation, so the position of any field within a value can
+//! be determined from the schema alone:
+//! - A `bool` is one byte; `u32`, `i32`, and `char` are four bytes; `u64` and `i64` are eight.
+//! - A struct or tuple is the concatenation of its fields.
+//! - An enum value is the address of a record containing its discriminant, as a `u32`, followed by
+//!   its fields.
+//! - A string is the address of a record containing its length in bytes, as a `u32`, followed by
+//!   its UTF-8 contents.
+//! - A sequence is the address of a record containing its number of elements, as a `u32`, followed
+//!   by the elements.
+//! - An option is `0` if the value is absent; otherwise, it is the address of the value. (The
+//!   header occupies address `0`, so no value can be stored there.)
+//!
+//! Records are written after the values they contain, so the data can be produced in one pass.
+
+use crate::prelude::*;
+
+use serde::ser;
+use serde::Serialize;
+
+
+
+// =================
+// === Constants ===
+// =================
+
+/// The bytes at the start of any data in this format.
+pub const MAGIC: [u8; 4] = *b"ENSF";
+/// The version of the layout described in the module documentation. This must be incremented
+/// whenever the layout changes.
+pub const VERSION: u32 = 1;
+/// The size of the header, in bytes.
+pub const HEADER_LEN: usize = 20;
+const ROOT_ADDRESS_OFFSET: usize = 16;
+
+
+
+// =================
+// === Interface ===
+// =================
+
+/// Serialize a value, identifying its schema with the given hash.
+pub fn serialize<T: Serialize + ?Sized>(value: &T, schema_hash: u64) -> Result<Vec<u8>, Error> {
+    let mut serializer = Serializer { output: Vec::new() };
+    serializer.output.extend(MAGIC);
+    serializer.output.extend(VERSION.to_le_bytes());
+    serializer.output.extend(schema_hash.to_le_bytes());
+    serializer.output.extend(0u32.to_le_bytes());
+    debug_assert_eq!(serializer.output.len(), HEADER_LEN);
+    let root = value.serialize(&mut serializer)?;
+    let root = serializer.record(&root)?;
+    serializer.output[ROOT_ADDRESS_OFFSET..HEADER_LEN].copy_from_slice(&root);
+    Ok(serializer.output)
+}
+
+/// An error that prevented serialization.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Error {
+    /// The data contains a type that has no representation in this format.
+    Unsupported(&'static str),
+    /// The output would be too large to address with 32-bit addresses.
+    TooLarge,
+    /// An error reported by a `Serialize` implementation.
+    Custom(String),
+}
+
+impl Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::Unsupported(ty) => write!(f, "Type not supported by the flat format: {ty}."),
+            Error::TooLarge => write!(f, "Serialized data exceeds 4 GiB."),
+            Error::Custom(message) => write!(f, "{message}"),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+impl ser::Error for Error {
+    fn custom<T: Display>(msg: T) -> Self {
+        Error::Custom(msg.to_string())
+    }
+}
+
+
+
+// ==================
+// === Serializer ===
+// ==================
+
+/// Produces the flat format. Serializing a value returns its inline representation; values that
+/// are stored out of line are written to the output, and represented inline by their address.
+#[derive(Debug)]
+struct Serializer {
+    output: Vec<u8>,
+}
+
+impl Serializer {
+    /// Write a record to the output, and return the inline representation of its address.
+    fn record(&mut self, data: &[u8]) -> Result<Vec<u8>, Error> {
+        let address = u32::try_from(self.output.len()).map_err(|_| Error::TooLarge)?;
+        self.output.extend(data);
+        Ok(address.to_le_bytes().to_vec())
+    }
+
+    fn compound(&mut self, layout: Layout, prefix: Vec<u8>) -> Compound {
+        let data = prefix;
+        let count = 0;
+        Compound { serializer: self, layout, data, count }
+    }
+}
+
+/// How a compound value is represented.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Layout {
+    /// The fields are stored inline.
+    Inline,
+    /// The fields are stored in a record, preceded by a discriminant.
+    Variant,
+    /// The elements are stored in a record, preceded by their count.
+    Sequence,
+}
+
+/// Accumulates the fields or elements of a compound value.
+#[derive(Debug)]
+struct Compound<'a> {
+    serializer: &'a mut Serializer,
+    layout:     Layout,
+    data:       Vec<u8>,
+    count:      u32,
+}
+
+impl<'a> Compound<'a> {
+    fn element<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), Error> {
+        let inline = value.serialize(&mut *self.serializer)?;
+        self.data.extend(inline);
+        self.count += 1;
+        Ok(())
+    }
+
+    fn finish(mut self) -> Result<Vec<u8>, Error> {
+        match self.layout {
+            Layout::Inline => Ok(self.data),
+            Layout::Variant => self.serializer.record(&self.data),
+            Layout::Sequence => {
+                self.data[..4].copy_from_slice(&self.count.to_le_bytes());
+                self.serializer.record(&self.data)
+            }
+        }
+    }
+}
+
+fn discriminant(variant_index: u32) -> Vec<u8> {
+    variant_index.to_le_bytes().to_vec()
+}
+
+impl<'a> ser::Serializer for &'a mut Serializer {
+    type Ok = Vec<u8>;
+    type Error = Error;
+    type SerializeSeq = Compound<'a>;
+    type SerializeTuple = Compound<'a>;
+    type SerializeTupleStruct = Compound<'a>;
+    type SerializeTupleVariant = Compound<'a>;
+    type SerializeMap = ser::Impossible<Vec<u8>, Error>;
+    type SerializeStruct = Compound<'a>;
+    type SerializeStructVariant = Compound<'a>;
+
+    fn serialize_bool(self, v: bool) -> Result<Self::Ok, Self::Error> {
+        Ok(vec![v as u8])
+    }
+
+    fn serialize_i8(self, _: i8) -> Result<Self::Ok, Self::Error> {
+        Err(Error::Unsupported("i8"))
+    }
+
+    fn serialize_i16(self, _: i16) -> Result<Self::Ok, Self::Error> {
+        Err(Error::Unsupported("i16"))
+    }
+
+    fn serialize_i32(self, v: i32) -> Result<Self::Ok, Self::Error> {
+        Ok(v.to_le_bytes().to_vec())
+    }
+
+    fn serialize_i64(self, v: i64) -> Result<Self::Ok, Self::Error> {
+        Ok(v.to_le_bytes().to_vec())
+    }
+
+    fn serialize_u8(self, _: u8) -> Result<Self::Ok, Self::Error> {
+        Err(Error::Unsupported("u8"))
+    }
+
+    fn serialize_u16(self, _: u16) -> Result<Self::Ok, Self::Error> {
+        Err(Error::Unsupported("u16"))
+    }
+
+    fn serialize_u32(self, v: u32) -> Result<Self::Ok, Self::Error> {
+        Ok(v.to_le_bytes().to_vec())
+    }
+
+    fn serialize_u64(self, v: u64) -> Result<Self::Ok, Self::Error> {
+        Ok(v.to_le_bytes().to_vec())
+    }
+
+    fn serialize_f32(self, _: f32) -> Result<Self::Ok, Self::Error> {
+        Err(Error::Unsupported("f32"))
+    }
+
+    fn serialize_f64(self, _: f64) -> Result<Self::Ok, Self::Error> {
+        Err(Error::Unsupported("f64"))
+    }
+
+    fn serialize_char(self, v: char) -> Result<Self::Ok, Self::Error> {
+        self.serialize_u32(v as u32)
+    }
+
+    fn serialize_str(self, v: &str) -> Result<Self::Ok, Self::Error> {
+        let len = u32::try_from(v.len()).map_err(|_| Error::TooLarge)?;
+        let mut data = len.to_le_bytes().to_vec();
+        data.extend(v.as_bytes());
+        self.record(&data)
+    }
+
+    fn serialize_bytes(self, _: &[u8]) -> Result<Self::Ok, Self::Error> {
+        Err(Error::Unsupported("bytes"))
+    }
+
+    fn serialize_none(self) -> Result<Self::Ok, Self::Error> {
+        Ok(0u32.to_le_bytes().to_vec())
+    }
+
+    fn serialize_some<T: Serialize + ?Sized>(self, value: &T) -> Result<Self::Ok, Self::Error> {
+        let inline = value.serialize(&mut *self)?;
+        self.record(&inline)
+    }
+
+    fn serialize_unit(self) -> Result<Self::Ok, Self::Error> {
+        Ok(vec![])
+    }
+
+    fn serialize_unit_struct(self, _name: &'static str) -> Result<Self::Ok, Self::Error> {
+        Ok(vec![])
+    }
+
+    fn serialize_unit_variant(
+        self,
+        _name: &'static str,
+        variant_index: u32,
+        _variant: &'static str,
+    ) -> Result<Self::Ok, Self::Error> {
+        self.record(&discriminant(variant_index))
+    }
+
+    fn serialize_newtype_struct<T: Serialize + ?Sized>(
+        self,
+        _name: &'static str,
+        value: &T,
+    ) -> Result<Self::Ok, Self::Error> {
+        value.serialize(self)
+    }
+
+    fn serialize_newtype_variant<T: Serialize + ?Sized>(
+        self,
+        _name: &'static str,
+        variant_index: u32,
+        _variant: &'static str,
+        value: &T,
+    ) -> Result<Self::Ok, Self::Error> {
+        let mut data = discriminant(variant_index);
+        data.extend(value.serialize(&mut *self)?);
+        self.record(&data)
+    }
+
+    fn serialize_seq(self, _len: Option<usize>) -> Result<Self::SerializeSeq, Self::Error> {
+        Ok(self.compound(Layout::Sequence, 0u32.to_le_bytes().to_vec()))
+    }
+
+    fn serialize_tuple(self, _len: usize) -> Result<Self::SerializeTuple, Self::Error> {
+        Ok(self.compound(Layout::Inline, vec![]))
+    }
+
+    fn serialize_tuple_struct(
+        self,
+        _name: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeTupleStruct, Self::Error> {
+        Ok(self.compound(Layout::Inline, vec![]))
+    }
+
+    fn serialize_tuple_variant(
+        self,
+        _name: &'static str,
+        variant_index: u32,
+        _variant: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeTupleVariant, Self::Error> {
+        Ok(self.compound(Layout::Variant, discriminant(variant_index)))
+    }
+
+    fn serialize_map(self, _len: Option<usize>) -> Result<Self::SerializeMap, Self::Error> {
+        Err(Error::Unsupported("map"))
+    }
+
+    fn serialize_struct(
+        self,
+        _name: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeStruct, Self::Error> {
+        Ok(self.compound(Layout::Inline, vec![]))
+    }
+
+    fn serialize_struct_variant(
+        self,
+        _name: &'static str,
+        variant_index: u32,
+        _variant: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeStructVariant, Self::Error> {
+        Ok(self.compound(Layout::Variant, discriminant(variant_index)))
+    }
+}
+
+impl<'a> ser::SerializeSeq for Compound<'a> {
+    type Ok = Vec<u8>;
+    type Error = Error;
+
+    fn serialize_element<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), Error> {
+        self.element(value)
+    }
+
+    fn end(self) -> Result<Self::Ok, Self::Error> {
+        self.finish()
+    }
+}
+
+impl<'a> ser::SerializeTuple for Compound<'a> {
+    type Ok = Vec<u8>;
+    type Error = Error;
+
+    fn serialize_element<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), Error> {
+        self.element(value)
+    }
+
+    fn end(self) -> Result<Self::Ok, Self::Error> {
+        self.finish()
+    }
+}
+
+impl<'a> ser::SerializeTupleStruct for Compound<'a> {
+    type Ok = Vec<u8>;
+    type Error = Error;
+
+    fn serialize_field<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), Error> {
+        self.element(value)
+    }
+
+    fn end(self) -> Result<Self::Ok, Self::Error> {
+        self.finish()
+    }
+}
+
+impl<'a> ser::SerializeTupleVariant for Compound<'a> {
+    type Ok = Vec<u8>;
+    type Error = Error;
+
+    fn serialize_field<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), Error> {
+        self.element(value)
+    }
+
+    fn end(self) -> Result<Self::Ok, Self::Error> {
+        self.finish()
+    }
+}
+
+impl<'a> ser::SerializeStruct for Compound<'a> {
+    type Ok = Vec<u8>;
+    type Error = Error;
+
+    fn serialize_field<T: Serialize + ?Sized>(
+        &mut self,
+        _key: &'static str,
+        value: &T,
+    ) -> Result<(), Error> {
+        self.element(value)
+    }
+
+    fn end(self) -> Result<Self::Ok, Self::Error> {
+        self.finish()
+    }
+}
+
+impl<'a> ser::SerializeStructVariant for Compound<'a> {
+    type Ok = Vec<u8>;
+    type Error = Error;
+
+    fn serialize_field<T: Serialize + ?Sized>(
+        &mut self,
+        _key: &'static str,
+        value: &T,
+    ) -> Result<(), Error> {
+        self.element(value)
+    }
+
+    fn end(self) -> Result<Self::Ok, Self::Error> {
+        self.finish()
+    }
+}
+
+
+
+// =============
+// === Tests ===
+// =============
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Serialize)]
+    struct Outer {
+        flag:     bool,
+        value:    u64,
+        name:     String,
+        shape:    Shape,
+        children: Vec<u32>,
+        parent:   Option<u32>,
+    }
+
+    #[derive(Serialize)]
+    enum Shape {
+        Empty,
+        Point { x: i32, y: i32 },
+    }
+
+    fn u32_at(data: &[u8], address: usize) -> u32 {
+        u32::from_le_bytes(data[address..address + 4].try_into().unwrap())
+    }
+
+    #[test]
+    fn layout() {
+        let value = Outer {
+            flag:     true,
+            value:    7,
+            name:     "ab".into(),
+            shape:    Shape::Point { x: 1, y: -1 },
+            children: vec![3, 4],
+            parent:   None,
+        };
+        let data = serialize(&value, 0x1234).unwrap();
+        assert_eq!(data[..4], MAGIC);
+        assert_eq!(u32_at(&data, 4), VERSION);
+        assert_eq!(u64::from_le_bytes(data[8..16].try_into().unwrap()), 0x1234);
+        let root = u32_at(&data, ROOT_ADDRESS_OFFSET) as usize;
+        assert_eq!(data.len() - root, 1 + 8 + 4 + 4 + 4 + 4);
+        assert_eq!(data[root], 1);
+        assert_eq!(u64::from_le_bytes(data[root + 1..root + 9].try_into().unwrap()), 7);
+        let name = u32_at(&data, root + 9) as usize;
+        assert_eq!(u32_at(&data, name), 2);
+        assert_eq!(&data[name + 4..name + 6], b"ab");
+        let shape = u32_at(&data, root + 13) as usize;
+        assert_eq!(u32_at(&data, shape), 1);
+        assert_eq!(u32_at(&data, shape + 4) as i32, 1);
+        assert_eq!(u32_at(&data, shape + 8) as i32, -1);
+        let children = u32_at(&data, root + 17) as usize;
+        assert_eq!(u32_at(&data, children), 2);
+        assert_eq!(u32_at(&data, children + 4), 3);
+        assert_eq!(u32_at(&data, children + 8), 4);
+        assert_eq!(u32_at(&data, root + 21), 0);
+        let empty = serialize(&Shape::Empty, 0).unwrap();
+        let root = u32_at(&empty, ROOT_ADDRESS_OFFSET) as usize;
+        let shape = u32_at(&empty, root) as usize;
+        assert_eq!(u32_at(&empty, shape), 0);
+    }
+}
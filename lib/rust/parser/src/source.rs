@@ -9,6 +9,7 @@
 // ==============
 
 pub mod code;
+pub mod files;
 pub mod span;
 
 pub use code::Code;
@@ -15,6 +15,7 @@ pub struct Code<'s> {
     #[serde(serialize_with = "crate::serialization::serialize_cow")]
     #[serde(deserialize_with = "crate::serialization::deserialize_cow")]
     #[reflect(as = "crate::serialization::Code", flatten, hide)]
+    #[reflect(visit_with = "crate::serialization::visit_cow")]
     #[deref]
     pub repr:  Cow<'s, str>,
     #[reflect(hide)]
@@ -129,6 +130,12 @@ impl Length {
     pub fn utf8_bytes(&self) -> usize {
         self.utf8
     }
+
+    /// Return the length in UTF-16 code units.
+    #[inline(always)]
+    pub fn utf16_len(&self) -> usize {
+        self.utf16
+    }
 }
 
 impl Add for Length {
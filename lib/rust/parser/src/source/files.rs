@@ -0,0 +1,45 @@
+//! Locating Enso source files, and positions within their code.
+
+use std::path::Path;
+use std::path::PathBuf;
+
+
+
+// ====================
+// === Source files ===
+// ====================
+
+/// Return the paths of all Enso source files in the given directory and its subdirectories, in
+/// sorted order. If the path is a file, it is returned.
+pub fn enso_files(path: &Path) -> std::io::Result<Vec<PathBuf>> {
+    let mut files = vec![];
+    collect_enso_files(path, &mut files)?;
+    Ok(files)
+}
+
+fn collect_enso_files(path: &Path, files: &mut Vec<PathBuf>) -> std::io::Result<()> {
+    if !path.is_dir() {
+        files.push(path.to_owned());
+        return Ok(());
+    }
+    let mut entries =
+        std::fs::read_dir(path)?.map(|e| e.map(|e| e.path())).collect::<Result<Vec<_>, _>>()?;
+    entries.sort();
+    for entry in entries {
+        if entry.is_dir() {
+            collect_enso_files(&entry, files)?;
+        } else if entry.extension().map_or(false, |ext| ext == "enso") {
+            files.push(entry);
+        }
+    }
+    Ok(())
+}
+
+/// Return the 1-based line and column (in chars) of a byte offset in the given code.
+pub fn line_column(code: &str, offset: usize) -> (usize, usize) {
+    let before = &code[..offset];
+    let line = before.matches('\n').count() + 1;
+    let line_start = before.rfind('\n').map_or(0, |i| i + 1);
+    let column = before[line_start..].chars().count() + 1;
+    (line, column)
+}
@@ -5,6 +5,7 @@ use crate::prelude::*;
 use crate::syntax;
 use crate::syntax::token;
 use crate::syntax::token::Token;
+use crate::syntax::tree::diagnostic::ErrorCode;
 
 
 
@@ -149,9 +150,11 @@ impl<'s> ExpressionBuilder<'s> {
     pub fn operand(&mut self, operand: Operand<syntax::Tree<'s>>) {
         if self.prev_type == Some(ItemType::Ast) {
             if let Some(Operand { value: syntax::Tree { variant: box
-                    syntax::tree::Variant::TextLiteral(ref mut lhs), .. }, .. }) = self.output.last_mut()
+                    syntax::tree::Variant::TextLiteral(ref mut lhs), span: ref mut lhs_span },
+                    .. }) = self.output.last_mut()
                     && !lhs.closed
                     && let box syntax::tree::Variant::TextLiteral(mut rhs) = operand.value.variant {
+                *lhs_span = mem::take(lhs_span).add(&mut operand.value.span.clone());
                 syntax::tree::join_text_literals(lhs, &mut rhs, operand.value.span);
                 if let syntax::tree::TextLiteral { open: Some(open), newline: None, elements, closed: true, close: None } = lhs
                     && open.code.starts_with('#') {
@@ -215,7 +218,7 @@ impl<'s> ExpressionBuilder<'s> {
             self.application();
             if self.nospace {
                 if let Unary::Simple(token) = arity {
-                    let error = "Space required between term and unary-operator expression.".into();
+                    let error = ErrorCode::UnaryOperatorSpacing.into();
                     arity = Unary::Invalid { token, error };
                 }
             }
@@ -231,9 +234,29 @@ impl<'s> ExpressionBuilder<'s> {
         assoc: token::Associativity,
         opr: token::Operator<'s>,
     ) {
+        if self.prev_type == Some(ItemType::Opr)
+                && let Some(Operator { opr: Arity::Unary(Unary::Fragment { .. }), .. }) =
+                    self.operator_stack.last() {
+            // An arrow fragment followed by a binary operator has no body; the fragment becomes the
+            // binary operator's LHS.
+            if let Some(Operator { opr: Arity::Unary(Unary::Fragment { mut fragment }), .. }) =
+                    self.operator_stack.pop() && let Some(operand) = fragment.finish() {
+                self.output.push(operand);
+                self.prev_type = Some(ItemType::Ast);
+            }
+        }
         if self.prev_type == Some(ItemType::Opr)
                 && let Some(prev_opr) = self.operator_stack.last_mut()
                 && let Arity::Binary { tokens, .. } = &mut prev_opr.opr {
+            if tokens.len() == 1 && tokens[0].properties.is_assignment() {
+                // The operators following an assignment are an error within the assigned
+                // expression. Applying them to the expression as invalid unary operators keeps the
+                // assignment, so that only the expression is invalid.
+                let error = ErrorCode::MultipleOperators.into();
+                let arity = Unary::Invalid { token: opr, error };
+                self.push_operator(prec, assoc, Arity::Unary(arity));
+                return;
+            }
             if tokens.len() == 1 && tokens[0].properties.is_dot() {
                 let Token { left_offset, code, .. } = opr;
                 let is_operator = true;
@@ -410,7 +433,7 @@ impl<'s> Arity<'s> {
 #[derive(Debug, PartialEq, Eq)]
 enum Unary<'s> {
     Simple(token::Operator<'s>),
-    Invalid { token: token::Operator<'s>, error: Cow<'static, str> },
+    Invalid { token: token::Operator<'s>, error: syntax::tree::Error },
     Fragment { fragment: ExpressionBuilder<'s> },
 }
 
@@ -287,7 +287,7 @@ macro_rules! with_token_definition { ($f:ident ($($args:tt)*)) => { $f! { $($arg
         TextEscape {
             #[serde(serialize_with = "crate::serialization::serialize_optional_char")]
             #[serde(deserialize_with = "crate::serialization::deserialize_optional_char")]
-            #[reflect(as = "char")]
+            #[reflect(as = "char", visit_with = "crate::serialization::visit_optional_char")]
             pub value: Option<char>,
         },
         TextInitialNewline,
@@ -5,6 +5,7 @@ use crate::source::*;
 use crate::syntax::*;
 
 use crate::span_builder;
+use crate::syntax::tree::diagnostic::ErrorCode;
 
 use enso_parser_syntax_tree_visitor::Visitor;
 use enso_shapely_macros::tagged_enum;
@@ -15,6 +16,9 @@ use enso_shapely_macros::tagged_enum;
 // ==============
 
 pub mod block;
+pub mod diagnostic;
+pub mod lossless;
+pub mod query;
 
 
 
@@ -123,7 +127,7 @@ macro_rules! with_ast_definition { ($f:ident ($($args:tt)*)) => { $f! { $($args)
             pub token: token::Wildcard<'s>,
             #[serde(serialize_with = "crate::serialization::serialize_optional_int")]
             #[serde(deserialize_with = "crate::serialization::deserialize_optional_int")]
-            #[reflect(as = "i32")]
+            #[reflect(as = "i32", visit_with = "crate::serialization::visit_optional_int")]
             pub de_bruijn_index: Option<u32>,
         },
         /// The auto-scoping marker, `...`.
@@ -392,25 +396,30 @@ with_ast_definition!(generate_ast_definition());
 /// Error of parsing attached to an [`Tree`] node.
 #[derive(Clone, Debug, Eq, PartialEq, Visitor, Serialize, Reflect, Deserialize)]
 #[allow(missing_docs)]
-#[reflect(transparent)]
 #[serde(from = "crate::serialization::Error")]
 pub struct Error {
-    #[serde(skip_deserializing)]
+    pub code:    ErrorCode,
     pub message: Cow<'static, str>,
 }
 
 impl Error {
     /// Constructor.
-    pub fn new(message: impl Into<Cow<'static, str>>) -> Self {
+    pub fn new(code: ErrorCode, message: impl Into<Cow<'static, str>>) -> Self {
         let message = message.into();
-        Self { message }
+        Self { code, message }
+    }
+}
+
+impl From<ErrorCode> for Error {
+    fn from(code: ErrorCode) -> Self {
+        Self::new(code, code.message())
     }
 }
 
 impl<'s> Tree<'s> {
     /// Constructor.
-    pub fn with_error(self, message: impl Into<Cow<'static, str>>) -> Self {
-        Tree::invalid(Error::new(message), self)
+    pub fn with_error(self, error: impl Into<Error>) -> Self {
+        Tree::invalid(error.into(), self)
     }
 }
 
@@ -674,7 +683,10 @@ impl<'s> Case<'s> {
 
 impl<'s> span::Builder<'s> for Case<'s> {
     fn add_to_span(&mut self, span: Span<'s>) -> Span<'s> {
-        span.add(&mut self.pattern).add(&mut self.arrow).add(&mut self.expression)
+        span.add(&mut self.documentation)
+            .add(&mut self.pattern)
+            .add(&mut self.arrow)
+            .add(&mut self.expression)
     }
 }
 
@@ -769,27 +781,37 @@ pub fn apply<'s>(mut func: Tree<'s>, mut arg: Tree<'s>) -> Tree<'s> {
     match (&mut *func.variant, &mut *arg.variant) {
         (Variant::Number(func_ @ Number { base: _, integer: None, fractional_digits: None }),
                 Variant::Number(Number { base: None, integer, fractional_digits })) => {
+            func.span.code_length += arg.span.left_offset.code.length() + arg.span.code_length;
             func_.integer = mem::take(integer);
             func_.fractional_digits = mem::take(fractional_digits);
             func
         }
         (Variant::Annotated(func_ @ Annotated { argument: None, .. }), _) => {
+            func.span.code_length += arg.span.left_offset.code.length() + arg.span.code_length;
             func_.argument = maybe_apply(mem::take(&mut func_.argument), arg).into();
             func
         }
         (Variant::AnnotatedBuiltin(func_), _) => {
+            func.span.code_length += arg.span.left_offset.code.length() + arg.span.code_length;
             func_.expression = maybe_apply(mem::take(&mut func_.expression), arg).into();
             func
         }
         (Variant::OprApp(OprApp { lhs: Some(_), opr: Ok(_), rhs }),
                 Variant::ArgumentBlockApplication(ArgumentBlockApplication { lhs: None, arguments }))
         if rhs.is_none() => {
-            *rhs = block::body_from_lines(mem::take(arguments)).into();
+            if let Some(first) = arguments.first_mut() {
+                first.newline.left_offset += mem::take(&mut arg.span.left_offset);
+            }
+            let body = block::body_from_lines(mem::take(arguments));
+            func.span.code_length += body.span.left_offset.code.length() + body.span.code_length;
+            *rhs = body.into();
             func
         }
         (_, Variant::ArgumentBlockApplication(block)) if block.lhs.is_none() => {
             let func_left_offset = mem::take(&mut func.span.left_offset);
             let arg_left_offset = mem::replace(&mut arg.span.left_offset, func_left_offset);
+            arg.span.code_length =
+                func.span.code_length + arg_left_offset.code.length() + arg.span.code_length;
             if let Some(first) = block.arguments.first_mut() {
                 first.newline.left_offset += arg_left_offset;
             }
@@ -799,6 +821,8 @@ pub fn apply<'s>(mut func: Tree<'s>, mut arg: Tree<'s>) -> Tree<'s> {
         (_, Variant::OperatorBlockApplication(block)) if block.lhs.is_none() => {
             let func_left_offset = mem::take(&mut func.span.left_offset);
             let arg_left_offset = mem::replace(&mut arg.span.left_offset, func_left_offset);
+            arg.span.code_length =
+                func.span.code_length + arg_left_offset.code.length() + arg.span.code_length;
             if let Some(first) = block.expressions.first_mut() {
                 first.newline.left_offset += arg_left_offset;
             }
@@ -848,7 +872,12 @@ pub fn join_text_literals<'s>(
         Some(TextElement::Escape { token }) => token.left_offset += rhs_span.left_offset,
         Some(TextElement::Splice { open, .. }) => open.left_offset += rhs_span.left_offset,
         Some(TextElement::Newline { newline }) => newline.left_offset += rhs_span.left_offset,
-        None => (),
+        None =>
+            if let Some(newline) = &mut rhs.newline {
+                newline.left_offset += rhs_span.left_offset;
+            } else if let Some(close) = &mut rhs.close {
+                close.left_offset += rhs_span.left_offset;
+            },
     }
     if let Some(newline) = rhs.newline.take() {
         lhs.newline = newline.into();
@@ -876,7 +905,7 @@ pub fn apply_operator<'s>(
     };
     if let Ok(opr_) = &opr && opr_.properties.is_special() {
         let tree = Tree::opr_app(lhs, opr, rhs);
-        return tree.with_error("Invalid use of special operator.");
+        return tree.with_error(ErrorCode::InvalidUseOfSpecialOperator);
     }
     if let Ok(opr_) = &opr && opr_.properties.is_type_annotation() {
         return match (lhs, rhs) {
@@ -886,12 +915,13 @@ pub fn apply_operator<'s>(
             },
             (lhs, rhs) => {
                 let invalid = Tree::opr_app(lhs, opr, rhs);
-                invalid.with_error("`:` operator must be applied to two operands.")
+                invalid.with_error(ErrorCode::TypeAnnotationOperands)
             }
         };
     }
     if let Ok(opr_) = &opr && !opr_.properties.can_form_section() && lhs.is_none() && rhs.is_none() {
-        let error = format!("Operator `{opr:?}` must be applied to two operands.");
+        let message = format!("Operator `{opr:?}` must be applied to two operands.");
+        let error = Error::new(ErrorCode::MissingOperands, message);
         let invalid = Tree::opr_app(lhs, opr, rhs);
         return invalid.with_error(error);
     }
@@ -905,6 +935,7 @@ pub fn apply_operator<'s>(
     {
         let dot = opr.clone();
         let digits = digits.clone();
+        lhs.span.code_length += dot.code.length() + digits.code.length();
         lhs_.fractional_digits = Some(FractionalDigits { dot, digits });
         return lhs.clone();
     }
@@ -936,7 +967,8 @@ pub fn apply_unary_operator<'s>(opr: token::Operator<'s>, rhs: Option<Tree<'s>>)
         };
     }
     if !opr.properties.can_form_section() && rhs.is_none() {
-        let error = format!("Operator `{opr:?}` must be applied to an operand.");
+        let message = format!("Operator `{opr:?}` must be applied to an operand.");
+        let error = Error::new(ErrorCode::MissingOperand, message);
         let invalid = Tree::unary_opr_app(opr, rhs);
         return invalid.with_error(error);
     }
@@ -976,9 +1008,9 @@ pub fn to_ast(token: Token) -> Tree {
         token::Variant::Wildcard(wildcard) => Tree::wildcard(token.with_variant(wildcard), default()),
         token::Variant::AutoScope(t) => Tree::auto_scope(token.with_variant(t)),
         token::Variant::OpenSymbol(s) =>
-            Tree::group(Some(token.with_variant(s)), default(), default()).with_error("Unmatched delimiter"),
+            Tree::group(Some(token.with_variant(s)), default(), default()).with_error(ErrorCode::UnmatchedDelimiter),
         token::Variant::CloseSymbol(s) =>
-            Tree::group(default(), default(), Some(token.with_variant(s))).with_error("Unmatched delimiter"),
+            Tree::group(default(), default(), Some(token.with_variant(s))).with_error(ErrorCode::UnmatchedDelimiter),
         // These should be unreachable: They are handled when assembling items into blocks,
         // before parsing proper.
         token::Variant::Newline(_)
@@ -992,7 +1024,7 @@ pub fn to_ast(token: Token) -> Tree {
             let message = format!("Unexpected token: {token:?}");
             let ident = token::variant::Ident(false, 0, false, false, false);
             let value = Tree::ident(token.with_variant(ident));
-            Tree::with_error(value, message)
+            Tree::with_error(value, Error::new(ErrorCode::UnexpectedToken, message))
         }
     }
 }
@@ -1207,6 +1239,7 @@ macro_rules! spanless_leaf_impls {
 spanless_leaf_impls!(u32);
 spanless_leaf_impls!(bool);
 spanless_leaf_impls!(VisibleOffset);
+spanless_leaf_impls!(ErrorCode);
 
 
 // === TreeVisitable special cases ===
@@ -1341,7 +1374,8 @@ impl<'s, 'a> ItemVisitor<'s, 'a> for CodePrinterVisitor {
 }
 
 impl<'s> Tree<'s> {
-    /// Code generator of this AST.
+    /// Code generator of this AST. For a tree produced by the parser, the result is exactly the
+    /// parsed source code; see [`lossless`].
     pub fn code(&self) -> String {
         let mut visitor = CodePrinterVisitor::default();
         self.visit_item(&mut visitor);
@@ -87,7 +87,7 @@ where I: Iterator<Item = Line<'s>>
             match line.expression.map(Prefix::try_from) {
                 Some(Ok(prefix)) => {
                     match self.prefixes.last_mut() {
-                        Some(prefix) => prefix.newlines().push(line.newline),
+                        Some(prefix) => prefix.push_newline(line.newline),
                         None => self.newline = Some(line.newline),
                     };
                     self.prefixes.push(prefix);
@@ -95,7 +95,7 @@ where I: Iterator<Item = Line<'s>>
                 Some(Err(mut statement)) => {
                     return Some(match self.prefixes.last_mut() {
                         Some(prefix) => {
-                            prefix.newlines().push(line.newline);
+                            prefix.push_newline(line.newline);
                             for prefix in self.prefixes.drain(..).rev() {
                                 statement = prefix.apply_to(statement);
                             }
@@ -107,7 +107,7 @@ where I: Iterator<Item = Line<'s>>
                 }
                 None => {
                     match self.prefixes.last_mut() {
-                        Some(prefix) => prefix.newlines().push(line.newline),
+                        Some(prefix) => prefix.push_newline(line.newline),
                         None => return Some(line.newline.into()),
                     };
                 }
@@ -153,23 +153,31 @@ impl<'s> TryFrom<Tree<'s>> for Prefix<'s> {
 }
 
 impl<'s> Prefix<'s> {
-    fn newlines(&mut self) -> &mut Vec<token::Newline<'s>> {
+    fn newlines_and_span(&mut self) -> (&mut Vec<token::Newline<'s>>, &mut Span<'s>) {
         match self {
-            Prefix::Annotation { node: Annotated { newlines, .. }, .. }
-            | Prefix::BuiltinAnnotation { node: AnnotatedBuiltin { newlines, .. }, .. }
+            Prefix::Annotation { node: Annotated { newlines, .. }, span }
+            | Prefix::BuiltinAnnotation { node: AnnotatedBuiltin { newlines, .. }, span }
             | Prefix::Documentation {
                 node: Documented { documentation: DocComment { newlines, .. }, .. },
-                ..
-            } => newlines,
+                span,
+            } => (newlines, span),
         }
     }
 
-    fn apply_to(mut self, expression: Tree<'s>) -> Tree<'s> {
-        *(match &mut self {
-            Prefix::Annotation { node, .. } => &mut node.expression,
-            Prefix::BuiltinAnnotation { node, .. } => &mut node.expression,
-            Prefix::Documentation { node, .. } => &mut node.expression,
-        }) = Some(expression);
+    fn push_newline(&mut self, mut newline: token::Newline<'s>) {
+        let (newlines, span) = self.newlines_and_span();
+        *span = mem::take(span).add(&mut newline);
+        newlines.push(newline);
+    }
+
+    fn apply_to(mut self, mut expression: Tree<'s>) -> Tree<'s> {
+        let (expression_, span) = match &mut self {
+            Prefix::Annotation { node, span } => (&mut node.expression, span),
+            Prefix::BuiltinAnnotation { node, span } => (&mut node.expression, span),
+            Prefix::Documentation { node, span } => (&mut node.expression, span),
+        };
+        *span = mem::take(span).add(&mut expression);
+        *expression_ = Some(expression);
         self.into()
     }
 }
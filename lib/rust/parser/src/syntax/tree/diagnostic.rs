@@ -0,0 +1,503 @@
+//! Machine-readable diagnostics of the errors found in a [`Tree`].
+//!
+//! Every error the parser can produce is identified by an [`ErrorCode`]. The diagnostics of a tree
+//! are obtained with [`Tree::diagnostics`]; each of them locates the erroneous code, optionally
+//! points at related code with secondary labels, and may suggest a fix as a set of source edits.
+//! Locations are expressed both in UTF-8 bytes and in UTF-16 code units, so that they can be used
+//! directly by clients on either side of the JNI boundary.
+
+use crate::syntax::tree::*;
+
+use strum::EnumIter;
+
+
+
+// =================
+// === ErrorCode ===
+// =================
+
+/// Identifies a kind of parse error. All the kinds are listed by `ErrorCode::iter()`.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq, Hash, Serialize, Reflect, Deserialize)]
+#[derive(EnumIter)]
+#[allow(missing_docs)]
+pub enum ErrorCode {
+    /// An error that has not been assigned a more specific code.
+    #[default]
+    Other,
+    Internal,
+    UnexpectedToken,
+    UnmatchedDelimiter,
+    InvalidUseOfSpecialOperator,
+    TypeAnnotationOperands,
+    MissingOperands,
+    MissingOperand,
+    UnaryOperatorSpacing,
+    MultipleOperators,
+    UnexpectedMacroTokens,
+    InvalidMacroInvocation,
+    ExpectedImportTarget,
+    ExpectedExportTarget,
+    ExpectedTypeName,
+    InvalidCaseExpression,
+    MalformedSequence,
+    ExpectedForeignLanguage,
+    ExpectedForeignName,
+    ExpectedForeignFunction,
+    ExpectedForeignBody,
+    ExpectedIdentifier,
+    ExpectedQualifiedName,
+    ExpectedTokens,
+}
+
+impl ErrorCode {
+    /// A stable, human-readable identifier of the error kind.
+    pub fn id(self) -> &'static str {
+        match self {
+            ErrorCode::Other => "other",
+            ErrorCode::Internal => "internal",
+            ErrorCode::UnexpectedToken => "unexpected-token",
+            ErrorCode::UnmatchedDelimiter => "unmatched-delimiter",
+            ErrorCode::InvalidUseOfSpecialOperator => "invalid-use-of-special-operator",
+            ErrorCode::TypeAnnotationOperands => "type-annotation-operands",
+            ErrorCode::MissingOperands => "missing-operands",
+            ErrorCode::MissingOperand => "missing-operand",
+            ErrorCode::UnaryOperatorSpacing => "unary-operator-spacing",
+            ErrorCode::MultipleOperators => "multiple-operators",
+            ErrorCode::UnexpectedMacroTokens => "unexpected-macro-tokens",
+            ErrorCode::InvalidMacroInvocation => "invalid-macro-invocation",
+            ErrorCode::ExpectedImportTarget => "expected-import-target",
+            ErrorCode::ExpectedExportTarget => "expected-export-target",
+            ErrorCode::ExpectedTypeName => "expected-type-name",
+            ErrorCode::InvalidCaseExpression => "invalid-case-expression",
+            ErrorCode::MalformedSequence => "malformed-sequence",
+            ErrorCode::ExpectedForeignLanguage => "expected-foreign-language",
+            ErrorCode::ExpectedForeignName => "expected-foreign-name",
+            ErrorCode::ExpectedForeignFunction => "expected-foreign-function",
+            ErrorCode::ExpectedForeignBody => "expected-foreign-body",
+            ErrorCode::ExpectedIdentifier => "expected-identifier",
+            ErrorCode::ExpectedQualifiedName => "expected-qualified-name",
+            ErrorCode::ExpectedTokens => "expected-tokens",
+        }
+    }
+
+    /// The default message describing an error of this kind.
+    pub fn message(self) -> &'static str {
+        match self {
+            ErrorCode::Other => "Syntax error.",
+            ErrorCode::Internal => "Internal error.",
+            ErrorCode::UnexpectedToken => "Unexpected token.",
+            ErrorCode::UnmatchedDelimiter => "Unmatched delimiter",
+            ErrorCode::InvalidUseOfSpecialOperator => "Invalid use of special operator.",
+            ErrorCode::TypeAnnotationOperands => "`:` operator must be applied to two operands.",
+            ErrorCode::MissingOperands => "Operator must be applied to two operands.",
+            ErrorCode::MissingOperand => "Operator must be applied to an operand.",
+            ErrorCode::UnaryOperatorSpacing =>
+                "Space required between term and unary-operator expression.",
+            ErrorCode::MultipleOperators => "Multiple operators found next to each other.",
+            ErrorCode::UnexpectedMacroTokens => "Unexpected tokens in macro invocation.",
+            ErrorCode::InvalidMacroInvocation => "Invalid macro invocation.",
+            ErrorCode::ExpectedImportTarget =>
+                "Expected name or `all` keyword following `import` keyword.",
+            ErrorCode::ExpectedExportTarget =>
+                "Expected name or `all` keyword following `export` keyword.",
+            ErrorCode::ExpectedTypeName => "Expected identifier after `type` keyword.",
+            ErrorCode::InvalidCaseExpression => "Invalid case expression.",
+            ErrorCode::MalformedSequence => "Malformed comma-delimited sequence.",
+            ErrorCode::ExpectedForeignLanguage =>
+                "Expected an identifier specifying foreign method's language.",
+            ErrorCode::ExpectedForeignName =>
+                "Expected an identifier specifying foreign function's name.",
+            ErrorCode::ExpectedForeignFunction =>
+                "Expected a function definition after foreign declaration.",
+            ErrorCode::ExpectedForeignBody =>
+                "Expected a text literal as body of `foreign` declaration.",
+            ErrorCode::ExpectedIdentifier => "Expected identifier.",
+            ErrorCode::ExpectedQualifiedName => "Expected qualified name.",
+            ErrorCode::ExpectedTokens => "Expected tokens.",
+        }
+    }
+}
+
+impl Display for ErrorCode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.id())
+    }
+}
+
+
+
+// ================
+// === Location ===
+// ================
+
+/// A position in source code.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq, Hash, Serialize, Reflect, Deserialize)]
+pub struct Location {
+    /// Offset from the beginning of the code, in UTF-8 bytes.
+    pub utf8:  usize,
+    /// Offset from the beginning of the code, in UTF-16 code units.
+    pub utf16: usize,
+}
+
+impl Add<code::Length> for Location {
+    type Output = Location;
+
+    fn add(self, rhs: code::Length) -> Self::Output {
+        let utf8 = self.utf8 + rhs.utf8_bytes();
+        let utf16 = self.utf16 + rhs.utf16_len();
+        Self { utf8, utf16 }
+    }
+}
+
+impl AddAssign<code::Length> for Location {
+    fn add_assign(&mut self, rhs: code::Length) {
+        *self = *self + rhs;
+    }
+}
+
+/// A range of source code.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq, Hash, Serialize, Reflect, Deserialize)]
+pub struct SourceRange {
+    /// The position of the first character of the range.
+    pub start: Location,
+    /// The position following the last character of the range.
+    pub end:   Location,
+}
+
+impl SourceRange {
+    /// Constructor.
+    pub fn new(start: Location, end: Location) -> Self {
+        Self { start, end }
+    }
+
+    /// An empty range at the given location.
+    pub fn empty(at: Location) -> Self {
+        Self::new(at, at)
+    }
+
+    /// The range as byte offsets, suitable for indexing the source code.
+    pub fn utf8(&self) -> Range<usize> {
+        self.start.utf8..self.end.utf8
+    }
+}
+
+
+
+// ==================
+// === Diagnostic ===
+// ==================
+
+/// A parse error, located in the source code.
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Reflect, Deserialize)]
+pub struct Diagnostic {
+    /// The kind of error.
+    pub code:      ErrorCode,
+    /// A description of the error.
+    pub message:   String,
+    /// The erroneous code.
+    pub primary:   Label,
+    /// Other code related to the error.
+    pub secondary: Vec<Label>,
+    /// A change of the source code that would fix the error, if one can be suggested.
+    pub fix:       Option<Fix>,
+}
+
+/// A range of source code, with a description of its role in a [`Diagnostic`].
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Reflect, Deserialize)]
+pub struct Label {
+    #[allow(missing_docs)]
+    pub range:   SourceRange,
+    #[allow(missing_docs)]
+    pub message: String,
+}
+
+impl Label {
+    /// Constructor.
+    pub fn new(range: SourceRange, message: impl Into<String>) -> Self {
+        let message = message.into();
+        Self { range, message }
+    }
+}
+
+/// A suggested change of the source code.
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Reflect, Deserialize)]
+pub struct Fix {
+    /// A description of the change.
+    pub message: String,
+    /// Non-overlapping replacements, sorted by location.
+    pub edits:   Vec<Replacement>,
+}
+
+/// Replacement of a range of source code with new text.
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Reflect, Deserialize)]
+pub struct Replacement {
+    #[allow(missing_docs)]
+    pub range: SourceRange,
+    #[allow(missing_docs)]
+    pub text:  String,
+}
+
+impl Fix {
+    /// A fix consisting of a single replacement.
+    pub fn replace(
+        message: impl Into<String>,
+        range: SourceRange,
+        text: impl Into<String>,
+    ) -> Self {
+        let message = message.into();
+        let text = text.into();
+        Self { message, edits: vec![Replacement { range, text }] }
+    }
+
+    /// Apply the fix to the code it was suggested for.
+    pub fn apply(&self, code: &str) -> String {
+        let mut out = String::with_capacity(code.len());
+        let mut offset = 0;
+        for Replacement { range, text } in &self.edits {
+            out.push_str(&code[offset..range.start.utf8]);
+            out.push_str(text);
+            offset = range.end.utf8;
+        }
+        out.push_str(&code[offset..]);
+        out
+    }
+}
+
+
+
+// =================
+// === Collector ===
+// =================
+
+impl<'s> Tree<'s> {
+    /// Return diagnostics for all the errors in the tree, in the order of their locations.
+    pub fn diagnostics(&self) -> Vec<Diagnostic> {
+        let mut collector = DiagnosticCollector::default();
+        self.visit_item(&mut collector);
+        collector.diagnostics
+    }
+}
+
+/// A visitor tracking the location of each item, and collecting diagnostics of errors.
+#[derive(Debug, Default)]
+struct DiagnosticCollector {
+    offset:      Location,
+    diagnostics: Vec<Diagnostic>,
+}
+
+impl Visitor for DiagnosticCollector {}
+impl<'s, 'a> ItemVisitor<'s, 'a> for DiagnosticCollector {
+    fn visit_item(&mut self, item: item::Ref<'s, 'a>) -> bool {
+        match item {
+            item::Ref::Tree(tree) => {
+                self.offset += tree.span.left_offset.code.length();
+                let range = SourceRange::new(self.offset, self.offset + tree.span.code_length);
+                match &*tree.variant {
+                    // An error wrapping a more specific error is reported as the inner error.
+                    Variant::Invalid(Invalid { ast, .. })
+                        if matches!(&*ast.variant, Variant::Invalid(_)) => {}
+                    Variant::Invalid(Invalid { error, ast }) =>
+                        self.diagnostics.push(invalid_diagnostic(error, ast, range)),
+                    Variant::OprApp(OprApp { lhs, opr: Err(error), .. }) => {
+                        let start = operator_start(range.start, lhs);
+                        self.diagnostics.push(multiple_operators_diagnostic(error, start));
+                    }
+                    _ => {}
+                }
+            }
+            item::Ref::Token(token) => {
+                self.offset += token.left_offset.code.length();
+                self.offset += token.code.length();
+            }
+        }
+        true
+    }
+}
+
+fn invalid_diagnostic(error: &Error, ast: &Tree, range: SourceRange) -> Diagnostic {
+    let code = error.code;
+    let message = error.message.to_string();
+    let mut primary = Label::new(range, code.message());
+    let mut secondary = vec![];
+    let mut fix = None;
+    match (code, &*ast.variant) {
+        (ErrorCode::UnmatchedDelimiter, Variant::Group(Group { open: Some(open), .. })) => {
+            let close = match &*open.code.repr {
+                "(" => ")",
+                "[" => "]",
+                "{" => "}",
+                _ => "",
+            };
+            if !close.is_empty() {
+                let at = SourceRange::empty(range.end);
+                fix = Some(Fix::replace("Insert the closing delimiter.", at, close));
+            }
+        }
+        (ErrorCode::UnmatchedDelimiter, Variant::Group(Group { close: Some(_), .. })) =>
+            fix = Some(Fix::replace("Remove the delimiter.", range, "")),
+        (ErrorCode::UnaryOperatorSpacing, Variant::UnaryOprApp(UnaryOprApp { opr, .. })) => {
+            let at = SourceRange::empty(range.start);
+            secondary.push(Label::new(token_range(range.start, opr), "Unary operator."));
+            fix = Some(Fix::replace("Insert a space before the operator.", at, " "));
+        }
+        (
+            ErrorCode::InvalidUseOfSpecialOperator
+            | ErrorCode::TypeAnnotationOperands
+            | ErrorCode::MissingOperands,
+            Variant::OprApp(OprApp { lhs, opr: Ok(opr), .. }),
+        ) => {
+            let start = operator_start(range.start, lhs);
+            secondary.push(Label::new(token_range(start, opr), "Operator."));
+        }
+        (ErrorCode::MissingOperand, Variant::UnaryOprApp(UnaryOprApp { opr, .. })) =>
+            secondary.push(Label::new(token_range(range.start, opr), "Operator.")),
+        (ErrorCode::MultipleOperators, Variant::UnaryOprApp(UnaryOprApp { opr, .. })) => {
+            let opr = token_range(range.start, opr);
+            primary = Label::new(opr, "Unexpected operator.");
+            fix = Some(Fix::replace("Remove the unexpected operator.", opr, ""));
+        }
+        _ => {}
+    }
+    Diagnostic { code, message, primary, secondary, fix }
+}
+
+fn multiple_operators_diagnostic(error: &MultipleOperatorError, start: Location) -> Diagnostic {
+    let code = ErrorCode::MultipleOperators;
+    let message = code.message().to_string();
+    let mut ranges = vec![];
+    let mut offset = start;
+    for operator in error.operators.iter() {
+        let range = token_range(offset, operator);
+        offset = range.end;
+        ranges.push(range);
+    }
+    let first = ranges[0];
+    let rest = SourceRange::new(ranges.get(1).map_or(offset, |range| range.start), offset);
+    let primary = Label::new(rest, "Unexpected operators.");
+    let secondary = vec![Label::new(first, "Operator.")];
+    let fix = Some(Fix::replace("Remove the unexpected operators.", rest, ""));
+    Diagnostic { code, message, primary, secondary, fix }
+}
+
+/// Return the range of the code of a token, given the location of its left offset.
+fn token_range<T>(offset: Location, token: &Token<'_, T>) -> SourceRange {
+    let start = offset + token.left_offset.code.length();
+    SourceRange::new(start, start + token.code.length())
+}
+
+/// Return the location of the left offset of the operator of an operator application, given the
+/// location of the application and its left operand.
+fn operator_start(start: Location, lhs: &Option<Tree>) -> Location {
+    match lhs {
+        Some(lhs) => start + lhs.span.left_offset.code.length() + lhs.span.code_length,
+        None => start,
+    }
+}
+
+
+
+// =============
+// === Tests ===
+// =============
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use strum::IntoEnumIterator;
+
+    fn diagnostics(code: &str) -> Vec<Diagnostic> {
+        let tree = crate::Parser::new().run(code);
+        tree.diagnostics()
+    }
+
+    #[test]
+    fn valid_code_has_no_diagnostics() {
+        assert_eq!(diagnostics("main =\n    x = foo (1 + 2)\n    x\n"), vec![]);
+    }
+
+    #[test]
+    fn unmatched_open_delimiter() {
+        let code = "x = (foo";
+        let found = diagnostics(code);
+        assert_eq!(found.len(), 1);
+        let diagnostic = &found[0];
+        assert_eq!(diagnostic.code, ErrorCode::UnmatchedDelimiter);
+        assert_eq!(&code[diagnostic.primary.range.utf8()], "(");
+        let fixed = diagnostic.fix.as_ref().unwrap().apply(code);
+        assert_eq!(fixed, "x = ()foo");
+    }
+
+    #[test]
+    fn multiple_operators() {
+        let code = "x + + x";
+        let found = diagnostics(code);
+        assert_eq!(found.len(), 1);
+        let diagnostic = &found[0];
+        assert_eq!(diagnostic.code, ErrorCode::MultipleOperators);
+        assert_eq!(diagnostic.primary.range.utf8(), 4..5);
+        assert_eq!(diagnostic.secondary[0].range.utf8(), 2..3);
+        let fixed = diagnostic.fix.as_ref().unwrap().apply(code);
+        assert!(crate::Parser::new().run(&fixed).diagnostics().is_empty());
+    }
+
+    #[test]
+    fn operator_after_assignment() {
+        let code = "main =\n    x = * 2\n    y = 2\n";
+        let found = diagnostics(code);
+        assert_eq!(found.len(), 1);
+        let diagnostic = &found[0];
+        assert_eq!(diagnostic.code, ErrorCode::MultipleOperators);
+        assert_eq!(&code[diagnostic.primary.range.utf8()], "*");
+        let fixed = diagnostic.fix.as_ref().unwrap().apply(code);
+        assert!(crate::Parser::new().run(&fixed).diagnostics().is_empty());
+    }
+
+    #[test]
+    fn errors_are_isolated() {
+        // Each code contains one error, reported for the given part of the code only.
+        let cases = [
+            // Macro resolution is limited to a line.
+            ("main =\n    x = if a\n    y = if b then c else d\n", "if"),
+            // A token expected by an enclosing macro ends the nested invocations.
+            ("x = foo (if a) bar", "if"),
+            ("x = [1, if a] + 2", "if"),
+            // Only the invalid line of a `case` block is marked.
+            ("case x of\n    foo\n    Bar -> y\n", "foo"),
+            // An operator following an assignment is invalid within the assigned expression.
+            ("main =\n    x = , 1\n    y = 2\n", ","),
+        ];
+        for (code, error) in cases {
+            let found = diagnostics(code);
+            let errors = found.iter().map(|d| &code[d.primary.range.utf8()]).collect_vec();
+            assert_eq!(errors, [error], "{code:?}");
+        }
+    }
+
+    #[test]
+    fn utf16_locations() {
+        let code = "é = 'ā' )";
+        let found = diagnostics(code);
+        let diagnostic = found.iter().find(|d| d.code == ErrorCode::UnmatchedDelimiter).unwrap();
+        let start = diagnostic.primary.range.start;
+        assert_eq!(&code[diagnostic.primary.range.utf8()], ")");
+        assert_eq!(start.utf16, code[..start.utf8].encode_utf16().count());
+    }
+
+    #[test]
+    fn error_codes_are_serialized() {
+        let tree = crate::Parser::new().run("x = (foo\ny = + +\n");
+        let data = crate::serialization::serialize_tree(&tree).unwrap();
+        let restored = crate::serialization::deserialize_tree(&data).unwrap();
+        let codes = |tree: &Tree| tree.diagnostics().into_iter().map(|d| d.code).collect_vec();
+        assert!(!codes(&tree).is_empty());
+        assert_eq!(codes(&restored), codes(&tree));
+    }
+
+    #[test]
+    fn error_codes_are_distinct() {
+        let codes = ErrorCode::iter().collect_vec();
+        let ids: HashSet<_> = codes.iter().map(|code| code.id()).collect();
+        assert_eq!(ids.len(), codes.len());
+    }
+}
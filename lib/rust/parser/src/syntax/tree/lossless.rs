@@ -0,0 +1,232 @@
+//! Verification that a [`Tree`] represents its source code losslessly.
+//!
+//! A tree produced by the parser must contain every byte of its input: printing the tree with
+//! [`Tree::code`] must reproduce the input exactly, including whitespace and comments, and the span
+//! of every node must cover exactly the code of its contents. Tools that edit code through the
+//! tree, such as the GUI's double representation, rely on this. [`Tree::check_code`] reports any
+//! way in which a tree fails to meet these requirements.
+
+use crate::prelude::*;
+
+use crate::source::Code;
+use crate::syntax::item;
+use crate::syntax::tree::ItemVisitable;
+use crate::syntax::tree::ItemVisitor;
+use crate::syntax::tree::Visitor;
+use crate::syntax::Tree;
+
+
+
+// ===================
+// === Discrepancy ===
+// ===================
+
+/// A way in which a tree does not represent its source code losslessly. Offsets are in bytes, from
+/// the start of the source code.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Discrepancy {
+    /// The code printed from the tree differs from the source code, starting at the given offset.
+    Code {
+        /// The offset of the first byte that is not reproduced.
+        offset:   usize,
+        /// The source code starting at the offset, up to the end of the line.
+        expected: String,
+        /// The printed code starting at the offset, up to the end of the line.
+        found:    String,
+    },
+    /// The span of a node does not match the code of its contents.
+    Span {
+        /// The offset of the node's code, not including its left offset.
+        offset:   usize,
+        /// The length of the node's code, according to its span; in UTF-8 and UTF-16 code units.
+        expected: (usize, usize),
+        /// The length of the code of the node's contents; in UTF-8 and UTF-16 code units.
+        found:    (usize, usize),
+    },
+    /// A token or offset whose recorded UTF-16 length does not match its code.
+    Utf16 {
+        /// The offset of the code.
+        offset:   usize,
+        /// The UTF-16 length recorded.
+        expected: usize,
+        /// The UTF-16 length of the code.
+        found:    usize,
+    },
+}
+
+impl Discrepancy {
+    /// The offset in the source code at which the discrepancy was found.
+    pub fn offset(&self) -> usize {
+        match self {
+            Discrepancy::Code { offset, .. }
+            | Discrepancy::Span { offset, .. }
+            | Discrepancy::Utf16 { offset, .. } => *offset,
+        }
+    }
+}
+
+impl Display for Discrepancy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Discrepancy::Code { expected, found, .. } =>
+                write!(f, "Code not reproduced: expected {expected:?}, found {found:?}."),
+            Discrepancy::Span { expected, found, .. } => write!(
+                f,
+                "Span of length {expected:?} (UTF-8, UTF-16) covers code of length {found:?}."
+            ),
+            Discrepancy::Utf16 { expected, found, .. } =>
+                write!(f, "Recorded UTF-16 length {expected} differs from actual length {found}."),
+        }
+    }
+}
+
+
+
+// ===============
+// === Checker ===
+// ===============
+
+impl<'s> Tree<'s> {
+    /// Check that the tree represents the given source code losslessly: that [`Tree::code`]
+    /// reproduces it exactly, and that the spans of all nodes are consistent with their contents.
+    /// Returns all the discrepancies found; the result is empty for any tree produced by
+    /// [`crate::Parser::run`] from the same source code.
+    pub fn check_code(&self, source: &str) -> Vec<Discrepancy> {
+        let mut checker = Checker::default();
+        self.visit_item(&mut checker);
+        let Checker { code, mut discrepancies, .. } = checker;
+        if code != source {
+            let offset = common_prefix_len(&code, source);
+            let line_from = |s: &str| s[offset..].lines().next().unwrap_or_default().to_owned();
+            let expected = line_from(source);
+            let found = line_from(&code);
+            discrepancies.push(Discrepancy::Code { offset, expected, found });
+        }
+        discrepancies.sort_by_key(Discrepancy::offset);
+        discrepancies
+    }
+}
+
+/// Prints the code of a tree, checking the consistency of each node's span with its contents.
+#[derive(Debug, Default)]
+struct Checker {
+    code:          String,
+    utf16:         usize,
+    discrepancies: Vec<Discrepancy>,
+}
+
+impl Checker {
+    fn push(&mut self, code: &Code) {
+        let utf16 = code.repr.encode_utf16().count();
+        if utf16 != code.utf16 {
+            let offset = self.code.len();
+            let expected = code.utf16;
+            self.discrepancies.push(Discrepancy::Utf16 { offset, expected, found: utf16 });
+        }
+        self.code.push_str(&code.repr);
+        self.utf16 += utf16;
+    }
+}
+
+impl Visitor for Checker {}
+impl<'s, 'a> ItemVisitor<'s, 'a> for Checker {
+    fn visit_item(&mut self, item: item::Ref<'s, 'a>) -> bool {
+        match item {
+            item::Ref::Tree(tree) => {
+                self.push(&tree.span.left_offset.code);
+                let start = (self.code.len(), self.utf16);
+                tree.variant.visit_item(self);
+                let found = (self.code.len() - start.0, self.utf16 - start.1);
+                let length = tree.span.code_length;
+                let expected = (length.utf8_bytes(), length.utf16_len());
+                if found != expected {
+                    let offset = start.0;
+                    self.discrepancies.push(Discrepancy::Span { offset, expected, found });
+                }
+                // The contents have been visited above.
+                false
+            }
+            item::Ref::Token(token) => {
+                self.push(&token.left_offset.code);
+                self.push(token.code);
+                true
+            }
+        }
+    }
+}
+
+/// The length in bytes of the longest common prefix of the inputs that ends at a char boundary.
+fn common_prefix_len(a: &str, b: &str) -> usize {
+    let mut len = a.bytes().zip(b.bytes()).take_while(|(a, b)| a == b).count();
+    while !a.is_char_boundary(len) || !b.is_char_boundary(len) {
+        len -= 1;
+    }
+    len
+}
+
+
+
+// =============
+// === Tests ===
+// =============
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parsed_code_is_lossless() {
+        let cases = [
+            "",
+            "main = 42",
+            "  indented = 1  \n\n\n",
+            "main =\n    # Comment.  \n    x = 1 +   2\n\n    x\n",
+            "## Documentation\n   continued.\nfoo a b = a\n",
+            "type T\n    A x\n\n    m self = 'text\n        more'\n",
+            "tabs\t=\t1\nx = 'ß' + \"𝄞\"\n",
+            // Annotated, builtin-annotated and documented statements.
+            "@a x\nfoo = 1\n",
+            "main =\n    @a x\n    foo = 1\n",
+            "@Builtin_Type\ntype Foo\n",
+            "type T\n    @Builtin_Method \"a\"\n    foo = 1\n",
+            "main =\n    ## Doc.\n    foo = 1\n",
+            "## Doc.\n@a x\nfoo = 1\n",
+            // Argument and operator block applications.
+            "foo\n    a\n    b\n",
+            "main =\n    x = foo  \n        a\n        b\n",
+            "x =  \n    a\n",
+            "x = a\n    + b\n    * c\n",
+            "main =\n    a\n        + b\n",
+            // Joined text literals.
+            "x = '''\n    a\n\n    b\n",
+            "x = 'a\n    '\n",
+            "x = \"\"\"\n\n",
+            // Nodes absorbing the following tokens.
+            "foo x = @Builtin_Method \"foo\"\nbar = 1\n",
+            "e = 2.718\nx = 0x1F\n",
+            "main = case x of\n    ## Doc.\n    A -> 1\n    _ -> 2\n",
+        ];
+        let parser = crate::Parser::new();
+        for code in cases {
+            let tree = parser.run(code);
+            assert_eq!(tree.check_code(code), vec![], "{code:?}");
+        }
+    }
+
+    #[test]
+    fn detects_code_mismatch() {
+        let tree = crate::Parser::new().run("main = 42\n");
+        let discrepancies = tree.check_code("main = 43\n");
+        let expected =
+            Discrepancy::Code { offset: 8, expected: "3".into(), found: "2".into() };
+        assert_eq!(discrepancies, vec![expected]);
+    }
+
+    #[test]
+    fn detects_span_mismatch() {
+        let mut tree = crate::Parser::new().run("main = 42");
+        tree.span.code_length = Code::from("main = 4").length();
+        let discrepancies = tree.check_code("main = 42");
+        assert!(matches!(discrepancies[..], [Discrepancy::Span { offset: 0, .. }]));
+    }
+}
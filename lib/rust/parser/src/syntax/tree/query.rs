@@ -0,0 +1,573 @@
+//! A pattern language for finding [`Tree`] nodes of particular shapes, with captures.
+//!
+//! A [`Query`] is written as an S-expression, similar to tree-sitter queries:
+//! ```text
+//! (OprApp lhs: _ @receiver opr: "." rhs: (Ident token: "to_text")) @call
+//! ```
+//! The patterns are:
+//! - `(Variant field: pattern ...)`: A [`Tree`] of the given [`Variant`], whose fields match the
+//!   given patterns. Fields not mentioned may contain anything. Field names are as in the Rust
+//!   definitions, without any trailing underscore (`type` rather than `type_`).
+//! - `(Variant !field ...)`: A [`Tree`] of the given [`Variant`], whose field is empty (a [`None`]
+//!   or an empty [`Vec`]).
+//! - `"code"`: A token or tree whose code, excluding its left offset, is exactly the given text.
+//!   Quotes and backslashes in the text are escaped with a backslash.
+//! - `_`: Anything.
+//! - `[pattern ...]`: Anything matching at least one of the patterns.
+//!
+//! Any pattern can be followed by one or more captures, `@name`; when the query matches, each
+//! capture names the tree or token the pattern matched.
+//!
+//! A field holding a single tree or token matches a pattern if its value does. A field holding a
+//! sequence of items, such as the lines of a block, matches a pattern if any of its items does;
+//! only the trees and tokens directly within the field are considered, not their descendants.
+
+use crate::prelude::*;
+
+use crate::syntax::item;
+use crate::syntax::tree::ItemVisitable;
+use crate::syntax::tree::ItemVisitor;
+use crate::syntax::tree::Variant;
+use crate::syntax::tree::Visitor;
+use crate::syntax::Tree;
+
+use std::ptr;
+
+
+
+// =============
+// === Query ===
+// =============
+
+/// A compiled pattern. See the module documentation for the syntax.
+#[derive(Clone, Debug)]
+pub struct Query {
+    pattern: Pattern,
+}
+
+impl Query {
+    /// Parse a query.
+    pub fn new(source: &str) -> Result<Self, Error> {
+        let mut parser = QueryParser { source, offset: 0 };
+        let pattern = parser.pattern()?;
+        if parser.peek().is_some() {
+            return Err(parser.error("Unexpected input after pattern."));
+        }
+        Ok(Self { pattern })
+    }
+
+    /// Find all the nodes of the tree matching the query, in pre-order. Spans are in bytes, from
+    /// the start of the code of the given tree (including its left offset).
+    pub fn matches<'s, 'a>(&self, tree: &'a Tree<'s>) -> Vec<Match<'s, 'a>> {
+        let mut matcher = Matcher { pattern: &self.pattern, offset: 0, matches: default() };
+        tree.visit_item(&mut matcher);
+        matcher.matches
+    }
+}
+
+/// A node matching a [`Query`].
+#[derive(Clone, Debug)]
+pub struct Match<'s, 'a> {
+    /// The node matched.
+    pub tree:     &'a Tree<'s>,
+    /// The location of the node's code, not including its left offset.
+    pub span:     Range<usize>,
+    /// The items captured, in the order their patterns completed matching.
+    pub captures: Vec<Capture<'s, 'a>>,
+}
+
+impl<'s, 'a> Match<'s, 'a> {
+    /// Return the first item captured with the given name.
+    pub fn capture(&self, name: &str) -> Option<&Capture<'s, 'a>> {
+        self.captures.iter().find(|capture| capture.name == name)
+    }
+}
+
+/// A tree or token captured by a pattern.
+#[derive(Clone, Debug)]
+pub struct Capture<'s, 'a> {
+    /// The name of the capture, without the `@`.
+    pub name: String,
+    /// The item captured.
+    pub item: item::Ref<'s, 'a>,
+    /// The location of the item's code, not including its left offset.
+    pub span: Range<usize>,
+}
+
+
+
+// ===============
+// === Pattern ===
+// ===============
+
+#[derive(Clone, Debug)]
+struct Pattern {
+    kind:     PatternKind,
+    captures: Vec<String>,
+}
+
+#[derive(Clone, Debug)]
+enum PatternKind {
+    Any,
+    Code(String),
+    Alternatives(Vec<Pattern>),
+    Node { variant: &'static str, fields: Vec<FieldPattern> },
+}
+
+/// A constraint on a field, identified by its index in the [`VARIANTS`] table.
+#[derive(Clone, Debug)]
+enum FieldPattern {
+    Matches(usize, Pattern),
+    Absent(usize),
+}
+
+impl Pattern {
+    /// Check whether the item matches. If so, its captures are appended to the given vector;
+    /// otherwise, the vector is unchanged.
+    fn matches<'q, 's, 'a>(
+        &'q self,
+        item: item::Ref<'s, 'a>,
+        captures: &mut Vec<(&'q str, item::Ref<'s, 'a>)>,
+    ) -> bool {
+        let checkpoint = captures.len();
+        let matched = match &self.kind {
+            PatternKind::Any => true,
+            PatternKind::Code(code) => match item {
+                // Building the code of a tree is costly, so it is only done for trees of the
+                // right length.
+                item::Ref::Tree(tree) => {
+                    let length = tree.span.code_length.utf8_bytes();
+                    length == code.len() && tree.trimmed_code() == *code
+                }
+                item::Ref::Token(token) => token.code.repr == *code,
+            },
+            PatternKind::Alternatives(alternatives) =>
+                alternatives.iter().any(|pattern| pattern.matches(item, captures)),
+            PatternKind::Node { variant, fields } => match item {
+                item::Ref::Tree(tree) if variant_name(&tree.variant) == *variant => {
+                    let values = field_items(&tree.variant);
+                    fields.iter().all(|field| match field {
+                        FieldPattern::Absent(index) => values[*index].is_empty(),
+                        FieldPattern::Matches(index, pattern) =>
+                            values[*index].iter().any(|value| pattern.matches(*value, captures)),
+                    })
+                }
+                _ => false,
+            },
+        };
+        if matched {
+            captures.extend(self.captures.iter().map(|name| (name.as_str(), item)));
+        } else {
+            captures.truncate(checkpoint);
+        }
+        matched
+    }
+}
+
+
+
+// ==================
+// === Reflection ===
+// ==================
+
+macro_rules! generate_variant_reflection {
+    (
+        $(#$enum_meta:tt)*
+        pub enum $enum:ident<'s> {
+            $(
+                $(#$variant_meta:tt)*
+                $variant:ident $({$($(#$field_meta:tt)* pub $field:ident : $field_ty:ty),* $(,)? })?
+            ),* $(,)?
+        }
+    ) => {
+        /// The names of the [`Variant`]s, and of their fields.
+        const VARIANTS: &[(&str, &[&str])] =
+            &[$((stringify!($variant), &[$($(stringify!($field)),*)?])),*];
+
+        fn variant_name(variant: &Variant) -> &'static str {
+            match variant {
+                $(Variant::$variant(_) => stringify!($variant),)*
+            }
+        }
+
+        /// The items directly within each field of the variant, in the order of [`VARIANTS`].
+        #[allow(unused_variables)]
+        fn field_items<'s, 'a>(variant: &'a Variant<'s>) -> Vec<Vec<item::Ref<'s, 'a>>> {
+            match variant {
+                $(Variant::$variant(node) => vec![$($(items(&node.$field)),*)?],)*
+            }
+        }
+    };
+}
+
+crate::with_ast_definition!(generate_variant_reflection());
+
+/// Return the trees and tokens directly within the value.
+fn items<'s, 'a>(value: &'a impl ItemVisitable<'s, 'a>) -> Vec<item::Ref<'s, 'a>> {
+    struct Items<'s, 'a>(Vec<item::Ref<'s, 'a>>);
+    impl<'s, 'a> Visitor for Items<'s, 'a> {}
+    impl<'s, 'a> ItemVisitor<'s, 'a> for Items<'s, 'a> {
+        fn visit_item(&mut self, item: item::Ref<'s, 'a>) -> bool {
+            self.0.push(item);
+            false
+        }
+    }
+    let mut visitor = Items(vec![]);
+    value.visit_item(&mut visitor);
+    visitor.0
+}
+
+
+
+// ================
+// === Matching ===
+// ================
+
+/// Tries to match every node of a tree, tracking the offset of each.
+#[derive(Debug)]
+struct Matcher<'q, 's, 'a> {
+    pattern: &'q Pattern,
+    offset:  usize,
+    matches: Vec<Match<'s, 'a>>,
+}
+
+impl<'q, 's, 'a> Visitor for Matcher<'q, 's, 'a> {}
+impl<'q, 's, 'a> ItemVisitor<'s, 'a> for Matcher<'q, 's, 'a> {
+    fn visit_item(&mut self, item: item::Ref<'s, 'a>) -> bool {
+        match item {
+            item::Ref::Tree(tree) => {
+                self.offset += tree.span.left_offset.code.repr.len();
+                let start = self.offset;
+                let span = start..start + tree.span.code_length.utf8_bytes();
+                let mut captured = vec![];
+                if self.pattern.matches(item, &mut captured) {
+                    let captures = captured
+                        .into_iter()
+                        .map(|(name, item)| Capture {
+                            name: name.to_owned(),
+                            item,
+                            span: locate(tree, start, item),
+                        })
+                        .collect();
+                    self.matches.push(Match { tree, span: span.clone(), captures });
+                }
+                tree.variant.visit_item(self);
+                self.offset = span.end;
+                // The contents have been visited above.
+                false
+            }
+            item::Ref::Token(token) => {
+                self.offset += token.left_offset.code.repr.len() + token.code.repr.len();
+                true
+            }
+        }
+    }
+}
+
+/// Return the span of an item within a tree whose code starts at the given offset.
+fn locate<'s, 'a>(tree: &'a Tree<'s>, start: usize, target: item::Ref<'s, 'a>) -> Range<usize> {
+    let end = start + tree.span.code_length.utf8_bytes();
+    if let item::Ref::Tree(target) = target && ptr::eq(tree, target) {
+        return start..end;
+    }
+    let mut locator = Locator { target, offset: start, found: None };
+    tree.variant.visit_item(&mut locator);
+    locator.found.unwrap_or(start..end)
+}
+
+/// Finds the span of an item, identified by its address.
+#[derive(Debug)]
+struct Locator<'s, 'a> {
+    target: item::Ref<'s, 'a>,
+    offset: usize,
+    found:  Option<Range<usize>>,
+}
+
+impl<'s, 'a> Visitor for Locator<'s, 'a> {}
+impl<'s, 'a> ItemVisitor<'s, 'a> for Locator<'s, 'a> {
+    fn visit_item(&mut self, item: item::Ref<'s, 'a>) -> bool {
+        if self.found.is_some() {
+            return false;
+        }
+        match item {
+            item::Ref::Tree(tree) => {
+                self.offset += tree.span.left_offset.code.repr.len();
+                let start = self.offset;
+                let end = start + tree.span.code_length.utf8_bytes();
+                match self.target {
+                    item::Ref::Tree(target) if ptr::eq(tree, target) =>
+                        self.found = Some(start..end),
+                    _ => tree.variant.visit_item(self),
+                }
+                self.offset = end;
+                false
+            }
+            item::Ref::Token(token) => {
+                self.offset += token.left_offset.code.repr.len();
+                let start = self.offset;
+                self.offset += token.code.repr.len();
+                if let item::Ref::Token(target) = self.target
+                    && ptr::eq(token.code, target.code) {
+                    self.found = Some(start..self.offset);
+                }
+                true
+            }
+        }
+    }
+}
+
+
+
+// ===============
+// === Parsing ===
+// ===============
+
+#[derive(Debug)]
+struct QueryParser<'q> {
+    source: &'q str,
+    offset: usize,
+}
+
+impl<'q> QueryParser<'q> {
+    fn pattern(&mut self) -> Result<Pattern, Error> {
+        let kind = match self.peek() {
+            Some('(') => {
+                self.offset += 1;
+                self.node()?
+            }
+            Some('[') => {
+                self.offset += 1;
+                let mut alternatives = vec![self.pattern()?];
+                while !self.eat(']') {
+                    alternatives.push(self.pattern()?);
+                }
+                PatternKind::Alternatives(alternatives)
+            }
+            Some('"') => PatternKind::Code(self.string()?),
+            _ => match self.name() {
+                Some("_") => PatternKind::Any,
+                _ => return Err(self.error("Expected a pattern.")),
+            },
+        };
+        let mut captures = vec![];
+        while self.eat('@') {
+            let name = self.name().ok_or_else(|| self.error("Expected a capture name."))?;
+            captures.push(name.to_owned());
+        }
+        Ok(Pattern { kind, captures })
+    }
+
+    fn node(&mut self) -> Result<PatternKind, Error> {
+        let name = self.name().ok_or_else(|| self.error("Expected a variant name."))?;
+        let &(variant, field_names) = VARIANTS
+            .iter()
+            .find(|(variant, _)| *variant == name)
+            .ok_or_else(|| Error::UnknownVariant(name.to_owned()))?;
+        let mut fields = vec![];
+        while !self.eat(')') {
+            let absent = self.eat('!');
+            let name = self.name().ok_or_else(|| self.error("Expected a field name or `)`."))?;
+            let index = field_names
+                .iter()
+                .position(|field| field.trim_end_matches('_') == name)
+                .ok_or_else(|| Error::UnknownField { variant, field: name.to_owned() })?;
+            if absent {
+                fields.push(FieldPattern::Absent(index));
+            } else {
+                if !self.eat(':') {
+                    return Err(self.error("Expected `:`."));
+                }
+                fields.push(FieldPattern::Matches(index, self.pattern()?));
+            }
+        }
+        Ok(PatternKind::Node { variant, fields })
+    }
+
+    fn string(&mut self) -> Result<String, Error> {
+        let start = self.offset;
+        let mut string = String::new();
+        let mut chars = self.source[start + 1..].char_indices();
+        while let Some((i, char)) = chars.next() {
+            match char {
+                '"' => {
+                    self.offset = start + 1 + i + 1;
+                    return Ok(string);
+                }
+                '\\' => match chars.next() {
+                    Some((_, char @ ('"' | '\\'))) => string.push(char),
+                    _ => {
+                        self.offset = start + 1 + i;
+                        return Err(self.error("Invalid escape sequence."));
+                    }
+                },
+                _ => string.push(char),
+            }
+        }
+        self.offset = start;
+        Err(self.error("Unterminated string."))
+    }
+
+    fn name(&mut self) -> Option<&'q str> {
+        self.skip_whitespace();
+        let rest = &self.source[self.offset..];
+        let length = rest.find(|c: char| !(c.is_ascii_alphanumeric() || c == '_'));
+        let name = &rest[..length.unwrap_or(rest.len())];
+        self.offset += name.len();
+        (!name.is_empty()).then_some(name)
+    }
+
+    fn eat(&mut self, char: char) -> bool {
+        let matches = self.peek() == Some(char);
+        if matches {
+            self.offset += char.len_utf8();
+        }
+        matches
+    }
+
+    fn peek(&mut self) -> Option<char> {
+        self.skip_whitespace();
+        self.source[self.offset..].chars().next()
+    }
+
+    fn skip_whitespace(&mut self) {
+        let rest = &self.source[self.offset..];
+        self.offset += rest.len() - rest.trim_start().len();
+    }
+
+    fn error(&self, message: &'static str) -> Error {
+        Error::Syntax { offset: self.offset, message }
+    }
+}
+
+
+
+// =============
+// === Error ===
+// =============
+
+/// A reason a query could not be parsed.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Error {
+    /// The query is not well-formed.
+    Syntax {
+        /// The offset in the query, in bytes, at which the error was found.
+        offset:  usize,
+        /// A description of the error.
+        message: &'static str,
+    },
+    /// A node pattern names a type that is not a [`Variant`].
+    UnknownVariant(String),
+    /// A node pattern names a field that its [`Variant`] does not have.
+    UnknownField {
+        /// The variant of the node pattern.
+        variant: &'static str,
+        /// The name of the field.
+        field:   String,
+    },
+}
+
+impl Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::Syntax { offset, message } => write!(f, "At offset {offset}: {message}"),
+            Error::UnknownVariant(name) => write!(f, "Unknown tree variant: {name}."),
+            Error::UnknownField { variant, field } =>
+                write!(f, "Tree variant {variant} has no field {field}."),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+
+
+// =============
+// === Tests ===
+// =============
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Return the code of each match, and of each of its captures.
+    fn find(query: &str, code: &str) -> Vec<(String, Vec<(String, String)>)> {
+        let tree = crate::Parser::new().run(code);
+        let query = Query::new(query).unwrap();
+        let matches = query.matches(&tree);
+        let captured =
+            |capture: &Capture| (capture.name.clone(), code[capture.span.clone()].into());
+        let describe =
+            |m: &Match| (code[m.span.clone()].into(), m.captures.iter().map(captured).collect());
+        matches.iter().map(describe).collect()
+    }
+
+    #[test]
+    fn method_call() {
+        let code = "main =\n    x = foo.to_text\n    y = bar.length\n    x.to_text + y\n";
+        let query = r#"(OprApp lhs: _ @receiver opr: "." rhs: (Ident token: "to_text")) @call"#;
+        let receiver = |code: &str| ("receiver".to_owned(), code.to_owned());
+        let call = |code: &str| ("call".to_owned(), code.to_owned());
+        let expected = vec![
+            ("foo.to_text".into(), vec![receiver("foo"), call("foo.to_text")]),
+            ("x.to_text".into(), vec![receiver("x"), call("x.to_text")]),
+        ];
+        assert_eq!(find(query, code), expected);
+    }
+
+    #[test]
+    fn code_patterns() {
+        let code = "a = foo.to_text\nb = foo.to_text.length\n";
+        let found = find(r#"(OprApp rhs: "to_text")"#, code);
+        let found: Vec<_> = found.into_iter().map(|(code, _)| code).collect();
+        assert_eq!(found, ["foo.to_text", "foo.to_text"]);
+    }
+
+    #[test]
+    fn nested_matches() {
+        let found = find("(App func: _ @func)", "f a b");
+        let expected = vec![
+            ("f a b".into(), vec![("func".into(), "f a".into())]),
+            ("f a".into(), vec![("func".into(), "f".into())]),
+        ];
+        assert_eq!(found, expected);
+    }
+
+    #[test]
+    fn alternatives_and_absent_fields() {
+        let code = "f = (+ 1)\ng = 1 + 2\nh = (* 3)\n";
+        let found = find(r#"(OprApp !lhs opr: ["+" "*"] @operator)"#, code);
+        let expected = vec![
+            ("+ 1".into(), vec![("operator".into(), "+".into())]),
+            ("* 3".into(), vec![("operator".into(), "*".into())]),
+        ];
+        assert_eq!(found, expected);
+    }
+
+    #[test]
+    fn sequence_fields() {
+        let code = "main =\n    x = 1\n    x\nother =\n    2\n";
+        let found = find("(BodyBlock statements: (Assignment)) @block", code);
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].0, "\n    x = 1\n    x");
+    }
+
+    #[test]
+    fn renamed_fields() {
+        let found = find(r#"(TypeSignature type: "Integer" @type)"#, "x : Integer\n");
+        assert_eq!(found, vec![("x : Integer".into(), vec![("type".into(), "Integer".into())])]);
+    }
+
+    #[test]
+    fn invalid_queries() {
+        let error = |query: &str| Query::new(query).unwrap_err();
+        assert_eq!(error("(Bogus)"), Error::UnknownVariant("Bogus".into()));
+        let unknown_field = Error::UnknownField { variant: "App", field: "lhs".into() };
+        assert_eq!(error("(App lhs: _)"), unknown_field);
+        let syntax = |offset, message| Error::Syntax { offset, message };
+        assert_eq!(error(r#"(App arg: "x)"#), syntax(10, "Unterminated string."));
+        assert_eq!(error("(App) _"), syntax(6, "Unexpected input after pattern."));
+        assert_eq!(error("(App arg _)"), syntax(9, "Expected `:`."));
+        assert_eq!(error("(App"), syntax(4, "Expected a field name or `)`."));
+        assert_eq!(error("_ @"), syntax(3, "Expected a capture name."));
+    }
+}
@@ -21,10 +21,19 @@ use std::vec::Drain;
 #[derive(crate::serde_reexports::Serialize)]
 #[derive(crate::serde_reexports::Deserialize)]
 pub struct NonEmptyVec<T, I = usize> {
-    #[reflect(as = "Vec<T>")]
+    #[reflect(as = "Vec<T>", visit_with = "visit_elems")]
     pub elems: VecIndexedBy<T, I>,
 }
 
+/// Visit the elements as a `Vec`, which is how they are reflected.
+fn visit_elems<T, I>(elems: &VecIndexedBy<T, I>, visitor: &mut dyn enso_reflect::Visitor)
+where T: enso_reflect::Reflect {
+    visitor.visit_sequence(elems.len());
+    for elem in elems.iter() {
+        enso_reflect::Reflect::reflect_value(elem, visitor);
+    }
+}
+
 impl<T, I> NonEmptyVec<T, I> {
     /// Construct a new non-empty vector.
     ///
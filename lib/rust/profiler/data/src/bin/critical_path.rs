@@ -0,0 +1,75 @@
+//! Tool that explains why a measurement took as long as it did, by reporting its critical path.
+//!
+//! # Usage
+//!
+//! The tool reads a
+//! [JSON-formatted event log](https://github.com/enso-org/design/blob/main/epics/profiling/implementation.md#file-format)
+//! from stdin, and writes the critical path of each measurement with the given name to stdout; see
+//! [`enso_profiler_data::critical_path`]. If no name is given, the critical paths of the
+//! `@highlight` measurements are reported.
+//!
+//! For example:
+//!
+//! ```console
+//! ~/git/enso/data $ cargo run --bin critical_path -- open_project < profile.json
+//! ```
+
+// === Standard Linter Configuration ===
+#![deny(non_ascii_idents)]
+#![warn(unsafe_code)]
+#![allow(clippy::bool_to_int_with_if)]
+#![allow(clippy::let_and_return)]
+// === Non-Standard Linter Configuration ===
+#![deny(unconditional_recursion)]
+#![warn(missing_copy_implementations)]
+#![warn(missing_debug_implementations)]
+#![warn(missing_docs)]
+#![warn(trivial_casts)]
+#![warn(trivial_numeric_casts)]
+#![warn(unused_import_braces)]
+
+use enso_profiler_data as profiler_data;
+use profiler_data::critical_path;
+use profiler_data::OpaqueMetadata;
+use profiler_data::Profile;
+use std::io::Read;
+
+
+
+// ============
+// === main ===
+// ============
+
+fn main() {
+    let args: Vec<_> = std::env::args().skip(1).collect();
+    let name = match &args[..] {
+        [] => None,
+        [name] if !name.starts_with('-') => Some(name),
+        _ => {
+            eprintln!("Usage: critical_path [NAME] < profile.json");
+            std::process::exit(2)
+        }
+    };
+    let mut log = String::new();
+    std::io::stdin().read_to_string(&mut log).unwrap();
+    let profiles: Vec<Profile<OpaqueMetadata>> =
+        profiler_data::parse_multiprocess_profile(&log).map(|profile| profile.unwrap()).collect();
+    let mut found = false;
+    for profile in &profiles {
+        let targets: Vec<_> = match name {
+            Some(name) => critical_path::find_by_name(profile, name).collect(),
+            None => critical_path::highlights(profile).collect(),
+        };
+        for target in targets {
+            found = true;
+            match critical_path::critical_path(profile, target) {
+                Some(path) => println!("{}", path.format(profile)),
+                None => println!("{} did not finish.\n", profile[target].label),
+            }
+        }
+    }
+    if !found {
+        eprintln!("No matching measurements found.");
+        std::process::exit(1);
+    }
+}
@@ -0,0 +1,135 @@
+//! Tool that compares the measurements of two profiles, to catch performance regressions between
+//! builds.
+//!
+//! # Usage
+//!
+//! The tool reads two JSON-formatted event logs, a baseline and a candidate, and writes a report
+//! of the changes of each measurement to stdout. Measurements are matched by their label path; see
+//! [`enso_profiler_data::diff`]. The process fails if any metric regressed by more than the
+//! thresholds.
+//!
+//! For example:
+//!
+//! ```console
+//! ~/git/enso/data $ cargo run --bin diff -- --relative 5 --absolute 2 base.json new.json
+//! ```
+//!
+//! Options:
+//! - `--relative PERCENT`: Minimum relative increase of a metric to report as a regression.
+//! - `--absolute MS`: Minimum increase of a duration to report as a regression.
+//! - `--count N`: Minimum increase of a measurement count to report as a regression.
+//! - `--metrics LIST`: Comma-separated metrics checked for regressions; any of `count`, `total`,
+//!   `self`, `p50`, `p90`, `p99`.
+//! - `--all`: Report all measurements, not only those that changed.
+
+// === Standard Linter Configuration ===
+#![deny(non_ascii_idents)]
+#![warn(unsafe_code)]
+#![allow(clippy::bool_to_int_with_if)]
+#![allow(clippy::let_and_return)]
+// === Non-Standard Linter Configuration ===
+#![deny(unconditional_recursion)]
+#![warn(missing_copy_implementations)]
+#![warn(missing_debug_implementations)]
+#![warn(missing_docs)]
+#![warn(trivial_casts)]
+#![warn(trivial_numeric_casts)]
+#![warn(unused_import_braces)]
+
+use enso_profiler_data as profiler_data;
+use profiler_data::diff;
+use profiler_data::diff::Metric;
+use profiler_data::OpaqueMetadata;
+use profiler_data::Profile;
+
+
+
+// ============
+// === main ===
+// ============
+
+fn main() {
+    let mut thresholds = diff::Thresholds::default();
+    let mut all = false;
+    let mut paths = vec![];
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        let mut value = || args.next().unwrap_or_else(|| usage(&format!("{arg} expects a value.")));
+        match arg.as_str() {
+            "--relative" => thresholds.relative = parse_number(&value()) / 100.0,
+            "--absolute" => thresholds.absolute = parse_number(&value()),
+            "--count" => thresholds.count = parse_number(&value()),
+            "--metrics" =>
+                thresholds.metrics = value()
+                    .split(',')
+                    .map(|name| {
+                        Metric::from_name(name)
+                            .unwrap_or_else(|| usage(&format!("Unknown metric: {name:?}.")))
+                    })
+                    .collect(),
+            "--all" => all = true,
+            _ if arg.starts_with("--") => usage(&format!("Unknown option: {arg}")),
+            _ => paths.push(arg),
+        }
+    }
+    let [baseline, candidate] = &paths[..] else { usage("Expected two profiles.") };
+    let baseline = diff::collect(&read_profile(baseline));
+    let candidate = diff::collect(&read_profile(candidate));
+    let changes = diff::compare(&baseline, &candidate, &thresholds);
+    let mut regressions = 0;
+    for change in &changes {
+        if !change.regressions.is_empty() {
+            regressions += 1;
+        } else if !all && change.baseline == change.candidate {
+            continue;
+        }
+        print_change(change);
+    }
+    if regressions != 0 {
+        eprintln!("{regressions} of {} measurements regressed.", changes.len());
+        std::process::exit(1);
+    }
+}
+
+fn read_profile(path: &str) -> Profile<OpaqueMetadata> {
+    let log = std::fs::read_to_string(path)
+        .unwrap_or_else(|e| panic!("Failed to read profile {path:?}: {e}"));
+    log.parse().unwrap_or_else(|e| panic!("Failed to parse profile {path:?}: {e}"))
+}
+
+fn parse_number(value: &str) -> f64 {
+    value.parse().unwrap_or_else(|_| usage(&format!("Expected a number: {value:?}.")))
+}
+
+fn usage(error: &str) -> ! {
+    eprintln!("{error}");
+    eprintln!(
+        "Usage: diff [--relative PERCENT] [--absolute MS] [--count N] [--metrics LIST] [--all] \
+         BASELINE CANDIDATE"
+    );
+    std::process::exit(2)
+}
+
+
+
+// =================
+// === Reporting ===
+// =================
+
+/// Print the changes of the metrics of a measurement. Regressed metrics are marked with `!`.
+fn print_change(change: &diff::Change) {
+    let status = if change.regressions.is_empty() { "" } else { "REGRESSION " };
+    println!("{status}{}", change.path.join(" / "));
+    for metric in Metric::ALL {
+        let baseline = change.baseline.get(metric);
+        let candidate = change.candidate.get(metric);
+        let ratio = match change.ratio(metric) {
+            Some(ratio) => format!("{:+.1}%", ratio * 100.0),
+            None if candidate == 0.0 => String::new(),
+            None => "new".to_owned(),
+        };
+        let mark = if change.regressions.contains(&metric) { "!" } else { " " };
+        let name = metric.name();
+        println!("  {mark}{name:<5} {baseline:>10.1} -> {candidate:>10.1} {ratio:>8}");
+    }
+}
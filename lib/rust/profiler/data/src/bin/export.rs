@@ -0,0 +1,78 @@
+//! Tool that converts profiling data to the formats of external profile viewers.
+//!
+//! # Usage
+//!
+//! The tool reads a
+//! [JSON-formatted event log](https://github.com/enso-org/design/blob/main/epics/profiling/implementation.md#file-format)
+//! from stdin, and writes the converted profile to stdout. The log may contain the profiles of
+//! multiple processes; the `pprof` format supports only a single profile.
+//!
+//! Supported formats: `speedscope`, `trace-event` (for Perfetto and `chrome://tracing`), `pprof`.
+//!
+//! For example:
+//!
+//! ```console
+//! ~/git/enso/data $ cargo run --bin export speedscope < profile.json > profile.speedscope.json
+//! ```
+
+// === Standard Linter Configuration ===
+#![deny(non_ascii_idents)]
+#![warn(unsafe_code)]
+#![allow(clippy::bool_to_int_with_if)]
+#![allow(clippy::let_and_return)]
+// === Non-Standard Linter Configuration ===
+#![deny(unconditional_recursion)]
+#![warn(missing_copy_implementations)]
+#![warn(missing_debug_implementations)]
+#![warn(missing_docs)]
+#![warn(trivial_casts)]
+#![warn(trivial_numeric_casts)]
+#![warn(unused_import_braces)]
+
+use enso_profiler_data as profiler_data;
+use profiler_data::export;
+use profiler_data::OpaqueMetadata;
+use profiler_data::Profile;
+use std::io::Read;
+use std::io::Write;
+
+
+
+// ============
+// === main ===
+// ============
+
+fn main() {
+    let args: Vec<_> = std::env::args().skip(1).collect();
+    let [format] = &args[..] else {
+        usage();
+    };
+    let mut log = String::new();
+    std::io::stdin().read_to_string(&mut log).unwrap();
+    let profiles: Vec<Profile<OpaqueMetadata>> =
+        profiler_data::parse_multiprocess_profile(&log).map(|profile| profile.unwrap()).collect();
+    let mut out = std::io::stdout().lock();
+    match format.as_str() {
+        "speedscope" => {
+            let file = export::speedscope::export("profile", &profiles);
+            serde_json::to_writer(out, &file).unwrap();
+        }
+        "trace-event" => {
+            let file = export::trace_event::export(&profiles);
+            serde_json::to_writer(out, &file).unwrap();
+        }
+        "pprof" => {
+            let [profile] = &profiles[..] else {
+                eprintln!("The pprof format supports only a single profile.");
+                std::process::exit(1);
+            };
+            out.write_all(&export::pprof::export(profile)).unwrap();
+        }
+        _ => usage(),
+    }
+}
+
+fn usage() -> ! {
+    eprintln!("Usage: export speedscope|trace-event|pprof < profile.json");
+    std::process::exit(2)
+}
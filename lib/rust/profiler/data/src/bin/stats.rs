@@ -0,0 +1,93 @@
+//! Tool that summarizes the measurements of many profiles of repeated runs of a workflow, to tell
+//! whether a change in performance is significant compared to the noise between runs.
+//!
+//! # Usage
+//!
+//! The tool reads every JSON-formatted event log in a directory as a separate run, and writes the
+//! distribution of the durations of each measurement to stdout. Measurements are matched by their
+//! label path; see [`enso_profiler_data::stats`].
+//!
+//! For example:
+//!
+//! ```console
+//! ~/git/enso/data $ cargo run --bin stats -- --reject-outliers --csv runs/ > stats.csv
+//! ```
+//!
+//! Options:
+//! - `--csv`: Write CSV instead of a table.
+//! - `--per-run`: Sum the durations of the measurements with the same path in each run, and
+//!   summarize one sample per run instead of one per measurement.
+//! - `--reject-outliers`: Exclude samples outside Tukey's fences.
+//! - `--fence K`: The multiple of the interquartile range used by `--reject-outliers`; defaults to
+//!   1.5.
+
+// === Standard Linter Configuration ===
+#![deny(non_ascii_idents)]
+#![warn(unsafe_code)]
+#![allow(clippy::bool_to_int_with_if)]
+#![allow(clippy::let_and_return)]
+// === Non-Standard Linter Configuration ===
+#![deny(unconditional_recursion)]
+#![warn(missing_copy_implementations)]
+#![warn(missing_debug_implementations)]
+#![warn(missing_docs)]
+#![warn(trivial_casts)]
+#![warn(trivial_numeric_casts)]
+#![warn(unused_import_braces)]
+
+use enso_profiler_data as profiler_data;
+use profiler_data::stats;
+
+
+
+// ============
+// === main ===
+// ============
+
+fn main() {
+    let mut csv = false;
+    let mut sampling = stats::Sampling::Measurement;
+    let mut reject_outliers = false;
+    let mut fences = stats::Fences::default();
+    let mut dirs = vec![];
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        let mut value = || args.next().unwrap_or_else(|| usage(&format!("{arg} expects a value.")));
+        match arg.as_str() {
+            "--csv" => csv = true,
+            "--per-run" => sampling = stats::Sampling::Run,
+            "--reject-outliers" => reject_outliers = true,
+            "--fence" => {
+                let k = value();
+                fences.k =
+                    k.parse().unwrap_or_else(|_| usage(&format!("Expected a number: {k:?}.")));
+            }
+            _ if arg.starts_with("--") => usage(&format!("Unknown option: {arg}")),
+            _ => dirs.push(arg),
+        }
+    }
+    let [dir] = &dirs[..] else { usage("Expected a directory.") };
+    let mut runs = stats::Runs::default();
+    match runs.add_dir(dir, sampling) {
+        Ok(0) => usage(&format!("No profiles found in {dir:?}.")),
+        Ok(_) => (),
+        Err(e) => {
+            eprintln!("{e}");
+            std::process::exit(1);
+        }
+    }
+    let summaries = runs.summarize(reject_outliers.then_some(fences));
+    match csv {
+        true => print!("{}", stats::to_csv(&summaries)),
+        false => {
+            println!("{} runs.", runs.runs);
+            print!("{}", stats::to_table(&summaries));
+        }
+    }
+}
+
+fn usage(error: &str) -> ! {
+    eprintln!("{error}");
+    eprintln!("Usage: stats [--csv] [--per-run] [--reject-outliers] [--fence K] DIR");
+    std::process::exit(2)
+}
@@ -0,0 +1,414 @@
+//! Supports explaining why a measurement took as long as it did, by finding the chain of activity
+//! that determined when it ended.
+//!
+//! The *critical path* of a measurement is reconstructed backwards from the end of the
+//! measurement's last interval. While a measurement is active, it is on the path. When it was
+//! paused, the path follows whatever it was waiting for:
+//! - If one of its descendants (e.g. a child task it awaited) was the last of them to finish
+//!   running before the measurement resumed, the path continues through the descendant; when the
+//!   path reaches the start of the descendant, it continues in the descendant's parent.
+//! - Otherwise, the wait is attributed to the last interval of an unrelated measurement that ended
+//!   during the wait, if any; in a single-threaded executor, this is usually the work that woke the
+//!   measurement. The path continues in the waiting measurement.
+//! - If nothing ran during the wait, the measurement was waiting for something outside the process,
+//!   such as I/O or a timer.
+
+use crate::Class;
+use crate::IntervalId;
+use crate::MeasurementId;
+use crate::Profile;
+use crate::Timestamp;
+
+use std::collections::HashMap;
+use std::fmt::Write;
+
+
+
+// ====================
+// === CriticalPath ===
+// ====================
+
+/// The chain of activity that determined when a measurement ended.
+#[derive(Clone, Debug)]
+pub struct CriticalPath {
+    /// The measurement the path leads to the end of.
+    pub target:   MeasurementId,
+    /// The segments of the path, in chronological order.
+    pub segments: Vec<Segment>,
+}
+
+/// A period of time on the critical path.
+#[derive(Copy, Clone, Debug)]
+pub struct Segment {
+    /// The measurement that was active or waiting.
+    pub measurement: MeasurementId,
+    /// When the segment began.
+    pub start:       Timestamp,
+    /// When the segment ended.
+    pub end:         Timestamp,
+    /// What the measurement was doing.
+    pub kind:        Kind,
+}
+
+impl Segment {
+    /// The duration of the segment, in milliseconds.
+    pub fn duration_ms(&self) -> f64 {
+        self.end.into_ms() - self.start.into_ms()
+    }
+}
+
+/// Distinguishes active segments from waits.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Kind {
+    /// The measurement was running.
+    Active,
+    /// The measurement was paused or had not started yet.
+    Waiting(Cause),
+}
+
+/// The activity a wait is attributed to.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Cause {
+    /// The waiting measurement was resumed after the given descendant finished running. The
+    /// descendant's activity precedes the wait on the path.
+    Descendant(MeasurementId),
+    /// The waiting measurement was resumed after the given unrelated measurement finished running.
+    Other(MeasurementId),
+    /// Nothing ran during the wait.
+    External,
+}
+
+impl CriticalPath {
+    /// The total time spent in active segments, in milliseconds.
+    pub fn active_ms(&self) -> f64 {
+        let active = self.segments.iter().filter(|segment| segment.kind == Kind::Active);
+        active.map(Segment::duration_ms).sum()
+    }
+
+    /// The total time spent in waits, in milliseconds.
+    pub fn waiting_ms(&self) -> f64 {
+        let waiting = self.segments.iter().filter(|segment| segment.kind != Kind::Active);
+        waiting.map(Segment::duration_ms).sum()
+    }
+
+    /// Format the path as a table, with a row for each segment.
+    pub fn format<M>(&self, profile: &Profile<M>) -> String {
+        let mut out = String::new();
+        let label = &profile[self.target].label;
+        let (active, waiting) = (self.active_ms(), self.waiting_ms());
+        let total = active + waiting;
+        writeln!(out, "Critical path of {label}: {total:.3} ms").unwrap();
+        writeln!(out, "({active:.3} ms active, {waiting:.3} ms waiting)").unwrap();
+        writeln!(out, "{:>12} {:>12} {:>10}", "start", "end", "duration").unwrap();
+        for segment in &self.segments {
+            let Segment { measurement, start, end, kind } = *segment;
+            let (start, end) = (start.into_ms(), end.into_ms());
+            let duration = segment.duration_ms();
+            write!(out, "{start:>12.3} {end:>12.3} {duration:>10.3} ").unwrap();
+            let label = &profile[measurement].label;
+            match kind {
+                Kind::Active => writeln!(out, "active  {label}"),
+                Kind::Waiting(Cause::Descendant(cause)) =>
+                    writeln!(out, "waiting {label} for {}", profile[cause].label),
+                Kind::Waiting(Cause::Other(cause)) =>
+                    writeln!(out, "waiting {label} until after {}", profile[cause].label),
+                Kind::Waiting(Cause::External) =>
+                    writeln!(out, "waiting {label} (nothing running)"),
+            }
+            .unwrap();
+        }
+        out
+    }
+}
+
+
+
+// ================
+// === Analysis ===
+// ================
+
+/// Find the critical path of a measurement. Returns [`None`] if the measurement has no interval
+/// with a known end.
+pub fn critical_path<M>(profile: &Profile<M>, target: MeasurementId) -> Option<CriticalPath> {
+    let last = *profile[target].intervals.last()?;
+    let end = profile[last].interval.end?;
+    let mut parents = HashMap::new();
+    for id in profile.measurement_ids() {
+        for child in &profile[id].children {
+            parents.insert(*child, id);
+        }
+    }
+    let mut tracer = Tracer { profile, parents, segments: vec![] };
+    tracer.trace(target, end);
+    let mut segments = tracer.segments;
+    segments.reverse();
+    Some(CriticalPath { target, segments })
+}
+
+/// Find the measurements with a given name, e.g. to select the target of [`critical_path`].
+pub fn find_by_name<'p, M>(
+    profile: &'p Profile<M>,
+    name: &'p str,
+) -> impl Iterator<Item = MeasurementId> + 'p {
+    profile.measurement_ids().filter(move |id| profile[*id].label.name == name)
+}
+
+/// Find the measurements of the [`Class::Highlight`] profilers, which mark the regions of interest
+/// of a profile.
+pub fn highlights<M>(profile: &Profile<M>) -> impl Iterator<Item = MeasurementId> + '_ {
+    profile.measurement_ids().filter(|id| profile[*id].classify() == Class::Highlight)
+}
+
+struct Tracer<'p, M> {
+    profile:  &'p Profile<M>,
+    parents:  HashMap<MeasurementId, MeasurementId>,
+    /// The segments found so far, in reverse chronological order.
+    segments: Vec<Segment>,
+}
+
+impl<'p, M> Tracer<'p, M> {
+    /// Build the path backwards from the given time, until the creation of the target.
+    fn trace(&mut self, target: MeasurementId, end: Timestamp) {
+        let profile = self.profile;
+        let (mut current, mut time) = (target, end);
+        // When the path reaches the start of a measurement, it continues in the measurement's
+        // parent; a gap between the parent's activity and the start is the child's wait to start.
+        let mut starting = None;
+        loop {
+            let measurement = &profile[current];
+            let intervals = &measurement.intervals;
+            let count = intervals.partition_point(|id| profile[*id].interval.start < time);
+            let Some(interval) = count.checked_sub(1).map(|i| profile[intervals[i]].interval) else {
+                if current != target {
+                    starting = Some(current);
+                    current = self.parents[&current];
+                    continue;
+                }
+                if measurement.created < time {
+                    if let Some(next) = self.wait(current, measurement.created, time) {
+                        (current, time) = next;
+                        continue;
+                    }
+                }
+                break;
+            };
+            let active_end = interval.end.map_or(time, |end| end.min(time));
+            if active_end < time {
+                let waiting = starting.unwrap_or(current);
+                if let Some(next) = self.wait(waiting, active_end, time) {
+                    (current, time) = next;
+                    starting = None;
+                    continue;
+                }
+            }
+            starting = None;
+            self.push(current, interval.start, active_end, Kind::Active);
+            time = interval.start;
+        }
+    }
+
+    /// Attribute a wait of a measurement to its cause. If it was waiting for a descendant, return
+    /// the descendant and the time it finished running, where the path continues.
+    fn wait(
+        &mut self,
+        waiting: MeasurementId,
+        start: Timestamp,
+        end: Timestamp,
+    ) -> Option<(MeasurementId, Timestamp)> {
+        let mut descendant: Option<(MeasurementId, Timestamp)> = None;
+        let mut other: Option<(MeasurementId, Timestamp)> = None;
+        for id in 0..self.profile.root_interval_id().0 {
+            let interval = &self.profile[IntervalId(id)];
+            let measurement = interval.measurement;
+            let Some(finished) = interval.interval.end else { continue };
+            if finished <= start || finished > end || self.is_ancestor(measurement, waiting) {
+                continue;
+            }
+            let latest = match self.is_ancestor(waiting, measurement) {
+                true => &mut descendant,
+                false => &mut other,
+            };
+            if latest.map_or(true, |(_, time)| time < finished) {
+                *latest = Some((measurement, finished));
+            }
+        }
+        if let Some((cause, finished)) = descendant {
+            self.push(waiting, finished, end, Kind::Waiting(Cause::Descendant(cause)));
+            return Some((cause, finished));
+        }
+        let cause = other.map_or(Cause::External, |(cause, _)| Cause::Other(cause));
+        self.push(waiting, start, end, Kind::Waiting(cause));
+        None
+    }
+
+    /// Return whether the first measurement is the second, or one of its ancestors.
+    fn is_ancestor(&self, ancestor: MeasurementId, mut measurement: MeasurementId) -> bool {
+        loop {
+            if measurement == ancestor {
+                return true;
+            }
+            match self.parents.get(&measurement) {
+                Some(parent) => measurement = *parent,
+                None => return false,
+            }
+        }
+    }
+
+    fn push(&mut self, measurement: MeasurementId, start: Timestamp, end: Timestamp, kind: Kind) {
+        // A wait for nothing that took no time is an artifact of the order of events, e.g. between
+        // the creation and start of a synchronous profiler.
+        let artifact = kind == Kind::Waiting(Cause::External) && start.into_ms() == end.into_ms();
+        if start < end && !artifact {
+            self.segments.push(Segment { measurement, start, end, kind });
+        }
+    }
+}
+
+
+
+// =============
+// === Tests ===
+// =============
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use enso_profiler as profiler;
+    use profiler::format;
+    use profiler::profile;
+
+    type Description = (String, [f64; 2], &'static str, Option<String>);
+
+    /// Find the critical path of the measurement named `target` in a profile with the given
+    /// events. Describe each segment by the name of its measurement, its bounds in milliseconds,
+    /// its kind, and the name of the cause of a wait.
+    fn describe_path(log: format::Builder, target: &str) -> Vec<Description> {
+        let profile: Profile<crate::OpaqueMetadata> = log.build_string().parse().unwrap();
+        let target = find_by_name(&profile, target).next().unwrap();
+        let path = critical_path(&profile, target).unwrap();
+        let name = |id: MeasurementId| profile[id].label.name.clone();
+        let describe = |segment: &Segment| {
+            let (kind, cause) = match segment.kind {
+                Kind::Active => ("active", None),
+                Kind::Waiting(Cause::Descendant(cause)) => ("descendant", Some(name(cause))),
+                Kind::Waiting(Cause::Other(cause)) => ("other", Some(name(cause))),
+                Kind::Waiting(Cause::External) => ("external", None),
+            };
+            let bounds = [segment.start.into_ms(), segment.end.into_ms()];
+            (name(segment.measurement), bounds, kind, cause)
+        };
+        path.segments.iter().map(describe).collect()
+    }
+
+    fn expected(segments: &[(&str, [f64; 2], &'static str, Option<&str>)]) -> Vec<Description> {
+        let describe = |&(name, bounds, kind, cause): &(&str, _, _, Option<&str>)| {
+            (name.to_owned(), bounds, kind, cause.map(str::to_owned))
+        };
+        segments.iter().map(describe).collect()
+    }
+
+    /// A measurement that pauses at 2 ms, and resumes from 6 ms to 8 ms.
+    fn paused_target(log: &mut format::Builder) {
+        let ms = format::Timestamp::from_ms;
+        let target = log.create(Some(ms(0.0)), format::Parent::root(), "target (?:?)");
+        log.start(ms(0.0), target);
+        log.pause(ms(2.0), target);
+        log.start(ms(6.0), target);
+        log.end(ms(8.0), target);
+    }
+
+    #[test]
+    fn wait_for_other_measurement() {
+        let ms = format::Timestamp::from_ms;
+        let mut log = format::Builder::new();
+        paused_target(&mut log);
+        let other = log.create(Some(ms(3.0)), format::Parent::root(), "other (?:?)");
+        log.start(ms(3.0), other);
+        log.end(ms(5.0), other);
+        let expected = expected(&[
+            ("target", [0.0, 2.0], "active", None),
+            ("target", [2.0, 6.0], "other", Some("other")),
+            ("target", [6.0, 8.0], "active", None),
+        ]);
+        assert_eq!(describe_path(log, "target"), expected);
+    }
+
+    #[test]
+    fn external_wait() {
+        let mut log = format::Builder::new();
+        paused_target(&mut log);
+        let expected = expected(&[
+            ("target", [0.0, 2.0], "active", None),
+            ("target", [2.0, 6.0], "external", None),
+            ("target", [6.0, 8.0], "active", None),
+        ]);
+        assert_eq!(describe_path(log, "target"), expected);
+    }
+
+    #[test]
+    fn continue_in_parent_of_descendant() {
+        let ms = format::Timestamp::from_ms;
+        let mut log = format::Builder::new();
+        let parent = log.create(Some(ms(0.0)), format::Parent::root(), "parent (?:?)");
+        log.start(ms(0.0), parent);
+        let child = log.create(Some(ms(1.0)), parent.into(), "child (?:?)");
+        log.pause(ms(2.0), parent);
+        let other = log.create(Some(ms(2.0)), format::Parent::root(), "other (?:?)");
+        log.start(ms(2.0), other);
+        log.end(ms(2.5), other);
+        log.start(ms(3.0), child);
+        log.end(ms(5.0), child);
+        log.start(ms(6.0), parent);
+        log.end(ms(8.0), parent);
+        // When the path reaches the start of the child, it continues in the parent; the gap
+        // between the parent's pause and the child's start is the child's wait to start.
+        let expected = expected(&[
+            ("parent", [0.0, 2.0], "active", None),
+            ("child", [2.0, 3.0], "other", Some("other")),
+            ("child", [3.0, 5.0], "active", None),
+            ("parent", [5.0, 6.0], "descendant", Some("child")),
+            ("parent", [6.0, 8.0], "active", None),
+        ]);
+        assert_eq!(describe_path(log, "parent"), expected);
+    }
+
+    #[test]
+    fn await_child() {
+        #[profile(Objective)]
+        async fn parent() {
+            sibling();
+            child().await;
+        }
+        #[profile(Objective)]
+        async fn child() {}
+        #[profile(Objective)]
+        fn sibling() {}
+        futures::executor::block_on(parent());
+        let profile: Profile<crate::OpaqueMetadata> =
+            profiler::internal::get_log().parse().unwrap();
+        let parent = find_by_name(&profile, "parent").next().unwrap();
+        let child = find_by_name(&profile, "child").next().unwrap();
+        let path = critical_path(&profile, parent).unwrap();
+        // Waits for nothing may or may not be observed, depending on the resolution of the clock.
+        let segments: Vec<_> = path
+            .segments
+            .iter()
+            .filter(|segment| segment.kind != Kind::Waiting(Cause::External))
+            .map(|segment| (segment.measurement, segment.kind))
+            .collect();
+        // The sibling ran synchronously within the parent's first interval, so it is not a
+        // separate segment.
+        let expected = [
+            (parent, Kind::Active),
+            (child, Kind::Active),
+            (parent, Kind::Waiting(Cause::Descendant(child))),
+            (parent, Kind::Active),
+        ];
+        assert_eq!(segments, expected);
+        for pair in path.segments.windows(2) {
+            assert_eq!(pair[0].end.into_ms(), pair[1].start.into_ms());
+        }
+        assert_eq!(path.segments[0].start.into_ms(), profile[parent].created.into_ms());
+        assert!(path.format(&profile).contains(" for child"));
+    }
+}
@@ -0,0 +1,352 @@
+//! Supports comparing the measurements of two profiles, to find performance regressions between
+//! builds.
+//!
+//! Measurements are matched by their *label path*: the names of the profilers from the root of the
+//! [`crate::Measurement`] hierarchy to the measurement. All measurements with the same path are
+//! summarized together, so that profiles of runs that perform the same work in a different order
+//! or a different number of times can still be compared.
+
+use enso_prelude::*;
+
+use std::collections::BTreeMap;
+use std::collections::BTreeSet;
+
+
+
+// =============
+// === Stats ===
+// =============
+
+/// The names of the profilers from the root of the measurement hierarchy to a measurement.
+pub type Path = Vec<String>;
+
+/// Summary of all the measurements of a profile that have a particular [`Path`].
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Stats {
+    /// The number of measurements.
+    pub count:     usize,
+    /// The time spent in the measurements' intervals, in milliseconds.
+    pub total:     f64,
+    /// The time spent in the measurements' intervals, excluding time spent in child intervals, in
+    /// milliseconds.
+    pub self_time: f64,
+    /// The total duration of each measurement, in ascending order.
+    durations:     Vec<f64>,
+}
+
+impl Stats {
+    /// Return the duration of a single measurement such that the given fraction of the measurements
+    /// took no longer. Uses the nearest-rank method; returns 0 if there are no measurements.
+    pub fn percentile(&self, fraction: f64) -> f64 {
+        crate::stats::percentile(&self.durations, fraction)
+    }
+
+    /// Return the total duration of each measurement, in ascending order.
+    pub fn durations(&self) -> &[f64] {
+        &self.durations
+    }
+
+    /// Return the value of a metric.
+    pub fn get(&self, metric: Metric) -> f64 {
+        match metric {
+            Metric::Count => self.count as f64,
+            Metric::Total => self.total,
+            Metric::SelfTime => self.self_time,
+            Metric::P50 => self.percentile(0.5),
+            Metric::P90 => self.percentile(0.9),
+            Metric::P99 => self.percentile(0.99),
+        }
+    }
+
+    /// Add a measurement.
+    fn log(&mut self, total: f64, self_time: f64) {
+        self.count += 1;
+        self.total += total;
+        self.self_time += self_time;
+        let index = self.durations.partition_point(|&duration| duration < total);
+        self.durations.insert(index, total);
+    }
+}
+
+
+// === Collecting ===
+
+/// Summarize the measurements of a profile by [`Path`].
+///
+/// Only the closed intervals of measurements are counted; a measurement that was never run is
+/// included in the count, with a duration of zero.
+pub fn collect<M>(profile: &crate::Profile<M>) -> BTreeMap<Path, Stats> {
+    let mut collector = Collector { profile, path: default(), stats: default() };
+    for child in &profile.root_measurement().children {
+        collector.visit(*child);
+    }
+    collector.stats
+}
+
+struct Collector<'p, M> {
+    profile: &'p crate::Profile<M>,
+    path:    Path,
+    stats:   BTreeMap<Path, Stats>,
+}
+
+impl<'p, M> Collector<'p, M> {
+    fn visit(&mut self, id: crate::MeasurementId) {
+        let measurement = &self.profile[id];
+        self.path.push(measurement.label.name.clone());
+        let mut total = 0.0;
+        let mut self_time = 0.0;
+        for interval in &measurement.intervals {
+            let interval = &self.profile[*interval];
+            if let Some(duration) = interval.interval.duration_ms() {
+                let children = interval.children.iter();
+                let children_duration: f64 =
+                    children.filter_map(|child| self.profile[*child].interval.duration_ms()).sum();
+                total += duration;
+                self_time += duration - children_duration;
+            }
+        }
+        self.stats.entry(self.path.clone()).or_default().log(total, self_time);
+        for child in &measurement.children {
+            self.visit(*child);
+        }
+        self.path.pop();
+    }
+}
+
+
+
+// ==============
+// === Metric ===
+// ==============
+
+/// A quantity that can be compared between profiles.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Metric {
+    /// The number of measurements.
+    Count,
+    /// The time spent in all the measurements.
+    Total,
+    /// The time spent in all the measurements, excluding their children.
+    SelfTime,
+    /// The median duration of a measurement.
+    P50,
+    /// The 90th percentile duration of a measurement.
+    P90,
+    /// The 99th percentile duration of a measurement.
+    P99,
+}
+
+impl Metric {
+    /// All the metrics, in the order they are reported.
+    pub const ALL: [Metric; 6] =
+        [Metric::Count, Metric::Total, Metric::SelfTime, Metric::P50, Metric::P90, Metric::P99];
+
+    /// The name used to identify the metric in reports and on the command line.
+    pub fn name(self) -> &'static str {
+        match self {
+            Metric::Count => "count",
+            Metric::Total => "total",
+            Metric::SelfTime => "self",
+            Metric::P50 => "p50",
+            Metric::P90 => "p90",
+            Metric::P99 => "p99",
+        }
+    }
+
+    /// Look up a metric by its [`Metric::name`].
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|metric| metric.name() == name)
+    }
+}
+
+
+
+// ==================
+// === Thresholds ===
+// ==================
+
+/// Determines which changes are considered regressions.
+///
+/// A metric has regressed if it increased by more than both the relative and the absolute
+/// threshold. Requiring both keeps noise in very short measurements from being reported, as well as
+/// small relative changes to very long measurements.
+#[derive(Clone, Debug)]
+pub struct Thresholds {
+    /// The minimum increase, as a fraction of the baseline value.
+    pub relative: f64,
+    /// The minimum increase of a duration, in milliseconds.
+    pub absolute: f64,
+    /// The minimum increase of a count.
+    pub count:    f64,
+    /// The metrics that are checked for regressions.
+    pub metrics:  Vec<Metric>,
+}
+
+impl Default for Thresholds {
+    fn default() -> Self {
+        let metrics = vec![Metric::Total, Metric::SelfTime, Metric::P90];
+        Self { relative: 0.1, absolute: 1.0, count: 0.0, metrics }
+    }
+}
+
+impl Thresholds {
+    /// Return whether a change of a metric is a regression.
+    pub fn is_regression(&self, metric: Metric, baseline: f64, candidate: f64) -> bool {
+        let absolute = match metric {
+            Metric::Count => self.count,
+            _ => self.absolute,
+        };
+        let increase = candidate - baseline;
+        self.metrics.contains(&metric)
+            && increase > absolute
+            && increase > self.relative * baseline.abs()
+    }
+}
+
+
+
+// ==============
+// === Change ===
+// ==============
+
+/// Comparison of the measurements with a particular [`Path`] in two profiles.
+#[derive(Clone, Debug)]
+pub struct Change {
+    /// Identifies the measurements.
+    pub path:        Path,
+    /// Summary of the measurements in the baseline profile.
+    pub baseline:    Stats,
+    /// Summary of the measurements in the candidate profile.
+    pub candidate:   Stats,
+    /// The metrics that increased by more than the [`Thresholds`].
+    pub regressions: Vec<Metric>,
+}
+
+impl Change {
+    /// Return the difference in a metric, from the baseline to the candidate.
+    pub fn delta(&self, metric: Metric) -> f64 {
+        self.candidate.get(metric) - self.baseline.get(metric)
+    }
+
+    /// Return the relative difference in a metric, or `None` if the baseline value is zero.
+    pub fn ratio(&self, metric: Metric) -> Option<f64> {
+        let baseline = self.baseline.get(metric);
+        (baseline != 0.0).then(|| self.delta(metric) / baseline)
+    }
+}
+
+/// Compare the summaries of two profiles, as produced by [`collect`]. Returns a [`Change`] for
+/// every path present in either profile, ordered by path.
+pub fn compare(
+    baseline: &BTreeMap<Path, Stats>,
+    candidate: &BTreeMap<Path, Stats>,
+    thresholds: &Thresholds,
+) -> Vec<Change> {
+    let paths: BTreeSet<&Path> = baseline.keys().chain(candidate.keys()).collect();
+    let get = |stats: &BTreeMap<Path, Stats>, path| stats.get(path).cloned().unwrap_or_default();
+    paths
+        .into_iter()
+        .map(|path| {
+            let baseline = get(baseline, path);
+            let candidate = get(candidate, path);
+            let regressions = Metric::ALL
+                .into_iter()
+                .filter(|&metric| {
+                    let (baseline, candidate) = (baseline.get(metric), candidate.get(metric));
+                    thresholds.is_regression(metric, baseline, candidate)
+                })
+                .collect();
+            Change { path: path.clone(), baseline, candidate, regressions }
+        })
+        .collect()
+}
+
+
+
+// =============
+// === Tests ===
+// =============
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn stats(durations: &[f64]) -> Stats {
+        let mut stats = Stats::default();
+        for &duration in durations {
+            stats.log(duration, duration / 2.0);
+        }
+        stats
+    }
+
+    fn path(names: &[&str]) -> Path {
+        names.iter().map(|name| name.to_string()).collect()
+    }
+
+    #[test]
+    fn percentiles() {
+        let stats = stats(&[5.0, 1.0, 4.0, 2.0, 3.0]);
+        assert_eq!(stats.count, 5);
+        assert_eq!(stats.total, 15.0);
+        assert_eq!(stats.self_time, 7.5);
+        assert_eq!(stats.percentile(0.0), 1.0);
+        assert_eq!(stats.percentile(0.5), 3.0);
+        assert_eq!(stats.percentile(0.9), 5.0);
+        assert_eq!(stats.percentile(1.0), 5.0);
+        assert_eq!(Stats::default().percentile(0.5), 0.0);
+    }
+
+    #[test]
+    fn thresholds() {
+        let thresholds = Thresholds::default();
+        // Over the relative threshold, but under the absolute threshold.
+        assert!(!thresholds.is_regression(Metric::Total, 1.0, 1.5));
+        // Over the absolute threshold, but under the relative threshold.
+        assert!(!thresholds.is_regression(Metric::Total, 100.0, 105.0));
+        assert!(thresholds.is_regression(Metric::Total, 100.0, 115.0));
+        // Not checked by default.
+        assert!(!thresholds.is_regression(Metric::P50, 100.0, 115.0));
+        // Improvements are never regressions.
+        assert!(!thresholds.is_regression(Metric::Total, 115.0, 100.0));
+    }
+
+    #[test]
+    fn compare_paths() {
+        let mut baseline = BTreeMap::new();
+        baseline.insert(path(&["a"]), stats(&[10.0, 10.0]));
+        baseline.insert(path(&["a", "removed"]), stats(&[1.0]));
+        let mut candidate = BTreeMap::new();
+        candidate.insert(path(&["a"]), stats(&[10.0, 20.0]));
+        candidate.insert(path(&["a", "added"]), stats(&[50.0]));
+        let changes = compare(&baseline, &candidate, &Thresholds::default());
+        let paths: Vec<_> = changes.iter().map(|change| change.path.clone()).collect();
+        assert_eq!(paths, vec![path(&["a"]), path(&["a", "added"]), path(&["a", "removed"])]);
+        assert_eq!(changes[0].regressions, vec![Metric::Total, Metric::SelfTime, Metric::P90]);
+        assert_eq!(changes[0].delta(Metric::Total), 10.0);
+        assert_eq!(changes[0].ratio(Metric::Total), Some(0.5));
+        assert_eq!(changes[1].regressions, vec![Metric::Total, Metric::SelfTime, Metric::P90]);
+        assert_eq!(changes[1].ratio(Metric::Total), None);
+        assert!(changes[2].regressions.is_empty());
+    }
+
+    #[test]
+    fn collect_label_paths() {
+        use enso_profiler as profiler;
+        use profiler::profile;
+        #[profile(Objective)]
+        fn parent() {
+            child();
+            child();
+        }
+        #[profile(Objective)]
+        fn child() {}
+        parent();
+        let profile: crate::Profile<crate::OpaqueMetadata> =
+            profiler::internal::get_log().parse().unwrap();
+        let stats = collect(&profile);
+        assert_eq!(stats[&path(&["parent"])].count, 1);
+        assert_eq!(stats[&path(&["parent", "child"])].count, 2);
+        let parent = &stats[&path(&["parent"])];
+        assert!(parent.self_time <= parent.total);
+    }
+}
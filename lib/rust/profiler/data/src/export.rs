@@ -0,0 +1,38 @@
+//! Conversion of profiles to the formats of external profile viewers.
+//!
+//! - [`speedscope`]: The file format of [speedscope](https://www.speedscope.app/).
+//! - [`trace_event`]: The Trace Event format, read by Perfetto and `chrome://tracing`.
+//! - [`pprof`]: The protobuf format of [pprof](https://github.com/google/pprof).
+
+
+// ==============
+// === Export ===
+// ==============
+
+pub mod pprof;
+pub mod speedscope;
+pub mod trace_event;
+
+
+
+// =================
+// === Intervals ===
+// =================
+
+/// Return the closed intervals that are children of an interval, in order of their start times.
+///
+/// Open intervals, and their descendants, are omitted: their extent is unknown, and most viewers
+/// require each interval to be closed.
+pub(crate) fn closed_children<M>(
+    profile: &crate::Profile<M>,
+    parent: crate::IntervalId,
+) -> Vec<crate::IntervalId> {
+    let mut children: Vec<_> = profile[parent]
+        .children
+        .iter()
+        .copied()
+        .filter(|child| profile[*child].interval.closed())
+        .collect();
+    children.sort_by_key(|child| profile[*child].interval.start);
+    children
+}
@@ -0,0 +1,251 @@
+//! Support for the [pprof](https://github.com/google/pprof/blob/main/proto/profile.proto) format.
+//!
+//! The intervals of a profile are aggregated by stack, as by [`crate::aggregate`]; each stack of
+//! profilers becomes a sample, with the number of intervals and the self-time spent in the
+//! innermost profiler as its values. Each profiler is represented by a function and a location.
+//!
+//! The output is an uncompressed serialized `Profile` message; `pprof` accepts both compressed and
+//! uncompressed input.
+
+use enso_prelude::*;
+
+
+
+// ==============
+// === Export ===
+// ==============
+
+/// The types of the values of each sample, as `(type, unit)`.
+const SAMPLE_TYPES: [(&str, &str); 2] = [("intervals", "count"), ("self_time", "nanoseconds")];
+
+/// Convert a profile to a serialized pprof `Profile` message.
+pub fn export<M>(profile: &crate::Profile<M>) -> Vec<u8> {
+    let mut builder = Builder::default();
+    builder.strings.get("");
+    let root = profile.root_interval_id();
+    for child in super::closed_children(profile, root) {
+        builder.visit_interval(profile, child);
+    }
+    builder.finish(profile)
+}
+
+
+// === Builder ===
+
+/// Aggregates the intervals of a profile by stack, and serializes the result.
+#[derive(Default)]
+struct Builder {
+    strings:   Strings,
+    /// Functions, by label; a function's ID is its index plus one.
+    functions: Vec<Rc<crate::Label>>,
+    labels:    HashMap<String, u64>,
+    /// The current stack of function IDs, innermost last.
+    stack:     Vec<u64>,
+    /// Aggregated values for each stack, in the order the stacks were first encountered.
+    samples:   Vec<(Vec<u64>, Sample)>,
+    stacks:    HashMap<Vec<u64>, usize>,
+}
+
+#[derive(Default, Copy, Clone)]
+struct Sample {
+    intervals: i64,
+    self_time: f64,
+}
+
+impl Builder {
+    fn visit_interval<M>(&mut self, profile: &crate::Profile<M>, id: crate::IntervalId) {
+        let active = &profile[id];
+        let label = &profile[active.measurement].label;
+        let function = self.function(label);
+        self.stack.push(function);
+        let children = super::closed_children(profile, id);
+        let children_duration: f64 =
+            children.iter().filter_map(|child| profile[*child].interval.duration_ms()).sum();
+        let duration = active.interval.duration_ms().unwrap_or_default();
+        let index = match self.stacks.get(&self.stack) {
+            Some(index) => *index,
+            None => {
+                self.samples.push((self.stack.clone(), default()));
+                self.stacks.insert(self.stack.clone(), self.samples.len() - 1);
+                self.samples.len() - 1
+            }
+        };
+        let sample = &mut self.samples[index].1;
+        sample.intervals += 1;
+        sample.self_time += duration - children_duration;
+        for child in children {
+            self.visit_interval(profile, child);
+        }
+        self.stack.pop();
+    }
+
+    fn function(&mut self, label: &Rc<crate::Label>) -> u64 {
+        let functions = &mut self.functions;
+        *self.labels.entry(label.to_string()).or_insert_with(|| {
+            functions.push(label.clone());
+            functions.len() as u64
+        })
+    }
+
+    fn finish<M>(mut self, profile: &crate::Profile<M>) -> Vec<u8> {
+        let mut out = Message::default();
+        for (type_, unit) in SAMPLE_TYPES {
+            let value_type = self.value_type(type_, unit);
+            out.message(1, &value_type);
+        }
+        for (stack, sample) in &self.samples {
+            let mut message = Message::default();
+            // Locations are listed innermost first.
+            let locations: Vec<_> = stack.iter().rev().copied().collect();
+            message.packed(1, &locations);
+            let self_time_ns = (sample.self_time * 1_000_000.0).round() as i64;
+            message.packed(2, &[sample.intervals as u64, self_time_ns as u64]);
+            out.message(2, &message);
+        }
+        // Each function has exactly one location, with the same ID.
+        for (i, label) in self.functions.iter().enumerate() {
+            let id = i as u64 + 1;
+            let mut line = Message::default();
+            line.varint(1, id);
+            line.varint(2, label.pos.as_ref().map_or(0, |pos| pos.line as u64));
+            let mut location = Message::default();
+            location.varint(1, id);
+            location.message(4, &line);
+            out.message(4, &location);
+        }
+        let functions = mem::take(&mut self.functions);
+        for (i, label) in functions.iter().enumerate() {
+            let mut function = Message::default();
+            function.varint(1, i as u64 + 1);
+            let name = self.strings.get(&label.name);
+            function.varint(2, name);
+            function.varint(3, name);
+            if let Some(pos) = label.pos.as_ref() {
+                function.varint(4, self.strings.get(&pos.file));
+                function.varint(5, pos.line as u64);
+            }
+            out.message(5, &function);
+        }
+        let period_type = self.value_type("self_time", "nanoseconds");
+        for string in &self.strings.strings {
+            out.bytes(6, string.as_bytes());
+        }
+        let time_offset = profile.headers.time_offset.map(|offset| offset.into_ms());
+        let time_nanos = time_offset.map_or(0, |ms| (ms * 1_000_000.0).round() as u64);
+        out.varint(9, time_nanos);
+        let end = profile.intervals.iter().filter_map(|active| active.interval.end).max();
+        let duration_nanos = end.map_or(0, |end| (end.into_ms() * 1_000_000.0).round() as u64);
+        out.varint(10, duration_nanos);
+        out.message(11, &period_type);
+        out.into_bytes()
+    }
+
+    fn value_type(&mut self, type_: &str, unit: &str) -> Message {
+        let mut message = Message::default();
+        message.varint(1, self.strings.get(type_));
+        message.varint(2, self.strings.get(unit));
+        message
+    }
+}
+
+
+// === Strings ===
+
+/// The string table of a profile. Strings are referred to by index.
+#[derive(Default)]
+struct Strings {
+    strings: Vec<String>,
+    indexes: HashMap<String, u64>,
+}
+
+impl Strings {
+    fn get(&mut self, string: &str) -> u64 {
+        if let Some(index) = self.indexes.get(string) {
+            return *index;
+        }
+        let index = self.strings.len() as u64;
+        self.strings.push(string.to_owned());
+        self.indexes.insert(string.to_owned(), index);
+        index
+    }
+}
+
+
+
+// ================
+// === Protobuf ===
+// ================
+
+/// A serialized protobuf message.
+#[derive(Default)]
+struct Message {
+    data: Vec<u8>,
+}
+
+impl Message {
+    const VARINT: u64 = 0;
+    const LENGTH_DELIMITED: u64 = 2;
+
+    /// Write an integer field. Fields with the default value of zero are omitted.
+    fn varint(&mut self, field: u64, value: u64) {
+        if value != 0 {
+            self.write_varint(field << 3 | Self::VARINT);
+            self.write_varint(value);
+        }
+    }
+
+    /// Write a repeated integer field, in packed encoding.
+    fn packed(&mut self, field: u64, values: &[u64]) {
+        let mut data = Message::default();
+        for value in values {
+            data.write_varint(*value);
+        }
+        self.bytes(field, &data.data);
+    }
+
+    /// Write a nested message field.
+    fn message(&mut self, field: u64, message: &Message) {
+        self.bytes(field, &message.data);
+    }
+
+    /// Write a string or bytes field.
+    fn bytes(&mut self, field: u64, bytes: &[u8]) {
+        self.write_varint(field << 3 | Self::LENGTH_DELIMITED);
+        self.write_varint(bytes.len() as u64);
+        self.data.extend_from_slice(bytes);
+    }
+
+    fn write_varint(&mut self, mut value: u64) {
+        while value >= 0x80 {
+            self.data.push(value as u8 | 0x80);
+            value >>= 7;
+        }
+        self.data.push(value as u8);
+    }
+
+    fn into_bytes(self) -> Vec<u8> {
+        self.data
+    }
+}
+
+
+
+// =============
+// === Tests ===
+// =============
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn varint_encoding() {
+        let mut message = Message::default();
+        message.varint(1, 150);
+        assert_eq!(message.into_bytes(), vec![0x08, 0x96, 0x01]);
+        let mut message = Message::default();
+        message.varint(1, 0);
+        message.packed(4, &[3, 270]);
+        assert_eq!(message.into_bytes(), vec![0x22, 0x03, 0x03, 0x8e, 0x02]);
+    }
+}
@@ -0,0 +1,170 @@
+//! Support for the [speedscope file format][format].
+//!
+//! Each profile is exported as an *evented* speedscope profile, with an open and a close event for
+//! each closed interval. Frames are shared by all profiles in a file, so that the same profiler
+//! can be compared across processes.
+//!
+//! [format]: https://github.com/jlfwong/speedscope/wiki/Importing-from-custom-sources
+
+use enso_prelude::*;
+
+use serde::Serialize;
+
+
+
+// ============
+// === File ===
+// ============
+
+const SCHEMA: &str = "https://www.speedscope.app/file-format-schema.json";
+
+/// A speedscope file.
+#[derive(Clone, Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct File {
+    #[serde(rename = "$schema")]
+    schema:                   &'static str,
+    /// Frames referenced by the profiles' events.
+    pub shared:               Shared,
+    /// The profiles contained in the file.
+    pub profiles:             Vec<EventedProfile>,
+    /// The name of the file, displayed by the viewer.
+    pub name:                 String,
+    /// The profile shown when the file is opened.
+    pub active_profile_index: usize,
+    /// Identifies the program that produced the file.
+    pub exporter:             &'static str,
+}
+
+/// Data shared by the profiles in a [`File`].
+#[derive(Clone, Debug, Default, Serialize)]
+pub struct Shared {
+    /// Stack frames, referenced by index.
+    pub frames: Vec<Frame>,
+}
+
+/// A stack frame, identifying a profiler.
+#[derive(Clone, Debug, Serialize)]
+pub struct Frame {
+    /// The name of the profiler.
+    pub name: String,
+    /// The file the profiler is defined in, if known.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub file: Option<String>,
+    /// The line the profiler is defined at, if known.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub line: Option<u32>,
+}
+
+/// A profile represented as a sequence of open and close events.
+#[derive(Clone, Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EventedProfile {
+    #[serde(rename = "type")]
+    type_:           &'static str,
+    /// The name of the profile, displayed by the viewer.
+    pub name:        String,
+    /// The unit of the events' times.
+    pub unit:        &'static str,
+    /// The time of the start of the profile.
+    pub start_value: f64,
+    /// The time of the end of the profile.
+    pub end_value:   f64,
+    /// The events, ordered by time.
+    pub events:      Vec<Event>,
+}
+
+/// The opening or closing of a frame.
+#[derive(Clone, Copy, Debug, Serialize)]
+pub struct Event {
+    /// `O` for an open event, or `C` for a close event.
+    #[serde(rename = "type")]
+    pub type_: char,
+    /// Index of the frame in [`Shared::frames`].
+    pub frame: usize,
+    /// The time of the event, in milliseconds.
+    pub at:    f64,
+}
+
+
+
+// ==============
+// === Export ===
+// ==============
+
+/// Convert profiles to a speedscope file. Each profile is named after its process, if the profile
+/// identifies it.
+pub fn export<'p, M: 'p>(
+    name: impl Into<String>,
+    profiles: impl IntoIterator<Item = &'p crate::Profile<M>>,
+) -> File {
+    let mut frames = Frames::default();
+    let profiles = profiles
+        .into_iter()
+        .enumerate()
+        .map(|(i, profile)| {
+            let name = profile.headers.process.clone().unwrap_or_else(|| format!("Profile {i}"));
+            export_profile(&mut frames, name, profile)
+        })
+        .collect();
+    let shared = Shared { frames: frames.frames };
+    let name = name.into();
+    let exporter = "enso_profiler_data";
+    File { schema: SCHEMA, shared, profiles, name, active_profile_index: 0, exporter }
+}
+
+fn export_profile<M>(
+    frames: &mut Frames,
+    name: String,
+    profile: &crate::Profile<M>,
+) -> EventedProfile {
+    let mut events = vec![];
+    let root = profile.root_interval_id();
+    for child in super::closed_children(profile, root) {
+        visit_interval(frames, profile, child, &mut events);
+    }
+    let start_value = events.first().map(|event| event.at).unwrap_or_default();
+    let end_value = events.last().map(|event| event.at).unwrap_or_default();
+    let unit = "milliseconds";
+    EventedProfile { type_: "evented", name, unit, start_value, end_value, events }
+}
+
+fn visit_interval<M>(
+    frames: &mut Frames,
+    profile: &crate::Profile<M>,
+    id: crate::IntervalId,
+    events: &mut Vec<Event>,
+) {
+    let active = &profile[id];
+    let frame = frames.get(&profile[active.measurement].label);
+    let start = active.interval.start.into_ms();
+    let end = active.interval.end.map(|end| end.into_ms()).unwrap_or(start);
+    events.push(Event { type_: 'O', frame, at: start });
+    for child in super::closed_children(profile, id) {
+        visit_interval(frames, profile, child, events);
+    }
+    events.push(Event { type_: 'C', frame, at: end });
+}
+
+
+// === Frames ===
+
+/// Assigns indexes to frames, in the order they are first encountered.
+#[derive(Default)]
+struct Frames {
+    frames:  Vec<Frame>,
+    indexes: HashMap<String, usize>,
+}
+
+impl Frames {
+    fn get(&mut self, label: &crate::Label) -> usize {
+        let frames = &mut self.frames;
+        *self.indexes.entry(label.to_string()).or_insert_with(|| {
+            let name = label.name.clone();
+            let file = label.pos.as_ref().map(|pos| pos.file.clone());
+            let line = label.pos.as_ref().map(|pos| pos.line);
+            frames.push(Frame { name, file, line });
+            frames.len() - 1
+        })
+    }
+}
@@ -0,0 +1,137 @@
+//! Support for the [Trace Event format][format], as read by [Perfetto](https://ui.perfetto.dev/)
+//! and `chrome://tracing`.
+//!
+//! Each profile is exported as a separate process, named after the profile's
+//! [`Header::Process`](enso_profiler::format::Header::Process), so that every process gets its own
+//! track. Intervals are exported as complete (`X`) events.
+//!
+//! [format]: https://docs.google.com/document/d/1CvAClvFfyA5R-PhYUmn5OOQtYMH4h6I0nSsKchNAySU/preview
+
+use serde::Serialize;
+
+
+
+// ============
+// === File ===
+// ============
+
+/// A Trace Event file, in the JSON object format.
+#[derive(Clone, Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct File {
+    /// The events, ordered by process and time.
+    pub trace_events:      Vec<Event>,
+    /// The unit used to display times.
+    pub display_time_unit: &'static str,
+}
+
+/// A trace event.
+#[derive(Clone, Debug, Serialize)]
+pub struct Event {
+    /// The name of the profiler, or of the metadata entry.
+    pub name:       String,
+    /// Comma-separated categories of the event.
+    #[serde(rename = "cat")]
+    pub category:   &'static str,
+    /// The type of the event.
+    #[serde(rename = "ph")]
+    pub event_type: EventType,
+    /// The start time of the event, in microseconds.
+    #[serde(rename = "ts")]
+    pub timestamp:  f64,
+    /// The duration of a complete event, in microseconds.
+    #[serde(rename = "dur")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub duration:   Option<f64>,
+    /// Identifies the process; each process is shown as a separate track.
+    #[serde(rename = "pid")]
+    pub process_id: u32,
+    /// Identifies the thread within the process.
+    #[serde(rename = "tid")]
+    pub thread_id:  u32,
+    /// Additional data.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub args:       Option<Args>,
+}
+
+/// The type of a trace event.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Serialize)]
+pub enum EventType {
+    /// An event with a start and a duration.
+    #[serde(rename = "X")]
+    Complete,
+    /// Information about a process or thread.
+    #[serde(rename = "M")]
+    Metadata,
+}
+
+/// Additional data of an event.
+#[derive(Clone, Debug, Serialize)]
+pub struct Args {
+    /// For a metadata event, the name of the process or thread.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    /// For a complete event, the location of the profiler in the code.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub pos:  Option<String>,
+}
+
+
+
+// ==============
+// === Export ===
+// ==============
+
+/// The thread that all intervals are attributed to; profiles do not distinguish threads.
+const THREAD_ID: u32 = 1;
+
+/// Convert profiles to a Trace Event file. Each profile is assigned a process ID corresponding to
+/// its position in the input.
+pub fn export<'p, M: 'p>(profiles: impl IntoIterator<Item = &'p crate::Profile<M>>) -> File {
+    let mut trace_events = vec![];
+    for (i, profile) in profiles.into_iter().enumerate() {
+        let process_id = i as u32 + 1;
+        let name = profile.headers.process.clone().unwrap_or_else(|| format!("Profile {i}"));
+        let args = Some(Args { name: Some(name), pos: None });
+        trace_events.push(Event {
+            name: "process_name".to_owned(),
+            category: "__metadata",
+            event_type: EventType::Metadata,
+            timestamp: 0.0,
+            duration: None,
+            process_id,
+            thread_id: THREAD_ID,
+            args,
+        });
+        let root = profile.root_interval_id();
+        for child in super::closed_children(profile, root) {
+            visit_interval(profile, child, process_id, &mut trace_events);
+        }
+    }
+    File { trace_events, display_time_unit: "ms" }
+}
+
+fn visit_interval<M>(
+    profile: &crate::Profile<M>,
+    id: crate::IntervalId,
+    process_id: u32,
+    events: &mut Vec<Event>,
+) {
+    let active = &profile[id];
+    let label = &profile[active.measurement].label;
+    let pos = label.pos.as_ref().map(|pos| format!("{}:{}", pos.file, pos.line));
+    let duration = active.interval.duration_ms().map(|ms| ms * 1000.0);
+    events.push(Event {
+        name: label.name.clone(),
+        category: "interval",
+        event_type: EventType::Complete,
+        timestamp: active.interval.start.into_ms() * 1000.0,
+        duration,
+        process_id,
+        thread_id: THREAD_ID,
+        args: pos.map(|pos| Args { name: None, pos: Some(pos) }),
+    });
+    for child in super::closed_children(profile, id) {
+        visit_interval(profile, child, process_id, events);
+    }
+}
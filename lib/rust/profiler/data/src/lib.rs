@@ -9,6 +9,8 @@
 //! metadata entries fails, the resulting error type provides access to the result of deserializing
 //! all the data that succeeded (see [`Error::RecoverableFormatError`]).
 //!
+//! Logs too large to be held in memory can be processed incrementally; see [`stream`].
+//!
 //! # Usage example: storing and retrieving metadata
 //!
 //! ```
@@ -98,7 +100,12 @@ use std::rc::Rc;
 // ==============
 
 pub mod aggregate;
+pub mod critical_path;
+pub mod diff;
+pub mod export;
 pub mod parse;
+pub mod stats;
+pub mod stream;
 
 
 
@@ -213,7 +213,7 @@ pub enum State {
 
 /// An index into the event log. Mainly used for error reporting.
 #[derive(Debug, Copy, Clone)]
-pub struct EventId(usize);
+pub struct EventId(pub(crate) usize);
 
 impl From<EventId> for crate::Seq {
     fn from(id: EventId) -> Self {
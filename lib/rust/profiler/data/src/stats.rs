@@ -0,0 +1,371 @@
+//! Supports summarizing the measurements of many profiles of repeated runs of the same workflow, to
+//! tell whether a change in performance is significant compared to the noise between runs.
+//!
+//! Measurements are identified by their label path, as in [`crate::diff`]. Each measurement
+//! contributes a *sample* of its duration; alternatively, the durations of all the measurements
+//! with the same path in a run can be summed into a single sample per run (see [`Sampling`]).
+
+use crate::diff;
+use crate::diff::Path;
+
+use std::collections::BTreeMap;
+use std::error;
+use std::fmt;
+use std::fmt::Write;
+use std::io;
+
+
+
+// ==============
+// === Errors ===
+// ==============
+
+/// An error that occurred while reading the profiles in a directory.
+#[derive(Debug)]
+pub enum LoadError {
+    /// Failed to read the directory or a file in it.
+    Io {
+        /// The file or directory that could not be read.
+        path:  std::path::PathBuf,
+        /// The error.
+        error: io::Error,
+    },
+    /// Failed to parse a profile.
+    Parse {
+        /// The file containing the profile.
+        path:  std::path::PathBuf,
+        /// The error.
+        error: Box<crate::Error<crate::OpaqueMetadata>>,
+    },
+}
+
+impl fmt::Display for LoadError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LoadError::Io { path, error } => write!(f, "Failed to read {path:?}: {error}"),
+            LoadError::Parse { path, error } => write!(f, "Failed to parse {path:?}: {error}"),
+        }
+    }
+}
+
+impl error::Error for LoadError {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        Some(match self {
+            LoadError::Io { error, .. } => error,
+            LoadError::Parse { error, .. } => &**error,
+        })
+    }
+}
+
+
+
+// ============
+// === Runs ===
+// ============
+
+/// Determines what is counted as a single sample of a measurement's duration.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub enum Sampling {
+    /// Each measurement is a sample.
+    #[default]
+    Measurement,
+    /// The total duration of all the measurements with the same path in a run is a sample.
+    Run,
+}
+
+/// The samples of the measurements of a set of runs, by label path.
+#[derive(Clone, Debug, Default)]
+pub struct Runs {
+    /// The number of runs added.
+    pub runs: usize,
+    samples:  BTreeMap<Path, Vec<f64>>,
+}
+
+impl Runs {
+    /// Add the profiles of a single run. A run may consist of the profiles of several processes;
+    /// measurements with the same path in different processes are treated as the same.
+    pub fn add_run<'p, M: 'p>(
+        &mut self,
+        profiles: impl IntoIterator<Item = &'p crate::Profile<M>>,
+        sampling: Sampling,
+    ) {
+        let mut run = BTreeMap::<Path, Vec<f64>>::new();
+        for profile in profiles {
+            for (path, stats) in diff::collect(profile) {
+                run.entry(path).or_default().extend(stats.durations());
+            }
+        }
+        for (path, durations) in run {
+            let samples = self.samples.entry(path).or_default();
+            match sampling {
+                Sampling::Measurement => samples.extend(durations),
+                Sampling::Run => samples.push(durations.iter().sum()),
+            }
+        }
+        self.runs += 1;
+    }
+
+    /// Add every `.json` file in a directory as a run. Files are read in order of their names.
+    /// Returns the number of runs added.
+    pub fn add_dir(
+        &mut self,
+        dir: impl AsRef<std::path::Path>,
+        sampling: Sampling,
+    ) -> Result<usize, LoadError> {
+        let dir = dir.as_ref();
+        let io_error = |path: &std::path::Path| {
+            let path = path.to_owned();
+            move |error| LoadError::Io { path, error }
+        };
+        let mut paths = vec![];
+        for entry in std::fs::read_dir(dir).map_err(io_error(dir))? {
+            let path = entry.map_err(io_error(dir))?.path();
+            if path.extension().map_or(false, |extension| extension == "json") {
+                paths.push(path);
+            }
+        }
+        paths.sort();
+        for path in &paths {
+            let log = std::fs::read_to_string(path).map_err(io_error(path))?;
+            let parse_error =
+                |error| LoadError::Parse { path: path.clone(), error: Box::new(error) };
+            let mut profiles: Vec<crate::Profile<crate::OpaqueMetadata>> = vec![];
+            let stream = serde_json::Deserializer::from_str(&log);
+            for profile in stream.into_iter::<Box<serde_json::value::RawValue>>() {
+                let profile = profile.map_err(|e| parse_error(crate::Error::FormatError(e)))?;
+                profiles.push(profile.get().parse().map_err(parse_error)?);
+            }
+            self.add_run(&profiles, sampling);
+        }
+        Ok(paths.len())
+    }
+
+    /// Summarize the samples of each path, in order of their paths.
+    pub fn summarize(&self, outliers: Option<Fences>) -> Vec<(Path, Distribution)> {
+        let summarize = |samples: &Vec<f64>| {
+            let mut samples = samples.clone();
+            samples.sort_by(f64::total_cmp);
+            Distribution::new(&samples, outliers)
+        };
+        self.samples.iter().map(|(path, samples)| (path.clone(), summarize(samples))).collect()
+    }
+}
+
+
+
+// ====================
+// === Distribution ===
+// ====================
+
+/// Summary of the samples of a measurement, in milliseconds.
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+pub struct Distribution {
+    /// The number of samples, after excluding outliers.
+    pub count:    usize,
+    /// The number of samples excluded as outliers.
+    pub rejected: usize,
+    /// The shortest duration.
+    pub min:      f64,
+    /// The median duration.
+    pub median:   f64,
+    /// The 90th percentile duration.
+    pub p90:      f64,
+    /// The 99th percentile duration.
+    pub p99:      f64,
+    /// The longest duration.
+    pub max:      f64,
+    /// The mean duration.
+    pub mean:     f64,
+    /// The sample standard deviation of the durations; 0 if there are fewer than two samples.
+    pub stddev:   f64,
+}
+
+impl Distribution {
+    /// Summarize samples, which must be in ascending order. If fences are given, samples outside
+    /// them are excluded.
+    pub fn new(samples: &[f64], outliers: Option<Fences>) -> Self {
+        let included = match outliers {
+            Some(fences) => fences.filter(samples),
+            None => samples,
+        };
+        let rejected = samples.len() - included.len();
+        let count = included.len();
+        let mean = included.iter().sum::<f64>() / count.max(1) as f64;
+        let variance = match count {
+            0 | 1 => 0.0,
+            _ => included.iter().map(|x| (x - mean).powi(2)).sum::<f64>() / (count - 1) as f64,
+        };
+        Self {
+            count,
+            rejected,
+            min: included.first().copied().unwrap_or_default(),
+            median: percentile(included, 0.5),
+            p90: percentile(included, 0.9),
+            p99: percentile(included, 0.99),
+            max: included.last().copied().unwrap_or_default(),
+            mean,
+            stddev: variance.sqrt(),
+        }
+    }
+}
+
+/// Return the value of a sample such that the given fraction of the samples are no greater.
+/// Samples must be in ascending order. Uses the nearest-rank method; returns 0 if there are no
+/// samples.
+pub fn percentile(samples: &[f64], fraction: f64) -> f64 {
+    let rank = (fraction * samples.len() as f64).ceil() as usize;
+    match rank {
+        0 => samples.first().copied().unwrap_or_default(),
+        rank => samples[rank.min(samples.len()) - 1],
+    }
+}
+
+
+// === Fences ===
+
+/// Tukey's fences: samples further than a multiple of the interquartile range below the first
+/// quartile, or above the third quartile, are considered outliers.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Fences {
+    /// The multiple of the interquartile range.
+    pub k: f64,
+}
+
+impl Default for Fences {
+    fn default() -> Self {
+        Self { k: 1.5 }
+    }
+}
+
+impl Fences {
+    /// Return the samples within the fences. Samples must be in ascending order.
+    pub fn filter(self, samples: &[f64]) -> &[f64] {
+        let q1 = percentile(samples, 0.25);
+        let q3 = percentile(samples, 0.75);
+        let range = q3 - q1;
+        let (low, high) = (q1 - self.k * range, q3 + self.k * range);
+        let start = samples.partition_point(|&x| x < low);
+        let end = samples.partition_point(|&x| x <= high);
+        &samples[start..end]
+    }
+}
+
+
+
+// =================
+// === Reporting ===
+// =================
+
+const COLUMNS: [&str; 10] =
+    ["count", "rejected", "min", "median", "p90", "p99", "max", "mean", "stddev", "path"];
+
+/// Format summaries as a table, with a row for each path.
+pub fn to_table(summaries: &[(Path, Distribution)]) -> String {
+    let mut out = String::new();
+    let [count, rejected, numbers @ .., path] = COLUMNS;
+    write!(out, "{count:>7} {rejected:>8}").unwrap();
+    for name in numbers {
+        write!(out, " {name:>10}").unwrap();
+    }
+    writeln!(out, " {path}").unwrap();
+    for (path, distribution) in summaries {
+        write!(out, "{:>7} {:>8}", distribution.count, distribution.rejected).unwrap();
+        for value in durations(distribution) {
+            write!(out, " {value:>10.3}").unwrap();
+        }
+        writeln!(out, " {}", path.join(" / ")).unwrap();
+    }
+    out
+}
+
+/// Format summaries as CSV, with a header row and a row for each path.
+pub fn to_csv(summaries: &[(Path, Distribution)]) -> String {
+    let mut out = COLUMNS.join(",");
+    out.push('\n');
+    for (path, distribution) in summaries {
+        write!(out, "{},{}", distribution.count, distribution.rejected).unwrap();
+        for value in durations(distribution) {
+            write!(out, ",{value}").unwrap();
+        }
+        let path = path.join(" / ");
+        writeln!(out, ",\"{}\"", path.replace('"', "\"\"")).unwrap();
+    }
+    out
+}
+
+fn durations(distribution: &Distribution) -> [f64; 7] {
+    let Distribution { min, median, p90, p99, max, mean, stddev, .. } = *distribution;
+    [min, median, p90, p99, max, mean, stddev]
+}
+
+
+
+// =============
+// === Tests ===
+// =============
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn distribution() {
+        let distribution = Distribution::new(&[1.0, 2.0, 3.0, 4.0, 5.0], None);
+        assert_eq!(distribution.count, 5);
+        assert_eq!(distribution.min, 1.0);
+        assert_eq!(distribution.median, 3.0);
+        assert_eq!(distribution.p99, 5.0);
+        assert_eq!(distribution.mean, 3.0);
+        assert_eq!(distribution.stddev, 2.5f64.sqrt());
+        assert_eq!(Distribution::new(&[], None), Distribution::default());
+    }
+
+    #[test]
+    fn reject_outliers() {
+        let samples = [-50.0, 10.0, 10.0, 11.0, 12.0, 12.0, 13.0, 100.0];
+        let distribution = Distribution::new(&samples, Some(Fences::default()));
+        assert_eq!(distribution.count, 6);
+        assert_eq!(distribution.rejected, 2);
+        assert_eq!(distribution.min, 10.0);
+        assert_eq!(distribution.max, 13.0);
+        // Identical samples have no outliers.
+        assert_eq!(Fences::default().filter(&[1.0, 1.0]), &[1.0, 1.0]);
+    }
+
+    #[test]
+    fn sampling() {
+        use enso_profiler as profiler;
+        use profiler::profile;
+        #[profile(Objective)]
+        fn parent() {
+            child();
+            child();
+        }
+        #[profile(Objective)]
+        fn child() {}
+        parent();
+        let profile: crate::Profile<crate::OpaqueMetadata> =
+            profiler::internal::get_log().parse().unwrap();
+        let child = vec!["parent".to_owned(), "child".to_owned()];
+        let mut by_measurement = Runs::default();
+        let mut by_run = Runs::default();
+        for _ in 0..3 {
+            by_measurement.add_run([&profile], Sampling::Measurement);
+            by_run.add_run([&profile], Sampling::Run);
+        }
+        let by_measurement: BTreeMap<_, _> = by_measurement.summarize(None).into_iter().collect();
+        let by_run: BTreeMap<_, _> = by_run.summarize(None).into_iter().collect();
+        assert_eq!(by_measurement[&child].count, 6);
+        assert_eq!(by_run[&child].count, 3);
+    }
+
+    #[test]
+    fn csv() {
+        let path = vec!["a \"b\"".to_owned(), "c".to_owned()];
+        let distribution = Distribution::new(&[2.0], None);
+        let csv = to_csv(&[(path, distribution)]);
+        let expected = "count,rejected,min,median,p90,p99,max,mean,stddev,path\n\
+                        1,0,2,2,2,2,2,2,0,\"a \"\"b\"\" / c\"\n";
+        assert_eq!(csv, expected);
+    }
+}
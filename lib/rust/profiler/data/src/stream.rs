@@ -0,0 +1,684 @@
+//! Supports analyzing event logs that are too large to be parsed into a [`crate::Profile`].
+//!
+//! A [`Parser`] consumes [`format::Event`]s one at a time, and produces each [`Measurement`] as
+//! soon as it has ended. Only the state of the measurements that are still running is retained, so
+//! the memory used is bounded by the number of measurements that are alive at the same time, rather
+//! than by the length of the log.
+//!
+//! The measurements produced do not reference each other through a profile; each one identifies
+//! its parent by ID. IDs are assigned in the same way as by [`str::parse`], so the results can be
+//! correlated with a full parse of the same log.
+//!
+//! A [`Window`] can be used to select the measurements active during a slice of the log.
+//!
+//! # Usage example: reading a log
+//!
+//! ```
+//! use enso_profiler as profiler;
+//! use enso_profiler_data as profiler_data;
+//! use profiler::profile;
+//! use profiler_data::stream;
+//!
+//! #[profile(Objective)]
+//! fn parent() {
+//!     child();
+//! }
+//! #[profile(Objective)]
+//! fn child() {}
+//!
+//! parent();
+//! let log = profiler::internal::get_log();
+//! let mut labels = vec![];
+//! let window = stream::Window::default();
+//! stream::read::<profiler_data::OpaqueMetadata, _>(log.as_bytes(), window, |_, item| {
+//!     if let stream::Item::Measurement(measurement) = item {
+//!         labels.push(measurement.label.name.clone());
+//!     }
+//! })
+//! .unwrap();
+//! // Measurements are produced in the order they end.
+//! assert_eq!(labels, ["child", "parent"]);
+//! ```
+
+use crate::parse::DataError;
+use crate::parse::EventId;
+use crate::parse::State;
+
+use enso_profiler as profiler;
+use profiler::format;
+use std::collections;
+use std::error;
+use std::fmt;
+use std::io;
+use std::mem;
+use std::rc::Rc;
+
+
+
+// ==============
+// === Window ===
+// ==============
+
+/// A range of time in a profile. Unbounded by default.
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+pub struct Window {
+    /// If set, measurements that were not active since this time (in ms) are excluded.
+    pub start_ms: Option<f64>,
+    /// If set, measurements that were not active before this time (in ms) are excluded.
+    pub end_ms:   Option<f64>,
+}
+
+impl Window {
+    /// Return a window from the given start time to the given end time, in ms.
+    pub fn new(start_ms: f64, end_ms: f64) -> Self {
+        Self { start_ms: Some(start_ms), end_ms: Some(end_ms) }
+    }
+
+    /// Return whether the window contains the given time.
+    pub fn contains(self, time: crate::Timestamp) -> bool {
+        self.overlaps(crate::Interval { start: time, end: Some(time) })
+    }
+
+    /// Return whether any part of the given interval falls within the window.
+    pub fn overlaps(self, interval: crate::Interval) -> bool {
+        let starts_before_end = self.end_ms.map_or(true, |end| interval.start.into_ms() <= end);
+        let ends_after_start = match (self.start_ms, interval.end) {
+            (Some(start), Some(end)) => end.into_ms() >= start,
+            _ => true,
+        };
+        starts_before_end && ends_after_start
+    }
+}
+
+
+
+// ============
+// === Item ===
+// ============
+
+/// Data produced by a [`Parser`].
+#[derive(Clone, Debug)]
+pub enum Item<M> {
+    /// A measurement, which has ended or was still running at the end of the log.
+    Measurement(Measurement<M>),
+    /// Metadata logged while no measurement was running.
+    Metadata(crate::Timestamped<M>),
+}
+
+
+// === Measurement ===
+
+/// All the information produced by a profiler.
+#[derive(Clone, Debug)]
+pub struct Measurement<M> {
+    /// Identifies the measurement; the ID is the same as in a [`crate::Profile`] parsed from the
+    /// same log.
+    pub id:        crate::MeasurementId,
+    /// The measurement that started this measurement, or [`None`] for top-level measurements.
+    pub parent:    Option<crate::MeasurementId>,
+    /// Identifies the profiler's source and scope to the user.
+    pub label:     Rc<crate::Label>,
+    /// When the profiler was created.
+    pub created:   crate::Timestamp,
+    /// Whether the profiler logged its completion at the end of its last active interval.
+    pub finished:  bool,
+    /// When the profiler was running.
+    pub intervals: Vec<crate::Interval>,
+    /// Metadata emitted while this was the running measurement.
+    pub metadata:  Vec<crate::Timestamped<M>>,
+}
+
+impl<M> Measurement<M> {
+    /// Return whether the measurement was active during the window. A measurement that never ran
+    /// is considered active at the time it was created.
+    pub fn is_within(&self, window: Window) -> bool {
+        match self.intervals.is_empty() {
+            true => window.contains(self.created),
+            false => self.intervals.iter().any(|interval| window.overlaps(*interval)),
+        }
+    }
+}
+
+
+
+// ==============
+// === Parser ===
+// ==============
+
+/// Interprets a log of events incrementally. See the [module docs](self).
+#[derive(Debug)]
+pub struct Parser<M> {
+    window:          Window,
+    /// The next event's index in the log.
+    log_pos:         usize,
+    /// References to the locations in code that measurements measure.
+    labels:          Vec<Rc<crate::Label>>,
+    /// Measurements that have not ended yet.
+    live:            collections::HashMap<format::MeasurementId, Live<M>>,
+    /// The number of measurements created so far.
+    created:         usize,
+    /// Measurements currently running, as a LIFO stack.
+    active:          Vec<format::MeasurementId>,
+    /// Results that have not been taken yet.
+    output:          collections::VecDeque<Item<M>>,
+    /// Properties of the whole profile.
+    headers:         crate::Headers,
+    /// Errors for metadata objects that could not be deserialized as type [`M`].
+    metadata_errors: Vec<crate::EventError<serde_json::Error>>,
+}
+
+/// Data about a measurement that has not ended yet.
+#[derive(Debug)]
+struct Live<M> {
+    state:       State,
+    measurement: Measurement<M>,
+}
+
+impl<M> Default for Parser<M> {
+    fn default() -> Self {
+        Self::new(Window::default())
+    }
+}
+
+impl<M> Parser<M> {
+    /// Create a parser that produces the measurements active during the given window.
+    pub fn new(window: Window) -> Self {
+        Self {
+            window,
+            log_pos: Default::default(),
+            labels: Default::default(),
+            live: Default::default(),
+            created: Default::default(),
+            active: Default::default(),
+            output: Default::default(),
+            headers: Default::default(),
+            metadata_errors: Default::default(),
+        }
+    }
+
+    /// Properties of the profile that have been read so far.
+    pub fn headers(&self) -> &crate::Headers {
+        &self.headers
+    }
+
+    /// Errors for the metadata objects that could not be deserialized as type `M` so far. As in
+    /// [`crate::Error::RecoverableFormatError`], data of other types is unaffected.
+    pub fn metadata_errors(&self) -> &[crate::EventError<serde_json::Error>] {
+        &self.metadata_errors
+    }
+
+    /// Return the next result, if any is ready.
+    pub fn next_item(&mut self) -> Option<Item<M>> {
+        self.output.pop_front()
+    }
+
+    /// Return an iterator that feeds the given events to the parser, and yields the results. After
+    /// the last event, any measurements still running are yielded, as by [`Parser::finish`].
+    pub fn parse<'a, I>(self, events: I) -> Items<M, I::IntoIter>
+    where I: IntoIterator<Item = format::Event<'a>> {
+        Items { parser: self, events: events.into_iter(), done: false }
+    }
+
+    /// End any measurements still running, and make them available; no more events can be
+    /// processed afterwards.
+    pub fn finish(&mut self) {
+        let mut live: Vec<_> = self.live.drain().collect();
+        live.sort_unstable_by_key(|(id, _)| *id);
+        for (_, live) in live {
+            self.emit(live.measurement);
+        }
+        self.active.clear();
+    }
+
+    fn emit(&mut self, measurement: Measurement<M>) {
+        if measurement.is_within(self.window) {
+            self.output.push_back(Item::Measurement(measurement));
+        }
+    }
+
+    fn time(&self, time: format::Timestamp) -> crate::Timestamp {
+        crate::Timestamp { seq: crate::Seq::runtime_event(self.log_pos), time }
+    }
+
+    fn live(&mut self, id: format::MeasurementId) -> Result<&mut Live<M>, DataError> {
+        self.live.get_mut(&id).ok_or(DataError::MeasurementNotFound(id))
+    }
+
+    /// Remove the running measurement from the stack, checking that it is the expected one.
+    fn pop_active(&mut self, id: format::MeasurementId) -> Result<(), DataError> {
+        let expected = self.active.pop().ok_or(DataError::ActiveProfilerRequired)?;
+        match id == expected {
+            true => Ok(()),
+            false => Err(DataError::WrongProfiler { found: id, expected }),
+        }
+    }
+}
+
+impl<M: serde::de::DeserializeOwned> Parser<M> {
+    /// Process the next event in the log. Any measurement it ends is made available.
+    pub fn event(&mut self, event: format::Event) -> Result<(), crate::EventError<DataError>> {
+        let log_pos = self.log_pos;
+        let result = match event {
+            format::Event::Create(event) => self.create(event),
+            format::Event::Start { id, timestamp } => self.resume(id, timestamp),
+            format::Event::Pause { id, timestamp } => self.pause(id, timestamp),
+            format::Event::End { id, timestamp } => self.end(id, timestamp),
+            format::Event::Metadata(metadata) => self.metadata(metadata),
+            format::Event::Label { label } => self.label(label.as_ref()),
+        };
+        self.log_pos += 1;
+        result.map_err(|error| crate::EventError { log_pos, error })
+    }
+}
+
+
+// === Handlers for each event ===
+
+impl<M: serde::de::DeserializeOwned> Parser<M> {
+    fn create(&mut self, event: format::Start) -> Result<(), DataError> {
+        let parent = match event.parent {
+            format::Parent::Explicit(parent) => parent,
+            format::Parent::Implicit => match self.active.last() {
+                Some(id) => format::ParentId::Measurement(*id),
+                None => format::ParentId::Root,
+            },
+        };
+        let created = match (event.start, parent) {
+            (Some(time), _) => self.time(time),
+            (None, format::ParentId::Root) => crate::Timestamp::time_origin(),
+            (None, format::ParentId::Measurement(id)) => self.live(id)?.measurement.created,
+        };
+        let label = event.label.id();
+        let label = self.labels.get(label).ok_or(DataError::UndefinedLabel(label))?.clone();
+        let parent = match parent {
+            format::ParentId::Measurement(format::MeasurementId(id)) =>
+                Some(crate::MeasurementId(id)),
+            format::ParentId::Root => None,
+        };
+        let id = self.created;
+        self.created += 1;
+        let measurement = Measurement {
+            id: crate::MeasurementId(id),
+            parent,
+            label,
+            created,
+            finished: Default::default(),
+            intervals: Default::default(),
+            metadata: Default::default(),
+        };
+        let state = State::Paused(EventId(self.log_pos));
+        let old = self.live.insert(format::MeasurementId(id), Live { state, measurement });
+        assert!(old.is_none());
+        Ok(())
+    }
+
+    fn resume(
+        &mut self,
+        id: format::MeasurementId,
+        time: format::Timestamp,
+    ) -> Result<(), DataError> {
+        let start = self.time(time);
+        let state = State::Active(EventId(self.log_pos));
+        let live = self.live(id)?;
+        match mem::replace(&mut live.state, state) {
+            State::Paused(_) => (),
+            state => return Err(DataError::UnexpectedState(state)),
+        }
+        live.measurement.intervals.push(crate::Interval { start, end: None });
+        self.active.push(id);
+        Ok(())
+    }
+
+    fn pause(
+        &mut self,
+        id: format::MeasurementId,
+        time: format::Timestamp,
+    ) -> Result<(), DataError> {
+        self.pop_active(id)?;
+        let end = self.time(time);
+        let state = State::Paused(EventId(self.log_pos));
+        let live = self.live(id)?;
+        match mem::replace(&mut live.state, state) {
+            State::Active(_) => (),
+            state => return Err(DataError::UnexpectedState(state)),
+        }
+        if let Some(interval) = live.measurement.intervals.last_mut() {
+            interval.end = Some(end);
+        }
+        Ok(())
+    }
+
+    fn end(&mut self, id: format::MeasurementId, time: format::Timestamp) -> Result<(), DataError> {
+        let end = self.time(time);
+        let state = State::Ended(EventId(self.log_pos));
+        match mem::replace(&mut self.live(id)?.state, state) {
+            // Typical case: The current profiler ends.
+            State::Active(_) => {
+                self.pop_active(id)?;
+                let intervals = &mut self.live(id)?.measurement.intervals;
+                if let Some(interval) = intervals.last_mut() {
+                    interval.end = Some(end);
+                }
+            }
+            // Edge case: A profiler can be dropped without ever being started if an async block
+            // is created, but dropped without ever being awaited.
+            State::Paused(_) => (),
+            state => return Err(DataError::UnexpectedState(state)),
+        }
+        let mut live = self.live.remove(&id).ok_or(DataError::MeasurementNotFound(id))?;
+        live.measurement.finished = true;
+        self.emit(live.measurement);
+        Ok(())
+    }
+
+    fn metadata(
+        &mut self,
+        metadata: format::Timestamped<format::AnyMetadata>,
+    ) -> Result<(), DataError> {
+        let format::Timestamped { time, data } = metadata;
+        let time = self.time(time);
+        if let Ok(data) = serde_json::from_str(data.get()) {
+            match data {
+                format::Header::Process(process) => self.headers.process = Some(process),
+                format::Header::TimeOffset(offset) => self.headers.time_offset = Some(offset),
+            }
+            return Ok(());
+        }
+        match serde_json::from_str(data.get()) {
+            Ok(data) => {
+                let metadata = crate::Timestamped { time, data };
+                match self.active.last().copied() {
+                    Some(id) => self.live(id)?.measurement.metadata.push(metadata),
+                    None if self.window.contains(time) =>
+                        self.output.push_back(Item::Metadata(metadata)),
+                    None => (),
+                }
+            }
+            Err(error) => {
+                let log_pos = self.log_pos;
+                self.metadata_errors.push(crate::EventError { log_pos, error })
+            }
+        }
+        Ok(())
+    }
+
+    fn label(&mut self, label: &str) -> Result<(), DataError> {
+        let label = label.parse()?;
+        self.labels.push(Rc::new(label));
+        Ok(())
+    }
+}
+
+
+// === Items ===
+
+/// Iterator over the results of parsing a sequence of events; see [`Parser::parse`].
+#[derive(Debug)]
+pub struct Items<M, I> {
+    parser: Parser<M>,
+    events: I,
+    done:   bool,
+}
+
+impl<M, I> Items<M, I> {
+    /// The parser, e.g. to access the [`Parser::headers`] when iteration is complete.
+    pub fn parser(&self) -> &Parser<M> {
+        &self.parser
+    }
+}
+
+impl<'a, M, I> Iterator for Items<M, I>
+where
+    M: serde::de::DeserializeOwned,
+    I: Iterator<Item = format::Event<'a>>,
+{
+    type Item = Result<Item<M>, crate::EventError<DataError>>;
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some(item) = self.parser.next_item() {
+                return Some(Ok(item));
+            }
+            if self.done {
+                return None;
+            }
+            match self.events.next() {
+                Some(event) =>
+                    if let Err(error) = self.parser.event(event) {
+                        self.done = true;
+                        return Some(Err(error));
+                    },
+                None => {
+                    self.parser.finish();
+                    self.done = true;
+                }
+            }
+        }
+    }
+}
+
+
+
+// ============
+// === read ===
+// ============
+
+/// Parse a log from a reader, passing each result to a function along with the index of the
+/// profile it belongs to. A log may contain the profiles of multiple processes (see
+/// [`crate::parse_multiprocess_profile`]). Returns the [`Summary`] of each profile.
+///
+/// Events are read and interpreted one at a time; the log is never held in memory.
+pub fn read<M, R>(
+    reader: R,
+    window: Window,
+    mut f: impl FnMut(usize, Item<M>),
+) -> Result<Vec<Summary>, Error>
+where
+    M: serde::de::DeserializeOwned,
+    R: io::Read,
+{
+    use serde::de::Deserializer;
+    let mut deserializer = serde_json::Deserializer::from_reader(io::BufReader::new(reader));
+    let mut summaries = vec![];
+    loop {
+        let index = summaries.len();
+        let mut visitor = EventsVisitor {
+            parser: Parser::new(window),
+            f:      |item| f(index, item),
+            error:  None,
+        };
+        let result = (&mut deserializer).deserialize_seq(&mut visitor);
+        let EventsVisitor { mut parser, mut f, error } = visitor;
+        if let Some(error) = error {
+            return Err(Error::DataError(error));
+        }
+        result.map_err(Error::FormatError)?;
+        parser.finish();
+        while let Some(item) = parser.next_item() {
+            f(item);
+        }
+        let Parser { headers, metadata_errors, .. } = parser;
+        summaries.push(Summary { headers, metadata_errors });
+        if deserializer.end().is_ok() {
+            return Ok(summaries);
+        }
+    }
+}
+
+/// Information about a profile read by [`read`].
+#[derive(Debug)]
+pub struct Summary {
+    /// Optional information about the profile.
+    pub headers:         crate::Headers,
+    /// Errors for metadata objects that could not be deserialized.
+    pub metadata_errors: Vec<crate::EventError<serde_json::Error>>,
+}
+
+/// Feeds the elements of a JSON array of events to a [`Parser`] as they are deserialized.
+struct EventsVisitor<M, F> {
+    parser: Parser<M>,
+    f:      F,
+    /// The first [`DataError`]; it is reported to the deserializer as a custom error, which cannot
+    /// carry the original.
+    error:  Option<crate::EventError<DataError>>,
+}
+
+impl<'de, M, F> serde::de::Visitor<'de> for &mut EventsVisitor<M, F>
+where
+    M: serde::de::DeserializeOwned,
+    F: FnMut(Item<M>),
+{
+    type Value = ();
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("an array of profiler events")
+    }
+
+    fn visit_seq<A: serde::de::SeqAccess<'de>>(self, mut seq: A) -> Result<(), A::Error> {
+        while let Some(event) = seq.next_element::<format::Event>()? {
+            if let Err(error) = self.parser.event(event) {
+                let message = error.to_string();
+                self.error = Some(error);
+                return Err(serde::de::Error::custom(message));
+            }
+            while let Some(item) = self.parser.next_item() {
+                (self.f)(item);
+            }
+        }
+        Ok(())
+    }
+}
+
+
+// === Error ===
+
+/// An error reading a log.
+#[derive(Debug)]
+pub enum Error {
+    /// Failed to deserialize the event log. The file is corrupt, or in a completely incompatible
+    /// format.
+    FormatError(serde_json::Error),
+    /// Failed to interpret the event log data.
+    DataError(crate::EventError<DataError>),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::FormatError(e) => e.fmt(f),
+            Error::DataError(e) => e.fmt(f),
+        }
+    }
+}
+
+impl error::Error for Error {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        Some(match self {
+            Error::FormatError(e) => e,
+            Error::DataError(e) => e,
+        })
+    }
+}
+
+
+
+// =============
+// === Tests ===
+// =============
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use profiler::profile;
+
+    fn measurements(
+        items: Vec<Item<crate::OpaqueMetadata>>,
+    ) -> Vec<Measurement<crate::OpaqueMetadata>> {
+        let measurements = items.into_iter().filter_map(|item| match item {
+            Item::Measurement(measurement) => Some(measurement),
+            Item::Metadata(_) => None,
+        });
+        measurements.collect()
+    }
+
+    /// The measurements produced incrementally are the same as those of a full parse.
+    #[test]
+    fn consistent_with_profile() {
+        #[profile(Objective)]
+        async fn parent() {
+            child().await;
+            child().await;
+        }
+        #[profile(Objective)]
+        async fn child() {
+            futures::future::ready(()).await;
+        }
+        futures::executor::block_on(parent());
+        let _unstarted = child();
+        let log = profiler::internal::get_log();
+        let profile: crate::Profile<crate::OpaqueMetadata> = log.parse().unwrap();
+        let events: Vec<format::Event> = serde_json::from_str(&log).unwrap();
+        let items: Result<Vec<_>, _> = Parser::default().parse(events).collect();
+        let streamed = measurements(items.unwrap());
+        assert_eq!(streamed.len(), profile.root_measurement_id().0);
+        for measurement in &streamed {
+            let expected = &profile[measurement.id];
+            assert_eq!(measurement.label.to_string(), expected.label.to_string());
+            assert_eq!(measurement.created, expected.created);
+            assert_eq!(measurement.finished, expected.finished);
+            let intervals: Vec<_> =
+                expected.intervals.iter().map(|id| profile[*id].interval).collect();
+            assert_eq!(format!("{:?}", measurement.intervals), format!("{intervals:?}"));
+            let parent = measurement.parent.unwrap_or(profile.root_measurement_id());
+            assert!(profile[parent].children.contains(&measurement.id));
+        }
+        // Measurements are produced as they end; one never ended.
+        let labels: Vec<_> = streamed.iter().map(|m| m.label.name.as_str()).collect();
+        assert_eq!(labels, ["child", "child", "parent", "child"]);
+        assert!(!streamed[3].finished);
+    }
+
+    #[test]
+    fn window() {
+        let ms = format::Timestamp::from_ms;
+        let mut log = format::Builder::new();
+        let early = log.create(Some(ms(0.0)), format::Parent::root(), "early (?:?)");
+        log.start(ms(1.0), early);
+        log.end(ms(2.0), early);
+        let late = log.create(Some(ms(9.0)), format::Parent::root(), "late (?:?)");
+        log.start(ms(10.0), late);
+        log.end(ms(20.0), late);
+        let log = log.build_string();
+        let read_window = |window| {
+            let mut labels = vec![];
+            read::<crate::OpaqueMetadata, _>(log.as_bytes(), window, |_, item| {
+                if let Item::Measurement(measurement) = item {
+                    labels.push(measurement.label.name.clone());
+                }
+            })
+            .unwrap();
+            labels
+        };
+        assert_eq!(read_window(Window::default()), ["early", "late"]);
+        assert_eq!(read_window(Window::new(5.0, 15.0)), ["late"]);
+        assert_eq!(read_window(Window::new(0.0, 1.5)), ["early"]);
+        assert!(read_window(Window::new(3.0, 5.0)).is_empty());
+    }
+
+    #[test]
+    fn read_multiprocess() {
+        #[profile(Objective)]
+        fn process() {}
+        process();
+        let log = profiler::internal::get_log();
+        let log = format!("{log}\n{log}");
+        let mut processes = vec![];
+        let window = Window::default();
+        let summaries =
+            read::<crate::OpaqueMetadata, _>(log.as_bytes(), window, |i, _| processes.push(i));
+        assert_eq!(summaries.unwrap().len(), 2);
+        assert_eq!(processes, [0, 1]);
+        let log = r#"[{"E":{"i":0,"t":0}}]"#;
+        let error = read::<crate::OpaqueMetadata, _>(log.as_bytes(), window, |_, _| ());
+        assert!(matches!(error, Err(Error::DataError(_))));
+    }
+}
@@ -0,0 +1,104 @@
+//! Golden-file tests of the exporters, using profiles produced by `enso_profiler_demo_data`.
+//!
+//! The timestamps of the demo data are replaced with the index of each event, and its time offset
+//! with zero, so that the output is deterministic. To update the golden files after an intended
+//! change to an exporter, run the tests with the `UPDATE_GOLDEN` environment variable set.
+
+use enso_profiler::format;
+use enso_profiler_data::export;
+use enso_profiler_data::OpaqueMetadata;
+use enso_profiler_data::Profile;
+use std::path::PathBuf;
+
+
+
+// ===============
+// === Fixture ===
+// ===============
+
+/// Run the demo, and return its profile with the timestamps replaced, once for each given process
+/// name.
+fn demo_profiles(processes: &[&str]) -> Vec<Profile<OpaqueMetadata>> {
+    let log = futures::executor::block_on(enso_profiler_demo_data::create_data());
+    let mut events: Vec<format::Event> = serde_json::from_str(&log).unwrap();
+    for (i, event) in events.iter_mut().enumerate() {
+        let time = format::Timestamp::from_ms(i as f64);
+        match event {
+            format::Event::Create(format::Start { start, .. }) =>
+                if let Some(start) = start {
+                    *start = time;
+                },
+            format::Event::Start { timestamp, .. }
+            | format::Event::End { timestamp, .. }
+            | format::Event::Pause { timestamp, .. } => *timestamp = time,
+            format::Event::Metadata(metadata) => {
+                metadata.time = time;
+                let header = serde_json::from_str(metadata.data.get());
+                if let Ok(format::Header::TimeOffset(_)) = header {
+                    let header = format::Header::TimeOffset(format::Timestamp::default());
+                    metadata.data = serde_json::value::to_raw_value(&header).unwrap();
+                }
+            }
+            format::Event::Label { .. } => (),
+        }
+    }
+    processes
+        .iter()
+        .map(|process| {
+            let header = format::Header::Process(process.to_string());
+            let data = serde_json::value::to_raw_value(&header).unwrap();
+            let time = format::Timestamp::default();
+            let mut events = events.clone();
+            events.insert(0, format::Event::Metadata(format::Timestamped { time, data }));
+            serde_json::to_string(&events).unwrap().parse().unwrap()
+        })
+        .collect()
+}
+
+/// Compare the output of an exporter to a golden file, or update the file if requested.
+fn check_golden(name: &str, actual: &[u8]) {
+    let path: PathBuf = [env!("CARGO_MANIFEST_DIR"), "tests", "golden", name].iter().collect();
+    if std::env::var_os("UPDATE_GOLDEN").is_some() {
+        std::fs::write(&path, actual).unwrap();
+        return;
+    }
+    let expected =
+        std::fs::read(&path).unwrap_or_else(|e| panic!("Failed to read golden file {path:?}: {e}"));
+    if actual != expected {
+        let actual = String::from_utf8_lossy(actual);
+        let expected = String::from_utf8_lossy(&expected);
+        panic!("Output differs from {path:?}.\nExpected:\n{expected}\nActual:\n{actual}");
+    }
+}
+
+fn to_json(value: &impl serde::Serialize) -> Vec<u8> {
+    let mut json = serde_json::to_vec_pretty(value).unwrap();
+    json.push(b'\n');
+    json
+}
+
+
+
+// =============
+// === Tests ===
+// =============
+
+#[test]
+fn speedscope() {
+    let profiles = demo_profiles(&["Ide"]);
+    let file = export::speedscope::export("demo", &profiles);
+    check_golden("demo.speedscope.json", &to_json(&file));
+}
+
+#[test]
+fn trace_event() {
+    let profiles = demo_profiles(&["Ide", "Backend"]);
+    let file = export::trace_event::export(&profiles);
+    check_golden("demo.trace.json", &to_json(&file));
+}
+
+#[test]
+fn pprof() {
+    let profiles = demo_profiles(&["Ide"]);
+    check_golden("demo.pb", &export::pprof::export(&profiles[0]));
+}
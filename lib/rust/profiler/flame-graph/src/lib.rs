@@ -19,6 +19,9 @@ use enso_profiler_data as data;
 
 type RowNumber = i32;
 
+/// The row of the blocks highlighting a critical path; below the performance blocks.
+const CRITICAL_PATH_ROW: RowNumber = -2;
+
 
 // =======================
 // === Label Formating ===
@@ -45,6 +48,13 @@ pub enum Performance {
     Bad,
 }
 
+/// Distinguishes the segments of a critical path; see [`data::critical_path`].
+#[derive(Copy, Clone, Debug)]
+pub enum PathSegment {
+    Active,
+    Waiting,
+}
+
 /// A `Block` contains the data required to render a single block of a frame graph.
 #[derive(Clone, Debug)]
 pub struct Block<T> {
@@ -97,6 +107,8 @@ pub struct Graph {
     pub performance_blocks: Vec<Block<Performance>>,
     /// Collection of marks that can be shown in the flame graph.
     pub marks:              Vec<Mark>,
+    /// Collection of blocks highlighting the critical path of a measurement.
+    pub critical_path:      Vec<Block<PathSegment>>,
 }
 
 impl Graph {
@@ -127,6 +139,33 @@ impl Graph {
         }
     }
 
+    /// Highlight the critical path of a measurement, in a lane below the other blocks.
+    pub fn add_critical_path<Metadata>(
+        &mut self,
+        profile: &data::Profile<Metadata>,
+        path: &data::critical_path::CriticalPath,
+    ) {
+        use data::critical_path::Cause;
+        use data::critical_path::Kind;
+        for segment in &path.segments {
+            let start = segment.start.into_ms();
+            let end = segment.end.into_ms();
+            let label = &profile[segment.measurement].label;
+            let (label, block_type) = match segment.kind {
+                Kind::Active => (label.to_string(), PathSegment::Active),
+                Kind::Waiting(Cause::Descendant(cause) | Cause::Other(cause)) => (
+                    format!("{label} (waiting for {})", profile[cause].label),
+                    PathSegment::Waiting,
+                ),
+                Kind::Waiting(Cause::External) =>
+                    (format!("{label} (waiting)"), PathSegment::Waiting),
+            };
+            let label = with_timing_info(&label, [start, end]);
+            let row = CRITICAL_PATH_ROW;
+            self.critical_path.push(Block { start, end, row, label, block_type });
+        }
+    }
+
     /// Height of the graph in rows.
     pub fn height(&self) -> RowNumber {
         let performance_rows = self.performance_blocks.iter().map(|mark| mark.row);
@@ -161,6 +200,7 @@ impl<'p, Metadata> CallgraphBuilder<'p, Metadata> {
             activity_blocks:    blocks,
             marks:              Vec::default(),
             performance_blocks: Vec::default(),
+            critical_path:      Vec::default(),
         }
     }
 }
@@ -212,6 +252,7 @@ impl<'p, Metadata> RungraphBuilder<'p, Metadata> {
             activity_blocks:    blocks,
             marks:              Vec::default(),
             performance_blocks: Vec::default(),
+            critical_path:      Vec::default(),
         }
     }
 }
@@ -329,6 +370,7 @@ fn new_hybrid_graph<Metadata>(profile: &data::Profile<Metadata>) -> Graph {
         activity_blocks:    blocks,
         marks:              Vec::default(),
         performance_blocks: Vec::default(),
+        critical_path:      Vec::default(),
     }
 }
 
@@ -363,6 +405,7 @@ impl From<FlamegraphBuilder> for Graph {
             activity_blocks:    blocks,
             marks:              Vec::default(),
             performance_blocks: Vec::default(),
+            critical_path:      Vec::default(),
         }
     }
 }
@@ -418,4 +461,34 @@ mod tests {
         assert_eq!(flame_graph.activity_blocks[0].row, 0);
         assert!(flame_graph.activity_blocks[0].label.contains("profiled_a"));
     }
+
+    #[test]
+    fn check_critical_path_lane() {
+        use profiler::format;
+        let ms = format::Timestamp::from_ms;
+        let mut log = format::Builder::new();
+        let parent = log.create(Some(ms(0.0)), format::Parent::root(), "parent (?:?)");
+        log.start(ms(0.0), parent);
+        let child = log.create(Some(ms(1.0)), parent.into(), "child (?:?)");
+        log.pause(ms(2.0), parent);
+        log.start(ms(2.0), child);
+        log.end(ms(5.0), child);
+        log.start(ms(6.0), parent);
+        log.end(ms(8.0), parent);
+        let profile: data::Profile<data::OpaqueMetadata> = log.build_string().parse().unwrap();
+        let target = data::critical_path::find_by_name(&profile, "parent").next().unwrap();
+        let path = data::critical_path::critical_path(&profile, target).unwrap();
+        let mut flame_graph = Graph::new_callgraph(&profile);
+        flame_graph.add_critical_path(&profile, &path);
+        let lane = &flame_graph.critical_path;
+        let bounds: Vec<_> = lane.iter().map(|block| [block.start, block.end]).collect();
+        assert_eq!(bounds, [[0.0, 2.0], [2.0, 5.0], [5.0, 6.0], [6.0, 8.0]]);
+        assert!(lane.iter().all(|block| block.row == CRITICAL_PATH_ROW));
+        let waiting: Vec<_> =
+            lane.iter().map(|block| matches!(block.block_type, PathSegment::Waiting)).collect();
+        assert_eq!(waiting, [false, false, true, false]);
+        assert!(lane[1].label.starts_with("child"));
+        assert!(lane[2].label.starts_with("parent (waiting for child"));
+        assert!(lane[2].label.ends_with("[5.00,6.00]"));
+    }
 }
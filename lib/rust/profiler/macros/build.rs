@@ -16,6 +16,7 @@
 
 fn main() {
     declare_env_dependence("ENSO_MAX_PROFILING_LEVEL");
+    declare_env_dependence("ENSO_DEFAULT_PROFILING_LEVEL");
 }
 
 /// Make cargo aware that the result of compiling this crate depends on an environment variable.
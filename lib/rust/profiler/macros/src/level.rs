@@ -26,7 +26,8 @@ pub fn parse_levels(var: &str, ts: proc_macro::TokenStream) -> Vec<Level> {
     let parser = punctuated::Punctuated::<syn::Ident, syn::Token![,]>::parse_terminated;
     let obj_idents: Vec<_> = parser.parse(ts).unwrap().into_iter().collect();
     let level_names: Vec<_> = obj_idents.iter().map(|id| id.to_string().to_snake_case()).collect();
-    let max_enabled = level_from_env_var(var, &level_names);
+    // If the variable isn't set, we default to the minimum.
+    let max_enabled = level_from_env_var(var, &level_names).unwrap_or_default();
     obj_idents
         .into_iter()
         .enumerate()
@@ -40,16 +41,13 @@ pub fn parse_levels(var: &str, ts: proc_macro::TokenStream) -> Vec<Level> {
         .collect()
 }
 
-/// Return the numeric Profiling/Log Level (counting from 0 = top-level only).
-fn level_from_env_var(var: &str, levels: &[impl AsRef<str>]) -> usize {
-    let enabled = match env::var(var) {
-        Ok(level) => level,
-        // If the variable isn't set, we default to the minimum.
-        Err(_) => return 0,
-    };
+/// Return the numeric Profiling/Log Level (counting from 0 = top-level only), or [`None`] if the
+/// variable is not set.
+pub fn level_from_env_var(var: &str, levels: &[impl AsRef<str>]) -> Option<usize> {
+    let enabled = env::var(var).ok()?;
     for (i, name) in levels.iter().enumerate() {
         if &enabled[..] == name.as_ref() {
-            return i;
+            return Some(i);
         }
     }
     panic!("{var} set to unknown level: {enabled}")
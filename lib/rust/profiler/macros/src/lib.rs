@@ -40,6 +40,7 @@ mod profile_attribute;
 // ==================================
 
 const PROFILING_LEVEL_ENV_VAR: &str = "ENSO_MAX_PROFILING_LEVEL";
+const DEFAULT_PROFILING_LEVEL_ENV_VAR: &str = "ENSO_DEFAULT_PROFILING_LEVEL";
 
 
 
@@ -28,9 +28,41 @@ pub fn define_profiling_levels(ts: proc_macro::TokenStream) -> proc_macro::Token
     }
     let ident = syn::Ident::new("ProfilingLevel", proc_macro2::Span::call_site());
     out.extend(level::make_enum(ident, levels.iter().map(|level| &level.obj_ident)));
+    out.extend(define_level_constants(&levels));
     out
 }
 
+/// Defines the compile-time configuration of the levels, and lookup of a level by name.
+fn define_level_constants(levels: &[level::Level]) -> proc_macro::TokenStream {
+    let names: Vec<_> = levels.iter().map(|level| &level.name).collect();
+    let max = levels.iter().rposition(|level| level.enabled).unwrap_or_default();
+    let default = level::level_from_env_var(crate::DEFAULT_PROFILING_LEVEL_ENV_VAR, &names);
+    let default = default.unwrap_or(max).min(max);
+    let max = &levels[max].obj_ident;
+    let default = &levels[default].obj_ident;
+    let idents = levels.iter().map(|level| &level.obj_ident);
+    (quote::quote! {
+        /// The finest-grained profiling level enabled at compile time. Profilers of finer levels
+        /// are compiled to no-ops.
+        pub const MAX_PROFILING_LEVEL: ProfilingLevel = ProfilingLevel::#max;
+
+        /// The finest-grained profiling level recorded, unless changed at runtime with
+        /// [`filter::set_max_level`].
+        pub const DEFAULT_PROFILING_LEVEL: ProfilingLevel = ProfilingLevel::#default;
+
+        impl ProfilingLevel {
+            /// Look up a level by its name, in `snake_case`.
+            pub fn from_name(name: &str) -> Option<Self> {
+                match name {
+                    #(#names => Some(ProfilingLevel::#idents),)*
+                    _ => None,
+                }
+            }
+        }
+    })
+    .into()
+}
+
 struct Profiler {
     ident:      syn::Ident,
     start:      syn::Ident,
@@ -80,14 +112,21 @@ fn define_enabled_profiler(profiler: &Profiler) -> proc_macro::TokenStream {
                 let level = crate::ProfilingLevel::#profiling_level_variant;
                 #ident(EventLog.start(parent, label, time, start, level))
             }
+            // The clock is not read for a profiler excluded by the runtime filter.
             fn finish(self) {
-                EventLog.end(self.0, Timestamp::now())
+                if self.0 != EventId::FILTERED {
+                    EventLog.end(self.0, Timestamp::now())
+                }
             }
             fn pause(self) {
-                EventLog.pause(self.0, Timestamp::now());
+                if self.0 != EventId::FILTERED {
+                    EventLog.pause(self.0, Timestamp::now());
+                }
             }
             fn resume(self) {
-                EventLog.resume(self.0, Timestamp::now());
+                if self.0 != EventId::FILTERED {
+                    EventLog.resume(self.0, Timestamp::now());
+                }
             }
         }
 
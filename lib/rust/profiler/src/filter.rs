@@ -0,0 +1,311 @@
+//! Runtime selection of the profilers that are recorded.
+//!
+//! The [profiling levels](crate#profiling-levels) enabled at compile time set an upper bound on
+//! what can be measured; within that bound, a [`Filter`] selects at runtime which profilers are
+//! recorded. A profiler excluded by the filter logs no events, and its children are attributed to
+//! its nearest recorded ancestor.
+//!
+//! The filter applies to the profilers started on the current thread, as does the event log.
+//!
+//! # Syntax
+//!
+//! A filter can be parsed from a comma-separated list of terms:
+//! - A level name, e.g. `detail`, sets the finest-grained level recorded.
+//! - `+PATTERN` records only profilers whose labels match one of the given patterns.
+//! - `-PATTERN` does not record profilers whose labels match the pattern.
+//!
+//! Patterns are matched against the whole label of a profiler, including its location in the code,
+//! e.g. `compile_shaders (ensogl/core/src/display/world.rs:12)`. In a pattern, `*` matches any
+//! sequence of characters, and `?` matches any single character.
+//!
+//! ```
+//! # use enso_profiler as profiler;
+//! let filter: profiler::filter::Filter = "detail,+*graph*,-*render*".parse().unwrap();
+//! profiler::filter::set(filter);
+//! ```
+
+use crate::ProfilingLevel;
+
+use std::cell::Cell;
+use std::cell::RefCell;
+use std::error;
+use std::fmt;
+use std::str::FromStr;
+
+
+
+// ====================
+// === Global state ===
+// ====================
+
+thread_local! {
+    static MAX_LEVEL: Cell<ProfilingLevel> = Cell::new(crate::DEFAULT_PROFILING_LEVEL);
+    static PATTERNS: RefCell<Patterns> = RefCell::new(Patterns::default());
+}
+
+/// Set the finest-grained level of profilers to record. Levels finer than
+/// [`crate::MAX_PROFILING_LEVEL`] are never recorded.
+pub fn set_max_level(level: ProfilingLevel) {
+    MAX_LEVEL.with(|max_level| max_level.set(level));
+}
+
+/// Return the finest-grained level of profilers that is recorded.
+pub fn max_level() -> ProfilingLevel {
+    MAX_LEVEL.with(|max_level| max_level.get()).min(crate::MAX_PROFILING_LEVEL)
+}
+
+/// Replace the current filter.
+pub fn set(filter: Filter) {
+    let Filter { max_level, include, exclude } = filter;
+    set_max_level(max_level);
+    PATTERNS.with(|patterns| *patterns.borrow_mut() = Patterns { include, exclude });
+}
+
+/// Return the current filter.
+pub fn get() -> Filter {
+    let max_level = MAX_LEVEL.with(|max_level| max_level.get());
+    PATTERNS.with(|patterns| {
+        let Patterns { include, exclude } = patterns.borrow().clone();
+        Filter { max_level, include, exclude }
+    })
+}
+
+/// Return whether a profiler with the given level and label should be recorded.
+#[inline]
+pub(crate) fn is_enabled(level: ProfilingLevel, label: crate::internal::Label) -> bool {
+    level <= MAX_LEVEL.with(|max_level| max_level.get())
+        && PATTERNS.with(|patterns| patterns.borrow().matches(label.0))
+}
+
+
+
+// ==============
+// === Filter ===
+// ==============
+
+/// Selects the profilers that are recorded.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Filter {
+    /// The finest-grained level of profilers to record.
+    pub max_level: ProfilingLevel,
+    /// If non-empty, only profilers with labels matching one of these patterns are recorded.
+    pub include:   Vec<Pattern>,
+    /// Profilers with labels matching any of these patterns are not recorded.
+    pub exclude:   Vec<Pattern>,
+}
+
+impl Default for Filter {
+    fn default() -> Self {
+        let max_level = crate::DEFAULT_PROFILING_LEVEL;
+        Self { max_level, include: Default::default(), exclude: Default::default() }
+    }
+}
+
+impl FromStr for Filter {
+    type Err = ParseError;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut filter = Filter::default();
+        for term in s.split(',').map(str::trim).filter(|term| !term.is_empty()) {
+            if let Some(pattern) = term.strip_prefix('+') {
+                filter.include.push(Pattern::new(pattern));
+            } else if let Some(pattern) = term.strip_prefix('-') {
+                filter.exclude.push(Pattern::new(pattern));
+            } else {
+                let level = ProfilingLevel::from_name(term);
+                filter.max_level =
+                    level.ok_or_else(|| ParseError::UnknownLevel(term.to_owned()))?;
+            }
+        }
+        Ok(filter)
+    }
+}
+
+
+// === Patterns ===
+
+#[derive(Clone, Debug, Default)]
+struct Patterns {
+    include: Vec<Pattern>,
+    exclude: Vec<Pattern>,
+}
+
+impl Patterns {
+    fn matches(&self, label: &str) -> bool {
+        let included = self.include.is_empty() || self.include.iter().any(|p| p.matches(label));
+        included && !self.exclude.iter().any(|p| p.matches(label))
+    }
+}
+
+
+// === ParseError ===
+
+/// An error parsing a [`Filter`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ParseError {
+    /// A term is not a pattern, nor the name of a profiling level.
+    UnknownLevel(String),
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ParseError::UnknownLevel(level) => write!(f, "Unknown profiling level: {level:?}."),
+        }
+    }
+}
+
+impl error::Error for ParseError {}
+
+
+
+// ===============
+// === Pattern ===
+// ===============
+
+/// A glob pattern matched against profiler labels. `*` matches any sequence of characters, and `?`
+/// matches any single character.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Pattern {
+    pattern: String,
+}
+
+impl Pattern {
+    /// Create a pattern.
+    pub fn new(pattern: impl Into<String>) -> Self {
+        Self { pattern: pattern.into() }
+    }
+
+    /// Return whether the whole of the given text matches the pattern.
+    pub fn matches(&self, text: &str) -> bool {
+        let pattern = &self.pattern;
+        let next = |s: &str, i: usize| s[i..].chars().next();
+        let (mut p, mut t) = (0, 0);
+        // The position of the last `*` in the pattern, and the position in the text it was matched
+        // at; on a mismatch, the `*` is extended by one character and matching is retried.
+        let mut backtrack = None;
+        while let Some(c) = next(text, t) {
+            match next(pattern, p) {
+                Some('*') => {
+                    backtrack = Some((p, t));
+                    p += 1;
+                }
+                Some(pc) if pc == '?' || pc == c => {
+                    p += pc.len_utf8();
+                    t += c.len_utf8();
+                }
+                _ => match backtrack {
+                    Some((star, matched)) => {
+                        let matched = matched + next(text, matched).map_or(1, char::len_utf8);
+                        backtrack = Some((star, matched));
+                        p = star + 1;
+                        t = matched;
+                    }
+                    None => return false,
+                },
+            }
+        }
+        pattern[p..].chars().all(|c| c == '*')
+    }
+}
+
+impl fmt::Display for Pattern {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.pattern.fmt(f)
+    }
+}
+
+
+
+// =============
+// === Tests ===
+// =============
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::internal::Label;
+
+    #[test]
+    fn patterns() {
+        let matches = |pattern: &str, text: &str| Pattern::new(pattern).matches(text);
+        assert!(matches("*", ""));
+        assert!(matches("draw*", "draw_frame (lib.rs:1)"));
+        assert!(!matches("draw", "draw_frame (lib.rs:1)"));
+        assert!(matches("*frame*", "draw_frame (lib.rs:1)"));
+        assert!(matches("*(lib.rs:?)", "draw_frame (lib.rs:1)"));
+        assert!(!matches("*(lib.rs:?)", "draw_frame (lib.rs:12)"));
+        assert!(matches("a*b*c", "aXbYbZc"));
+        assert!(!matches("a*b*c", "aXbYbZ"));
+        assert!(matches("*?é", "añé"));
+    }
+
+    #[test]
+    fn parse() {
+        let filter: Filter = "objective, +draw*, -*frame*".parse().unwrap();
+        assert_eq!(filter.max_level, ProfilingLevel::Objective);
+        assert_eq!(filter.include, vec![Pattern::new("draw*")]);
+        assert_eq!(filter.exclude, vec![Pattern::new("*frame*")]);
+        assert_eq!("".parse(), Ok(Filter::default()));
+        let error = "verbose".parse::<Filter>();
+        assert_eq!(error, Err(ParseError::UnknownLevel("verbose".to_owned())));
+    }
+
+    #[test]
+    fn filter_profilers() {
+        let label = Label("draw_frame (lib.rs:1)");
+        assert!(is_enabled(ProfilingLevel::Objective, label));
+        set("+draw*,-*frame*".parse().unwrap());
+        assert!(!is_enabled(ProfilingLevel::Objective, label));
+        set("+draw*".parse().unwrap());
+        assert!(is_enabled(ProfilingLevel::Objective, label));
+        set_max_level(ProfilingLevel::Objective);
+        assert!(!is_enabled(ProfilingLevel::Task, label));
+        set(Filter::default());
+    }
+
+    #[test]
+    fn filtered_profilers_are_not_logged() {
+        use crate as profiler;
+        use profiler::profile;
+        #[profile(Objective)]
+        fn outer() {
+            inner();
+        }
+        #[profile(Objective)]
+        fn inner() {}
+        set("-outer*".parse().unwrap());
+        outer();
+        set(Filter::default());
+        let log = crate::internal::get_raw_log().events;
+        match &log[..] {
+            [crate::Event::Start(start), crate::Event::End { .. }] => {
+                assert!(start.label.0.starts_with("inner "));
+                assert_eq!(start.parent, crate::EventId::IMPLICIT);
+            }
+            _ => panic!("log: {log:?}"),
+        }
+    }
+
+    /// The overhead of a profiler excluded at runtime must stay below the cost of recording it, so
+    /// that release builds can include finer-grained profilers than they record by default.
+    #[test]
+    fn filtered_profilers_are_within_overhead_budget() {
+        use crate as profiler;
+        use profiler::profile;
+        const MEASUREMENTS: usize = 100_000;
+        #[profile(Objective)]
+        fn measured() {}
+        let measure = || {
+            let start = std::time::Instant::now();
+            for _ in 0..MEASUREMENTS {
+                measured();
+            }
+            start.elapsed()
+        };
+        let recorded = measure();
+        set("+unrecorded".parse().unwrap());
+        let filtered = measure();
+        set(Filter::default());
+        assert!(filtered < recorded, "Filtered: {filtered:?}; recorded: {recorded:?}.");
+    }
+}
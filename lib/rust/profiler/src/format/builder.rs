@@ -78,7 +78,7 @@ impl<'a> Builder<'a> {
     }
 
     /// Attach a header to the profile identifying its process.
-    pub fn process<'b: 'a>(&mut self, process: &'b str) {
+    pub fn process(&mut self, process: &str) {
         self.header(format::Header::Process(process.to_string()));
     }
 
@@ -93,6 +93,13 @@ impl<'a> Builder<'a> {
     pub fn build_string(self) -> String {
         serde_json::to_string(&self.events).unwrap()
     }
+
+    /// Remove and return the events logged so far. The builder retains the labels and measurement
+    /// IDs it has assigned, so that the events logged subsequently can be appended to those
+    /// returned.
+    pub fn take_events(&mut self) -> Vec<format::Event<'a>> {
+        std::mem::take(&mut self.events)
+    }
 }
 
 
@@ -39,29 +39,8 @@ pub(crate) static METADATA_LOGS: log::ThreadLocalLog<rc::Rc<dyn MetadataSource>>
 
 /// Produce a JSON-formatted event log from the internal event logs.
 pub fn get_log() -> String {
-    let LogData { events, metadata_names, mut metadata_entries } = get_raw_log();
-    let mut out = LogTranslator::new();
-    for (id, event) in events.into_iter().enumerate() {
-        let id = EventId(id as u32);
-        match event {
-            Event::Metadata { timestamp, data } => {
-                let ExternalMetadata { type_id } = data;
-                let id = type_id as usize;
-                let name = metadata_names[id];
-                let data = metadata_entries[id].next().unwrap();
-                out.metadata(timestamp, name, data);
-            }
-            Event::Start(Start { parent, start, label, .. }) => {
-                out.create(start, parent, label, id);
-                out.start(start.unwrap(), id);
-            }
-            Event::StartPaused(Start { parent, start, label, .. }) =>
-                out.create(start, parent, label, id),
-            Event::End { id, timestamp } => out.end(timestamp, id),
-            Event::Pause { id, timestamp } => out.pause(timestamp, id),
-            Event::Resume { id, timestamp } => out.start(timestamp, id),
-        }
-    }
+    let mut out = LogTranslator::new("Ide");
+    out.log(get_raw_log());
     out.finish()
 }
 
@@ -94,7 +73,7 @@ pub(crate) struct LogData {
 
 /// Translates [`profiler::internal`] types and IDs to [`profiler::format`] equivalents.
 #[derive(Debug)]
-struct LogTranslator<'a> {
+pub(crate) struct LogTranslator<'a> {
     profile: format::Builder<'a>,
     ids:     std::collections::HashMap<EventId, format::MeasurementId>,
 }
@@ -108,10 +87,10 @@ macro_rules! translate_transition {
 }
 
 impl<'a> LogTranslator<'a> {
-    fn new() -> Self {
+    pub(crate) fn new(process: &str) -> Self {
         let mut profile = format::Builder::new();
         profile.time_offset(Timestamp::time_offset().into());
-        profile.process("Ide");
+        profile.process(process);
         let ids = Default::default();
         Self { profile, ids }
     }
@@ -120,6 +99,54 @@ impl<'a> LogTranslator<'a> {
         self.profile.build_string()
     }
 
+    /// Translate all the events of a log.
+    pub(crate) fn log(&mut self, log: LogData) {
+        let LogData { events, metadata_names, mut metadata_entries } = log;
+        for (id, event) in events.into_iter().enumerate() {
+            let id = EventId(id as u32);
+            self.event(id, event, |type_id| {
+                let id = type_id as usize;
+                (metadata_names[id], metadata_entries[id].next().unwrap())
+            });
+        }
+    }
+
+    /// Remove and return the translated events.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub(crate) fn take_events(&mut self) -> Vec<format::Event<'a>> {
+        self.profile.take_events()
+    }
+
+    /// Translate an event. The data of a metadata event is obtained by passing its type ID to
+    /// `metadata`, which returns the name of the type and the serialized data.
+    pub(crate) fn event(
+        &mut self,
+        id: EventId,
+        event: Event,
+        metadata: impl FnOnce(u32) -> (&'static str, format::AnyMetadata),
+    ) {
+        match event {
+            Event::Metadata { timestamp, data } => {
+                let ExternalMetadata { type_id } = data;
+                let (name, data) = metadata(type_id);
+                self.metadata(timestamp, name, data);
+            }
+            Event::Start(Start { parent, start, label, .. }) => {
+                self.create(start, parent, label, id);
+                self.start(start.unwrap(), id);
+            }
+            Event::StartPaused(Start { parent, start, label, .. }) =>
+                self.create(start, parent, label, id),
+            Event::End { id, timestamp } => {
+                self.end(timestamp, id);
+                // An ended measurement is not referred to by any later event.
+                self.ids.remove(&id);
+            }
+            Event::Pause { id, timestamp } => self.pause(timestamp, id),
+            Event::Resume { id, timestamp } => self.start(timestamp, id),
+        }
+    }
+
     fn metadata(&mut self, time: Timestamp, name: &'static str, data: format::AnyMetadata) {
         self.profile.metadata(time.into(), name, data);
     }
@@ -154,6 +181,7 @@ pub(crate) struct MetadataLog<T> {
 pub(crate) trait MetadataSource {
     fn name(&self) -> &'static str;
     fn get_all(&self) -> Box<dyn Iterator<Item = Box<serde_json::value::RawValue>>>;
+    fn get_last(&self) -> Option<Box<serde_json::value::RawValue>>;
 }
 
 impl<T: 'static + serde::Serialize> MetadataSource for MetadataLog<T> {
@@ -166,6 +194,11 @@ impl<T: 'static + serde::Serialize> MetadataSource for MetadataLog<T> {
         self.entries.for_each(|x| entries.push(serde_json::value::to_raw_value(&x).unwrap()));
         Box::new(entries.into_iter())
     }
+
+    fn get_last(&self) -> Option<Box<serde_json::value::RawValue>> {
+        let last = self.entries.len().checked_sub(1)?;
+        Some(serde_json::value::to_raw_value(&self.entries[last]).unwrap())
+    }
 }
 
 
@@ -196,6 +229,10 @@ impl<T: 'static + serde::Serialize> MetadataLogger<T> {
     ///
     /// Returns an identifier that can be used to create references between log entries.
     pub fn log(&self, t: T) -> EventId {
+        #[cfg(not(target_arch = "wasm32"))]
+        if let Some(id) = crate::native::stream_metadata(self.id, &t) {
+            return id;
+        }
         self.entries.push(t);
         EventLog.metadata(self.id)
     }
@@ -213,6 +250,9 @@ pub struct EventLog;
 
 impl EventLog {
     /// Log the beginning of a measurement.
+    ///
+    /// If the measurement is excluded by the runtime [`crate::filter`], nothing is logged, and
+    /// [`EventId::FILTERED`] is returned.
     #[inline]
     pub fn start(
         self,
@@ -222,6 +262,11 @@ impl EventLog {
         state: StartState,
         level: ProfilingLevel,
     ) -> EventId {
+        if !crate::filter::is_enabled(level, label) {
+            return EventId::FILTERED;
+        }
+        // The children of an unrecorded profiler are attributed to the current profiler.
+        let parent = if parent == EventId::FILTERED { EventId::IMPLICIT } else { parent };
         let m = Start { parent, label, start, level };
         let event = match state {
             StartState::Active => Event::Start(m),
@@ -233,19 +278,25 @@ impl EventLog {
     /// Log the end of a measurement.
     #[inline]
     pub fn end(self, id: EventId, timestamp: Timestamp) {
-        self.log_event(Event::End { id, timestamp });
+        if id != EventId::FILTERED {
+            self.log_event(Event::End { id, timestamp });
+        }
     }
 
     /// Log the beginning of an interval in which the measurement is not active.
     #[inline]
     pub fn pause(self, id: EventId, timestamp: Timestamp) {
-        self.log_event(Event::Pause { id, timestamp });
+        if id != EventId::FILTERED {
+            self.log_event(Event::Pause { id, timestamp });
+        }
     }
 
     /// Log the end of an interval in which the measurement is not active.
     #[inline]
     pub fn resume(self, id: EventId, timestamp: Timestamp) {
-        self.log_event(Event::Resume { id, timestamp });
+        if id != EventId::FILTERED {
+            self.log_event(Event::Resume { id, timestamp });
+        }
     }
 
     /// Log metadata.
@@ -258,6 +309,10 @@ impl EventLog {
 
     #[inline(always)]
     fn log_event(self, event: Event) -> EventId {
+        #[cfg(not(target_arch = "wasm32"))]
+        if let Some(id) = crate::native::stream_event(event) {
+            return id;
+        }
         let id = EventId(EVENTS.len() as u32);
         EVENTS.push(event);
         id
@@ -370,7 +425,8 @@ impl fmt::Display for Label {
 
 /// Time elapsed since the [time origin](https://www.w3.org/TR/hr-time-2/#sec-time-origin).
 ///
-/// Stored as the raw output of performance.now() (floating-point milliseconds).
+/// Stored as the raw output of performance.now() (floating-point milliseconds). In native code, the
+/// time origin is the first use of the clock (see [`crate::native`]).
 #[derive(Copy, Clone, PartialEq, PartialOrd, Debug)]
 pub struct Timestamp {
     ms: f64,
@@ -425,23 +481,19 @@ fn now() -> f64 {
 }
 #[cfg(not(target_arch = "wasm32"))]
 fn now() -> f64 {
-    // Monotonically-increasing timestamp, providing slightly more realistic data for tests than
-    // a constant.
-    thread_local! {
-        static NEXT_TIMESTAMP: std::cell::Cell<f64> = Default::default();
-    }
-    NEXT_TIMESTAMP.with(|timestamp| {
-        let now = timestamp.get();
-        timestamp.set(now + 0.1);
-        now
-    })
+    crate::native::now()
 }
 
+#[cfg(target_arch = "wasm32")]
 fn time_origin() -> f64 {
     use enso_web as web;
     use enso_web::traits::*;
     web::window.performance_or_panic().time_origin()
 }
+#[cfg(not(target_arch = "wasm32"))]
+fn time_origin() -> f64 {
+    crate::native::time_origin()
+}
 
 
 // === Conversions to related types ===
@@ -470,6 +522,10 @@ impl EventId {
     /// Special value indicating the root pseudo-profiler (the parent of runtime root profilers).
     pub const APP_LIFETIME: EventId = EventId(u32::MAX - 1);
 
+    /// Special value identifying a profiler that was excluded by the runtime [`crate::filter`];
+    /// events of such profilers are not logged.
+    pub const FILTERED: EventId = EventId(u32::MAX - 2);
+
     /// Special value indicating that no explicit prior event is associated.
     ///
     /// When used to identify a parent, this indicates that the parent can be inferred to be the
@@ -497,7 +553,7 @@ impl EventId {
 /// Indicates where in the event log metadata from a particular external source should be inserted.
 #[derive(Debug, Copy, Clone)]
 pub struct ExternalMetadata {
-    type_id: u32,
+    pub(crate) type_id: u32,
 }
 
 
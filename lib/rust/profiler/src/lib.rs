@@ -42,6 +42,16 @@
 //! If the environment variable is not set, the level will default to the minimum supported,
 //! *objective*.
 //!
+//! ## Runtime filtering
+//!
+//! Within the levels enabled at compile time, the profilers that are recorded can be selected at
+//! runtime with the [`filter`] module: the finest-grained level recorded can be lowered, and
+//! profilers can be included or excluded by patterns matching their labels. The level recorded by
+//! default is set at compile time with `ENSO_DEFAULT_PROFILING_LEVEL`, e.g. a release build can be
+//! compiled with `ENSO_MAX_PROFILING_LEVEL=detail` and `ENSO_DEFAULT_PROFILING_LEVEL=objective` so
+//! that *detail* profiling is available when requested, but not recorded otherwise. If the
+//! variable is not set, all the levels enabled at compile time are recorded.
+//!
 //! # Structured measurement
 //!
 //! This API can be used to make arbitrary measurements; in order to ensure measurements are easy to
@@ -131,6 +141,8 @@
 #![feature(maybe_uninit_uninit_array)]
 #![feature(extend_one)]
 #![feature(result_option_inspect)]
+#![feature(once_cell)]
+#![feature(let_chains)]
 // === Standard Linter Configuration ===
 #![deny(non_ascii_idents)]
 #![warn(unsafe_code)]
@@ -150,9 +162,12 @@
 // === Export ===
 // ==============
 
+pub mod filter;
 pub mod format;
 pub mod internal;
 pub mod log;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod native;
 
 
 
@@ -619,6 +634,10 @@ mod log_tests {
 // most expensive, it should be on the order of a 1μs (for reasonable numbers of measurements); so
 // the variance introduced by this framework shouldn't disturb even very small measurements (I
 // expect <1% added variability for a 1ms measurement).
+//
+// Profilers excluded by the runtime filter: such a profiler reads the clock once when it starts,
+// and logs nothing. Its cost must stay below that of a recorded profiler; this budget is checked
+// by a test in the `filter` module.
 #[cfg(test)]
 mod bench {
     use crate as profiler;
@@ -642,6 +661,21 @@ mod bench {
         b.iter(|| log_measurements(10_000));
     }
 
+    /// Perform a specified number of measurements with profilers excluded by the runtime filter,
+    /// for comparison with [`log_measurements`].
+    fn filtered_measurements(count: usize) {
+        profiler::filter::set("+unrecorded".parse().unwrap());
+        for _ in 0..count {
+            let _profiler = start_objective!(profiler::APP_LIFETIME, "log_measurement");
+        }
+        profiler::filter::set(Default::default());
+    }
+
+    #[bench]
+    fn filtered_measurements_10_000(b: &mut test::Bencher) {
+        b.iter(|| filtered_measurements(10_000));
+    }
+
     /// For comparison with time taken by [`log_measurements`].
     fn push_vec(count: usize, log: &mut Vec<profiler::Event>) {
         for _ in 0..count {
@@ -0,0 +1,345 @@
+//! Support for profiling native (non-WASM) code.
+//!
+//! In native code, timestamps are obtained from a monotonic clock, whose time origin is the first
+//! time it is read.
+//!
+//! The event log of a thread can be streamed to a file or any other writer as the events occur,
+//! with [`stream_to`] or [`stream_to_file`]; this allows long-running processes to be profiled,
+//! and the resulting log to be analyzed with the same tools as the logs of the IDE (see
+//! `enso_profiler_data`). The output is a JSON-formatted event log, as described in
+//! [`crate::format`]. As profiling data is collected per-thread, each thread that is profiled must
+//! stream to its own writer.
+//!
+//! Once a thread has begun streaming, its events are no longer retained in memory, so that the
+//! memory used by profiling does not grow with the running time of the process. Such events are not
+//! observed by [`crate::internal::get_log`] or the event streams of the crate, including after the
+//! stream has been finished.
+
+use crate::format;
+use crate::internal::get_raw_log;
+use crate::internal::Event;
+use crate::internal::EventId;
+use crate::internal::ExternalMetadata;
+use crate::internal::LogTranslator;
+use crate::internal::Timestamp;
+use crate::internal::EVENTS;
+use crate::internal::METADATA_LOGS;
+
+use std::cell::Cell;
+use std::cell::RefCell;
+use std::fs;
+use std::io;
+use std::path::Path;
+use std::sync::OnceLock;
+use std::time::Instant;
+use std::time::SystemTime;
+
+
+
+// =============
+// === Clock ===
+// =============
+
+/// The origin of the clock's timestamps.
+#[derive(Debug)]
+struct Clock {
+    origin:      Instant,
+    /// The time of the origin, in ms since the Unix epoch.
+    origin_time: f64,
+}
+
+static CLOCK: OnceLock<Clock> = OnceLock::new();
+
+fn clock() -> &'static Clock {
+    CLOCK.get_or_init(|| {
+        let origin = Instant::now();
+        let since_epoch = SystemTime::now().duration_since(SystemTime::UNIX_EPOCH);
+        let origin_time = since_epoch.unwrap_or_default().as_secs_f64() * 1000.0;
+        Clock { origin, origin_time }
+    })
+}
+
+/// Return the time elapsed since the time origin, in ms.
+pub(crate) fn now() -> f64 {
+    clock().origin.elapsed().as_secs_f64() * 1000.0
+}
+
+/// Return the time of the time origin, in ms since the Unix epoch.
+pub(crate) fn time_origin() -> f64 {
+    clock().origin_time
+}
+
+
+
+// ============
+// === Sink ===
+// ============
+
+thread_local! {
+    static SINK: RefCell<Option<Sink>> = RefCell::new(None);
+    /// The ID of the next event of the thread, if its events are streamed instead of retained.
+    static NEXT_STREAMED_ID: Cell<Option<u32>> = Cell::new(None);
+}
+
+/// Stream the profiling events of the current thread to the given writer. The log identifies its
+/// process with the given name.
+///
+/// The events logged by the thread before this call are written first. Any sink previously
+/// installed on the thread is closed. The log is completed when [`finish_stream`] is called, or
+/// when the thread exits.
+pub fn stream_to(process: &str, writer: impl io::Write + 'static) {
+    let mut sink = Sink::new(process, Box::new(writer));
+    sink.translator.log(get_raw_log());
+    sink.write_events();
+    SINK.with_borrow_mut(|current| *current = Some(sink));
+    NEXT_STREAMED_ID.with(|next| {
+        if next.get().is_none() {
+            next.set(Some(EVENTS.len() as u32));
+        }
+    });
+}
+
+/// Stream the profiling events of the current thread to a file; see [`stream_to`].
+pub fn stream_to_file(process: &str, path: impl AsRef<Path>) -> io::Result<()> {
+    let file = fs::File::create(path)?;
+    stream_to(process, io::BufWriter::new(file));
+    Ok(())
+}
+
+/// Complete the log being streamed by the current thread, and stop streaming. Returns the first
+/// error encountered while writing the log, if any.
+pub fn finish_stream() -> io::Result<()> {
+    match SINK.with_borrow_mut(Option::take) {
+        Some(mut sink) => sink.close(),
+        None => Ok(()),
+    }
+}
+
+/// If the events of the current thread are streamed, write an event to the thread's sink (if it
+/// has one), and return the ID of the event. Otherwise, returns [`None`]; the event is to be
+/// retained in the thread's log.
+#[inline]
+pub(crate) fn stream_event(event: Event) -> Option<EventId> {
+    stream(event, |type_id| {
+        METADATA_LOGS.get(type_id as usize, |log| {
+            log.get_last().expect("Metadata event logged without data.")
+        })
+    })
+}
+
+/// If the events of the current thread are streamed, write a metadata event with the given data;
+/// see [`stream_event`].
+pub(crate) fn stream_metadata(type_id: u32, data: &impl serde::Serialize) -> Option<EventId> {
+    if !NEXT_STREAMED_ID.try_with(|next| next.get().is_some()).unwrap_or_default() {
+        return None;
+    }
+    let timestamp = Timestamp::now();
+    let event = Event::Metadata { timestamp, data: ExternalMetadata { type_id } };
+    let data = serde_json::value::to_raw_value(data).unwrap();
+    stream(event, |_| data)
+}
+
+fn stream(event: Event, data: impl FnOnce(u32) -> format::AnyMetadata) -> Option<EventId> {
+    // The thread-local state may have been destroyed if the thread is exiting.
+    let id = NEXT_STREAMED_ID.try_with(|next| {
+        let id = next.get()?;
+        next.set(Some(id + 1));
+        Some(EventId(id))
+    });
+    let id = id.ok().flatten()?;
+    let _ = SINK.try_with(|sink| {
+        if let Some(sink) = sink.borrow_mut().as_mut() {
+            sink.event(id, event, data);
+        }
+    });
+    Some(id)
+}
+
+
+// === Sink ===
+
+/// Translates events to the JSON format, and writes them incrementally.
+struct Sink {
+    translator: LogTranslator<'static>,
+    writer:     Box<dyn io::Write>,
+    /// The number of events written.
+    written:    usize,
+    closed:     bool,
+    /// The first error that occurred; once writing has failed, the sink writes nothing more.
+    error:      Option<io::Error>,
+}
+
+impl Sink {
+    fn new(process: &str, writer: Box<dyn io::Write>) -> Self {
+        let translator = LogTranslator::new(process);
+        Self { translator, writer, written: 0, closed: false, error: None }
+    }
+
+    /// Write an event. The data of a metadata event is obtained by passing its type ID to `data`.
+    fn event(&mut self, id: EventId, event: Event, data: impl FnOnce(u32) -> format::AnyMetadata) {
+        self.translator.event(id, event, |type_id| {
+            let name = METADATA_LOGS.get(type_id as usize, |log| log.name());
+            (name, data(type_id))
+        });
+        self.write_events();
+    }
+
+    fn write_events(&mut self) {
+        for event in self.translator.take_events() {
+            let separator = if self.written == 0 { b"[" } else { b"," };
+            self.written += 1;
+            self.write(|writer| {
+                writer.write_all(separator)?;
+                serde_json::to_writer(writer, &event).map_err(io::Error::from)
+            });
+        }
+    }
+
+    fn write(&mut self, f: impl FnOnce(&mut dyn io::Write) -> io::Result<()>) {
+        if self.error.is_none() && let Err(error) = f(&mut self.writer) {
+            self.error = Some(error);
+        }
+    }
+
+    fn close(&mut self) -> io::Result<()> {
+        if !self.closed {
+            self.closed = true;
+            let end: &[u8] = if self.written == 0 { b"[]" } else { b"]" };
+            self.write(|writer| {
+                writer.write_all(end)?;
+                writer.flush()
+            });
+        }
+        match self.error.take() {
+            Some(error) => Err(error),
+            None => Ok(()),
+        }
+    }
+}
+
+impl Drop for Sink {
+    fn drop(&mut self) {
+        let _ = self.close();
+    }
+}
+
+impl std::fmt::Debug for Sink {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Sink").field("written", &self.written).finish_non_exhaustive()
+    }
+}
+
+
+
+// =============
+// === Tests ===
+// =============
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::internal::Label;
+    use crate::internal::Profiler;
+    use crate::internal::StartState;
+    use crate::internal::Started;
+    use crate::internal::Timestamp;
+    use crate::Objective;
+    use crate::Parent;
+
+    use std::rc::Rc;
+
+    /// A writer whose output can be read while it is installed in a sink.
+    #[derive(Clone, Debug, Default)]
+    struct SharedBuffer(Rc<RefCell<Vec<u8>>>);
+
+    impl io::Write for SharedBuffer {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            self.0.borrow_mut().write(buf)
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    impl SharedBuffer {
+        fn contents(&self) -> String {
+            String::from_utf8(self.0.borrow().clone()).unwrap()
+        }
+    }
+
+    fn start_objective(label: &'static str) -> Started<Objective> {
+        let now = Some(Timestamp::now());
+        let parent = crate::APP_LIFETIME.0;
+        Started(Objective::start(parent, Label(label), now, StartState::Active))
+    }
+
+    #[test]
+    fn clock_is_monotonic() {
+        let first = now();
+        let second = now();
+        assert!(second >= first);
+        assert!(time_origin() > 0.0);
+    }
+
+    #[test]
+    fn streams_events() {
+        let early = start_objective("early");
+        let buffer = SharedBuffer::default();
+        stream_to("Test", buffer.clone());
+        // Events logged before streaming began are written immediately.
+        let log = buffer.contents() + "]";
+        let events: Vec<format::Event> = serde_json::from_str(&log).unwrap();
+        assert!(matches!(events[..], [.., format::Event::Start { .. }]));
+        {
+            let _late: Started<Objective> = early.start_child(Label("late"));
+        }
+        drop(early);
+        finish_stream().unwrap();
+        let log = buffer.contents();
+        let events: Vec<format::Event> = serde_json::from_str(&log).unwrap();
+        let count = |f: fn(&format::Event) -> bool| events.iter().filter(|e| f(e)).count();
+        assert_eq!(count(|event| matches!(event, format::Event::Metadata(_))), 2);
+        assert_eq!(count(|event| matches!(event, format::Event::Create(_))), 2);
+        assert_eq!(count(|event| matches!(event, format::Event::End { .. })), 2);
+        // Events logged after the stream is finished are not written.
+        let _after = start_objective("after");
+        assert_eq!(buffer.contents(), log);
+    }
+
+    #[test]
+    fn streams_metadata() {
+        crate::metadata_logger!("Message", log_message(String));
+        let buffer = SharedBuffer::default();
+        stream_to("Test", buffer.clone());
+        log_message("hello".to_owned());
+        finish_stream().unwrap();
+        let log = buffer.contents();
+        let events: Vec<format::Event> = serde_json::from_str(&log).unwrap();
+        let Some(format::Event::Metadata(metadata)) = events.last() else {
+            panic!("Expected metadata: {events:?}");
+        };
+        assert_eq!(metadata.data.get(), r#"{"Message":"hello"}"#);
+    }
+
+    #[test]
+    fn streamed_events_are_not_retained() {
+        crate::metadata_logger!("Message", log_message(String));
+        let buffer = SharedBuffer::default();
+        stream_to("Test", buffer.clone());
+        let retained = EVENTS.len();
+        {
+            let _measurement = start_objective("streamed");
+            log_message("hello".to_owned());
+        }
+        assert_eq!(EVENTS.len(), retained);
+        let message_log = METADATA_LOGS.len() - 1;
+        assert!(METADATA_LOGS.get(message_log, |log| log.get_last()).is_none());
+        finish_stream().unwrap();
+        // The thread's events are not retained after the stream is finished either.
+        let _after = start_objective("after");
+        assert_eq!(EVENTS.len(), retained);
+        let events: Vec<format::Event> = serde_json::from_str(&buffer.contents()).unwrap();
+        assert_eq!(events.iter().filter(|e| matches!(e, format::Event::Create(_))).count(), 1);
+    }
+}
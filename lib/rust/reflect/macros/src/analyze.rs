@@ -61,6 +61,7 @@ fn parse_fields(fields: syn::Fields) -> Fields {
                         FieldAttr::Hide => field_.hide = true,
                         FieldAttr::Subtype => field_.subtype = true,
                         FieldAttr::As(ty) => field_.refer = Some(ty),
+                        FieldAttr::VisitWith(path) => field_.visit_with = Some(path),
                         FieldAttr::Rename(ident) => field_.rename = Some(ident),
                         // NOTE: Implementing `skip` at analysis time makes our Rust information
                         // incomplete. For `reflect` to be used to generate Rust deserialization
@@ -69,6 +70,10 @@ fn parse_fields(fields: syn::Fields) -> Fields {
                         FieldAttr::Skip => continue 'fields,
                     }
                 }
+                if field_.refer.is_some() && field_.visit_with.is_none() {
+                    let name = &field_.name;
+                    panic!("Field `{name}`: `#[reflect(as = ..)]` requires `visit_with = ..`.");
+                }
                 fields.push(field_);
             }
             Fields::Named { fields }
@@ -129,6 +134,7 @@ enum FieldAttr {
     Skip,
     Subtype,
     As(Box<syn::Type>),
+    VisitWith(syn::Path),
     Rename(syn::LitStr),
 }
 
@@ -170,6 +176,7 @@ fn parse_field_annotation(meta: &syn::NestedMeta, attr: &syn::Attribute) -> Fiel
             match ident.to_string().as_str() {
                 "as" => FieldAttr::As(Box::new(lit.parse().expect(INVALID_HELPER_SYNTAX))),
                 "rename" => FieldAttr::Rename(lit.clone()),
+                "visit_with" => FieldAttr::VisitWith(lit.parse().expect(INVALID_HELPER_SYNTAX)),
                 _ => panic!("{}: {}.", UNKNOWN_HELPER, ident.into_token_stream()),
             }
         }
@@ -9,11 +9,15 @@
 //! The field will be excluded from reflection data.
 //! When this attribute is present, the field's type does not need to implement `Reflect`.
 //!
-//! ### `#[reflect(as = "OtherType")]` (field attribute)
+//! ### `#[reflect(as = "OtherType", visit_with = "function")]` (field attribute)
 //! The field's type in the reflection data will be `OtherType` rather than the field's real type.
 //! When this attribute is present, the field's real type does not need to implement `Reflect`. The
 //! alternative type specified must implement `Reflect`.
 //!
+//! The data of the field is passed to `Reflect::reflect_value` visitors by the given function,
+//! which must have the signature `fn(&FieldType, &mut dyn enso_reflect::Visitor)`, and visit the
+//! data of a value of `OtherType`.
+//!
 //! ## Attributes for Abstraction Customization
 //!
 //! Application of `#[derive(Reflect)]` to data types is enough to enable reflection over Rust
@@ -131,6 +135,7 @@ use syn::Token;
 
 mod analyze;
 mod runtime;
+mod value;
 
 use runtime::Quote;
 
@@ -159,23 +164,26 @@ enum Data {
 
 #[derive(Debug)]
 struct NamedField {
-    name:    syn::Ident,
-    type_:   syn::Type,
-    subtype: bool,
-    refer:   Option<Box<syn::Type>>,
-    flatten: bool,
-    hide:    bool,
-    rename:  Option<syn::LitStr>,
+    name:       syn::Ident,
+    type_:      syn::Type,
+    subtype:    bool,
+    refer:      Option<Box<syn::Type>>,
+    /// Function visiting the value of a field with a `refer` type.
+    visit_with: Option<syn::Path>,
+    flatten:    bool,
+    hide:       bool,
+    rename:     Option<syn::LitStr>,
 }
 
 impl NamedField {
     pub fn new(name: syn::Ident, type_: syn::Type) -> Self {
         let subtype = Default::default();
         let refer = Default::default();
+        let visit_with = Default::default();
         let flatten = Default::default();
         let hide = Default::default();
         let rename = Default::default();
-        Self { name, type_, subtype, refer, flatten, hide, rename }
+        Self { name, type_, subtype, refer, visit_with, flatten, hide, rename }
     }
 }
 
@@ -223,6 +231,7 @@ pub fn derive_reflect(input: proc_macro::TokenStream) -> proc_macro::TokenStream
     let type_bounds = type_.generic_params.iter().map(with_bound);
     generic_bounds.extend(type_bounds);
     let type_expr = type_.quote();
+    let value_body = value::visit_body(&type_);
     let static_lifetimes: Vec<_> = type_.lifetimes.iter().map(|_| quote! { 'static }).collect();
     let to_static =
         |param| (quote! { <#param as enso_reflect::Reflect>::Static }).into_token_stream();
@@ -252,6 +261,10 @@ pub fn derive_reflect(input: proc_macro::TokenStream) -> proc_macro::TokenStream
             fn reflect() -> enso_reflect::metamodel::rust::TypeData {
                 #type_expr
             }
+            #[allow(unused_variables)]
+            fn reflect_value(&self, visitor: &mut dyn enso_reflect::Visitor) {
+                #value_body
+            }
         }
     };
     impl_reflect.into()
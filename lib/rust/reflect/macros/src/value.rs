@@ -0,0 +1,79 @@
+//! Generate the implementation of `Reflect::reflect_value`, which visits the data of a value.
+
+use super::*;
+
+use quote::format_ident;
+
+
+
+// ==================
+// === Visit Body ===
+// ==================
+
+/// Produce the body of the `reflect_value` method of a type.
+pub(crate) fn visit_body(type_: &Type) -> TokenStream {
+    match &type_.data {
+        Data::Struct(fields) => {
+            let accessors = match fields {
+                Fields::Named { fields } =>
+                    fields.iter().map(|field| field.name.to_token_stream()).collect(),
+                Fields::Unnamed(fields) =>
+                    (0..fields.len()).map(|i| syn::Index::from(i).to_token_stream()).collect(),
+                Fields::Unit => vec![],
+            };
+            let values = accessors.into_iter().map(|accessor| quote! { &self.#accessor });
+            visit_fields(fields, values)
+        }
+        Data::Enum(variants) if variants.is_empty() => quote! { match *self {} },
+        Data::Enum(variants) => {
+            let arms = variants.iter().enumerate().map(|(index, variant)| {
+                let ident = &variant.ident;
+                let index = index as u32;
+                let bindings: Vec<_> = match &variant.fields {
+                    Fields::Named { fields } => (0..fields.len()).map(binding).collect(),
+                    Fields::Unnamed(fields) => (0..fields.len()).map(binding).collect(),
+                    Fields::Unit => vec![],
+                };
+                let pattern = match &variant.fields {
+                    Fields::Named { fields } => {
+                        let names = fields.iter().map(|field| &field.name);
+                        quote! { Self::#ident { #(#names: #bindings,)* .. } }
+                    }
+                    Fields::Unnamed(_) => quote! { Self::#ident(#(#bindings),*) },
+                    Fields::Unit => quote! { Self::#ident },
+                };
+                let values = bindings.iter().map(ToTokens::to_token_stream);
+                let visit = visit_fields(&variant.fields, values);
+                quote! {
+                    #pattern => {
+                        visitor.visit_variant(#index);
+                        #visit
+                    }
+                }
+            });
+            quote! {
+                match self {
+                    #(#arms)*
+                }
+            }
+        }
+    }
+}
+
+/// Visit the values of the given fields, which are expressions evaluating to references.
+fn visit_fields(fields: &Fields, values: impl Iterator<Item = TokenStream>) -> TokenStream {
+    let visitors: Vec<_> = match fields {
+        Fields::Named { fields } => fields.iter().map(|field| field.visit_with.as_ref()).collect(),
+        Fields::Unnamed(fields) => fields.iter().map(|_| None).collect(),
+        Fields::Unit => vec![],
+    };
+    let visits = visitors.into_iter().zip(values).map(|(visit_with, value)| match visit_with {
+        Some(function) => quote! { #function(#value, visitor); },
+        None => quote! { enso_reflect::Reflect::reflect_value(#value, visitor); },
+    });
+    quote! { #(#visits)* }
+}
+
+fn binding(index: usize) -> syn::Ident {
+    format_ident!("field{index}")
+}
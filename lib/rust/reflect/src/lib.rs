@@ -7,7 +7,9 @@
 //! new, even more obtuse associated types will be added to the trait in the future.
 //!
 //! For data consumers: The `Reflect` trait can be used to obtain reflection data; after that, the
-//! [`enso_metamodel`] crate supports working with it--see the docs there.
+//! [`enso_metamodel`] crate supports working with it--see the docs there. The data of a value can
+//! be read with [`Reflect::reflect_value`], which passes it to a [`Visitor`] in a form described by
+//! the reflection data.
 //!
 //! # Implementation
 //!
@@ -164,6 +166,8 @@ pub trait Reflect {
     fn reflect_type(&self) -> TypeData {
         Self::reflect()
     }
+    /// Pass the data of the value to the visitor, in the order of the fields of its type.
+    fn reflect_value(&self, visitor: &mut dyn Visitor);
 }
 
 
@@ -175,6 +179,9 @@ impl Reflect for std::borrow::Cow<'_, str> {
     fn reflect() -> TypeData {
         <String as Reflect>::reflect()
     }
+    fn reflect_value(&self, visitor: &mut dyn Visitor) {
+        visitor.visit_str(self)
+    }
 }
 
 impl<T> Reflect for std::rc::Rc<T>
@@ -185,6 +192,9 @@ where T: Reflect
     fn reflect() -> TypeData {
         T::reflect()
     }
+    fn reflect_value(&self, visitor: &mut dyn Visitor) {
+        T::reflect_value(self, visitor)
+    }
 }
 
 impl<T> Reflect for Box<T>
@@ -195,6 +205,9 @@ where T: Reflect
     fn reflect() -> TypeData {
         T::reflect()
     }
+    fn reflect_value(&self, visitor: &mut dyn Visitor) {
+        T::reflect_value(self, visitor)
+    }
 }
 
 impl<T> Reflect for Option<T>
@@ -209,6 +222,12 @@ where T: Reflect
         let subtype_erased = generic_id::<Self>();
         TypeData { id, name, data, subtype_erased }
     }
+    fn reflect_value(&self, visitor: &mut dyn Visitor) {
+        visitor.visit_option(self.is_some());
+        if let Some(value) = self {
+            value.reflect_value(visitor);
+        }
+    }
 }
 
 impl<T, E> Reflect for Result<T, E>
@@ -227,6 +246,18 @@ where
         let subtype_erased = generic_id::<Self>();
         TypeData { id, name, data, subtype_erased }
     }
+    fn reflect_value(&self, visitor: &mut dyn Visitor) {
+        match self {
+            Ok(value) => {
+                visitor.visit_variant(0);
+                value.reflect_value(visitor);
+            }
+            Err(error) => {
+                visitor.visit_variant(1);
+                error.reflect_value(visitor);
+            }
+        }
+    }
 }
 
 impl Reflect for &'_ str {
@@ -235,6 +266,9 @@ impl Reflect for &'_ str {
     fn reflect() -> TypeData {
         <String as Reflect>::reflect()
     }
+    fn reflect_value(&self, visitor: &mut dyn Visitor) {
+        visitor.visit_str(self)
+    }
 }
 
 impl<T> Reflect for Vec<T>
@@ -249,10 +283,16 @@ where T: Reflect
         let subtype_erased = generic_id::<Self>();
         TypeData { id, name, data, subtype_erased }
     }
+    fn reflect_value(&self, visitor: &mut dyn Visitor) {
+        visitor.visit_sequence(self.len());
+        for value in self {
+            value.reflect_value(visitor);
+        }
+    }
 }
 
 macro_rules! reflect_primitive {
-    ($ty: ty, $primitive: expr) => {
+    ($ty: ty, $primitive: expr, $visit: ident) => {
         impl Reflect for $ty {
             type Static = Self;
             type SubtypeErased = Self::Static;
@@ -263,16 +303,65 @@ macro_rules! reflect_primitive {
                 let subtype_erased = generic_id::<Self>();
                 TypeData { id, name, data, subtype_erased }
             }
+            fn reflect_value(&self, visitor: &mut dyn Visitor) {
+                visitor.$visit(*self)
+            }
         }
     };
 }
 
-reflect_primitive!(bool, Primitive::Bool);
-reflect_primitive!(usize, Primitive::Usize);
-reflect_primitive!(u32, Primitive::U32);
-reflect_primitive!(i32, Primitive::I32);
-reflect_primitive!(char, Primitive::Char);
-reflect_primitive!(String, Primitive::String);
+reflect_primitive!(bool, Primitive::Bool, visit_bool);
+reflect_primitive!(usize, Primitive::Usize, visit_usize);
+reflect_primitive!(u32, Primitive::U32, visit_u32);
+reflect_primitive!(i32, Primitive::I32, visit_i32);
+reflect_primitive!(char, Primitive::Char, visit_char);
+
+impl Reflect for String {
+    type Static = Self;
+    type SubtypeErased = Self::Static;
+    fn reflect() -> TypeData {
+        let id = type_id::<String>();
+        let name = "String".to_owned();
+        let data = Data::Primitive(Primitive::String);
+        let subtype_erased = generic_id::<Self>();
+        TypeData { id, name, data, subtype_erased }
+    }
+    fn reflect_value(&self, visitor: &mut dyn Visitor) {
+        visitor.visit_str(self)
+    }
+}
+
+
+
+// ===============
+// === Visitor ===
+// ===============
+
+/// Receives the data of a value from [`Reflect::reflect_value`].
+///
+/// A value is visited as a sequence of primitive values, in the order of the fields of its type;
+/// the structure of the data is described by the type's reflection information. Fields marked
+/// `#[reflect(skip)]` are not visited.
+pub trait Visitor {
+    /// Visit a `bool`.
+    fn visit_bool(&mut self, value: bool);
+    /// Visit a `u32`.
+    fn visit_u32(&mut self, value: u32);
+    /// Visit an `i32`.
+    fn visit_i32(&mut self, value: i32);
+    /// Visit a `usize`.
+    fn visit_usize(&mut self, value: usize);
+    /// Visit a `char`.
+    fn visit_char(&mut self, value: char);
+    /// Visit a string.
+    fn visit_str(&mut self, value: &str);
+    /// Visit the index of the variant of an `enum` or a `Result`; the variant's fields follow.
+    fn visit_variant(&mut self, index: u32);
+    /// Visit whether an `Option` is present; if it is, the contained value follows.
+    fn visit_option(&mut self, is_some: bool);
+    /// Visit the length of a sequence; its elements follow.
+    fn visit_sequence(&mut self, len: usize);
+}
 
 
 